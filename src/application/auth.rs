@@ -13,16 +13,24 @@
 //! - Automatic re-authentication when tokens expire
 
 use crate::application::config::Config;
+use crate::application::interfaces::authenticator::IgAuthenticator;
 use crate::application::rate_limiter::RateLimiter;
+use crate::constants::OAUTH_EXPIRY_MARGIN_SECS;
 use crate::error::AppError;
+use crate::model::auth::SessionDetails;
 pub(crate) use crate::model::auth::{OAuthToken, SecurityHeaders, SessionResponse};
-use crate::model::http::make_http_request;
+use crate::model::http::{make_http_request, read_json};
+use crate::model::responses::AccountsResponse;
 use crate::model::retry::RetryConfig;
 use crate::prelude::Deserialize;
+use crate::presentation::account::Account;
+use crate::utils::single_flight::SingleFlight;
+use async_trait::async_trait;
 use chrono::Utc;
 use pretty_simple_display::{DebugPretty, DisplaySimple};
 use reqwest::{Client, Method};
 use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
@@ -82,6 +90,11 @@ pub struct Session {
     /// - OAuth (v3): expires in 30 seconds
     /// - API v2: expires in 6 hours (21600 seconds)
     pub expires_at: u64,
+    /// Whether the session's account can place orders, fetched at login
+    ///
+    /// `None` when it could not be determined, in which case orders are sent
+    /// and IG decides.
+    pub can_trade: Option<bool>,
 }
 
 impl Session {
@@ -103,17 +116,18 @@ impl Session {
     pub fn is_expired(&self, margin_seconds: Option<u64>) -> bool {
         let margin = margin_seconds.unwrap_or(60);
         let now = Utc::now().timestamp() as u64;
-        now >= (self.expires_at - margin)
+        now >= self.expires_at.saturating_sub(margin)
     }
 
     /// Gets the number of seconds until session expires
     ///
     /// # Returns
-    /// * Positive number if session is still valid
-    /// * Negative number if session is already expired
+    /// * Seconds left if session is still valid
+    /// * `0` if session is already expired
     #[must_use]
     pub fn seconds_until_expiry(&self) -> u64 {
-        self.expires_at - Utc::now().timestamp() as u64
+        self.expires_at
+            .saturating_sub(Utc::now().timestamp() as u64)
     }
 
     /// Checks if OAuth token needs refresh (alias for is_expired for backwards compatibility)
@@ -159,6 +173,8 @@ pub struct Auth {
     client: Client,
     session: Arc<RwLock<Option<Session>>>,
     rate_limiter: Arc<RwLock<RateLimiter>>,
+    relogin: SingleFlight<Session>,
+    can_trade: std::sync::Mutex<HashMap<String, bool>>,
 }
 
 impl Auth {
@@ -167,21 +183,36 @@ impl Auth {
     /// # Arguments
     /// * `config` - Configuration containing credentials and API settings
     pub fn new(config: Arc<Config>) -> Self {
+        let rate_limiter = Arc::new(RwLock::new(RateLimiter::for_config(&config)));
+        Self::with_rate_limiter(config, rate_limiter)
+    }
+
+    /// Creates a new Auth instance that draws on an existing rate limiter
+    ///
+    /// # Arguments
+    /// * `config` - Configuration containing credentials and API settings
+    /// * `rate_limiter` - Limiter shared with other clients using the same API key
+    pub fn with_rate_limiter(config: Arc<Config>, rate_limiter: Arc<RwLock<RateLimiter>>) -> Self {
         let client = Client::builder()
             .user_agent(USER_AGENT)
             .build()
             .expect("Failed to create HTTP client");
 
-        let rate_limiter = Arc::new(RwLock::new(RateLimiter::new(&config.rate_limiter)));
-
         Self {
             config,
             client,
             session: Arc::new(RwLock::new(None)),
             rate_limiter,
+            relogin: SingleFlight::new(),
+            can_trade: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
+    /// Shared slot holding the current session
+    pub(crate) fn session_slot(&self) -> Arc<RwLock<Option<Session>>> {
+        Arc::clone(&self.session)
+    }
+
     /// Gets the WebSocket password for Lightstreamer authentication
     ///
     /// # Returns
@@ -218,15 +249,31 @@ impl Auth {
 
         // No session exists, need to login
         info!("No active session, logging in");
-        self.login().await
+        self.relogin().await
+    }
+
+    /// Logs in again, sharing the login with concurrent callers
+    ///
+    /// When several tasks find the session expired at the same time only one
+    /// login request is sent; the others wait for it and receive the same
+    /// session. Use `login` to force a request regardless.
+    ///
+    /// # Returns
+    /// * `Ok(Session)` - Fresh session
+    /// * `Err(AppError)` - If this caller's login failed
+    pub async fn relogin(&self) -> Result<Session, AppError> {
+        self.relogin.run(|| self.login()).await
     }
 
     /// Performs initial login to IG Markets API
     ///
     /// Automatically detects API version from config and uses appropriate authentication method.
+    /// When `credentials.auto_switch_account` is set (the default) and IG logs
+    /// in to a different default account, the session is moved to the
+    /// configured `account_id` before it is returned.
     ///
     /// # Returns
-    /// * `Ok(Session)` - Authenticated session
+    /// * `Ok(Session)` - Authenticated session on the configured account
     /// * `Err(AppError)` - If login fails
     pub async fn login(&self) -> Result<Session, AppError> {
         let api_version = self.config.api_version.unwrap_or(2);
@@ -242,9 +289,131 @@ impl Auth {
         // Store session
         let mut sess = self.session.write().await;
         *sess = Some(session.clone());
+        drop(sess);
 
         info!("✓ Login successful, account: {}", session.account_id);
-        Ok(session)
+        let session = self.switch_to_configured_account(session).await?;
+        Ok(self.with_trading_permission(session).await)
+    }
+
+    /// Records on the session whether its account can trade
+    ///
+    /// An account's permission does not change between logins, so it is
+    /// looked up in `GET /accounts` only the first time an account is used and
+    /// carried forward to later sessions on that account. A failed lookup is
+    /// logged and leaves `can_trade` unset rather than failing the login.
+    async fn with_trading_permission(&self, mut session: Session) -> Session {
+        let known = self
+            .can_trade
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&session.account_id)
+            .copied();
+        session.can_trade = session.can_trade.or(known);
+        if session.can_trade.is_none() {
+            match self.fetch_accounts(&session).await {
+                Ok(accounts) => {
+                    session.can_trade = accounts
+                        .accounts
+                        .iter()
+                        .find(|account| account.account_id == session.account_id)
+                        .map(Account::can_trade);
+                    if session.can_trade == Some(false) {
+                        warn!("Account {} cannot trade", session.account_id);
+                    }
+                }
+                Err(e) => warn!(
+                    "Could not check whether account {} can trade: {}",
+                    session.account_id, e
+                ),
+            }
+        }
+        let Some(can_trade) = session.can_trade else {
+            return session;
+        };
+        self.can_trade
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(session.account_id.clone(), can_trade);
+        let mut stored = self.session.write().await;
+        if let Some(current) = stored
+            .as_mut()
+            .filter(|current| current.account_id == session.account_id)
+        {
+            current.can_trade = Some(can_trade);
+        }
+        drop(stored);
+        session
+    }
+
+    /// Version 1 JSON headers authenticating a request with `session`
+    ///
+    /// OAuth sessions send a bearer token and `IG-ACCOUNT-ID`; CST sessions
+    /// send their `CST` and `X-SECURITY-TOKEN` tokens.
+    fn auth_headers(&self, session: &Session) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            ("X-IG-API-KEY", self.config.credentials.api_key.clone()),
+            ("Content-Type", "application/json".to_string()),
+            ("Version", "1".to_string()),
+        ];
+        if let Some(oauth) = &session.oauth_token {
+            headers.push(("Authorization", format!("Bearer {}", oauth.access_token)));
+            headers.push(("IG-ACCOUNT-ID", session.account_id.clone()));
+        } else {
+            if let Some(cst) = &session.cst {
+                headers.push(("CST", cst.clone()));
+            }
+            if let Some(token) = &session.x_security_token {
+                headers.push(("X-SECURITY-TOKEN", token.clone()));
+            }
+        }
+        headers
+    }
+
+    /// Fetches the user's accounts from `GET /accounts` with the given session
+    async fn fetch_accounts(&self, session: &Session) -> Result<AccountsResponse, AppError> {
+        let url = format!("{}/accounts", self.config.rest_api.base_url);
+
+        let headers = self.auth_headers(session);
+
+        let response = make_http_request(
+            &self.client,
+            self.rate_limiter.clone(),
+            Method::GET,
+            &url,
+            header_refs(&headers),
+            &None::<()>,
+            RetryConfig::with_max_retries(3),
+        )
+        .await?;
+        read_json(response).await
+    }
+
+    /// Moves a fresh session to the configured account when it differs
+    ///
+    /// Skipped when `auto_switch_account` is off or no account is configured.
+    /// CST sessions are switched with `PUT /session`; OAuth sessions select the
+    /// account per request through the `IG-ACCOUNT-ID` header, so the account
+    /// is only recorded on the session.
+    async fn switch_to_configured_account(&self, session: Session) -> Result<Session, AppError> {
+        let credentials = &self.config.credentials;
+        let Some(account_id) = credentials.configured_account_id() else {
+            return Ok(session);
+        };
+        if !credentials.auto_switch_account || session.account_id == account_id {
+            return Ok(session);
+        }
+
+        if session.is_oauth() {
+            info!("Using configured account {} for OAuth session", account_id);
+            let mut session = session;
+            session.account_id = account_id.to_string();
+            session.can_trade = None;
+            *self.session.write().await = Some(session.clone());
+            return Ok(session);
+        }
+
+        self.switch_account(account_id, None).await
     }
 
     /// Performs login using API v2 (CST/X-SECURITY-TOKEN) with automatic retry on rate limit
@@ -316,7 +485,7 @@ impl Auth {
             x_ig_api_key,
         };
 
-        let mut response: SessionResponse = response.json().await?;
+        let mut response: SessionResponse = read_json(response).await?;
         let session = response.get_session_v2(&security_headers);
 
         Ok(session)
@@ -350,7 +519,7 @@ impl Auth {
         )
         .await?;
 
-        let response: SessionResponse = response.json().await?;
+        let response: SessionResponse = read_json(response).await?;
         let session = response.get_session();
         assert!(session.is_oauth());
 
@@ -371,15 +540,15 @@ impl Auth {
         };
 
         if let Some(sess) = current_session {
-            if sess.is_expired(Some(1)) {
+            if sess.is_expired(Some(OAUTH_EXPIRY_MARGIN_SECS)) {
                 debug!("Session expired, performing login");
-                self.login().await
+                self.relogin().await
             } else {
                 Ok(sess)
             }
         } else {
             warn!("No session to refresh, performing login");
-            self.login().await
+            self.relogin().await
         }
     }
 
@@ -462,22 +631,144 @@ impl Auth {
         // After switching, update the session
         let mut new_session = current_session.clone();
         new_session.account_id = account_id.to_string();
+        new_session.can_trade = None;
 
         let mut session = self.session.write().await;
         *session = Some(new_session.clone());
+        drop(session);
 
         info!("✓ Switched to account: {}", account_id);
-        Ok(new_session)
+        Ok(self.with_trading_permission(new_session).await)
     }
 
-    /// Logs out and clears the current session
+    /// Fetches the details of the given session from `GET /session`
+    ///
+    /// IG may move a session to a different Lightstreamer endpoint during long
+    /// uptimes; when the returned endpoint differs from the stored session's,
+    /// the stored session is updated so that new stream connections use it.
+    ///
+    /// # Arguments
+    /// * `session` - Session whose tokens authenticate the request
+    ///
+    /// # Returns
+    /// * `Ok(SessionDetails)` - Account, client, timezone and streaming endpoint
+    /// * `Err(AppError)` - If the request fails
+    pub async fn get_session_details(&self, session: &Session) -> Result<SessionDetails, AppError> {
+        let url = format!("{}/session", self.config.rest_api.base_url);
+
+        let headers = self.auth_headers(session);
+
+        let response = make_http_request(
+            &self.client,
+            self.rate_limiter.clone(),
+            Method::GET,
+            &url,
+            header_refs(&headers),
+            &None::<()>,
+            RetryConfig::infinite(),
+        )
+        .await?;
+        let details: SessionDetails = read_json(response).await?;
+
+        if details.lightstreamer_endpoint != session.lightstreamer_endpoint {
+            let mut stored = self.session.write().await;
+            if let Some(current) = stored
+                .as_mut()
+                .filter(|current| current.account_id == details.account_id)
+            {
+                info!(
+                    "Lightstreamer endpoint changed to {}",
+                    details.lightstreamer_endpoint
+                );
+                current.lightstreamer_endpoint = details.lightstreamer_endpoint.clone();
+            }
+        }
+
+        Ok(details)
+    }
+
+    /// Logs out with `DELETE /session` and clears the current session
+    ///
+    /// A session IG no longer recognises, such as an expired one, is treated
+    /// as already logged out. Without a stored session nothing is sent.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the session was closed or had already expired
+    /// * `Err(AppError)` - If IG could not be reached; the session is kept
     pub async fn logout(&self) -> Result<(), AppError> {
+        let Some(session) = self.session.read().await.clone() else {
+            debug!("No session to log out");
+            return Ok(());
+        };
         info!("Logging out");
 
-        let mut session = self.session.write().await;
-        *session = None;
+        let url = format!("{}/session", self.config.rest_api.base_url);
+        let headers = self.auth_headers(&session);
+
+        match make_http_request(
+            &self.client,
+            self.rate_limiter.clone(),
+            Method::DELETE,
+            &url,
+            header_refs(&headers),
+            &None::<()>,
+            RetryConfig::with_max_retries(3),
+        )
+        .await
+        {
+            Ok(_) => {}
+            Err(e) if is_session_gone(&e) => {
+                warn!("Session had already expired at logout: {}", e);
+            }
+            Err(e) => {
+                error!("Logout failed: {}", e);
+                return Err(e);
+            }
+        }
+
+        let mut stored = self.session.write().await;
+        if stored
+            .as_ref()
+            .is_some_and(|current| current.account_id == session.account_id)
+        {
+            *stored = None;
+        }
 
         info!("✓ Logged out successfully");
         Ok(())
     }
 }
+
+/// Borrows owned headers in the form `make_http_request` takes
+fn header_refs(headers: &[(&'static str, String)]) -> Vec<(&'static str, &str)> {
+    headers
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .collect()
+}
+
+/// Returns true if IG rejected a request because the session no longer exists
+///
+/// Only 401 and 404 mean the session is gone; a 403 is a refusal of a live
+/// session, such as a rate limit, and leaves it in place.
+fn is_session_gone(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::Unauthorized
+            | AppError::OAuthTokenExpired
+            | AppError::Unexpected(
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::NOT_FOUND
+            )
+    )
+}
+
+#[async_trait]
+impl IgAuthenticator for Auth {
+    async fn websocket_info(&self) -> Result<WebsocketInfo, AppError> {
+        Ok(self.get_session().await?.get_websocket_info())
+    }
+
+    async fn refresh(&self) -> Result<WebsocketInfo, AppError> {
+        Ok(self.relogin().await?.get_websocket_info())
+    }
+}
@@ -4,31 +4,63 @@
    Date: 19/10/25
 ******************************************************************************/
 use crate::application::auth::WebsocketInfo;
+use crate::application::config::{ApiVersions, Config};
 use crate::application::interfaces::account::AccountService;
+use crate::application::interfaces::listener::Listener;
 use crate::application::interfaces::market::MarketService;
 use crate::application::interfaces::order::OrderService;
+use crate::application::rate_limiter::{RateLimiterRegistry, RateLimiterStats};
+use crate::application::spread_fetcher;
+use crate::application::streaming::StreamConnectionState;
+use crate::constants::{
+    CONFIRM_POLL_INITIAL_DELAY_MS, CONFIRM_POLL_MAX_DELAY_MS, DB_ENTRIES_EXPIRY_CONCURRENCY,
+    HISTORICAL_PRICES_MAX_POINTS_PER_REQUEST, MARKET_STATE_CACHE_TTL_MS,
+    MAX_MARKET_DETAILS_CACHE_ENTRIES, MAX_MARKET_DETAILS_EPICS, PARTIAL_CLOSE_CONFIRM_TIMEOUT_SECS,
+    REALIZED_PNL_LOOKBACK_HOURS, STRATEGY_LEG_CONFIRM_TIMEOUT_SECS, TRADE_UPDATES_CHANNEL_CAPACITY,
+    TRANSACTIONS_DATE_FORMAT,
+};
 use crate::error::AppError;
-use crate::model::http::HttpClient;
+use crate::model::auth::SessionDetails;
+use crate::model::http::{HttpClient, RequestRecord};
 use crate::model::requests::RecentPricesRequest;
 use crate::model::requests::{
-    ClosePositionRequest, CreateOrderRequest, CreateWorkingOrderRequest, UpdatePositionRequest,
+    ClosePositionRequest, CreateOrderRequest, CreateWorkingOrderRequest, UpdateApplicationRequest,
+    UpdatePositionRequest,
 };
 use crate::model::responses::{
-    ClosePositionResponse, CreateOrderResponse, CreateWorkingOrderResponse, UpdatePositionResponse,
+    ApplicationDetails, ClosePositionResponse, CreateOrderResponse, CreateWorkingOrderResponse,
+    PartialCloseResult, StrategyResult, UpdatePositionResponse,
 };
 use crate::model::responses::{
-    DBEntryResponse, HistoricalPricesResponse, MarketNavigationResponse, MarketSearchResponse,
-    MultipleMarketDetailsResponse,
+    DBEntriesReport, DBEntryResponse, HistoricalPricesResponse, MarketNavigationResponse,
+    MarketSearchResponse, MultipleMarketDetailsResponse,
 };
+use crate::model::utils::split_historical_range;
 use crate::prelude::{
-    AccountActivityResponse, AccountsResponse, OrderConfirmationResponse, PositionsResponse,
-    TransactionHistoryResponse, WorkingOrdersResponse,
+    Account, AccountActivityResponse, AccountsResponse, OrderConfirmationResponse, PageData,
+    PortfolioSummary, PositionsResponse, SprintPositionsResponse, TransactionHistoryResponse,
+    TransactionMetadata, WorkingOrdersResponse,
+};
+use crate::presentation::account::Position;
+use crate::presentation::market::{
+    MarketData, MarketDetails, MarketNavigationNode, MarketOrdering, MarketState,
+    PresentationMarketData,
 };
-use crate::presentation::market::{MarketData, MarketDetails};
+use crate::presentation::order::Direction;
+use crate::presentation::trade::TradeData;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use futures::stream::{self, StreamExt};
 use serde_json::Value;
-use std::sync::Arc;
-use tracing::{debug, info};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
 
 /// Main client for interacting with IG Markets API
 ///
@@ -36,6 +68,32 @@ use tracing::{debug, info};
 /// including market data, account management, and order execution.
 pub struct Client {
     http_client: Arc<HttpClient>,
+    trade_updates: broadcast::Sender<TradeData>,
+    trade_stream_active: Arc<AtomicBool>,
+    market_details_cache: Mutex<HashMap<String, CachedMarketDetails>>,
+    market_details_ttl: Duration,
+    market_states: Arc<Mutex<HashMap<String, CachedMarketState>>>,
+    account_ids: Mutex<HashSet<String>>,
+    clock_offset: Mutex<Option<chrono::Duration>>,
+}
+
+/// Market state known to the client
+///
+/// States pushed by the streaming API have no fetch time and are kept until
+/// replaced, since the `MARKET_STATE` field is only pushed when it changes.
+#[derive(Debug, Clone)]
+struct CachedMarketState {
+    state: MarketState,
+    fetched_at: Option<Instant>,
+}
+
+/// Market details kept by the client's in-process cache
+#[derive(Debug, Clone)]
+pub struct CachedMarketDetails {
+    /// Details as returned by IG
+    pub details: MarketDetails,
+    /// When the details were fetched
+    pub fetched_at: DateTime<Utc>,
 }
 
 impl Client {
@@ -44,8 +102,470 @@ impl Client {
     /// # Returns
     /// A new Client with default configuration
     pub fn new() -> Self {
-        let http_client = Arc::new(HttpClient::default());
-        Self { http_client }
+        Self::from_http_client(HttpClient::default())
+    }
+
+    /// Creates a new client from an explicit configuration
+    ///
+    /// Use together with `Config::builder()` when credentials are not provided
+    /// through environment variables.
+    ///
+    /// # Arguments
+    /// * `config` - Configuration containing credentials and API settings
+    ///
+    /// # Returns
+    /// A new Client that authenticates lazily on the first request
+    pub fn with_config(config: Config) -> Self {
+        Self::from_http_client(HttpClient::new_lazy(config))
+    }
+
+    /// Creates a new client whose rate limits are shared through a registry
+    ///
+    /// For processes that manage several IG accounts or API keys: clients with
+    /// the same API key share one request budget, clients with different keys
+    /// do not interfere with each other. Single-client programs can keep using
+    /// `with_config`, which gives the client limiters of its own.
+    ///
+    /// # Arguments
+    /// * `config` - Configuration containing credentials and API settings
+    /// * `registry` - Rate limiters keyed by API key
+    ///
+    /// # Returns
+    /// A new Client that authenticates lazily on the first request
+    pub fn with_rate_limiters(config: Config, registry: &RateLimiterRegistry) -> Self {
+        Self::from_http_client(HttpClient::with_rate_limiters(config, registry))
+    }
+
+    fn from_http_client(http_client: HttpClient) -> Self {
+        let market_details_ttl =
+            Duration::from_secs(http_client.config().rest_api.market_details_cache_ttl);
+        let http_client = Arc::new(http_client);
+        let (trade_updates, _) = broadcast::channel(TRADE_UPDATES_CHANNEL_CAPACITY);
+        Self {
+            http_client,
+            trade_updates,
+            trade_stream_active: Arc::new(AtomicBool::new(false)),
+            market_details_cache: Mutex::new(HashMap::new()),
+            market_details_ttl,
+            market_states: Arc::new(Mutex::new(HashMap::new())),
+            account_ids: Mutex::new(HashSet::new()),
+            clock_offset: Mutex::new(None),
+        }
+    }
+
+    /// Fetches the current time on IG's servers
+    ///
+    /// Also measures how far the local clock is from the server's, for use by
+    /// `clock_offset` and `server_now`. The server time is compared with the
+    /// midpoint between sending the request and receiving the response, so
+    /// the round trip does not count as clock drift. See
+    /// `HttpClient::server_time` for the precision of the result.
+    ///
+    /// # Returns
+    /// * `Ok(DateTime<Utc>)` - Server time
+    /// * `Err(AppError)` - If the request fails or the response carries no date
+    pub async fn server_time(&self) -> Result<DateTime<Utc>, AppError> {
+        let sent = Utc::now();
+        let server_time = self.http_client.server_time().await?;
+        let offset = server_time - midpoint(sent, Utc::now());
+        debug!("Server clock offset: {} ms", offset.num_milliseconds());
+        *self
+            .clock_offset
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(offset);
+        Ok(server_time)
+    }
+
+    /// Server time minus local time, as last measured by `server_time`
+    ///
+    /// Positive when the local clock is behind IG's. `None` until
+    /// `server_time` has succeeded once.
+    pub fn clock_offset(&self) -> Option<chrono::Duration> {
+        *self
+            .clock_offset
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Current time corrected by the measured clock offset
+    ///
+    /// Falls back to the local clock until `server_time` has been called. Use
+    /// it instead of `Utc::now()` for decisions near market open, close or
+    /// expiry.
+    pub fn server_now(&self) -> DateTime<Utc> {
+        Utc::now() + self.clock_offset().unwrap_or_else(chrono::Duration::zero)
+    }
+
+    /// Time used for the client's own time checks
+    ///
+    /// `server_now` if `RestApiConfig::use_server_clock` is set, the local
+    /// clock otherwise.
+    fn now(&self) -> DateTime<Utc> {
+        if self.http_client.config().rest_api.use_server_clock {
+            self.server_now()
+        } else {
+            Utc::now()
+        }
+    }
+
+    /// Returns the cached market details for an epic, including when they were fetched
+    ///
+    /// Expired entries are not returned.
+    pub fn cached_market_details(&self, epic: &str) -> Option<CachedMarketDetails> {
+        let ttl = chrono::Duration::from_std(self.market_details_ttl).ok()?;
+        self.market_details_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(epic)
+            .filter(|cached| Utc::now() - cached.fetched_at < ttl)
+            .cloned()
+    }
+
+    /// Stores freshly fetched market details, replacing any entry for the epic
+    ///
+    /// The cache holds at most `MAX_MARKET_DETAILS_CACHE_ENTRIES` epics: expired
+    /// entries are dropped first, then the oldest one if it is still full.
+    fn cache_market_details(&self, epic: &str, details: &MarketDetails) {
+        if self.market_details_ttl.is_zero() {
+            return;
+        }
+        let now = Utc::now();
+        let mut cache = self
+            .market_details_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !cache.contains_key(epic) && cache.len() >= MAX_MARKET_DETAILS_CACHE_ENTRIES {
+            if let Ok(ttl) = chrono::Duration::from_std(self.market_details_ttl) {
+                cache.retain(|_, cached| now - cached.fetched_at < ttl);
+            }
+            if cache.len() >= MAX_MARKET_DETAILS_CACHE_ENTRIES
+                && let Some(oldest) = cache
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.fetched_at)
+                    .map(|(epic, _)| epic.clone())
+            {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(
+            epic.to_string(),
+            CachedMarketDetails {
+                details: details.clone(),
+                fetched_at: now,
+            },
+        );
+    }
+
+    /// Removes every entry from the market details cache
+    pub fn clear_market_details_cache(&self) {
+        self.market_details_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+
+    /// Creates a listener that keeps `market_state` current from the stream
+    ///
+    /// Attach it to a `MARKET:{epic}` subscription that includes the
+    /// `MARKET_STATE` field; `market_state` and `is_tradeable` then answer for
+    /// that epic without any REST call. Call `clear_market_states` when the
+    /// streaming connection drops, as state changes may have been missed.
+    ///
+    /// # Returns
+    /// * `Listener<PresentationMarketData>` ready to be attached to a Lightstreamer subscription
+    pub fn market_state_listener(&self) -> Listener<PresentationMarketData> {
+        let states = Arc::clone(&self.market_states);
+        Listener::new(move |update: &PresentationMarketData| {
+            if let Some(state) = update.fields.market_state.clone() {
+                let epic = update
+                    .item_name
+                    .strip_prefix("MARKET:")
+                    .unwrap_or(&update.item_name);
+                states
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .insert(
+                        epic.to_string(),
+                        CachedMarketState {
+                            state,
+                            fetched_at: None,
+                        },
+                    );
+            }
+            Ok(())
+        })
+    }
+
+    /// Forgets every known market state, streamed or fetched
+    pub fn clear_market_states(&self) {
+        self.market_states
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+
+    /// API version used for each group of endpoints, from the configuration
+    pub fn api_versions(&self) -> ApiVersions {
+        self.http_client.config().rest_api.api_versions
+    }
+
+    /// Streams the market details of many epics as each batch completes
+    ///
+    /// Epics are fetched in batches of at most 50 with the multiple market
+    /// details endpoint, one batch at a time, every request going through the
+    /// rate limiter. Results are yielded in input order as soon as their batch
+    /// returns, so large watchlists can be shown progressively. If a batch
+    /// request fails, its epics are fetched one by one so that a single bad
+    /// epic does not fail the whole batch, unless it was rejected by the rate
+    /// limit or maintenance, which every epic in it then reports. Epics
+    /// unknown to IG yield `AppError::NotFound`. `SpreadFetcher` does the same
+    /// paced over a time window, for lists too large to fetch in one burst.
+    ///
+    /// # Arguments
+    /// * `epics` - Epics to snapshot
+    ///
+    /// # Returns
+    /// * A stream of `(epic, result)` pairs, one per input epic
+    pub fn snapshot_all(
+        &self,
+        epics: Vec<String>,
+    ) -> impl Stream<Item = (String, Result<MarketDetails, AppError>)> + '_ {
+        spread_fetcher::snapshot_all(self, epics)
+    }
+
+    /// Walks the market navigation tree lazily, yielding each node as it is fetched
+    ///
+    /// Unlike `get_all_markets`, nothing is accumulated: each node is requested
+    /// only when the stream is polled, so callers can persist results
+    /// incrementally and drop the stream to stop early. Nodes are visited
+    /// breadth-first. A node that fails to load yields its error and the walk
+    /// carries on with the remaining nodes; its children are not visited.
+    ///
+    /// The starting node is yielded with an empty name, since IG only reports
+    /// names in the parent's listing; the top level also has an empty ID.
+    ///
+    /// # Arguments
+    /// * `root` - Node to start from, or `None` for the top level
+    /// * `max_depth` - Levels of children to descend below `root`; `0` yields
+    ///   only `root` itself
+    ///
+    /// # Returns
+    /// * A stream of `(node, contents)` pairs in traversal order
+    pub fn market_navigation_stream(
+        &self,
+        root: Option<&str>,
+        max_depth: usize,
+    ) -> impl Stream<Item = Result<(MarketNavigationNode, MarketNavigationResponse), AppError>> + '_
+    {
+        let start = MarketNavigationNode {
+            id: root.unwrap_or_default().to_string(),
+            name: String::new(),
+        };
+        let pending = VecDeque::from([(start, 0usize)]);
+        stream::unfold(pending, move |mut pending| async move {
+            let (node, depth) = pending.pop_front()?;
+            let result = if node.id.is_empty() {
+                self.get_market_navigation().await
+            } else {
+                self.get_market_navigation_node(&node.id).await
+            };
+            match result {
+                Ok(response) => {
+                    if depth < max_depth {
+                        pending.extend(
+                            response
+                                .nodes
+                                .iter()
+                                .cloned()
+                                .map(|child| (child, depth + 1)),
+                        );
+                    }
+                    Some((Ok((node, response)), pending))
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to get market navigation node '{}' at depth {}: {:?}",
+                        node.id, depth, e
+                    );
+                    Some((Err(e), pending))
+                }
+            }
+        })
+    }
+
+    /// Creates a listener that forwards trade stream updates to this client
+    ///
+    /// Add the returned listener to a `TRADE:{account_id}` subscription so that
+    /// `confirm_deal` can resolve confirmations from `CONFIRMS` pushes as well as
+    /// by polling REST. Pushes are only awaited once the stream's connection
+    /// state, passed to `follow_trade_stream`, reports it connected.
+    ///
+    /// # Returns
+    /// * `Listener<TradeData>` ready to be attached to a Lightstreamer subscription
+    pub fn trade_listener(&self) -> Listener<TradeData> {
+        let sender = self.trade_updates.clone();
+        Listener::new(move |update: &TradeData| {
+            // No receivers simply means nobody is waiting for a confirmation
+            let _ = sender.send(update.clone());
+            Ok(())
+        })
+    }
+
+    /// Waits for confirmations on the trade stream only while it is connected
+    ///
+    /// Spawns a task following the streaming connection state, usually
+    /// `SubscriptionManager::connection_state`. While the stream is not
+    /// connected, and once the state's sender is dropped, `confirm_deal` polls
+    /// REST straight away instead of first waiting for a push that cannot
+    /// arrive. Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// * `state` - Connection state of the stream carrying the trade subscription
+    pub fn follow_trade_stream(&self, mut state: watch::Receiver<StreamConnectionState>) {
+        let active = Arc::clone(&self.trade_stream_active);
+        tokio::spawn(async move {
+            loop {
+                let live = state.borrow_and_update().is_live();
+                active.store(live, Ordering::Release);
+                if state.changed().await.is_err() {
+                    debug!("Trade stream state closed, polling confirmations from now on");
+                    active.store(false, Ordering::Release);
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Subscribes to streamed trade updates if the trade stream is live
+    fn trade_updates_if_active(&self) -> Option<broadcast::Receiver<TradeData>> {
+        self.trade_stream_active
+            .load(Ordering::Acquire)
+            .then(|| self.trade_updates.subscribe())
+    }
+
+    /// The order with its expiry filled in, if missing and `fill_missing_expiry` is set
+    async fn with_order_expiry<'a>(
+        &self,
+        order: &'a CreateOrderRequest,
+    ) -> Result<Cow<'a, CreateOrderRequest>, AppError> {
+        if order.expiry.is_some() || !self.http_client.config().rest_api.fill_missing_expiry {
+            return Ok(Cow::Borrowed(order));
+        }
+        let mut filled = order.clone();
+        filled.expiry = Some(self.order_expiry(&order.epic).await?);
+        Ok(Cow::Owned(filled))
+    }
+
+    /// Fails early when the session's account is known not to be able to trade
+    ///
+    /// The flag is fetched at login (`Session::can_trade`); when it is unknown
+    /// the order is sent and IG decides.
+    async fn ensure_can_trade(&self) -> Result<(), AppError> {
+        let session = self.http_client.get_session().await?;
+        if session.can_trade == Some(false) {
+            return Err(AppError::TradingNotAllowed(format!(
+                "account {} cannot trade",
+                session.account_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Looks for the open position of a deal whose confirmation timed out
+    ///
+    /// # Returns
+    /// * `Some(Position)` - The deal filled and is still open
+    /// * `None` - No open position has this deal reference, or the lookup failed
+    async fn find_unconfirmed_fill(&self, deal_reference: &str) -> Option<Position> {
+        match self.get_positions().await {
+            Ok(response) => response
+                .positions
+                .into_iter()
+                .find(|position| position.position.deal_reference == deal_reference),
+            Err(e) => {
+                warn!(
+                    "Could not look up unconfirmed deal {} among open positions: {}",
+                    deal_reference, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Checks that an account belongs to the user and can trade
+    ///
+    /// Known tradeable account IDs are cached; the account list is refetched
+    /// only for an ID not seen before, so fanning orders across accounts costs
+    /// one extra request in total rather than one per order.
+    async fn ensure_own_account(&self, account_id: &str) -> Result<(), AppError> {
+        let known = self
+            .account_ids
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(account_id);
+        if known {
+            return Ok(());
+        }
+
+        let accounts = self.get_accounts().await?;
+        let target = accounts
+            .accounts
+            .iter()
+            .find(|account| account.account_id == account_id)
+            .map(Account::can_trade);
+        *self
+            .account_ids
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = accounts
+            .accounts
+            .into_iter()
+            .filter(Account::can_trade)
+            .map(|account| account.account_id)
+            .collect();
+        match target {
+            Some(true) => Ok(()),
+            Some(false) => Err(AppError::TradingNotAllowed(format!(
+                "account {account_id} cannot trade"
+            ))),
+            None => Err(AppError::InvalidInput(format!(
+                "account {account_id} is not one of the user's accounts"
+            ))),
+        }
+    }
+
+    /// Polls `confirms/{deal_reference}` with exponential backoff until a confirmation is returned
+    async fn poll_order_confirmation(&self, deal_reference: &str) -> OrderConfirmationResponse {
+        let mut delay_ms = CONFIRM_POLL_INITIAL_DELAY_MS;
+        loop {
+            match self.get_order_confirmation(deal_reference).await {
+                Ok(confirmation) => return confirmation,
+                Err(e) => {
+                    debug!(
+                        "Confirmation for {} not available yet ({}), retrying in {} ms",
+                        deal_reference, e, delay_ms
+                    );
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            delay_ms = (delay_ms * 2).min(CONFIRM_POLL_MAX_DELAY_MS);
+        }
+    }
+
+    /// Polls `confirms/{deal_reference}` with exponential backoff until the deal is terminal
+    async fn poll_until_terminal(&self, deal_reference: &str) -> OrderConfirmationResponse {
+        let mut delay_ms = CONFIRM_POLL_INITIAL_DELAY_MS;
+        loop {
+            let confirmation = self.poll_order_confirmation(deal_reference).await;
+            if confirmation.is_terminal() {
+                return confirmation;
+            }
+            debug!(
+                "Deal {} is still {:?}, checking again in {} ms",
+                deal_reference, confirmation.status, delay_ms
+            );
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            delay_ms = (delay_ms * 2).min(CONFIRM_POLL_MAX_DELAY_MS);
+        }
     }
 
     /// Gets WebSocket connection information for Lightstreamer
@@ -55,6 +575,62 @@ impl Client {
     pub async fn get_ws_info(&self) -> WebsocketInfo {
         self.http_client.get_ws_info().await
     }
+
+    /// Gets the details of the current session, including its Lightstreamer endpoint
+    ///
+    /// Call before reconnecting a stream after a long uptime: IG can move the
+    /// session to another endpoint, and the stored session is updated to match.
+    pub async fn get_session_details(&self) -> Result<SessionDetails, AppError> {
+        self.http_client.get_session_details().await
+    }
+
+    /// Updates the status or allowances of an API key
+    ///
+    /// Calls `PUT operations/application`. Requests that disable the key must
+    /// be built with `UpdateApplicationRequest::confirm_disable`.
+    ///
+    /// # Arguments
+    /// * `req` - Fields to change for the key
+    ///
+    /// # Returns
+    /// * `Ok(ApplicationDetails)` - The key as stored by IG after the update
+    /// * `Err(AppError)` - If the request is invalid or IG rejects it
+    pub async fn update_application(
+        &self,
+        req: UpdateApplicationRequest,
+    ) -> Result<ApplicationDetails, AppError> {
+        req.validate()?;
+        if req.disables_key() {
+            warn!("Setting API key status to {:?}", req.status);
+        }
+        info!("Updating application settings");
+        let result: ApplicationDetails = self
+            .http_client
+            .put("operations/application", &req, Some(1))
+            .await?;
+        debug!("Application status is now {:?}", result.status);
+        Ok(result)
+    }
+
+    /// Registers a callback invoked after every REST request
+    ///
+    /// Each `RequestRecord` carries the correlation ID of the request, which is
+    /// also recorded in the `ig_request` tracing span and, when enabled in
+    /// `RestApiConfig`, sent to IG in the `X-Request-ID` header.
+    pub fn set_request_observer<F>(&self, observer: F)
+    where
+        F: Fn(&RequestRecord) + Send + Sync + 'static,
+    {
+        self.http_client.set_request_observer(observer);
+    }
+
+    /// Returns how long requests made by this client have waited on the rate limiter
+    ///
+    /// The breakdown by category uses the first path segment of each request,
+    /// so a large `positions` share means trading calls are being throttled.
+    pub async fn rate_limiter_stats(&self) -> RateLimiterStats {
+        self.http_client.rate_limiter_stats().await
+    }
 }
 
 impl Default for Client {
@@ -63,6 +639,74 @@ impl Default for Client {
     }
 }
 
+/// Halfway between two instants, where a server timestamp is assumed to fall
+fn midpoint(start: DateTime<Utc>, end: DateTime<Utc>) -> DateTime<Utc> {
+    start + (end - start) / 2
+}
+
+/// Resolves a confirmation from whichever of the trade stream and `poll` yields first
+///
+/// With a live stream, a matching push and `poll` race each other, so a push
+/// missed before the subscription was made only costs the polling delay.
+/// Without a stream, or once it closes, only `poll` is awaited.
+async fn stream_or_poll<P>(
+    updates: Option<broadcast::Receiver<TradeData>>,
+    deal_reference: &str,
+    accept: fn(&OrderConfirmationResponse) -> bool,
+    poll: P,
+) -> OrderConfirmationResponse
+where
+    P: Future<Output = OrderConfirmationResponse>,
+{
+    let Some(updates) = updates else {
+        return poll.await;
+    };
+    tokio::select! {
+        Some(confirmation) = wait_for_streamed_confirmation(updates, deal_reference, accept) => {
+            confirmation
+        }
+        confirmation = poll => {
+            debug!("Confirmation for {} obtained by polling", deal_reference);
+            confirmation
+        }
+    }
+}
+
+/// Waits for a `CONFIRMS` push matching the deal reference on the trade stream
+///
+/// Matching confirmations rejected by `accept` are skipped. Returns `None` if
+/// the stream is closed before a matching confirmation arrives.
+async fn wait_for_streamed_confirmation(
+    mut updates: broadcast::Receiver<TradeData>,
+    deal_reference: &str,
+    accept: fn(&OrderConfirmationResponse) -> bool,
+) -> Option<OrderConfirmationResponse> {
+    loop {
+        match updates.recv().await {
+            Ok(update) => {
+                let Some(confirms) = update.fields.confirms.as_deref() else {
+                    continue;
+                };
+                match serde_json::from_str::<OrderConfirmationResponse>(confirms) {
+                    Ok(confirmation)
+                        if confirmation.deal_reference == deal_reference
+                            && accept(&confirmation) =>
+                    {
+                        debug!("Confirmation for {} received from stream", deal_reference);
+                        return Some(confirmation);
+                    }
+                    Ok(_) => {}
+                    Err(e) => debug!("Ignoring unparseable CONFIRMS payload: {}", e),
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("Trade stream lagged, {} updates skipped", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
 #[async_trait]
 impl MarketService for Client {
     async fn search_markets(&self, search_term: &str) -> Result<MarketSearchResponse, AppError> {
@@ -74,24 +718,70 @@ impl MarketService for Client {
     }
 
     async fn get_market_details(&self, epic: &str) -> Result<MarketDetails, AppError> {
+        if let Some(cached) = self.cached_market_details(epic) {
+            debug!(
+                "Market details for {} served from cache (fetched at {})",
+                epic, cached.fetched_at
+            );
+            return Ok(cached.details);
+        }
+        self.get_market_details_fresh(epic).await
+    }
+
+    async fn get_market_details_fresh(&self, epic: &str) -> Result<MarketDetails, AppError> {
         let path = format!("markets/{epic}");
         info!("Getting market details: {}", epic);
-        let market_value: Value = self.http_client.get(&path, Some(3)).await?;
+        let market_value: Value = self
+            .http_client
+            .get(&path, Some(self.api_versions().markets))
+            .await?;
         let market_details: MarketDetails = serde_json::from_value(market_value)?;
         debug!("Market details obtained for: {}", epic);
+
+        self.cache_market_details(epic, &market_details);
         Ok(market_details)
     }
 
+    async fn order_expiry(&self, epic: &str) -> Result<String, AppError> {
+        let details = self.get_market_details(epic).await?;
+        // Rolling epics such as `MONTH1` move to the next contract after their
+        // last dealing date, so cached details past it are fetched again
+        let expired = details
+            .instrument
+            .last_dealing_datetime()
+            .is_some_and(|until| until <= self.now());
+        let expiry = if expired {
+            self.get_market_details_fresh(epic).await?.instrument.expiry
+        } else {
+            details.instrument.expiry
+        };
+        debug!("Order expiry for {}: {}", epic, expiry);
+        Ok(expiry)
+    }
+
+    async fn order_for_epic(
+        &self,
+        epic: &str,
+        direction: Direction,
+        size: f64,
+    ) -> Result<CreateOrderRequest, AppError> {
+        let market_details = self.get_market_details(epic).await?;
+        let mut order = CreateOrderRequest::smart(&market_details, direction, size)?
+            .with_size(size, self.http_client.config().rest_api.size_rounding);
+        order.expiry = Some(self.order_expiry(epic).await?);
+        Ok(order)
+    }
+
     async fn get_multiple_market_details(
         &self,
         epics: &[String],
     ) -> Result<MultipleMarketDetailsResponse, AppError> {
         if epics.is_empty() {
             return Ok(MultipleMarketDetailsResponse::default());
-        } else if epics.len() > 50 {
-            return Err(AppError::InvalidInput(
-                "The maximum number of EPICs is 50".to_string(),
-            ));
+        } else if epics.len() > MAX_MARKET_DETAILS_EPICS {
+            return Err(AppError::InvalidInput(format!(
+                "The maximum number of EPICs is {MAX_MARKET_DETAILS_EPICS}"
+            )));
         }
 
         let epics_str = epics.join(",");
@@ -101,11 +791,63 @@ impl MarketService for Client {
             epics.len()
         );
 
-        let response: MultipleMarketDetailsResponse = self.http_client.get(&path, Some(2)).await?;
+        let response: MultipleMarketDetailsResponse = self
+            .http_client
+            .get(&path, Some(self.api_versions().markets_bulk))
+            .await?;
 
         Ok(response)
     }
 
+    async fn market_state(&self, epic: &str) -> Result<MarketState, AppError> {
+        let ttl = Duration::from_millis(MARKET_STATE_CACHE_TTL_MS);
+        let known = self
+            .market_states
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(epic)
+            .filter(|cached| cached.fetched_at.is_none_or(|at| at.elapsed() < ttl))
+            .map(|cached| cached.state.clone());
+        if let Some(state) = known {
+            debug!("Market state for {} served from cache: {:?}", epic, state);
+            return Ok(state);
+        }
+
+        // The snapshot-only filter skips the instrument and dealing rules
+        let path = format!("markets?epics={epic}&filter=SNAPSHOT_ONLY");
+        let response: Value = self
+            .http_client
+            .get(&path, Some(self.api_versions().markets_bulk))
+            .await?;
+        let status = response["marketDetails"]
+            .as_array()
+            .and_then(|details| details.first())
+            .and_then(|details| details["snapshot"]["marketStatus"].as_str())
+            .ok_or(AppError::NotFound)?;
+        let state = MarketState::parse(status).unwrap_or_else(|| {
+            warn!("Unknown market status {} for {}", status, epic);
+            MarketState::Offline
+        });
+        debug!("Market state for {}: {:?}", epic, state);
+
+        self.market_states
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(epic.to_string())
+            .and_modify(|cached| {
+                // Never overwrite a state pushed by the stream in the meantime
+                if cached.fetched_at.is_some() {
+                    cached.state = state.clone();
+                    cached.fetched_at = Some(Instant::now());
+                }
+            })
+            .or_insert_with(|| CachedMarketState {
+                state: state.clone(),
+                fetched_at: Some(Instant::now()),
+            });
+        Ok(state)
+    }
+
     async fn get_historical_prices(
         &self,
         epic: &str,
@@ -118,11 +860,84 @@ impl MarketService for Client {
             epic, resolution, from, to
         );
         info!("Getting historical prices for: {}", epic);
-        let result: HistoricalPricesResponse = self.http_client.get(&path, Some(3)).await?;
+        let result: HistoricalPricesResponse = self
+            .http_client
+            .get(&path, Some(self.api_versions().prices))
+            .await?;
         debug!("Historical prices obtained for: {}", epic);
         Ok(result)
     }
 
+    async fn get_historical_prices_ranged(
+        &self,
+        epic: &str,
+        resolution: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<HistoricalPricesResponse, AppError> {
+        let ranges = split_historical_range(
+            resolution,
+            from,
+            to,
+            HISTORICAL_PRICES_MAX_POINTS_PER_REQUEST,
+        )?;
+        info!(
+            "Getting historical prices for {} from {} to {} in {} requests",
+            epic,
+            from,
+            to,
+            ranges.len()
+        );
+
+        let mut result: Option<HistoricalPricesResponse> = None;
+        let mut consumed: i64 = 0;
+        for (index, (range_from, range_to)) in ranges.iter().enumerate() {
+            let params = RecentPricesRequest::new(epic)
+                .with_resolution(resolution)
+                .with_from(range_from)
+                .with_to(range_to)
+                .with_max_points(HISTORICAL_PRICES_MAX_POINTS_PER_REQUEST as i32)
+                .with_page_size(0);
+            let response = self.get_recent_prices(&params).await?;
+            consumed += response.prices.len() as i64;
+            let remaining = response
+                .allowance
+                .as_ref()
+                .map(|allowance| allowance.remaining_allowance);
+
+            match result.as_mut() {
+                Some(acc) => {
+                    acc.extend_dedup(response);
+                }
+                None => result = Some(response),
+            }
+
+            if let Some(remaining) = remaining
+                && remaining <= 0
+                && index + 1 < ranges.len()
+            {
+                warn!(
+                    "Price allowance exhausted after {} of {} requests ({} points consumed); returning partial data for {}",
+                    index + 1,
+                    ranges.len(),
+                    consumed,
+                    epic
+                );
+                break;
+            }
+        }
+
+        let result = result
+            .ok_or_else(|| AppError::InvalidInput(format!("empty date range {from} - {to}")))?;
+        debug!(
+            "Ranged historical prices obtained for {}: {} candles, {} points consumed",
+            epic,
+            result.prices.len(),
+            consumed
+        );
+        Ok(result)
+    }
+
     async fn get_historical_prices_by_date_range(
         &self,
         epic: &str,
@@ -177,7 +992,10 @@ impl MarketService for Client {
 
         let path = format!("prices/{}{}", params.epic, query_string);
         info!("Getting recent prices for epic: {}", params.epic);
-        let result: HistoricalPricesResponse = self.http_client.get(&path, Some(3)).await?;
+        let result: HistoricalPricesResponse = self
+            .http_client
+            .get(&path, Some(self.api_versions().prices))
+            .await?;
         debug!(
             "Recent prices obtained for epic: {}, {} data points",
             params.epic,
@@ -247,7 +1065,55 @@ impl MarketService for Client {
         Ok(result)
     }
 
+    async fn get_node_markets_detailed(
+        &self,
+        node_id: &str,
+    ) -> Result<Vec<MarketDetails>, AppError> {
+        let node = self.get_market_navigation_node(node_id).await?;
+        let epics: Vec<String> = node
+            .markets
+            .iter()
+            .map(|market| market.epic.clone())
+            .filter(|epic| !epic.is_empty())
+            .collect();
+        info!(
+            "Getting full details for {} markets in node {}",
+            epics.len(),
+            node_id
+        );
+
+        let mut details = Vec::with_capacity(epics.len());
+        for chunk in epics.chunks(MAX_MARKET_DETAILS_EPICS) {
+            let response = self.get_multiple_market_details(chunk).await?;
+            details.extend(response.market_details);
+        }
+
+        debug!(
+            "{} market details obtained for node {}",
+            details.len(),
+            node_id
+        );
+        Ok(details)
+    }
+
     async fn get_all_markets(&self) -> Result<Vec<MarketData>, AppError> {
+        self.get_all_markets_cancellable(&CancellationToken::new())
+            .await
+    }
+
+    async fn get_all_markets_cancellable(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<MarketData>, AppError> {
+        self.get_all_markets_ordered(MarketOrdering::default(), cancel)
+            .await
+    }
+
+    async fn get_all_markets_ordered(
+        &self,
+        ordering: MarketOrdering,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<MarketData>, AppError> {
         let max_depth = 6;
         info!(
             "Starting comprehensive market hierarchy traversal (max {} levels)",
@@ -276,6 +1142,15 @@ impl MarketService for Client {
             );
 
             for node in &nodes_to_process {
+                if cancel.is_cancelled() {
+                    info!(
+                        "Market hierarchy traversal cancelled at level {}: returning {} markets",
+                        processed_levels,
+                        all_markets.len()
+                    );
+                    return Ok(ordering.apply(all_markets));
+                }
+
                 match self.get_market_navigation_node(&node.id).await {
                     Ok(node_response) => {
                         let node_markets = node_response.markets.len();
@@ -320,10 +1195,23 @@ impl MarketService for Client {
             processed_levels
         );
 
-        Ok(all_markets)
+        let collected = all_markets.len();
+        let markets = ordering.apply(all_markets);
+        if markets.len() < collected {
+            debug!(
+                "Removed {} duplicate markets listed under several nodes",
+                collected - markets.len()
+            );
+        }
+        Ok(markets)
     }
 
     async fn get_vec_db_entries(&self) -> Result<Vec<DBEntryResponse>, AppError> {
+        let report = self.get_vec_db_entries_with_report().await?;
+        Ok(report.entries)
+    }
+
+    async fn get_vec_db_entries_with_report(&self) -> Result<DBEntriesReport, AppError> {
         info!("Getting all markets from hierarchy for DB entries");
 
         let all_markets = self.get_all_markets().await?;
@@ -337,51 +1225,53 @@ impl MarketService for Client {
 
         info!("Created {} DB entries from markets", vec_db_entries.len());
 
-        // Collect unique symbols
-        let unique_symbols: std::collections::HashSet<String> = vec_db_entries
-            .iter()
-            .map(|entry| entry.symbol.clone())
-            .filter(|symbol| !symbol.is_empty())
-            .collect();
+        // One representative epic per unique symbol
+        let mut symbol_epics: HashMap<String, String> = HashMap::new();
+        for entry in &vec_db_entries {
+            if !entry.symbol.is_empty() {
+                symbol_epics
+                    .entry(entry.symbol.clone())
+                    .or_insert_with(|| entry.epic.clone());
+            }
+        }
 
         info!(
             "Found {} unique symbols to fetch expiry dates for",
-            unique_symbols.len()
+            symbol_epics.len()
         );
 
-        let mut symbol_expiry_map: std::collections::HashMap<String, String> =
-            std::collections::HashMap::new();
-
-        for symbol in unique_symbols {
-            if let Some(entry) = vec_db_entries
-                .iter()
-                .find(|e| e.symbol == symbol && !e.epic.is_empty())
-            {
-                match self.get_market_details(&entry.epic).await {
-                    Ok(market_details) => {
-                        let expiry_date = market_details
-                            .instrument
-                            .expiry_details
-                            .as_ref()
-                            .map(|details| details.last_dealing_date.clone())
-                            .unwrap_or_else(|| market_details.instrument.expiry.clone());
-
-                        symbol_expiry_map.insert(symbol.clone(), expiry_date);
-                        info!(
-                            "Fetched expiry date for symbol {}: {}",
-                            symbol,
-                            symbol_expiry_map.get(&symbol).unwrap()
-                        );
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            "Failed to get market details for epic {} (symbol {}): {:?}",
-                            entry.epic,
-                            symbol,
-                            e
-                        );
-                        symbol_expiry_map.insert(symbol.clone(), entry.expiry.clone());
-                    }
+        // Requests still go through the shared rate limiter; the concurrency
+        // bound only limits how many are waiting on it at once
+        let results: Vec<(String, String, Result<MarketDetails, AppError>)> =
+            stream::iter(symbol_epics)
+                .map(|(symbol, epic)| async move {
+                    let result = self.get_market_details(&epic).await;
+                    (symbol, epic, result)
+                })
+                .buffer_unordered(DB_ENTRIES_EXPIRY_CONCURRENCY)
+                .collect()
+                .await;
+
+        let mut symbol_expiry_map: HashMap<String, String> = HashMap::new();
+        let mut failures = Vec::new();
+        for (symbol, epic, result) in results {
+            match result {
+                Ok(market_details) => {
+                    let expiry_date = market_details
+                        .instrument
+                        .expiry_details
+                        .as_ref()
+                        .map(|details| details.last_dealing_date.clone())
+                        .unwrap_or_else(|| market_details.instrument.expiry.clone());
+                    debug!("Fetched expiry date for symbol {}: {}", symbol, expiry_date);
+                    symbol_expiry_map.insert(symbol, expiry_date);
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to get market details for epic {} (symbol {}): {:?}",
+                        epic, symbol, e
+                    );
+                    failures.push((symbol, e));
                 }
             }
         }
@@ -392,8 +1282,15 @@ impl MarketService for Client {
             }
         }
 
-        info!("Updated expiry dates for {} entries", vec_db_entries.len());
-        Ok(vec_db_entries)
+        info!(
+            "Updated expiry dates for {} entries ({} symbols failed)",
+            vec_db_entries.len(),
+            failures.len()
+        );
+        Ok(DBEntriesReport {
+            entries: vec_db_entries,
+            failures,
+        })
     }
 }
 
@@ -409,13 +1306,53 @@ impl AccountService for Client {
         Ok(result)
     }
 
+    async fn get_preferred_account(&self) -> Result<Option<Account>, AppError> {
+        let accounts = self.get_accounts().await?;
+        let preferred = accounts.preferred().cloned();
+        match &preferred {
+            Some(account) => debug!("Preferred account: {}", account.account_id),
+            None => warn!(
+                "No preferred account found among {} accounts",
+                accounts.accounts.len()
+            ),
+        }
+        Ok(preferred)
+    }
+
+    async fn get_portfolio_summary(&self) -> Result<PortfolioSummary, AppError> {
+        let accounts = self.get_accounts().await?;
+        let summary = PortfolioSummary::from_accounts(accounts.accounts);
+        debug!(
+            "Portfolio summary: {} accounts in {} currencies",
+            summary.accounts.len(),
+            summary.by_currency.len()
+        );
+        Ok(summary)
+    }
+
     async fn get_positions(&self) -> Result<PositionsResponse, AppError> {
         debug!("Getting open positions");
-        let result: PositionsResponse = self.http_client.get("positions", Some(2)).await?;
+        let result: PositionsResponse = self
+            .http_client
+            .get("positions", Some(self.api_versions().positions))
+            .await?;
         debug!("Positions obtained: {} positions", result.positions.len());
         Ok(result)
     }
 
+    async fn get_sprint_positions(&self) -> Result<SprintPositionsResponse, AppError> {
+        debug!("Getting open sprint market positions");
+        let result: SprintPositionsResponse = self
+            .http_client
+            .get("positions/sprintmarkets", Some(2))
+            .await?;
+        debug!(
+            "Sprint market positions obtained: {} positions",
+            result.sprint_market_positions.len()
+        );
+        Ok(result)
+    }
+
     async fn get_positions_w_filter(&self, filter: &str) -> Result<PositionsResponse, AppError> {
         debug!("Getting open positions with filter: {}", filter);
         let mut positions = self.get_positions().await?;
@@ -433,7 +1370,10 @@ impl AccountService for Client {
 
     async fn get_working_orders(&self) -> Result<WorkingOrdersResponse, AppError> {
         info!("Getting working orders");
-        let result: WorkingOrdersResponse = self.http_client.get("workingorders", Some(2)).await?;
+        let result: WorkingOrdersResponse = self
+            .http_client
+            .get("workingorders", Some(self.api_versions().working_orders))
+            .await?;
         debug!(
             "Working orders obtained: {} orders",
             result.working_orders.len()
@@ -448,7 +1388,10 @@ impl AccountService for Client {
     ) -> Result<AccountActivityResponse, AppError> {
         let path = format!("history/activity?from={}&to={}&pageSize=500", from, to);
         info!("Getting account activity");
-        let result: AccountActivityResponse = self.http_client.get(&path, Some(3)).await?;
+        let result: AccountActivityResponse = self
+            .http_client
+            .get(&path, Some(self.api_versions().activity))
+            .await?;
         debug!(
             "Account activity obtained: {} activities",
             result.activities.len()
@@ -466,7 +1409,10 @@ impl AccountService for Client {
             from, to
         );
         info!("Getting detailed account activity");
-        let result: AccountActivityResponse = self.http_client.get(&path, Some(3)).await?;
+        let result: AccountActivityResponse = self
+            .http_client
+            .get(&path, Some(self.api_versions().activity))
+            .await?;
         debug!(
             "Detailed account activity obtained: {} activities",
             result.activities.len()
@@ -478,21 +1424,42 @@ impl AccountService for Client {
         &self,
         from: &str,
         to: &str,
+    ) -> Result<TransactionHistoryResponse, AppError> {
+        self.get_transactions_cancellable(from, to, &CancellationToken::new())
+            .await
+    }
+
+    async fn get_transactions_cancellable(
+        &self,
+        from: &str,
+        to: &str,
+        cancel: &CancellationToken,
     ) -> Result<TransactionHistoryResponse, AppError> {
         const PAGE_SIZE: u32 = 200;
         let mut all_transactions = Vec::new();
         let mut current_page = 1;
-        #[allow(unused_assignments)]
         let mut last_metadata = None;
 
         loop {
+            if cancel.is_cancelled() {
+                info!(
+                    "Transaction history cancelled before page {}: returning {} transactions",
+                    current_page,
+                    all_transactions.len()
+                );
+                break;
+            }
+
             let path = format!(
                 "history/transactions?from={}&to={}&pageSize={}&pageNumber={}",
                 from, to, PAGE_SIZE, current_page
             );
             info!("Getting transaction history page {}", current_page);
 
-            let result: TransactionHistoryResponse = self.http_client.get(&path, Some(2)).await?;
+            let result: TransactionHistoryResponse = self
+                .http_client
+                .get(&path, Some(self.api_versions().transactions))
+                .await?;
 
             let total_pages = result.metadata.page_data.total_pages as u32;
             last_metadata = Some(result.metadata);
@@ -509,10 +1476,27 @@ impl AccountService for Client {
             all_transactions.len()
         );
 
+        let metadata = match last_metadata {
+            Some(metadata) => metadata,
+            // Cancelled before the first page was fetched
+            None if cancel.is_cancelled() => TransactionMetadata {
+                page_data: PageData {
+                    page_number: 0,
+                    page_size: PAGE_SIZE as i32,
+                    total_pages: 0,
+                },
+                size: 0,
+            },
+            None => {
+                return Err(AppError::InvalidInput(
+                    "Could not retrieve metadata".to_string(),
+                ));
+            }
+        };
+
         Ok(TransactionHistoryResponse {
             transactions: all_transactions,
-            metadata: last_metadata
-                .ok_or_else(|| AppError::InvalidInput("Could not retrieve metadata".to_string()))?,
+            metadata,
         })
     }
 }
@@ -523,34 +1507,140 @@ impl OrderService for Client {
         &self,
         order: &CreateOrderRequest,
     ) -> Result<CreateOrderResponse, AppError> {
+        self.ensure_can_trade().await?;
+        let order = self.with_order_expiry(order).await?;
         info!("Creating order for: {}", order.epic);
         let result: CreateOrderResponse = self
             .http_client
-            .post("positions/otc", order, Some(2))
+            .post(
+                "positions/otc",
+                &*order,
+                Some(self.api_versions().positions),
+            )
+            .await?;
+        debug!("Order created with reference: {}", result.deal_reference);
+        Ok(result)
+    }
+
+    async fn create_order_for_account(
+        &self,
+        account_id: &str,
+        order: &CreateOrderRequest,
+    ) -> Result<CreateOrderResponse, AppError> {
+        self.ensure_own_account(account_id).await?;
+        let order = self.with_order_expiry(order).await?;
+        info!(
+            "Creating order for {} on account {}",
+            order.epic, account_id
+        );
+        let result: CreateOrderResponse = self
+            .http_client
+            .post_for_account(
+                "positions/otc",
+                &*order,
+                Some(self.api_versions().positions),
+                account_id,
+            )
             .await?;
         debug!("Order created with reference: {}", result.deal_reference);
         Ok(result)
     }
 
+    async fn try_create_order(
+        &self,
+        order: &CreateOrderRequest,
+    ) -> Result<Option<CreateOrderResponse>, AppError> {
+        self.ensure_can_trade().await?;
+        let order = self.with_order_expiry(order).await?;
+        let result: Option<CreateOrderResponse> = self
+            .http_client
+            .try_post(
+                "positions/otc",
+                &*order,
+                Some(self.api_versions().positions),
+            )
+            .await?;
+        match &result {
+            Some(created) => debug!("Order created with reference: {}", created.deal_reference),
+            None => info!("Order for {} not sent: rate limited", order.epic),
+        }
+        Ok(result)
+    }
+
     async fn get_order_confirmation(
         &self,
         deal_reference: &str,
     ) -> Result<OrderConfirmationResponse, AppError> {
         let path = format!("confirms/{}", deal_reference);
         info!("Getting confirmation for order: {}", deal_reference);
-        let result: OrderConfirmationResponse = self.http_client.get(&path, Some(1)).await?;
+        let result: OrderConfirmationResponse = self
+            .http_client
+            .get(&path, Some(self.api_versions().confirms))
+            .await?;
         debug!("Confirmation obtained for order: {}", deal_reference);
         Ok(result)
     }
 
+    async fn confirm_deal(
+        &self,
+        deal_reference: &str,
+        timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError> {
+        info!("Waiting for confirmation of deal: {}", deal_reference);
+
+        let confirmation = stream_or_poll(
+            self.trade_updates_if_active(),
+            deal_reference,
+            |_| true,
+            self.poll_order_confirmation(deal_reference),
+        );
+
+        tokio::time::timeout(timeout, confirmation)
+            .await
+            .map_err(|_| {
+                AppError::Timeout(format!(
+                    "no confirmation for deal {} within {:?}",
+                    deal_reference, timeout
+                ))
+            })
+    }
+
+    async fn track_until_terminal(
+        &self,
+        deal_reference: &str,
+        timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError> {
+        info!("Tracking deal {} until it settles", deal_reference);
+
+        let confirmation = stream_or_poll(
+            self.trade_updates_if_active(),
+            deal_reference,
+            OrderConfirmationResponse::is_terminal,
+            self.poll_until_terminal(deal_reference),
+        );
+
+        tokio::time::timeout(timeout, confirmation)
+            .await
+            .map_err(|_| {
+                AppError::Timeout(format!(
+                    "deal {} did not reach a final state within {:?}",
+                    deal_reference, timeout
+                ))
+            })
+    }
+
     async fn update_position(
         &self,
         deal_id: &str,
         update: &UpdatePositionRequest,
     ) -> Result<UpdatePositionResponse, AppError> {
+        self.ensure_can_trade().await?;
         let path = format!("positions/otc/{}", deal_id);
         info!("Updating position: {}", deal_id);
-        let result: UpdatePositionResponse = self.http_client.put(&path, update, Some(2)).await?;
+        let result: UpdatePositionResponse = self
+            .http_client
+            .put(&path, update, Some(self.api_versions().positions))
+            .await?;
         debug!(
             "Position updated: {} with deal reference: {}",
             deal_id, result.deal_reference
@@ -562,6 +1652,8 @@ impl OrderService for Client {
         &self,
         close_request: &ClosePositionRequest,
     ) -> Result<ClosePositionResponse, AppError> {
+        close_request.validate()?;
+        self.ensure_can_trade().await?;
         info!("Closing position");
 
         // IG API requires POST with _method: DELETE header for closing positions
@@ -575,14 +1667,158 @@ impl OrderService for Client {
         Ok(result)
     }
 
+    async fn partial_close_and_protect(
+        &self,
+        deal_id: &str,
+        close_size: f64,
+        new_stop: Option<f64>,
+        new_limit: Option<f64>,
+    ) -> Result<PartialCloseResult, AppError> {
+        if new_stop.is_none() && new_limit.is_none() {
+            return Err(AppError::InvalidInput(
+                "a new stop or limit level is required".to_string(),
+            ));
+        }
+
+        let positions = self.get_positions().await?;
+        let position = positions
+            .positions
+            .iter()
+            .find(|position| position.position.deal_id == deal_id)
+            .ok_or(AppError::NotFound)?;
+        if close_size <= 0.0 || close_size >= position.position.size {
+            return Err(AppError::InvalidInput(format!(
+                "close size {} must be positive and less than the position size {}",
+                close_size, position.position.size
+            )));
+        }
+
+        let close_request = ClosePositionRequest::market(
+            deal_id.to_string(),
+            position.position.direction.opposite(),
+            close_size,
+        );
+        let closed = self.close_position(&close_request).await?;
+        let close = self
+            .confirm_deal(
+                &closed.deal_reference,
+                Duration::from_secs(PARTIAL_CLOSE_CONFIRM_TIMEOUT_SECS),
+            )
+            .await?;
+
+        let mut result = PartialCloseResult { close, amend: None };
+        if !result.is_closed() {
+            warn!(
+                "Partial close of {} was not accepted ({}), stop/limit left unchanged",
+                deal_id,
+                result.close.reason.as_deref().unwrap_or("no reason given")
+            );
+            return Ok(result);
+        }
+
+        let update = UpdatePositionRequest {
+            stop_level: new_stop,
+            limit_level: new_limit,
+            trailing_stop: None,
+            trailing_stop_distance: None,
+        };
+        let amend = self.update_position(deal_id, &update).await;
+        if let Err(e) = &amend {
+            warn!(
+                "Closed {} of {} but failed to amend stop/limit on the remainder: {}",
+                close_size, deal_id, e
+            );
+        }
+        result.amend = Some(amend);
+        Ok(result)
+    }
+
+    async fn place_strategy(
+        &self,
+        legs: &[CreateOrderRequest],
+    ) -> Result<StrategyResult, AppError> {
+        if legs.is_empty() {
+            return Err(AppError::InvalidInput(
+                "a strategy needs at least one leg".to_string(),
+            ));
+        }
+        info!("Placing strategy with {} legs", legs.len());
+
+        let timeout = Duration::from_secs(STRATEGY_LEG_CONFIRM_TIMEOUT_SECS);
+        let mut result = StrategyResult {
+            requested: legs.len(),
+            legs: Vec::with_capacity(legs.len()),
+            unconfirmed_fills: Vec::new(),
+        };
+        for (index, leg) in legs.iter().enumerate() {
+            let outcome = match self.create_order(leg).await {
+                Ok(created) => {
+                    let outcome = self.confirm_deal(&created.deal_reference, timeout).await;
+                    if let Err(AppError::Timeout(_)) = &outcome
+                        && let Some(position) =
+                            self.find_unconfirmed_fill(&created.deal_reference).await
+                    {
+                        result.unconfirmed_fills.push(position);
+                    }
+                    outcome
+                }
+                Err(e) => Err(e),
+            };
+            let accepted = outcome
+                .as_ref()
+                .is_ok_and(OrderConfirmationResponse::is_accepted);
+            if !accepted {
+                warn!(
+                    "Strategy leg {} of {} on {} was not filled; {} legs not sent",
+                    index + 1,
+                    legs.len(),
+                    leg.epic,
+                    legs.len() - index - 1
+                );
+            }
+            result.legs.push(outcome);
+            if !accepted {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    async fn unwind_strategy(
+        &self,
+        result: &StrategyResult,
+    ) -> Vec<Result<ClosePositionResponse, AppError>> {
+        let requests = result.unwind_requests();
+        info!("Unwinding {} filled strategy legs", requests.len());
+        let mut closed = Vec::with_capacity(requests.len());
+        for request in &requests {
+            let outcome = self.close_position(request).await;
+            if let Err(e) = &outcome {
+                warn!(
+                    "Failed to unwind strategy leg {}: {}",
+                    request.deal_id.as_deref().unwrap_or_default(),
+                    e
+                );
+            }
+            closed.push(outcome);
+        }
+        closed
+    }
+
     async fn create_working_order(
         &self,
         order: &CreateWorkingOrderRequest,
     ) -> Result<CreateWorkingOrderResponse, AppError> {
+        order.validate()?;
+        self.ensure_can_trade().await?;
         info!("Creating working order for: {}", order.epic);
         let result: CreateWorkingOrderResponse = self
             .http_client
-            .post("workingorders/otc", order, Some(2))
+            .post(
+                "workingorders/otc",
+                order,
+                Some(self.api_versions().working_orders),
+            )
             .await?;
         debug!(
             "Working order created with reference: {}",
@@ -590,4 +1826,477 @@ impl OrderService for Client {
         );
         Ok(result)
     }
+
+    async fn realized_pnl_for(&self, deal_reference: &str) -> Result<Option<f64>, AppError> {
+        let confirmation = self.get_order_confirmation(deal_reference).await?;
+        if let Some(profit) = confirmation.profit {
+            return Ok(Some(profit));
+        }
+        let Some(deal_id) = confirmation.deal_id else {
+            debug!("Confirmation for {} has no deal ID yet", deal_reference);
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        let from = (now - chrono::Duration::hours(REALIZED_PNL_LOOKBACK_HOURS))
+            .format(TRANSACTIONS_DATE_FORMAT)
+            .to_string();
+        let to = now.format(TRANSACTIONS_DATE_FORMAT).to_string();
+        let history = self.get_transactions(&from, &to).await?;
+
+        let pnl = history
+            .transactions
+            .iter()
+            .find(|transaction| transaction.is_for_deal(&deal_id))
+            .and_then(|transaction| transaction.profit_and_loss_value());
+        if pnl.is_none() {
+            debug!("No transaction posted yet for deal {}", deal_id);
+        }
+        Ok(pnl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn confirmation(deal_reference: &str, deal_status: &str) -> OrderConfirmationResponse {
+        serde_json::from_value(serde_json::json!({
+            "date": "2025-10-14T08:12:44.421",
+            "status": "OPEN",
+            "reason": "SUCCESS",
+            "dealStatus": deal_status,
+            "dealReference": deal_reference,
+            "dealId": "DIAAAAREF1"
+        }))
+        .unwrap()
+    }
+
+    /// Poll that only yields long after any test has finished
+    async fn slow_poll() -> OrderConfirmationResponse {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        panic!("streamed confirmation lost the race to a slow poll")
+    }
+
+    fn confirms_push(confirmation: &OrderConfirmationResponse) -> TradeData {
+        let mut update = TradeData::default();
+        update.fields.confirms = Some(serde_json::to_string(confirmation).unwrap());
+        update
+    }
+
+    #[tokio::test]
+    async fn test_streamed_confirmation_wins_over_slow_poll() {
+        let (sender, receiver) = broadcast::channel(8);
+        sender
+            .send(confirms_push(&confirmation("OTHER-REF", "REJECTED")))
+            .unwrap();
+        sender
+            .send(confirms_push(&confirmation("DEAL-REF", "ACCEPTED")))
+            .unwrap();
+
+        let confirmed = stream_or_poll(Some(receiver), "DEAL-REF", |_| true, slow_poll()).await;
+        assert_eq!(confirmed.deal_reference, "DEAL-REF");
+        assert_eq!(confirmed.deal_status.as_deref(), Some("ACCEPTED"));
+    }
+
+    #[tokio::test]
+    async fn test_polled_confirmation_does_not_wait_for_the_stream() {
+        let (_sender, receiver) = broadcast::channel::<TradeData>(8);
+        let started = Instant::now();
+
+        let confirmed = stream_or_poll(Some(receiver), "DEAL-REF", |_| true, async {
+            confirmation("DEAL-REF", "ACCEPTED")
+        })
+        .await;
+        assert_eq!(confirmed.deal_reference, "DEAL-REF");
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        let (sender, closed) = broadcast::channel::<TradeData>(8);
+        drop(sender);
+        let polled_after_close = stream_or_poll(Some(closed), "DEAL-REF", |_| true, async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            confirmation("DEAL-REF", "ACCEPTED")
+        })
+        .await;
+        assert_eq!(polled_after_close.deal_reference, "DEAL-REF");
+
+        let polled_without_stream = stream_or_poll(None, "DEAL-REF", |_| true, async {
+            confirmation("DEAL-REF", "ACCEPTED")
+        })
+        .await;
+        assert_eq!(polled_without_stream.deal_reference, "DEAL-REF");
+    }
+
+    #[tokio::test]
+    async fn test_trade_stream_inactive_after_disconnect() {
+        let client = Client::new();
+        let _listener = client.trade_listener();
+        // Attaching the listener alone does not make the stream live
+        assert!(client.trade_updates_if_active().is_none());
+        let (state, receiver) = watch::channel(StreamConnectionState::Connected);
+        client.follow_trade_stream(receiver);
+        tokio::task::yield_now().await;
+        assert!(client.trade_updates_if_active().is_some());
+
+        state.send_replace(StreamConnectionState::Disconnected { reason: None });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(client.trade_updates_if_active().is_none());
+
+        state.send_replace(StreamConnectionState::Connected);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(client.trade_updates_if_active().is_some());
+
+        drop(state);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(client.trade_updates_if_active().is_none());
+    }
+
+    /// Client without rate limiting that sends its requests to `base_url`
+    fn client_at(builder: crate::application::config::ConfigBuilder, base_url: String) -> Client {
+        let mut config = builder
+            .username("user")
+            .password("secret")
+            .api_key("key")
+            .account_id("ABC123")
+            .build()
+            .unwrap();
+        config.rest_api.base_url = base_url;
+        config.disable_rate_limiting();
+        Client::with_config(config)
+    }
+
+    /// Client whose every request fails, as nothing listens at its base URL
+    fn offline_client(builder: crate::application::config::ConfigBuilder) -> Client {
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        client_at(builder, base_url)
+    }
+
+    /// Serves `body` as JSON to every connection and returns the base URL
+    fn serve_json(body: String) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => head.extend_from_slice(&buf[..read]),
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        base_url
+    }
+
+    /// Client sending its requests to `base_url` with a CST session already set
+    async fn logged_in_client_at(base_url: String) -> Client {
+        let client = client_at(Config::builder(), base_url);
+        *client.http_client.auth().session_slot().write().await =
+            Some(crate::application::auth::Session {
+                account_id: "ABC123".to_string(),
+                client_id: String::new(),
+                lightstreamer_endpoint: String::new(),
+                cst: Some("cst".to_string()),
+                x_security_token: Some("token".to_string()),
+                oauth_token: None,
+                api_version: 2,
+                expires_at: Utc::now().timestamp() as u64 + 21_600,
+                can_trade: None,
+            });
+        client
+    }
+
+    /// Client whose cache holds the fixture market with expiry `DEC-25`
+    ///
+    /// Nothing listens at the client's base URL, so any request it sends fails
+    /// instead of reaching IG.
+    #[cfg(feature = "testing")]
+    fn client_with_cached_expiry(
+        builder: crate::application::config::ConfigBuilder,
+        last_dealing: DateTime<Utc>,
+    ) -> Client {
+        let client = offline_client(builder);
+        let mut details = crate::test_fixtures::market_details();
+        details.instrument.expiry = "DEC-25".to_string();
+        details.instrument.expiry_details = Some(crate::presentation::market::ExpiryDetails {
+            last_dealing_date: last_dealing.to_rfc3339(),
+            settlement_info: None,
+        });
+        client.market_details_cache.lock().unwrap().insert(
+            details.instrument.epic.clone(),
+            CachedMarketDetails {
+                details,
+                fetched_at: Utc::now(),
+            },
+        );
+        client
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_order_expiry_refetched_after_last_dealing_date() {
+        let epic = crate::test_fixtures::market_details().instrument.epic;
+
+        let current =
+            client_with_cached_expiry(Config::builder(), Utc::now() + chrono::Duration::days(30));
+        assert_eq!(current.order_expiry(&epic).await.unwrap(), "DEC-25");
+
+        // The cached contract has rolled, so the stale expiry is not served
+        let rolled =
+            client_with_cached_expiry(Config::builder(), Utc::now() - chrono::Duration::days(1));
+        assert!(rolled.order_expiry(&epic).await.is_err());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_missing_order_expiry_filled_only_when_enabled() {
+        let last_dealing = Utc::now() + chrono::Duration::days(30);
+        let epic = crate::test_fixtures::market_details().instrument.epic;
+        let mut order = CreateOrderRequest::market(epic.clone(), Direction::Buy, 1.0, None, None);
+        order.expiry = None;
+
+        let enabled =
+            client_with_cached_expiry(Config::builder().fill_missing_expiry(true), last_dealing);
+        let filled = enabled.with_order_expiry(&order).await.unwrap();
+        assert_eq!(filled.expiry.as_deref(), Some("DEC-25"));
+
+        order.expiry = Some("JUN-26".to_string());
+        let kept = enabled.with_order_expiry(&order).await.unwrap();
+        assert!(matches!(kept, Cow::Borrowed(_)));
+        assert_eq!(kept.expiry.as_deref(), Some("JUN-26"));
+
+        order.expiry = None;
+        let disabled = client_with_cached_expiry(Config::builder(), last_dealing);
+        let untouched = disabled.with_order_expiry(&order).await.unwrap();
+        assert_eq!(untouched.expiry, None);
+
+        let built = disabled
+            .order_for_epic(&epic, Direction::Sell, 2.0)
+            .await
+            .unwrap();
+        assert_eq!(built.expiry.as_deref(), Some("DEC-25"));
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_order_for_epic_rounds_with_configured_mode() {
+        use crate::utils::finance::RoundingMode;
+
+        let last_dealing = Utc::now() + chrono::Duration::days(30);
+        let epic = crate::test_fixtures::market_details().instrument.epic;
+
+        let floor = client_with_cached_expiry(Config::builder(), last_dealing);
+        let order = floor.order_for_epic(&epic, Direction::Buy, 0.259).await;
+        assert_eq!(order.unwrap().size, 0.25);
+
+        let nearest = client_with_cached_expiry(
+            Config::builder().size_rounding(RoundingMode::Nearest),
+            last_dealing,
+        );
+        let order = nearest.order_for_epic(&epic, Direction::Buy, 0.259).await;
+        assert_eq!(order.unwrap().size, 0.26);
+    }
+
+    #[test]
+    fn test_clock_offset_measured_against_round_trip_midpoint() {
+        let sent = Utc::now();
+        let received = sent + chrono::Duration::milliseconds(400);
+        let server_time = sent + chrono::Duration::milliseconds(1_200);
+        assert_eq!(
+            server_time - midpoint(sent, received),
+            chrono::Duration::milliseconds(1_000)
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_order_expiry_uses_server_clock_when_enabled() {
+        let epic = crate::test_fixtures::market_details().instrument.epic;
+        let last_dealing = Utc::now() - chrono::Duration::hours(1);
+        let behind = Some(-chrono::Duration::hours(2));
+
+        // By the server's clock the contract is still dealing, so the cached
+        // expiry is served without a request
+        let server_clock =
+            client_with_cached_expiry(Config::builder().use_server_clock(true), last_dealing);
+        *server_clock.clock_offset.lock().unwrap() = behind;
+        assert_eq!(server_clock.order_expiry(&epic).await.unwrap(), "DEC-25");
+
+        let local_clock = client_with_cached_expiry(Config::builder(), last_dealing);
+        *local_clock.clock_offset.lock().unwrap() = behind;
+        assert!(local_clock.order_expiry(&epic).await.is_err());
+    }
+
+    fn cache_market_state(client: &Client, epic: &str, state: MarketState, age: Duration) {
+        client.market_states.lock().unwrap().insert(
+            epic.to_string(),
+            CachedMarketState {
+                state,
+                fetched_at: Some(Instant::now() - age),
+            },
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetched_market_state_reused_until_ttl() {
+        let ttl = Duration::from_millis(MARKET_STATE_CACHE_TTL_MS);
+        let client = offline_client(Config::builder());
+
+        cache_market_state(&client, "EPIC.A", MarketState::Tradeable, Duration::ZERO);
+        assert_eq!(
+            client.market_state("EPIC.A").await.unwrap(),
+            MarketState::Tradeable
+        );
+        assert!(client.is_tradeable("EPIC.A").await.unwrap());
+
+        // Past the TTL the state is fetched again, which fails offline
+        cache_market_state(&client, "EPIC.A", MarketState::Tradeable, ttl * 2);
+        assert!(client.market_state("EPIC.A").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_market_state_listener_updates_the_cache() {
+        use lightstreamer_rs::subscription::{ItemUpdate, SubscriptionListener};
+
+        let client = offline_client(Config::builder());
+        let listener = client.market_state_listener();
+        let push = |state: &str| {
+            let fields = HashMap::from([("MARKET_STATE".to_string(), Some(state.to_string()))]);
+            listener.on_item_update(&ItemUpdate {
+                item_name: Some("MARKET:EPIC.A".to_string()),
+                item_pos: 1,
+                is_snapshot: false,
+                changed_fields: fields.clone(),
+                fields,
+            });
+        };
+
+        push("suspended");
+        assert_eq!(
+            client.market_state("EPIC.A").await.unwrap(),
+            MarketState::Suspended
+        );
+        assert!(!client.is_tradeable("EPIC.A").await.unwrap());
+
+        // A fetched state older than the TTL does not hide a later push
+        cache_market_state(
+            &client,
+            "EPIC.A",
+            MarketState::Closed,
+            Duration::from_millis(MARKET_STATE_CACHE_TTL_MS * 2),
+        );
+        push("tradeable");
+        assert!(client.is_tradeable("EPIC.A").await.unwrap());
+
+        client.clear_market_states();
+        assert!(client.market_state("EPIC.A").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_market_status_is_not_tradeable() {
+        let client = logged_in_client_at(serve_json(
+            r#"{"marketDetails":[{"snapshot":{"marketStatus":"NEW_STATUS"}}]}"#.to_string(),
+        ))
+        .await;
+
+        assert!(!client.is_tradeable("EPIC.A").await.unwrap());
+        assert_eq!(
+            client.market_state("EPIC.A").await.unwrap(),
+            MarketState::Offline
+        );
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_market_details_cache_expires_after_ttl() {
+        let epic = crate::test_fixtures::market_details().instrument.epic;
+        let client =
+            client_with_cached_expiry(Config::builder(), Utc::now() + chrono::Duration::days(30));
+        assert!(client.cached_market_details(&epic).is_some());
+
+        let ttl = chrono::Duration::from_std(client.market_details_ttl).unwrap();
+        client
+            .market_details_cache
+            .lock()
+            .unwrap()
+            .get_mut(&epic)
+            .unwrap()
+            .fetched_at = Utc::now() - ttl - chrono::Duration::seconds(1);
+        assert!(client.cached_market_details(&epic).is_none());
+        // The expired entry is not served, so the details are fetched again
+        assert!(client.get_market_details(&epic).await.is_err());
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_fresh_market_details_replace_the_cached_entry() {
+        let fetched = crate::test_fixtures::market_details();
+        let epic = fetched.instrument.epic.clone();
+        let client = logged_in_client_at(serve_json(
+            crate::test_fixtures::MARKET_DETAILS_JSON.to_string(),
+        ))
+        .await;
+        let mut stale = fetched.clone();
+        stale.instrument.expiry = "DEC-25".to_string();
+        let stale_at = Utc::now() - chrono::Duration::seconds(10);
+        client.market_details_cache.lock().unwrap().insert(
+            epic.clone(),
+            CachedMarketDetails {
+                details: stale,
+                fetched_at: stale_at,
+            },
+        );
+
+        let details = client.get_market_details_fresh(&epic).await.unwrap();
+        assert_eq!(details.instrument.expiry, fetched.instrument.expiry);
+
+        let cached = client.cached_market_details(&epic).unwrap();
+        assert_eq!(cached.details.instrument.expiry, fetched.instrument.expiry);
+        assert!(cached.fetched_at > stale_at);
+        assert_eq!(client.market_details_cache.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_market_details_cache_evicts_the_oldest_entry_when_full() {
+        let client = offline_client(Config::builder());
+        let details = crate::test_fixtures::market_details();
+        let start = Utc::now() - chrono::Duration::seconds(60);
+        client.market_details_cache.lock().unwrap().extend(
+            (0..MAX_MARKET_DETAILS_CACHE_ENTRIES).map(|index| {
+                (
+                    format!("EPIC.{index}"),
+                    CachedMarketDetails {
+                        details: details.clone(),
+                        fetched_at: start + chrono::Duration::milliseconds(index as i64),
+                    },
+                )
+            }),
+        );
+
+        // Refreshing a cached epic replaces it without evicting anything
+        client.cache_market_details("EPIC.5", &details);
+        assert_eq!(
+            client.market_details_cache.lock().unwrap().len(),
+            MAX_MARKET_DETAILS_CACHE_ENTRIES
+        );
+
+        client.cache_market_details("EPIC.NEW", &details);
+        let cache = client.market_details_cache.lock().unwrap();
+        assert_eq!(cache.len(), MAX_MARKET_DETAILS_CACHE_ENTRIES);
+        assert!(!cache.contains_key("EPIC.0"));
+        assert!(cache.contains_key("EPIC.5"));
+        assert!(cache.contains_key("EPIC.NEW"));
+    }
 }
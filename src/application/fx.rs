@@ -0,0 +1,148 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Currency conversion from IG's own FX markets
+//!
+//! `FxConverter` converts amounts between currencies with the mid price of
+//! IG's daily FX markets (`CS.D.EURUSD.TODAY.IP`), so foreign P&L can be
+//! reported in the account currency without an external rate source. Rates
+//! are cached for a while; both directions of a pair are served from one
+//! fetch.
+
+use crate::application::interfaces::market::MarketService;
+use crate::constants::FX_RATE_CACHE_TTL_SECS;
+use crate::presentation::currency::CurrencyCode;
+use crate::presentation::market::MarketSnapshot;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Epic of IG's daily FX market quoting `base` in `quote`
+pub fn fx_epic(base: &CurrencyCode, quote: &CurrencyCode) -> String {
+    format!("CS.D.{}{}.TODAY.IP", base.code(), quote.code())
+}
+
+/// Mid rate of an FX market snapshot in quote currency per unit of base
+///
+/// IG quotes some FX markets in points (`10850.3` for 1.08503); the price is
+/// divided by the snapshot's scaling factor when there is one.
+///
+/// # Returns
+/// * `None` - If the bid or offer is missing or the rate is not positive
+pub fn rate_from_snapshot(snapshot: &MarketSnapshot) -> Option<f64> {
+    let mid = (snapshot.bid? + snapshot.offer?) / 2.0;
+    let scale = snapshot
+        .scaling_factor
+        .filter(|factor| *factor > 0)
+        .unwrap_or(1) as f64;
+    let rate = mid / scale;
+    (rate.is_finite() && rate > 0.0).then_some(rate)
+}
+
+/// Converts amounts between currencies with rates from IG's FX markets
+pub struct FxConverter<S: MarketService> {
+    service: Arc<S>,
+    rates: Mutex<HashMap<(CurrencyCode, CurrencyCode), (Instant, f64)>>,
+    ttl: Duration,
+}
+
+impl<S: MarketService> FxConverter<S> {
+    /// Creates a converter using the default rate cache duration
+    ///
+    /// # Arguments
+    /// * `service` - Market service used to fetch the FX markets
+    pub fn new(service: Arc<S>) -> Self {
+        Self::with_ttl(service, Duration::from_secs(FX_RATE_CACHE_TTL_SECS))
+    }
+
+    /// Creates a converter with a custom rate cache duration
+    ///
+    /// # Arguments
+    /// * `service` - Market service used to fetch the FX markets
+    /// * `ttl` - How long a fetched rate is reused before refetching
+    pub fn with_ttl(service: Arc<S>, ttl: Duration) -> Self {
+        Self {
+            service,
+            rates: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Stores a rate for `from` to `to`, and its inverse, as if just fetched
+    ///
+    /// Useful to seed the converter from a streamed price.
+    pub fn set_rate(&self, from: &CurrencyCode, to: &CurrencyCode, rate: f64) {
+        if !(rate.is_finite() && rate > 0.0) {
+            return;
+        }
+        let now = Instant::now();
+        let mut rates = self
+            .rates
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        rates.insert((from.clone(), to.clone()), (now, rate));
+        rates.insert((to.clone(), from.clone()), (now, 1.0 / rate));
+    }
+
+    /// Units of `to` per unit of `from`
+    ///
+    /// Served from the cache when fresh. Otherwise the `from`/`to` market is
+    /// fetched, and the `to`/`from` market with its rate inverted if IG has
+    /// no market in that direction.
+    ///
+    /// # Returns
+    /// * `None` - If IG quotes neither direction of the pair or the
+    ///   markets could not be fetched
+    pub async fn rate(&self, from: &CurrencyCode, to: &CurrencyCode) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        if let Some(rate) = self.cached_rate(from, to) {
+            return Some(rate);
+        }
+        if let Some(rate) = self.fetch_rate(from, to).await {
+            self.set_rate(from, to, rate);
+            return Some(rate);
+        }
+        let inverse = self.fetch_rate(to, from).await?;
+        self.set_rate(to, from, inverse);
+        Some(1.0 / inverse)
+    }
+
+    /// Converts `amount` from one currency to another
+    ///
+    /// # Returns
+    /// * `None` - If no rate is available for the pair, see `rate`
+    pub async fn convert(
+        &self,
+        amount: f64,
+        from: &CurrencyCode,
+        to: &CurrencyCode,
+    ) -> Option<f64> {
+        Some(amount * self.rate(from, to).await?)
+    }
+
+    fn cached_rate(&self, from: &CurrencyCode, to: &CurrencyCode) -> Option<f64> {
+        self.rates
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&(from.clone(), to.clone()))
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.ttl)
+            .map(|(_, rate)| *rate)
+    }
+
+    async fn fetch_rate(&self, base: &CurrencyCode, quote: &CurrencyCode) -> Option<f64> {
+        let epic = fx_epic(base, quote);
+        match self.service.get_market_details(&epic).await {
+            Ok(details) => rate_from_snapshot(&details.snapshot),
+            Err(e) => {
+                debug!("No FX rate from {}: {}", epic, e);
+                None
+            }
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::error::AppError;
 use crate::prelude::{
-    AccountActivityResponse, AccountsResponse, PositionsResponse, TransactionHistoryResponse,
-    WorkingOrdersResponse,
+    Account, AccountActivityResponse, AccountsResponse, PortfolioSummary, PositionsResponse,
+    SprintPositionsResponse, TransactionHistoryResponse, WorkingOrdersResponse,
 };
 use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
 
 /// Interface for the account service
 #[async_trait]
@@ -11,12 +12,31 @@ pub trait AccountService: Send + Sync {
     /// Gets information about all user accounts
     async fn get_accounts(&self) -> Result<AccountsResponse, AppError>;
 
+    /// Gets the preferred (default) account of the user
+    ///
+    /// Returns `None` if no account is flagged as preferred.
+    async fn get_preferred_account(&self) -> Result<Option<Account>, AppError>;
+
+    /// Gets total balance, available funds and running P&L across all accounts
+    ///
+    /// Balances come from a single `GET /accounts` call, which reports every
+    /// account without switching the session, so this costs one request
+    /// regardless of how many accounts the user holds. Totals are grouped by
+    /// account currency.
+    async fn get_portfolio_summary(&self) -> Result<PortfolioSummary, AppError>;
+
     /// Gets open positions
     async fn get_positions(&self) -> Result<PositionsResponse, AppError>;
 
     /// Gets open positions base in filter
     async fn get_positions_w_filter(&self, filter: &str) -> Result<PositionsResponse, AppError>;
 
+    /// Gets open positions on sprint markets
+    ///
+    /// Sprint market positions are listed separately from regular OTC positions
+    /// and include the payout amount and expiry time of each position.
+    async fn get_sprint_positions(&self) -> Result<SprintPositionsResponse, AppError>;
+
     /// Gets working orders
     async fn get_working_orders(&self) -> Result<WorkingOrdersResponse, AppError>;
 
@@ -57,4 +77,15 @@ pub trait AccountService: Send + Sync {
         from: &str,
         to: &str,
     ) -> Result<TransactionHistoryResponse, AppError>;
+
+    /// Same as `get_transactions`, stopping early when `cancel` is triggered
+    ///
+    /// The token is checked before every page fetch. On cancellation the
+    /// transactions collected so far are returned.
+    async fn get_transactions_cancellable(
+        &self,
+        from: &str,
+        to: &str,
+        cancel: &CancellationToken,
+    ) -> Result<TransactionHistoryResponse, AppError>;
 }
@@ -0,0 +1,17 @@
+use crate::application::auth::WebsocketInfo;
+use crate::error::AppError;
+use async_trait::async_trait;
+
+/// Source of streaming credentials that can renew them
+///
+/// Lightstreamer authenticates with the session tokens as password, so a
+/// stream cannot reconnect once they expire. `SubscriptionManager` uses this
+/// to log in again when the server rejects the password.
+#[async_trait]
+pub trait IgAuthenticator: Send + Sync {
+    /// Credentials of the current session, logging in if there is none
+    async fn websocket_info(&self) -> Result<WebsocketInfo, AppError>;
+
+    /// Logs in again and returns the credentials of the new session
+    async fn refresh(&self) -> Result<WebsocketInfo, AppError>;
+}
@@ -5,20 +5,207 @@
 ******************************************************************************/
 
 use crate::error::AppError;
+use crate::presentation::chart::ChartScale;
 use lightstreamer_rs::subscription::{ItemUpdate, SubscriptionListener};
-use std::fmt::{Debug, Display};
+use std::fmt::{self, Debug, Display};
 use std::sync::Arc;
 use tracing::log::debug;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Result type for listener operations that don't return a value but may return an error
 pub type ListenerResult = Result<(), AppError>;
 
+/// Callback invoked with an item name and the number of updates Lightstreamer
+/// dropped for it because the consumer was too slow
+pub type LostUpdatesHandler = Arc<dyn Fn(&str, u64) + Send + Sync>;
+
+/// Callback invoked with the Lightstreamer error code and message when the
+/// server refuses a subscription
+pub type SubscriptionErrorHandler = Arc<dyn Fn(i32, Option<&str>) + Send + Sync>;
+
+/// Fields of a `MARKET:{epic}` subscription
+pub const MARKET_FIELDS: [&str; 10] = [
+    "MID_OPEN",
+    "HIGH",
+    "LOW",
+    "CHANGE",
+    "CHANGE_PCT",
+    "UPDATE_TIME",
+    "MARKET_DELAY",
+    "MARKET_STATE",
+    "BID",
+    "OFFER",
+];
+
+/// Fields of an `ACCOUNT:{account_id}` subscription
+pub const ACCOUNT_FIELDS: [&str; 12] = [
+    "PNL",
+    "DEPOSIT",
+    "AVAILABLE_CASH",
+    "PNL_LR",
+    "PNL_NLR",
+    "FUNDS",
+    "MARGIN",
+    "MARGIN_LR",
+    "MARGIN_NLR",
+    "AVAILABLE_TO_DEAL",
+    "EQUITY",
+    "EQUITY_USED",
+];
+
+/// Fields of a `TRADE:{account_id}` subscription
+pub const TRADE_FIELDS: [&str; 3] = ["CONFIRMS", "OPU", "WOU"];
+
+/// Fields of a `CHART:{epic}:TICK` subscription
+pub const CHART_TICK_FIELDS: [&str; 11] = [
+    "BID",
+    "OFR",
+    "LTP",
+    "LTV",
+    "TTV",
+    "UTM",
+    "DAY_OPEN_MID",
+    "DAY_NET_CHG_MID",
+    "DAY_PERC_CHG_MID",
+    "DAY_HIGH",
+    "DAY_LOW",
+];
+
+/// Fields of a candle `CHART:{epic}:{scale}` subscription
+pub const CHART_CANDLE_FIELDS: [&str; 22] = [
+    "LTV",
+    "TTV",
+    "UTM",
+    "DAY_OPEN_MID",
+    "DAY_NET_CHG_MID",
+    "DAY_PERC_CHG_MID",
+    "DAY_HIGH",
+    "DAY_LOW",
+    "OFR_OPEN",
+    "OFR_HIGH",
+    "OFR_LOW",
+    "OFR_CLOSE",
+    "BID_OPEN",
+    "BID_HIGH",
+    "BID_LOW",
+    "BID_CLOSE",
+    "LTP_OPEN",
+    "LTP_HIGH",
+    "LTP_LOW",
+    "LTP_CLOSE",
+    "CONS_END",
+    "CONS_TICK_COUNT",
+];
+
+/// Lightstreamer item of the IG streaming API
+///
+/// Builds item names in IG's format so they do not have to be written by
+/// hand; a misspelt item is not an error on IG's side, it simply never
+/// receives updates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LsItem {
+    /// Prices and state of a market (`MARKET:{epic}`)
+    Market(String),
+    /// Balances of an account (`ACCOUNT:{account_id}`)
+    Account(String),
+    /// Confirmations and position and order updates (`TRADE:{account_id}`)
+    Trade(String),
+    /// Ticks or candles of a market (`CHART:{epic}:{scale}`)
+    Chart {
+        /// Epic of the market
+        epic: String,
+        /// Tick or candle scale
+        scale: ChartScale,
+    },
+}
+
+impl LsItem {
+    /// Item for a market's prices
+    pub fn market(epic: impl Into<String>) -> Self {
+        LsItem::Market(epic.into())
+    }
+
+    /// Item for an account's balances
+    pub fn account(account_id: impl Into<String>) -> Self {
+        LsItem::Account(account_id.into())
+    }
+
+    /// Item for an account's trade notifications
+    pub fn trade(account_id: impl Into<String>) -> Self {
+        LsItem::Trade(account_id.into())
+    }
+
+    /// Item for a market's ticks or candles
+    pub fn chart(epic: impl Into<String>, scale: ChartScale) -> Self {
+        LsItem::Chart {
+            epic: epic.into(),
+            scale,
+        }
+    }
+
+    /// Parses an item name such as `MARKET:IX.D.DAX.DAILY.IP`
+    ///
+    /// The prefix and chart scale are matched ignoring case.
+    ///
+    /// # Returns
+    /// * `None` - If the prefix or scale is unknown or the identifier is empty
+    pub fn parse(item: &str) -> Option<Self> {
+        let (kind, rest) = item.split_once(':')?;
+        if rest.is_empty() {
+            return None;
+        }
+        match kind.to_ascii_uppercase().as_str() {
+            "MARKET" => Some(LsItem::market(rest)),
+            "ACCOUNT" => Some(LsItem::account(rest)),
+            "TRADE" => Some(LsItem::trade(rest)),
+            "CHART" => {
+                let (epic, scale) = rest.rsplit_once(':')?;
+                (!epic.is_empty()).then_some(LsItem::chart(epic, ChartScale::parse(scale)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Item name as sent to Lightstreamer
+    pub fn name(&self) -> String {
+        self.to_string()
+    }
+
+    /// All fields IG publishes for this kind of item
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            LsItem::Market(_) => &MARKET_FIELDS,
+            LsItem::Account(_) => &ACCOUNT_FIELDS,
+            LsItem::Trade(_) => &TRADE_FIELDS,
+            LsItem::Chart {
+                scale: ChartScale::Tick,
+                ..
+            } => &CHART_TICK_FIELDS,
+            LsItem::Chart { .. } => &CHART_CANDLE_FIELDS,
+        }
+    }
+}
+
+impl Display for LsItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LsItem::Market(epic) => write!(f, "MARKET:{epic}"),
+            LsItem::Account(account_id) => write!(f, "ACCOUNT:{account_id}"),
+            LsItem::Trade(account_id) => write!(f, "TRADE:{account_id}"),
+            LsItem::Chart { epic, scale } => write!(f, "CHART:{epic}:{}", scale.as_str()),
+        }
+    }
+}
+
 /// Trade data listener that processes updates through a callback
 /// Thread-safe and can be shared between threads
 pub struct Listener<T> {
     /// The callback function that will be called with trade data updates
     callback: Arc<dyn Fn(&T) -> ListenerResult + Send + Sync>,
+    /// Called when the server reports lost updates for an item
+    lost_updates: Option<LostUpdatesHandler>,
+    /// Called when the server refuses the subscription
+    subscription_errors: Option<SubscriptionErrorHandler>,
 }
 
 impl<T> Listener<T>
@@ -40,9 +227,33 @@ where
     {
         Listener {
             callback: Arc::new(callback),
+            lost_updates: None,
+            subscription_errors: None,
         }
     }
 
+    /// Sets the handler called when Lightstreamer reports lost updates
+    ///
+    /// Without a handler lost updates are only logged. Use
+    /// `SubscriptionManager::lost_updates_handler` to turn them into
+    /// `SubscriptionEvent::LostUpdates` events.
+    #[must_use]
+    pub fn with_lost_updates(mut self, handler: LostUpdatesHandler) -> Self {
+        self.lost_updates = Some(handler);
+        self
+    }
+
+    /// Sets the handler called when the server refuses the subscription
+    ///
+    /// Without a handler refusals are only logged. Use
+    /// `SubscriptionManager::subscription_error_handler` to turn them into
+    /// `SubscriptionEvent::SubscriptionFailed` events naming the item.
+    #[must_use]
+    pub fn with_subscription_errors(mut self, handler: SubscriptionErrorHandler) -> Self {
+        self.subscription_errors = Some(handler);
+        self
+    }
+
     /// Updates the callback function
     ///
     /// # Arguments
@@ -95,6 +306,28 @@ where
         }
     }
 
+    fn on_item_lost_updates(&self, item_name: Option<&str>, item_pos: usize, lost_updates: u64) {
+        let item = item_name.map_or_else(|| format!("#{item_pos}"), str::to_string);
+        warn!(
+            "Lightstreamer dropped {} updates for {}",
+            lost_updates, item
+        );
+        if let Some(handler) = &self.lost_updates {
+            handler(&item, lost_updates);
+        }
+    }
+
+    fn on_subscription_error(&mut self, code: i32, message: Option<&str>) {
+        error!(
+            "Subscription refused by the server (code {}): {}",
+            code,
+            message.unwrap_or_default()
+        );
+        if let Some(handler) = &self.subscription_errors {
+            handler(code, message);
+        }
+    }
+
     fn on_subscription(&mut self) {
         info!("Trade Subscription confirmed by the server");
     }
@@ -1,11 +1,13 @@
 use crate::error::AppError;
-use crate::model::requests::RecentPricesRequest;
+use crate::model::requests::{CreateOrderRequest, RecentPricesRequest};
 use crate::model::responses::{
-    DBEntryResponse, HistoricalPricesResponse, MarketNavigationResponse, MarketSearchResponse,
-    MultipleMarketDetailsResponse,
+    DBEntriesReport, DBEntryResponse, HistoricalPricesResponse, MarketNavigationResponse,
+    MarketSearchResponse, MultipleMarketDetailsResponse,
 };
-use crate::presentation::market::{MarketData, MarketDetails};
+use crate::presentation::market::{MarketData, MarketDetails, MarketOrdering, MarketState};
+use crate::presentation::order::Direction;
 use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
 
 /// Interface for the market service
 #[async_trait]
@@ -13,9 +15,66 @@ pub trait MarketService: Send + Sync {
     /// Searches markets by search term
     async fn search_markets(&self, search_term: &str) -> Result<MarketSearchResponse, AppError>;
 
+    /// Searches markets by search term, best match first
+    ///
+    /// Same results as `search_markets`, which keeps IG's order, reordered
+    /// with `MarketSearchResponse::ranked` for pickers and autocompletion.
+    async fn search_markets_ranked(
+        &self,
+        search_term: &str,
+    ) -> Result<MarketSearchResponse, AppError> {
+        Ok(self.search_markets(search_term).await?.ranked(search_term))
+    }
+
     /// Gets details of a specific market by its EPIC
+    ///
+    /// Implementations may serve a recently fetched value; the price snapshot
+    /// within it can then be up to the cache TTL old. Use
+    /// `get_market_details_fresh` when current prices are required.
     async fn get_market_details(&self, epic: &str) -> Result<MarketDetails, AppError>;
 
+    /// Gets market details, bypassing the in-process market details cache
+    ///
+    /// The fresh result replaces any cached entry for the epic.
+    async fn get_market_details_fresh(&self, epic: &str) -> Result<MarketDetails, AppError>;
+
+    /// Expiry to send with orders on a market, e.g. `DEC-25` or `-`
+    ///
+    /// Read from the market details of that exact epic, so an underlying
+    /// listed with several expiries gets the one of the contract traded.
+    /// Implementations may serve it from cached details until the market's
+    /// last dealing date.
+    async fn order_expiry(&self, epic: &str) -> Result<String, AppError> {
+        Ok(self.get_market_details(epic).await?.instrument.expiry)
+    }
+
+    /// Builds an order for an epic with `CreateOrderRequest::smart`
+    ///
+    /// The expiry comes from `order_expiry`, so options and futures need no
+    /// hardcoded contract such as `JUL-25`, and an underlying listed with
+    /// several expiries gets the one of the contract traded rather than the
+    /// first.
+    ///
+    /// # Arguments
+    /// * `epic` - Epic to deal on
+    /// * `direction` - Direction of the order
+    /// * `size` - Size of the order
+    ///
+    /// # Returns
+    /// * `Err(AppError)` - If the market details cannot be fetched, or the
+    ///   market needs a limit order and has no price to set it at
+    async fn order_for_epic(
+        &self,
+        epic: &str,
+        direction: Direction,
+        size: f64,
+    ) -> Result<CreateOrderRequest, AppError> {
+        let market_details = self.get_market_details(epic).await?;
+        let mut order = CreateOrderRequest::smart(&market_details, direction, size)?;
+        order.expiry = Some(self.order_expiry(epic).await?);
+        Ok(order)
+    }
+
     /// Gets details of multiple markets by their EPICs in a single request
     ///
     /// This method accepts a vector of EPICs and returns a vector of market details.
@@ -32,6 +91,28 @@ pub trait MarketService: Send + Sync {
         epics: &[String],
     ) -> Result<MultipleMarketDetailsResponse, AppError>;
 
+    /// Gets the current trading state of a market as cheaply as possible
+    ///
+    /// Implementations should prefer a state pushed by the streaming API, then
+    /// the lightest REST call, and may reuse a result for a short while.
+    /// Unknown statuses are reported as `MarketState::Offline`.
+    ///
+    /// # Arguments
+    /// * `epic` - The EPIC of the market
+    ///
+    /// # Returns
+    /// * `Ok(MarketState)` - The market's current state
+    /// * `Err(AppError)` - If the state could not be obtained
+    async fn market_state(&self, epic: &str) -> Result<MarketState, AppError>;
+
+    /// Returns true if the market currently accepts new deals
+    ///
+    /// Shorthand for `market_state(epic)` being `MarketState::Tradeable`; use
+    /// `market_state` to tell a closed market from an auction or suspension.
+    async fn is_tradeable(&self, epic: &str) -> Result<bool, AppError> {
+        Ok(self.market_state(epic).await? == MarketState::Tradeable)
+    }
+
     /// Gets historical prices for a market
     async fn get_historical_prices(
         &self,
@@ -41,6 +122,26 @@ pub trait MarketService: Send + Sync {
         to: &str,
     ) -> Result<HistoricalPricesResponse, AppError>;
 
+    /// Gets historical prices for an arbitrary date range, splitting it into several requests
+    ///
+    /// The range is split into sub-ranges of at most
+    /// `HISTORICAL_PRICES_MAX_POINTS_PER_REQUEST` candles, fetched in order and
+    /// concatenated without duplicates. Fetching stops early, returning the
+    /// candles gathered so far, when the weekly price allowance is exhausted.
+    ///
+    /// # Arguments
+    /// * `epic` - Instrument epic
+    /// * `resolution` - Price resolution (e.g. MINUTE, HOUR, DAY)
+    /// * `from` - Start date (yyyy-MM-ddTHH:mm:ss)
+    /// * `to` - End date (yyyy-MM-ddTHH:mm:ss)
+    async fn get_historical_prices_ranged(
+        &self,
+        epic: &str,
+        resolution: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<HistoricalPricesResponse, AppError>;
+
     /// Gets historical prices for a market using path parameters (API v2)
     ///
     /// # Arguments
@@ -112,6 +213,21 @@ pub trait MarketService: Send + Sync {
         node_id: &str,
     ) -> Result<MarketNavigationResponse, AppError>;
 
+    /// Gets full market details for every market directly under a navigation node
+    ///
+    /// Fetches the node and then bulk-fetches the details of its markets in
+    /// chunks of at most 50 epics, each request going through the rate limiter.
+    ///
+    /// # Arguments
+    /// * `node_id` - The ID of the navigation node, typically a leaf node
+    ///
+    /// # Returns
+    /// * `Result<Vec<MarketDetails>, AppError>` - Details of the markets in the node
+    async fn get_node_markets_detailed(
+        &self,
+        node_id: &str,
+    ) -> Result<Vec<MarketDetails>, AppError>;
+
     /// Navigates through all levels of the market hierarchy and collects all MarketData
     ///
     /// This method performs a comprehensive traversal of the IG Markets hierarchy,
@@ -122,10 +238,49 @@ pub trait MarketService: Send + Sync {
     /// * `session` - The authenticated IG session
     /// * `max_levels` - Maximum depth to traverse (default: 5 levels)
     ///
+    /// Markets listed under several nodes are returned once, at their first
+    /// position in traversal order (`MarketOrdering::Deduplicated`).
+    ///
+    /// The whole tree is held in memory before returning; `Client` also offers
+    /// `market_navigation_stream`, which yields nodes as they are fetched.
+    ///
     /// # Returns
     /// * `Result<Vec<MarketData>, AppError>` - Vector containing all found market instruments
     async fn get_all_markets(&self) -> Result<Vec<MarketData>, AppError>;
 
+    /// Same as `get_all_markets`, stopping early when `cancel` is triggered
+    ///
+    /// The token is checked before every node fetch. On cancellation the
+    /// markets collected so far are returned.
+    ///
+    /// # Arguments
+    /// * `cancel` - Token used to interrupt the traversal
+    ///
+    /// # Returns
+    /// * `Result<Vec<MarketData>, AppError>` - All markets, or the partial result if cancelled
+    async fn get_all_markets_cancellable(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<MarketData>, AppError>;
+
+    /// Same as `get_all_markets_cancellable`, with explicit control over ordering
+    ///
+    /// Use `MarketOrdering::SortedByEpic` for output that is stable across runs
+    /// (snapshot diffs, cache keys) or `MarketOrdering::Raw` to keep duplicates
+    /// and the raw traversal order.
+    ///
+    /// # Arguments
+    /// * `ordering` - How to order and deduplicate the collected markets
+    /// * `cancel` - Token used to interrupt the traversal
+    ///
+    /// # Returns
+    /// * `Result<Vec<MarketData>, AppError>` - All markets, or the partial result if cancelled
+    async fn get_all_markets_ordered(
+        &self,
+        ordering: MarketOrdering,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<MarketData>, AppError>;
+
     /// Gets all markets converted to database entries format
     ///
     /// This method retrieves all available markets and converts them to a standardized
@@ -137,4 +292,14 @@ pub trait MarketService: Send + Sync {
     /// # Returns
     /// * `Result<Vec<DBEntry>, AppError>` - Vector of database entries representing all markets
     async fn get_vec_db_entries(&self) -> Result<Vec<DBEntryResponse>, AppError>;
+
+    /// Gets all markets as database entries together with a data quality report
+    ///
+    /// Expiry dates are refreshed per symbol with a bounded number of concurrent
+    /// market details requests. Symbols whose request fails keep the expiry from
+    /// the navigation and are listed in `DBEntriesReport::failures`.
+    ///
+    /// # Returns
+    /// * `Result<DBEntriesReport, AppError>` - Entries and per-symbol failures
+    async fn get_vec_db_entries_with_report(&self) -> Result<DBEntriesReport, AppError>;
 }
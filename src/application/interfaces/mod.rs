@@ -1,5 +1,7 @@
 /// Account service interface
 pub mod account;
+/// Authentication interface used to renew streaming credentials
+pub mod authenticator;
 /// Listener interface for streaming data
 pub mod listener;
 /// Market service interface
@@ -4,10 +4,12 @@ use crate::model::requests::{
 };
 use crate::model::responses::{
     ClosePositionResponse, CreateOrderResponse, CreateWorkingOrderResponse,
-    OrderConfirmationResponse, UpdatePositionResponse,
+    OrderConfirmationResponse, PartialCloseResult, StrategyResult, UpdatePositionResponse,
 };
 
 use async_trait::async_trait;
+use std::time::Duration;
+use tracing::{info, warn};
 
 #[async_trait]
 /// Service for creating, updating, and managing trading orders with the IG Markets API
@@ -15,6 +17,10 @@ use async_trait::async_trait;
 /// This trait defines the interface for interacting with the IG Markets order endpoints,
 /// allowing clients to create new orders, get order confirmations, update existing positions,
 /// and close positions.
+///
+/// Methods that deal fail early with `AppError::TradingNotAllowed` when the
+/// session's account is known not to be able to trade (`Session::can_trade`),
+/// instead of sending a request IG would reject.
 pub trait OrderService: Send + Sync {
     /// Creates a new order
     async fn create_order(
@@ -23,6 +29,89 @@ pub trait OrderService: Send + Sync {
         order: &CreateOrderRequest,
     ) -> Result<CreateOrderResponse, AppError>;
 
+    /// Creates a new order against another of the user's accounts
+    ///
+    /// Sends the account in the `IG-ACCOUNT-ID` header for this request only,
+    /// avoiding the token reissue of `switch_account`. Requires an OAuth (v3)
+    /// session. The account must be one returned by `get_accounts`.
+    ///
+    /// # Arguments
+    /// * `account_id` - Account to place the order on
+    /// * `order` - Order to place
+    ///
+    /// # Returns
+    /// * `Ok(CreateOrderResponse)` - The order was submitted
+    /// * `Err(AppError::InvalidInput)` - If the account is not the user's or the
+    ///   session is not OAuth
+    /// * `Err(AppError::TradingNotAllowed)` - If the account cannot trade
+    async fn create_order_for_account(
+        &self,
+        account_id: &str,
+        order: &CreateOrderRequest,
+    ) -> Result<CreateOrderResponse, AppError>;
+
+    /// Creates a new order only if the rate limiter allows it right now
+    ///
+    /// Unlike `create_order`, this never waits for the rate limiter: when no
+    /// token is available the order is not sent and `Ok(None)` is returned, so
+    /// the caller can skip or queue it instead of blocking.
+    ///
+    /// # Returns
+    /// * `Ok(Some(CreateOrderResponse))` - The order was submitted
+    /// * `Ok(None)` - Rate limited; nothing was sent
+    /// * `Err(AppError::RateLimitExceeded)` - If IG rejected the order as rate
+    ///   limited; it is not retried
+    /// * `Err(AppError)` - If the order was sent and failed
+    async fn try_create_order(
+        &self,
+        order: &CreateOrderRequest,
+    ) -> Result<Option<CreateOrderResponse>, AppError>;
+
+    /// Creates several orders one after another
+    ///
+    /// Orders are sent in sequence, each waiting for the rate limiter like
+    /// `create_order`, so large batches such as a grid are paced without the
+    /// caller managing the limiter. Deals are not confirmed; use the returned
+    /// deal references with `confirm_deal`.
+    ///
+    /// # Arguments
+    /// * `orders` - Orders to place, in sending order
+    /// * `stop_on_error` - Stop at the first order that fails instead of sending the rest
+    ///
+    /// # Returns
+    /// * The outcome of each order that was sent, in order; shorter than
+    ///   `orders` if `stop_on_error` stopped the batch
+    async fn create_orders(
+        &self,
+        orders: Vec<CreateOrderRequest>,
+        stop_on_error: bool,
+    ) -> Vec<Result<CreateOrderResponse, AppError>> {
+        info!("Creating {} orders", orders.len());
+        let mut results = Vec::with_capacity(orders.len());
+        for (index, order) in orders.iter().enumerate() {
+            let outcome = self.create_order(order).await;
+            let failed = outcome.is_err();
+            if let Err(e) = &outcome {
+                warn!(
+                    "Order {} of {} on {} failed: {}",
+                    index + 1,
+                    orders.len(),
+                    order.epic,
+                    e
+                );
+            }
+            results.push(outcome);
+            if failed && stop_on_error {
+                warn!(
+                    "Stopping batch: {} orders not sent",
+                    orders.len() - index - 1
+                );
+                break;
+            }
+        }
+        results
+    }
+
     /// Gets the confirmation of an order
     async fn get_order_confirmation(
         &self,
@@ -30,6 +119,83 @@ pub trait OrderService: Send + Sync {
         deal_reference: &str,
     ) -> Result<OrderConfirmationResponse, AppError>;
 
+    /// Waits for the confirmation of a deal, from the trade stream when it is live
+    ///
+    /// `confirms/{deal_reference}` is polled with backoff and, if a trade
+    /// stream is attached and connected (see `Client::trade_listener` and
+    /// `Client::follow_trade_stream`), raced against the matching `CONFIRMS`
+    /// push. Whichever yields first is returned.
+    ///
+    /// # Arguments
+    /// * `deal_reference` - Reference returned when the deal was submitted
+    /// * `timeout` - Maximum time to wait for a confirmation
+    ///
+    /// # Returns
+    /// * `Ok(OrderConfirmationResponse)` - The deal confirmation
+    /// * `Err(AppError::Timeout)` - If no confirmation arrived in time
+    async fn confirm_deal(
+        &self,
+        deal_reference: &str,
+        timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError>;
+
+    /// Waits until a deal reaches a final state and returns its last confirmation
+    ///
+    /// Unlike `confirm_deal`, which returns the first confirmation seen, this
+    /// keeps watching while IG reports a transient state such as `ACCEPTED`
+    /// and returns once the deal is open, closed, rejected or otherwise final
+    /// (see `OrderConfirmationResponse::is_terminal`). Like `confirm_deal`,
+    /// races the trade stream, when it is live, against polling
+    /// `confirms/{deal_reference}` with backoff.
+    ///
+    /// # Arguments
+    /// * `deal_reference` - Reference returned when the deal was submitted
+    /// * `timeout` - Maximum time to wait for a final state
+    ///
+    /// # Returns
+    /// * `Ok(OrderConfirmationResponse)` - The final confirmation
+    /// * `Err(AppError::Timeout)` - If the deal was still transient when the timeout elapsed
+    async fn track_until_terminal(
+        &self,
+        deal_reference: &str,
+        timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError>;
+
+    /// Creates an order and waits for its confirmation
+    ///
+    /// Combines `create_order` and `confirm_deal`, so it works for any order
+    /// `create_order` accepts, not only market orders. A rejected deal is not
+    /// an error: the confirmation is returned with its status and reason so
+    /// the caller can inspect why it was refused.
+    ///
+    /// # Arguments
+    /// * `order` - Order to place
+    /// * `timeout` - Maximum time to wait for the confirmation after submission
+    ///
+    /// # Returns
+    /// * `Ok(OrderConfirmationResponse)` - Accepted or rejected confirmation
+    /// * `Err(AppError::Timeout)` - If no confirmation arrived in time
+    /// * `Err(AppError)` - If the order could not be submitted
+    async fn place_and_confirm(
+        &self,
+        order: &CreateOrderRequest,
+        timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError> {
+        let created = self.create_order(order).await?;
+        let confirmation = self.confirm_deal(&created.deal_reference, timeout).await?;
+        info!(
+            "Deal {} confirmed with status {:?}{}",
+            confirmation.deal_reference,
+            confirmation.status,
+            confirmation
+                .reason
+                .as_deref()
+                .map(|reason| format!(" ({reason})"))
+                .unwrap_or_default()
+        );
+        Ok(confirmation)
+    }
+
     /// Updates an existing position
     async fn update_position(
         &self,
@@ -45,10 +211,81 @@ pub trait OrderService: Send + Sync {
         close_request: &ClosePositionRequest,
     ) -> Result<ClosePositionResponse, AppError>;
 
+    /// Closes part of a position and amends the stop/limit on the remainder
+    ///
+    /// IG cannot attach a stop or limit to a closing deal, so this closes
+    /// `close_size` at market, waits for the close to be confirmed, then amends
+    /// the remaining position. The amendment is only attempted once the close is
+    /// accepted; a failed amendment is reported in the result rather than as an
+    /// error, because the close has already happened at that point.
+    ///
+    /// # Arguments
+    /// * `deal_id` - Deal ID of the open position
+    /// * `close_size` - Size to close; must be less than the position size
+    /// * `new_stop` - Stop level for the remainder, if any
+    /// * `new_limit` - Limit level for the remainder, if any
+    ///
+    /// # Returns
+    /// * `Ok(PartialCloseResult)` - Close confirmation and, if it was accepted, the amendment result
+    /// * `Err(AppError::NotFound)` - If no open position has this deal ID
+    /// * `Err(AppError)` - If the input is invalid or the close could not be submitted or confirmed
+    async fn partial_close_and_protect(
+        &self,
+        deal_id: &str,
+        close_size: f64,
+        new_stop: Option<f64>,
+        new_limit: Option<f64>,
+    ) -> Result<PartialCloseResult, AppError>;
+
+    /// Places the legs of a multi-leg strategy, e.g. from `StrategyBuilder`
+    ///
+    /// Legs are sent one at a time and each is confirmed before the next is
+    /// sent. Placement stops at the first leg that is rejected, cannot be
+    /// submitted or is not confirmed in time; legs already filled stay open.
+    /// A leg not confirmed in time may still have filled, so it is looked up
+    /// among the open positions and, if found, recorded in
+    /// `StrategyResult::unconfirmed_fills`. Check `StrategyResult::needs_unwind`
+    /// and call `unwind_strategy` to close them. If the account cannot trade,
+    /// the first leg fails with `AppError::TradingNotAllowed` and nothing is
+    /// sent.
+    ///
+    /// # Returns
+    /// * `Ok(StrategyResult)` - Outcome of each leg that was sent
+    /// * `Err(AppError::InvalidInput)` - If `legs` is empty
+    async fn place_strategy(&self, legs: &[CreateOrderRequest])
+    -> Result<StrategyResult, AppError>;
+
+    /// Closes the filled legs of a partially placed strategy
+    ///
+    /// Every leg in `StrategyResult::unwind_requests` is attempted, even if an
+    /// earlier one fails.
+    ///
+    /// # Returns
+    /// * The outcome of each closing order, in leg order
+    async fn unwind_strategy(
+        &self,
+        result: &StrategyResult,
+    ) -> Vec<Result<ClosePositionResponse, AppError>>;
+
     /// Creates a new working order
     async fn create_working_order(
         &self,
 
         order: &CreateWorkingOrderRequest,
     ) -> Result<CreateWorkingOrderResponse, AppError>;
+
+    /// Gets the realised profit or loss of a closing deal
+    ///
+    /// Uses the profit reported on the deal confirmation when present and
+    /// otherwise looks for the transaction booked for the deal in the recent
+    /// transaction history.
+    ///
+    /// # Arguments
+    /// * `deal_reference` - Reference returned by `close_position`
+    ///
+    /// # Returns
+    /// * `Ok(Some(f64))` - Realised profit or loss in the account currency
+    /// * `Ok(None)` - If the transaction has not been posted yet
+    /// * `Err(AppError)` - If the confirmation or history cannot be fetched
+    async fn realized_pnl_for(&self, deal_reference: &str) -> Result<Option<f64>, AppError>;
 }
@@ -0,0 +1,128 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Entries kept current by the stream and resynced from REST
+//!
+//! `LiveBook` holds the state shared by `PositionBook` and
+//! `WorkingOrderBook`: entries keyed by deal ID, a stale flag, and a
+//! generation counter bumped by every stream update and `mark_stale`. A
+//! resync reads the generation before its REST request and only installs the
+//! snapshot if nothing changed while the request was in flight; otherwise the
+//! snapshot may predate an update already applied, such as a close, so it is
+//! discarded and the book stays stale.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct BookState<T> {
+    entries: HashMap<String, T>,
+    stale: bool,
+    generation: u64,
+}
+
+/// Entries keyed by deal ID with a stale flag and a change generation
+#[derive(Debug)]
+pub struct LiveBook<T> {
+    state: Arc<Mutex<BookState<T>>>,
+}
+
+impl<T> Clone for LiveBook<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<T> Default for LiveBook<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LiveBook<T> {
+    /// Creates an empty book, stale until first seeded
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BookState {
+                entries: HashMap::new(),
+                stale: true,
+                generation: 0,
+            })),
+        }
+    }
+
+    /// Applies a stream update to the entries
+    ///
+    /// `apply` returns false if the update could not be applied, which marks
+    /// the book stale. Either way the generation is bumped, so a resync in
+    /// flight does not overwrite the update.
+    pub fn update(&self, apply: impl FnOnce(&mut HashMap<String, T>) -> bool) {
+        let mut state = self.lock();
+        state.generation += 1;
+        if !apply(&mut state.entries) {
+            state.stale = true;
+        }
+    }
+
+    /// Marks the book stale
+    pub fn mark_stale(&self) {
+        let mut state = self.lock();
+        state.generation += 1;
+        state.stale = true;
+    }
+
+    /// Returns true if the book needs a resync before it can be trusted
+    pub fn is_stale(&self) -> bool {
+        self.lock().stale
+    }
+
+    /// Current change generation, to pass to `seed_if_unchanged`
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Replaces the entries and clears the stale flag
+    pub fn seed(&self, entries: HashMap<String, T>) {
+        let mut state = self.lock();
+        state.generation += 1;
+        state.entries = entries;
+        state.stale = false;
+    }
+
+    /// Replaces the entries only if nothing changed since `generation` was read
+    ///
+    /// # Returns
+    /// * `true` - The snapshot was installed and the stale flag cleared
+    /// * `false` - An update or `mark_stale` happened in between; the
+    ///   snapshot was discarded and the book left stale
+    pub fn seed_if_unchanged(&self, generation: u64, entries: HashMap<String, T>) -> bool {
+        let mut state = self.lock();
+        if state.generation != generation {
+            state.stale = true;
+            return false;
+        }
+        state.generation += 1;
+        state.entries = entries;
+        state.stale = false;
+        true
+    }
+
+    /// Clones of the current entries, in no particular order
+    pub fn values(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.lock().entries.values().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BookState<T>> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -4,7 +4,27 @@ pub mod auth;
 pub mod client;
 /// Application configuration module
 pub mod config;
+/// Currency conversion from IG's FX markets
+pub mod fx;
 /// Service interfaces and traits
 pub mod interfaces;
+/// Stream-updated entries with race-safe REST resyncs
+pub mod live_book;
+/// Append-only journal of submitted orders
+pub mod order_journal;
+/// Open positions kept current by the trade stream
+pub mod position_book;
+/// Current prices from the stream with a cached REST fallback
+pub mod price_source;
 /// Rate limiter module for API request throttling
 pub mod rate_limiter;
+/// Session guard that renews expiring sessions once per burst
+pub mod session_guard;
+/// Market details for large epic lists paced over a time window
+pub mod spread_fetcher;
+/// Streaming subscription manager with reconnect policy
+pub mod streaming;
+/// Reference-counted registry of streaming subscriptions
+pub mod subscription;
+/// Working orders kept current by the trade stream
+pub mod working_order_book;
@@ -0,0 +1,445 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Append-only journal of submitted orders
+//!
+//! `OrderJournal` writes one JSON record per line for every order sent to IG:
+//! the request before it is sent, then the deal reference IG returned, then
+//! the confirmation or the error. Each record is flushed to disk before the
+//! call returns, so after a crash `replay` lists the orders that were
+//! submitted but never confirmed and `reconcile` asks IG what became of them.
+//! Only errors that prove IG turned the request down complete an order; after
+//! a timeout or a dropped connection the order may still have been dealt, so
+//! it stays in flight until reconciled.
+
+use crate::application::interfaces::order::OrderService;
+use crate::error::AppError;
+use crate::model::requests::{ClosePositionRequest, CreateOrderRequest};
+use crate::model::responses::{
+    ClosePositionResponse, CreateOrderResponse, OrderConfirmationResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, warn};
+
+/// Order as submitted to IG
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JournalOrder {
+    /// A new position (`POST /positions/otc`)
+    CreateOrder(CreateOrderRequest),
+    /// A close of an existing position (`DELETE /positions/otc`)
+    ClosePosition(ClosePositionRequest),
+}
+
+/// What happened to a journalled order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JournalEvent {
+    /// The request is about to be sent
+    Submitted(JournalOrder),
+    /// IG accepted the request and returned a deal reference
+    Accepted {
+        /// Reference to confirm the deal with
+        deal_reference: String,
+    },
+    /// The deal confirmation was received
+    Confirmed(OrderConfirmationResponse),
+    /// IG rejected the request, so no deal was made
+    Failed {
+        /// Error as displayed
+        error: String,
+    },
+    /// The request failed in a way that may have left it dealt, such as a
+    /// timeout or a dropped connection; the order stays in flight
+    Unknown {
+        /// Error as displayed
+        error: String,
+    },
+}
+
+/// One line of the journal file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+    /// Journal-local identifier shared by every record of one order
+    pub id: u64,
+    /// When the record was written
+    pub recorded_at: DateTime<Utc>,
+    /// What happened
+    pub event: JournalEvent,
+}
+
+/// An order that was submitted but never confirmed or rejected
+#[derive(Debug, Clone)]
+pub struct InFlightOrder {
+    /// Journal identifier of the order
+    pub id: u64,
+    /// When the order was submitted
+    pub submitted_at: DateTime<Utc>,
+    /// The submitted request
+    pub order: JournalOrder,
+    /// Deal reference returned by IG, if the response was recorded
+    pub accepted_reference: Option<String>,
+}
+
+impl InFlightOrder {
+    /// Deal reference to look the order up with
+    ///
+    /// The reference returned by IG when recorded, otherwise the one set on a
+    /// `CreateOrderRequest`. A close whose response was never recorded has no
+    /// reference and can only be reconciled against the open positions.
+    pub fn deal_reference(&self) -> Option<&str> {
+        self.accepted_reference.as_deref().or(match &self.order {
+            JournalOrder::CreateOrder(order) => order.deal_reference.as_deref(),
+            JournalOrder::ClosePosition(_) => None,
+        })
+    }
+}
+
+/// Append-only JSONL journal of orders and their outcome
+pub struct OrderJournal {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+    next_id: AtomicU64,
+}
+
+impl OrderJournal {
+    /// Opens a journal file, creating it if needed
+    ///
+    /// New records are appended after any existing ones, with identifiers
+    /// continuing from the highest already in the file.
+    ///
+    /// # Arguments
+    /// * `path` - Location of the journal file
+    ///
+    /// # Returns
+    /// * `Ok(OrderJournal)` - Journal ready for writing
+    /// * `Err(AppError::Io)` - If the file cannot be opened or read
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AppError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+        // Terminate a line cut short by a crash so the next record starts cleanly
+        if ends_mid_line(&mut file)? {
+            file.write_all(b"\n")?;
+            file.sync_data()?;
+        }
+        let next_id = read_records(&path)?
+            .iter()
+            .map(|record| record.id + 1)
+            .max()
+            .unwrap_or(1);
+        Ok(Self {
+            path,
+            file: Arc::new(Mutex::new(file)),
+            next_id: AtomicU64::new(next_id),
+        })
+    }
+
+    /// Location of the journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records an order that is about to be sent
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - Identifier to record the outcome under
+    /// * `Err(AppError)` - If the record could not be written; do not send the order
+    pub fn record_submitted(&self, order: JournalOrder) -> Result<u64, AppError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.append(id, JournalEvent::Submitted(order))?;
+        Ok(id)
+    }
+
+    /// Records the deal reference IG returned for an order
+    pub fn record_accepted(&self, id: u64, deal_reference: &str) -> Result<(), AppError> {
+        self.append(
+            id,
+            JournalEvent::Accepted {
+                deal_reference: deal_reference.to_string(),
+            },
+        )
+    }
+
+    /// Records the confirmation of an order, completing it
+    pub fn record_confirmed(
+        &self,
+        id: u64,
+        confirmation: &OrderConfirmationResponse,
+    ) -> Result<(), AppError> {
+        self.append(id, JournalEvent::Confirmed(confirmation.clone()))
+    }
+
+    /// Records that IG rejected an order, completing it
+    pub fn record_failed(&self, id: u64, error: &AppError) -> Result<(), AppError> {
+        self.append(
+            id,
+            JournalEvent::Failed {
+                error: error.to_string(),
+            },
+        )
+    }
+
+    /// Records an error that leaves the outcome of an order unknown
+    ///
+    /// The order stays in `replay` so that `reconcile` can find out whether
+    /// it was dealt.
+    pub fn record_unknown(&self, id: u64, error: &AppError) -> Result<(), AppError> {
+        self.append(
+            id,
+            JournalEvent::Unknown {
+                error: error.to_string(),
+            },
+        )
+    }
+
+    /// Journals and sends a new order
+    ///
+    /// The request is recorded before it is sent and the deal reference or
+    /// error after. Confirm the deal and pass the result to `record_confirmed`.
+    /// Once the request has been sent IG's response is always returned: a
+    /// failure to journal it is logged rather than hiding a live deal
+    /// reference from the caller.
+    ///
+    /// # Returns
+    /// * `Ok((u64, CreateOrderResponse))` - Journal identifier and IG's response
+    /// * `Err(AppError)` - If the request could not be journalled, in which
+    ///   case it was not sent, or IG's error
+    pub async fn create_order<S: OrderService + ?Sized>(
+        &self,
+        service: &S,
+        order: &CreateOrderRequest,
+    ) -> Result<(u64, CreateOrderResponse), AppError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.append_blocking(
+            id,
+            JournalEvent::Submitted(JournalOrder::CreateOrder(order.clone())),
+        )
+        .await?;
+        let result = service.create_order(order).await;
+        self.record_outcome(
+            id,
+            result
+                .as_ref()
+                .map(|response| response.deal_reference.as_str()),
+        )
+        .await;
+        result.map(|response| (id, response))
+    }
+
+    /// Journals and sends a position close
+    ///
+    /// Same as `create_order` for a `ClosePositionRequest`.
+    pub async fn close_position<S: OrderService + ?Sized>(
+        &self,
+        service: &S,
+        close_request: &ClosePositionRequest,
+    ) -> Result<(u64, ClosePositionResponse), AppError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.append_blocking(
+            id,
+            JournalEvent::Submitted(JournalOrder::ClosePosition(close_request.clone())),
+        )
+        .await?;
+        let result = service.close_position(close_request).await;
+        self.record_outcome(
+            id,
+            result
+                .as_ref()
+                .map(|response| response.deal_reference.as_str()),
+        )
+        .await;
+        result.map(|response| (id, response))
+    }
+
+    /// Lists the orders that were submitted but never confirmed or rejected
+    ///
+    /// Lines that cannot be parsed, such as one cut short by a crash, are
+    /// skipped with a warning.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<InFlightOrder>)` - In-flight orders in submission order
+    /// * `Err(AppError::Io)` - If the file cannot be read
+    pub fn replay(&self) -> Result<Vec<InFlightOrder>, AppError> {
+        let mut in_flight: BTreeMap<u64, InFlightOrder> = BTreeMap::new();
+        for record in read_records(&self.path)? {
+            match record.event {
+                JournalEvent::Submitted(order) => {
+                    in_flight.insert(
+                        record.id,
+                        InFlightOrder {
+                            id: record.id,
+                            submitted_at: record.recorded_at,
+                            order,
+                            accepted_reference: None,
+                        },
+                    );
+                }
+                JournalEvent::Accepted { deal_reference } => {
+                    if let Some(order) = in_flight.get_mut(&record.id) {
+                        order.accepted_reference = Some(deal_reference);
+                    }
+                }
+                JournalEvent::Unknown { .. } => {}
+                JournalEvent::Confirmed(_) | JournalEvent::Failed { .. } => {
+                    in_flight.remove(&record.id);
+                }
+            }
+        }
+        Ok(in_flight.into_values().collect())
+    }
+
+    /// Asks IG for the confirmation of every in-flight order
+    ///
+    /// Confirmations found are recorded, completing those orders. Orders
+    /// without a deal reference get `AppError::InvalidInput`; IG's error is
+    /// returned for references it does not know.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<(InFlightOrder, Result<OrderConfirmationResponse, AppError>)>)` - Outcome per in-flight order
+    /// * `Err(AppError)` - If the journal cannot be read or written
+    pub async fn reconcile<S: OrderService + ?Sized>(
+        &self,
+        service: &S,
+    ) -> Result<Vec<(InFlightOrder, Result<OrderConfirmationResponse, AppError>)>, AppError> {
+        let in_flight = self.replay()?;
+        info!("Reconciling {} in-flight orders", in_flight.len());
+        let mut outcomes = Vec::with_capacity(in_flight.len());
+        for order in in_flight {
+            let outcome = match order.deal_reference() {
+                Some(deal_reference) => service.get_order_confirmation(deal_reference).await,
+                None => Err(AppError::InvalidInput(format!(
+                    "journalled order {} has no deal reference",
+                    order.id
+                ))),
+            };
+            if let Ok(confirmation) = &outcome {
+                self.append_blocking(order.id, JournalEvent::Confirmed(confirmation.clone()))
+                    .await?;
+            }
+            outcomes.push((order, outcome));
+        }
+        Ok(outcomes)
+    }
+
+    /// Records the result of a sent request, logging rather than returning journal errors
+    async fn record_outcome(&self, id: u64, result: Result<&str, &AppError>) {
+        let event = match result {
+            Ok(deal_reference) => JournalEvent::Accepted {
+                deal_reference: deal_reference.to_string(),
+            },
+            Err(e) if is_definite_rejection(e) => JournalEvent::Failed {
+                error: e.to_string(),
+            },
+            Err(e) => JournalEvent::Unknown {
+                error: e.to_string(),
+            },
+        };
+        if let Err(e) = self.append_blocking(id, event).await {
+            error!(
+                "Failed to journal the outcome of order {} in {}: {}",
+                id,
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    fn append(&self, id: u64, event: JournalEvent) -> Result<(), AppError> {
+        write_record(&self.file, &record_line(id, event)?)
+    }
+
+    /// Same as `append`, writing on the blocking thread pool
+    async fn append_blocking(&self, id: u64, event: JournalEvent) -> Result<(), AppError> {
+        let line = record_line(id, event)?;
+        let file = Arc::clone(&self.file);
+        tokio::task::spawn_blocking(move || write_record(&file, &line))
+            .await
+            .map_err(|e| AppError::Io(std::io::Error::other(e)))?
+    }
+}
+
+/// Returns true if an error proves IG did not deal the request
+///
+/// Client errors such as a rejected request, missing permissions or an
+/// exhausted allowance are definite. Timeouts, network errors, server errors
+/// and unreadable responses are not: the request may have been dealt.
+pub fn is_definite_rejection(error: &AppError) -> bool {
+    match error {
+        AppError::Unexpected(status) => status.is_client_error(),
+        AppError::Unauthorized
+        | AppError::OAuthTokenExpired
+        | AppError::NotFound
+        | AppError::RateLimitExceeded
+        | AppError::InvalidInput(_)
+        | AppError::TradingNotAllowed(_)
+        | AppError::Maintenance { .. }
+        | AppError::UnsupportedVersion { .. }
+        | AppError::PriceUnavailable { .. } => true,
+        _ => false,
+    }
+}
+
+/// Serializes a record as one journal line
+fn record_line(id: u64, event: JournalEvent) -> Result<String, AppError> {
+    let record = JournalRecord {
+        id,
+        recorded_at: Utc::now(),
+        event,
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Appends a line and flushes it to disk
+fn write_record(file: &Mutex<File>, line: &str) -> Result<(), AppError> {
+    let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    file.write_all(line.as_bytes())?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Returns true if the file is not empty and does not end with a newline
+fn ends_mid_line(file: &mut File) -> Result<bool, AppError> {
+    if file.metadata()?.len() == 0 {
+        return Ok(false);
+    }
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+/// Reads every parseable record of a journal file
+fn read_records(path: &Path) -> Result<Vec<JournalRecord>, AppError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JournalRecord>(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => warn!(
+                "Skipping unreadable journal line {} in {}: {}",
+                number + 1,
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(records)
+}
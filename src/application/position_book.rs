@@ -0,0 +1,189 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Live book of open positions
+//!
+//! `PositionBook` is seeded from `GET /positions` and kept current by the
+//! `OPU` (open position update) pushes of the trade stream, so the open
+//! positions can be read at any time without further REST calls. When the
+//! stream cannot be trusted (lost updates, a dropped connection, a position it
+//! has no market data for) the book is marked stale and the next read resyncs
+//! it from REST. A snapshot that raced with a stream update is discarded
+//! rather than undoing the update, see `LiveBook`.
+
+use crate::application::interfaces::account::AccountService;
+use crate::application::interfaces::listener::Listener;
+use crate::application::live_book::LiveBook;
+use crate::error::AppError;
+use crate::model::responses::PositionsResponse;
+use crate::presentation::account::Position;
+use crate::presentation::order::Status;
+use crate::presentation::trade::{OpenPositionUpdate, TradeData};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Open positions kept current by the trade stream
+pub struct PositionBook<S: AccountService> {
+    service: Arc<S>,
+    book: LiveBook<Position>,
+}
+
+impl<S: AccountService> PositionBook<S> {
+    /// Creates an empty book that resyncs from REST on first read
+    ///
+    /// # Arguments
+    /// * `service` - Account service used to seed and resync the book
+    pub fn new(service: Arc<S>) -> Self {
+        Self {
+            service,
+            book: LiveBook::new(),
+        }
+    }
+
+    /// Creates a listener that applies trade stream updates to this book
+    ///
+    /// Attach it to the `TRADE:{account_id}` subscription. Only `OPU` pushes
+    /// are used; confirmations and working order updates are ignored.
+    pub fn trade_listener(&self) -> Listener<TradeData> {
+        let book = self.book.clone();
+        Listener::new(move |update: &TradeData| {
+            if let Some(opu) = update.fields.opu.as_ref() {
+                book.update(|positions| apply_update(positions, opu));
+            }
+            Ok(())
+        })
+    }
+
+    /// Applies one open position update
+    ///
+    /// Closed positions are removed and amended ones get the new level and
+    /// size. An update for a position the book does not hold, such as a newly
+    /// opened one, cannot be applied since `OPU` carries no market data, so it
+    /// marks the book stale instead.
+    pub fn apply(&self, update: &OpenPositionUpdate) {
+        self.book
+            .update(|positions| apply_update(positions, update));
+    }
+
+    /// Marks the book stale so that the next `positions` call resyncs it
+    ///
+    /// Use as the `SubscriptionManager` overflow refresh for the trade item and
+    /// call it when the streaming connection drops, since updates may have
+    /// been missed.
+    pub fn mark_stale(&self) {
+        self.book.mark_stale();
+    }
+
+    /// Returns true if the book needs a REST resync before it can be trusted
+    pub fn is_stale(&self) -> bool {
+        self.book.is_stale()
+    }
+
+    /// Replaces the book with a full positions snapshot and clears the stale flag
+    pub fn seed(&self, response: PositionsResponse) {
+        self.book.seed(by_deal_id(response));
+    }
+
+    /// Reloads the book from `GET /positions`
+    ///
+    /// If a stream update or `mark_stale` arrives while the request is in
+    /// flight the snapshot is discarded, since it may predate the update, and
+    /// the book stays stale for the next read to retry.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of open positions in the snapshot
+    /// * `Err(AppError)` - If the request fails; the book stays stale
+    pub async fn resync(&self) -> Result<usize, AppError> {
+        let generation = self.book.generation();
+        let response = self.service.get_positions().await?;
+        let count = response.positions.len();
+        if self
+            .book
+            .seed_if_unchanged(generation, by_deal_id(response))
+        {
+            info!("Position book resynced: {} open positions", count);
+        } else {
+            debug!("Positions changed during resync, discarding the snapshot");
+        }
+        Ok(count)
+    }
+
+    /// Open positions as currently held, without any REST call
+    ///
+    /// May be out of date while `is_stale` is true. Positions are ordered by
+    /// creation time.
+    pub fn current_positions(&self) -> Vec<Position> {
+        let mut positions = self.book.values();
+        positions.sort_by(|a, b| {
+            a.position
+                .created_date_utc
+                .cmp(&b.position.created_date_utc)
+                .then_with(|| a.position.deal_id.cmp(&b.position.deal_id))
+        });
+        positions
+    }
+
+    /// Open positions, resyncing from REST first if the book is stale
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Position>)` - Current open positions
+    /// * `Err(AppError)` - If a needed resync fails
+    pub async fn positions(&self) -> Result<Vec<Position>, AppError> {
+        if self.is_stale() {
+            self.resync().await?;
+        }
+        Ok(self.current_positions())
+    }
+}
+
+/// Keys a positions snapshot by deal ID
+fn by_deal_id(response: PositionsResponse) -> HashMap<String, Position> {
+    response
+        .positions
+        .into_iter()
+        .map(|position| (position.position.deal_id.clone(), position))
+        .collect()
+}
+
+/// Applies an update to the positions, returning false if it could not be applied
+fn apply_update(positions: &mut HashMap<String, Position>, update: &OpenPositionUpdate) -> bool {
+    if update.deal_status == Some(Status::Rejected) {
+        return true;
+    }
+    let Some(deal_id) = update.deal_id.as_deref() else {
+        debug!("Ignoring position update without a deal ID");
+        return true;
+    };
+    match update.status {
+        Some(Status::Deleted | Status::Closed | Status::FullyClosed) => {
+            positions.remove(deal_id);
+            true
+        }
+        Some(
+            Status::Open
+            | Status::Opened
+            | Status::Updated
+            | Status::Amended
+            | Status::PartiallyClosed,
+        ) => match positions.get_mut(deal_id) {
+            Some(position) => {
+                if let Some(level) = update.level {
+                    position.position.level = level;
+                }
+                if let Some(size) = update.size {
+                    position.position.size = size;
+                }
+                true
+            }
+            None => {
+                debug!("Position {} is not in the book", deal_id);
+                false
+            }
+        },
+        _ => true,
+    }
+}
@@ -0,0 +1,226 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Uniform access to current market prices
+//!
+//! `PriceSource` prefers the latest value merged from the Lightstreamer market
+//! stream and falls back to REST market details when the stream has no data for
+//! an epic (out of market hours, connection refused, not yet subscribed). REST
+//! snapshots are cached briefly so repeated lookups do not consume allowance.
+
+use crate::application::interfaces::listener::{Listener, LsItem};
+use crate::application::interfaces::market::MarketService;
+use crate::constants::{MAX_MARKET_DETAILS_EPICS, PRICE_SNAPSHOT_CACHE_TTL_MS};
+use crate::error::AppError;
+use crate::presentation::market::{
+    MarketDelta, MarketFields, MarketTracker, PresentationMarketData,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Where a snapshot returned by `PriceSource` came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotOrigin {
+    /// Latest merged value from the market stream
+    Stream,
+    /// REST snapshot fetched or served from the short-lived cache
+    Rest,
+}
+
+/// Current prices for an epic regardless of whether streaming is up
+pub struct PriceSource<S: MarketService> {
+    service: Arc<S>,
+    stream: Arc<Mutex<MarketTracker>>,
+    stream_available: Arc<AtomicBool>,
+    rest_cache: Mutex<HashMap<String, (Instant, MarketFields)>>,
+    cache_ttl: Duration,
+}
+
+impl<S: MarketService> PriceSource<S> {
+    /// Creates a new price source using the default REST cache duration
+    ///
+    /// # Arguments
+    /// * `service` - Market service used for the REST fallback
+    pub fn new(service: Arc<S>) -> Self {
+        Self::with_cache_ttl(service, Duration::from_millis(PRICE_SNAPSHOT_CACHE_TTL_MS))
+    }
+
+    /// Creates a new price source with a custom REST cache duration
+    ///
+    /// # Arguments
+    /// * `service` - Market service used for the REST fallback
+    /// * `cache_ttl` - How long a REST snapshot is reused before refetching
+    pub fn with_cache_ttl(service: Arc<S>, cache_ttl: Duration) -> Self {
+        Self {
+            service,
+            stream: Arc::new(Mutex::new(MarketTracker::new())),
+            stream_available: Arc::new(AtomicBool::new(false)),
+            rest_cache: Mutex::new(HashMap::new()),
+            cache_ttl,
+        }
+    }
+
+    /// Creates a listener that feeds market stream updates into this source
+    ///
+    /// Attach it to a `MARKET:{epic}` subscription. Receiving an update marks the
+    /// stream as available.
+    pub fn market_listener(&self) -> Listener<PresentationMarketData> {
+        let stream = Arc::clone(&self.stream);
+        let available = Arc::clone(&self.stream_available);
+        Listener::new(move |update: &PresentationMarketData| {
+            let delta = MarketDelta {
+                item_name: update.item_name.clone(),
+                is_snapshot: update.is_snapshot,
+                changed_fields: update.changed_fields.clone(),
+            };
+            stream
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .apply(delta);
+            available.store(true, Ordering::Release);
+            Ok(())
+        })
+    }
+
+    /// Marks the stream as available or unavailable
+    ///
+    /// Call with `false` when the Lightstreamer connection drops so that lookups
+    /// go to REST instead of returning the last streamed value.
+    pub fn set_stream_available(&self, available: bool) {
+        self.stream_available.store(available, Ordering::Release);
+    }
+
+    /// Drops the streamed and cached values of an epic
+    ///
+    /// The next lookup fetches a fresh REST snapshot until the stream sends a
+    /// new update. Use as the `SubscriptionManager` overflow refresh so that
+    /// prices for items with lost updates are not served stale. Accepts either
+    /// the epic or the `MARKET:{epic}` item name.
+    pub fn invalidate(&self, item: &str) {
+        let epic = item.strip_prefix("MARKET:").unwrap_or(item);
+        {
+            let mut tracker = self
+                .stream
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            tracker.remove(&LsItem::market(epic).name());
+            tracker.remove(epic);
+        }
+        self.rest_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(epic);
+    }
+
+    /// Returns true if streamed values are currently used
+    pub fn is_stream_available(&self) -> bool {
+        self.stream_available.load(Ordering::Acquire)
+    }
+
+    /// Gets the current snapshot for an epic
+    ///
+    /// # Arguments
+    /// * `epic` - Instrument epic
+    ///
+    /// # Returns
+    /// * `Ok(MarketFields)` - Streamed fields if available, otherwise a REST snapshot
+    /// * `Err(AppError)` - If the REST fallback fails
+    pub async fn get_snapshot(&self, epic: &str) -> Result<MarketFields, AppError> {
+        self.get_snapshot_with_origin(epic)
+            .await
+            .map(|(fields, _)| fields)
+    }
+
+    /// Gets the current snapshot for an epic together with its origin
+    pub async fn get_snapshot_with_origin(
+        &self,
+        epic: &str,
+    ) -> Result<(MarketFields, SnapshotOrigin), AppError> {
+        if let Some(fields) = self.streamed(epic) {
+            return Ok((fields, SnapshotOrigin::Stream));
+        }
+        if let Some(fields) = self.cached(epic) {
+            return Ok((fields, SnapshotOrigin::Rest));
+        }
+
+        debug!("No streamed price for {}, fetching REST snapshot", epic);
+        let details = self.service.get_market_details_fresh(epic).await?;
+        let fields = MarketFields::from(&details.snapshot);
+        self.store(epic, fields.clone());
+        Ok((fields, SnapshotOrigin::Rest))
+    }
+
+    /// Gets current snapshots for several epics
+    ///
+    /// Epics without streamed or cached data are fetched with a single batched
+    /// REST call (at most 50 epics per call, as enforced by IG).
+    ///
+    /// # Returns
+    /// * `Ok(HashMap<String, MarketFields>)` - Snapshots keyed by epic; epics unknown
+    ///   to IG are omitted
+    pub async fn get_snapshots(
+        &self,
+        epics: &[String],
+    ) -> Result<HashMap<String, MarketFields>, AppError> {
+        let mut result = HashMap::with_capacity(epics.len());
+        let mut missing = Vec::new();
+        for epic in epics {
+            match self.streamed(epic).or_else(|| self.cached(epic)) {
+                Some(fields) => {
+                    result.insert(epic.clone(), fields);
+                }
+                None => missing.push(epic.clone()),
+            }
+        }
+
+        for chunk in missing.chunks(MAX_MARKET_DETAILS_EPICS) {
+            debug!("Fetching {} REST snapshots in a batch", chunk.len());
+            let response = self.service.get_multiple_market_details(chunk).await?;
+            for details in response.market_details {
+                let fields = MarketFields::from(&details.snapshot);
+                self.store(&details.instrument.epic, fields.clone());
+                result.insert(details.instrument.epic, fields);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn streamed(&self, epic: &str) -> Option<MarketFields> {
+        if !self.is_stream_available() {
+            return None;
+        }
+        let tracker = self
+            .stream
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        tracker
+            .get(&LsItem::market(epic).name())
+            .or_else(|| tracker.get(epic))
+            .cloned()
+    }
+
+    fn cached(&self, epic: &str) -> Option<MarketFields> {
+        let cache = self
+            .rest_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        cache
+            .get(epic)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.cache_ttl)
+            .map(|(_, fields)| fields.clone())
+    }
+
+    fn store(&self, epic: &str, fields: MarketFields) {
+        self.rest_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(epic.to_string(), (Instant::now(), fields));
+    }
+}
@@ -9,23 +9,63 @@
 //! This module provides rate limiting functionality using the `governor` crate
 //! to ensure compliance with IG Markets API rate limits.
 
-use crate::application::config::RateLimiterConfig;
+use crate::application::config::{Config, RateLimiterConfig};
+use crate::constants::RATE_LIMITER_SLOW_WAIT_THRESHOLD_MS;
 use governor::{
     Quota, RateLimiter as GovernorRateLimiter,
     clock::QuantaClock,
     state::{InMemoryState, NotKeyed},
 };
+use std::collections::HashMap;
 use std::num::NonZeroU32;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, error, warn};
+
+/// Category used by `RateLimiter::wait` when the caller does not provide one
+pub const DEFAULT_WAIT_CATEGORY: &str = "default";
+
+/// Cumulative time spent waiting on the rate limiter
+///
+/// Useful to tell whether slow throughput comes from the limiter (consider a
+/// larger safety margin or a higher IG allowance) or from IG itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimiterStats {
+    /// Total time spent waiting across all categories
+    pub total_wait: Duration,
+    /// Number of calls to `wait`
+    pub waits: u64,
+    /// Number of calls that had to wait at all
+    pub delayed: u64,
+    /// Longest single wait
+    pub max_wait: Duration,
+    /// Total wait time per request category
+    pub by_category: HashMap<String, Duration>,
+}
+
+impl RateLimiterStats {
+    fn record(&mut self, category: &str, waited: Duration) {
+        self.waits += 1;
+        if waited.is_zero() {
+            return;
+        }
+        self.delayed += 1;
+        self.total_wait += waited;
+        self.max_wait = self.max_wait.max(waited);
+        *self.by_category.entry(category.to_string()).or_default() += waited;
+    }
+}
 
 /// Rate limiter for controlling API request rates
 ///
 /// Uses the `governor` crate to implement a token bucket algorithm
-/// for rate limiting API requests.
+/// for rate limiting API requests. A no-op limiter, which never waits, can be
+/// created for tests against mock servers.
 #[derive(Clone)]
 pub struct RateLimiter {
-    limiter: Arc<GovernorRateLimiter<NotKeyed, InMemoryState, QuantaClock>>,
+    limiter: Option<Arc<GovernorRateLimiter<NotKeyed, InMemoryState, QuantaClock>>>,
+    stats: Arc<Mutex<RateLimiterStats>>,
 }
 
 impl RateLimiter {
@@ -49,12 +89,18 @@ impl RateLimiter {
     ///     max_requests: 60,
     ///     period_seconds: 60,
     ///     burst_size: 10,
+    ///     disabled: false,
     /// };
     ///
     /// let limiter = RateLimiter::new(&config);
     /// ```
     #[must_use]
     pub fn new(config: &RateLimiterConfig) -> Self {
+        if config.disabled {
+            warn!("Rate limiting is disabled");
+            return Self::noop();
+        }
+
         let period = Duration::from_secs(config.period_seconds);
 
         let burst_size = NonZeroU32::new(config.burst_size)
@@ -67,10 +113,47 @@ impl RateLimiter {
         let limiter = GovernorRateLimiter::direct(quota);
 
         Self {
-            limiter: Arc::new(limiter),
+            limiter: Some(Arc::new(limiter)),
+            stats: Arc::new(Mutex::new(RateLimiterStats::default())),
+        }
+    }
+
+    /// Creates a rate limiter for a full client configuration
+    ///
+    /// Same as `new`, except that a disabled rate limiter is re-enabled when the
+    /// configuration targets the live environment.
+    #[must_use]
+    pub fn for_config(config: &Config) -> Self {
+        if config.rate_limiter.disabled && config.is_live() {
+            error!(
+                "Rate limiting cannot be disabled against the live environment; keeping it enabled"
+            );
+            let enforced = RateLimiterConfig {
+                disabled: false,
+                ..config.rate_limiter.clone()
+            };
+            return Self::new(&enforced);
+        }
+        Self::new(&config.rate_limiter)
+    }
+
+    /// Creates a rate limiter that never waits
+    ///
+    /// Meant for tests against mock servers only.
+    #[must_use]
+    pub fn noop() -> Self {
+        Self {
+            limiter: None,
+            stats: Arc::new(Mutex::new(RateLimiterStats::default())),
         }
     }
 
+    /// Returns true if this limiter never waits
+    #[must_use]
+    pub fn is_noop(&self) -> bool {
+        self.limiter.is_none()
+    }
+
     /// Waits until a request can be made according to the rate limit
     ///
     /// This method blocks until the rate limiter allows the request to proceed.
@@ -83,9 +166,73 @@ impl RateLimiter {
     /// // Make API request here
     /// ```
     pub async fn wait(&self) {
-        while self.limiter.check().is_err() {
-            tokio::time::sleep(Duration::from_millis(10)).await;
+        self.wait_for(DEFAULT_WAIT_CATEGORY).await;
+    }
+
+    /// Waits like `wait`, attributing the time spent to a request category
+    ///
+    /// Waits longer than `RATE_LIMITER_SLOW_WAIT_THRESHOLD_MS` are logged at
+    /// debug level.
+    ///
+    /// # Arguments
+    /// * `category` - Label for the kind of request, e.g. the first path segment
+    ///
+    /// # Returns
+    /// * How long this call slept, `Duration::ZERO` if it was let straight through
+    pub async fn wait_for(&self, category: &str) -> Duration {
+        let waited = match &self.limiter {
+            // Only time spent sleeping counts, so a request let straight
+            // through is recorded as no wait at all
+            Some(limiter) if limiter.check().is_err() => {
+                let started = Instant::now();
+                loop {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    if limiter.check().is_ok() {
+                        break started.elapsed();
+                    }
+                }
+            }
+            _ => Duration::ZERO,
+        };
+
+        if waited >= Duration::from_millis(RATE_LIMITER_SLOW_WAIT_THRESHOLD_MS) {
+            debug!(
+                "Rate limiter held a {} request for {} ms",
+                category,
+                waited.as_millis()
+            );
         }
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .record(category, waited);
+        waited
+    }
+
+    /// Total time spent waiting on this limiter, shared by all its clones
+    #[must_use]
+    pub fn total_wait(&self) -> Duration {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .total_wait
+    }
+
+    /// Snapshot of the wait statistics, including the per-category breakdown
+    #[must_use]
+    pub fn stats(&self) -> RateLimiterStats {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Resets the wait statistics
+    pub fn reset_stats(&self) {
+        *self
+            .stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = RateLimiterStats::default();
     }
 
     /// Checks if a request can be made immediately without waiting
@@ -106,14 +253,84 @@ impl RateLimiter {
     /// ```
     #[must_use]
     pub fn check(&self) -> bool {
-        self.limiter.check().is_ok()
+        self.try_acquire()
+    }
+
+    /// Takes a token if one is available, without waiting
+    ///
+    /// # Returns
+    ///
+    /// * `true` if a token was taken and a request may be sent now
+    /// * `false` if the rate limit has been reached
+    #[must_use]
+    pub fn try_acquire(&self) -> bool {
+        self.limiter
+            .as_ref()
+            .is_none_or(|limiter| limiter.check().is_ok())
+    }
+}
+
+/// Rate limiters shared by every client using the same API key
+///
+/// By default each `Client` owns its own limiters, which is right when a
+/// process talks to IG with a single API key. A service managing several
+/// accounts should create one registry and build its clients with
+/// `Client::with_rate_limiters`: clients sharing an API key then share one
+/// budget, while different API keys keep independent budgets.
+///
+/// Cloning the registry shares its limiters.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiterRegistry {
+    limiters: Arc<Mutex<HashMap<String, Arc<RwLock<RateLimiter>>>>>,
+}
+
+impl RateLimiterRegistry {
+    /// Creates an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the limiter for the configuration's API key, creating it on first use
+    ///
+    /// The limiter is built with `RateLimiter::for_config` from the first
+    /// configuration seen for that key; later configurations with the same key
+    /// get the existing limiter whatever their own rate limiter settings.
+    pub fn limiter_for(&self, config: &Config) -> Arc<RwLock<RateLimiter>> {
+        self.limiters
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(config.credentials.api_key.clone())
+            .or_insert_with(|| Arc::new(RwLock::new(RateLimiter::for_config(config))))
+            .clone()
+    }
+
+    /// Number of API keys with a limiter
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.limiters
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+
+    /// Returns true if no limiter has been created yet
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
 impl std::fmt::Debug for RateLimiter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let limiter = if self.is_noop() {
+            "Noop"
+        } else {
+            "GovernorRateLimiter"
+        };
         f.debug_struct("RateLimiter")
-            .field("limiter", &"GovernorRateLimiter")
+            .field("limiter", &limiter)
+            .field("total_wait", &self.total_wait())
             .finish()
     }
 }
@@ -128,6 +345,7 @@ mod tests {
             max_requests: 10,
             period_seconds: 1,
             burst_size: 5,
+            disabled: false,
         };
 
         let limiter = RateLimiter::new(&config);
@@ -138,12 +356,33 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_noop_rate_limiter_never_waits() {
+        let config = RateLimiterConfig {
+            max_requests: 1,
+            period_seconds: 60,
+            burst_size: 1,
+            disabled: true,
+        };
+
+        let limiter = RateLimiter::new(&config);
+        assert!(limiter.is_noop());
+
+        let start = std::time::Instant::now();
+        for _ in 0..100 {
+            assert!(limiter.check());
+            limiter.wait().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
     #[tokio::test]
     async fn test_rate_limiter_wait() {
         let config = RateLimiterConfig {
             max_requests: 2,
             period_seconds: 1,
             burst_size: 2,
+            disabled: false,
         };
 
         let limiter = RateLimiter::new(&config);
@@ -160,4 +399,78 @@ mod tests {
         // Should have waited some time (but not too long for the test)
         assert!(elapsed.as_millis() > 0);
     }
+
+    #[test]
+    fn test_try_acquire_does_not_wait() {
+        let config = RateLimiterConfig {
+            max_requests: 1,
+            period_seconds: 60,
+            burst_size: 2,
+            disabled: false,
+        };
+
+        let limiter = RateLimiter::new(&config);
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+        assert!(RateLimiter::noop().try_acquire());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_records_wait_per_category() {
+        let config = RateLimiterConfig {
+            max_requests: 2,
+            period_seconds: 1,
+            burst_size: 1,
+            disabled: false,
+        };
+
+        let limiter = RateLimiter::new(&config);
+        let shared = limiter.clone();
+
+        assert_eq!(limiter.wait_for("markets").await, Duration::ZERO);
+        let waited = shared.wait_for("positions").await;
+        assert!(waited > Duration::ZERO);
+
+        let stats = limiter.stats();
+        assert_eq!(stats.waits, 2);
+        assert_eq!(stats.delayed, 1);
+        assert_eq!(limiter.total_wait(), waited);
+        assert_eq!(stats.max_wait, waited);
+        assert_eq!(stats.by_category.get("positions"), Some(&waited));
+        assert!(!stats.by_category.contains_key("markets"));
+
+        limiter.reset_stats();
+        assert_eq!(shared.stats(), RateLimiterStats::default());
+    }
+
+    #[tokio::test]
+    async fn test_registry_keys_limiters_by_api_key() {
+        let config_for = |api_key: &str| {
+            let mut config = Config::default();
+            config.credentials.api_key = api_key.to_string();
+            config.rate_limiter = RateLimiterConfig {
+                max_requests: 1,
+                period_seconds: 60,
+                burst_size: 1,
+                disabled: false,
+            };
+            config
+        };
+
+        let registry = RateLimiterRegistry::new();
+        assert!(registry.is_empty());
+
+        let first = registry.limiter_for(&config_for("KEY_A"));
+        let same_key = registry.clone().limiter_for(&config_for("KEY_A"));
+        let other_key = registry.limiter_for(&config_for("KEY_B"));
+
+        assert!(Arc::ptr_eq(&first, &same_key));
+        assert!(!Arc::ptr_eq(&first, &other_key));
+        assert_eq!(registry.len(), 2);
+
+        assert!(first.read().await.try_acquire());
+        assert!(!same_key.read().await.try_acquire());
+        assert!(other_key.read().await.try_acquire());
+    }
 }
@@ -0,0 +1,168 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Single enforcement point for a usable session
+//!
+//! `SessionGuard` hands out the stored session while it is comfortably within
+//! its lifetime and renews it otherwise, so every request is sent with fresh
+//! tokens. Expiring OAuth tokens are refreshed, CST sessions log in again.
+//! Renewals are coalesced: callers arriving while one is in flight wait for
+//! it, and a session renewed moments ago is reused instead of being replaced
+//! again when a burst of requests is rejected with the same stale tokens.
+
+use crate::application::auth::{Auth, Session};
+use crate::constants::{
+    OAUTH_EXPIRY_MARGIN_SECS, SESSION_EXPIRY_MARGIN_SECS, SESSION_RENEWAL_COOLDOWN_MS,
+};
+use crate::error::AppError;
+use crate::utils::single_flight::SingleFlight;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// Obtains a new session when the current one can no longer be used
+#[async_trait]
+pub trait SessionRenewer: Send + Sync {
+    /// Logs in again and returns the new session
+    async fn renew(&self) -> Result<Session, AppError>;
+
+    /// Refreshes an expiring OAuth token and returns the session
+    ///
+    /// Defaults to logging in again.
+    async fn refresh(&self) -> Result<Session, AppError> {
+        self.renew().await
+    }
+}
+
+#[async_trait]
+impl SessionRenewer for Auth {
+    async fn renew(&self) -> Result<Session, AppError> {
+        self.relogin().await
+    }
+
+    async fn refresh(&self) -> Result<Session, AppError> {
+        self.refresh_token().await
+    }
+}
+
+/// Guarantees a non-expired session, renewing it at most once per burst
+pub struct SessionGuard {
+    session: Arc<RwLock<Option<Session>>>,
+    renewer: Arc<dyn SessionRenewer>,
+    renewal: SingleFlight<Session>,
+    renewed_at: Mutex<Option<Instant>>,
+    margin_secs: u64,
+}
+
+impl SessionGuard {
+    /// Creates a guard over a shared session slot
+    ///
+    /// # Arguments
+    /// * `session` - Session slot shared with whatever else logs in
+    /// * `renewer` - Used to log in when the session is missing or expiring
+    pub fn new(session: Arc<RwLock<Option<Session>>>, renewer: Arc<dyn SessionRenewer>) -> Self {
+        Self {
+            session,
+            renewer,
+            renewal: SingleFlight::new(),
+            renewed_at: Mutex::new(None),
+            margin_secs: SESSION_EXPIRY_MARGIN_SECS,
+        }
+    }
+
+    /// Sets how long before expiry a CST session is renewed
+    ///
+    /// OAuth sessions always use the short `OAUTH_EXPIRY_MARGIN_SECS`.
+    #[must_use]
+    pub fn with_margin(mut self, margin_secs: u64) -> Self {
+        self.margin_secs = margin_secs;
+        self
+    }
+
+    /// Returns a session that is not within the margin of its expiry
+    ///
+    /// # Returns
+    /// * `Ok(Session)` - The stored session, or a renewed one
+    /// * `Err(AppError)` - If the session had to be renewed and renewal failed
+    pub async fn valid_session(&self) -> Result<Session, AppError> {
+        if let Some(session) = self.session.read().await.as_ref()
+            && !self.expiring(session)
+        {
+            return Ok(session.clone());
+        }
+        debug!("Session missing or expiring, renewing");
+        self.renewal
+            .run(|| async {
+                // Another caller may have renewed while this one waited
+                let oauth = match self.session.read().await.as_ref() {
+                    Some(session) if !self.expiring(session) => return Ok(session.clone()),
+                    Some(session) => session.is_oauth(),
+                    None => false,
+                };
+                if !oauth {
+                    return self.renew_now().await;
+                }
+                debug!("OAuth token expiring, refreshing");
+                let session = self.renewer.refresh().await?;
+                *self.session.write().await = Some(session.clone());
+                Ok(session)
+            })
+            .await
+    }
+
+    /// Renews a session IG rejected even though it had not expired locally
+    ///
+    /// A session renewed within the last moments is returned as is, so a
+    /// burst of requests rejected with the same old tokens renews only once.
+    ///
+    /// # Returns
+    /// * `Ok(Session)` - The renewed session
+    /// * `Err(AppError)` - If renewal failed
+    pub async fn renew_rejected(&self) -> Result<Session, AppError> {
+        self.renewal
+            .run(|| async {
+                if self.renewed_recently()
+                    && let Some(session) = self.session.read().await.as_ref()
+                {
+                    return Ok(session.clone());
+                }
+                self.renew_now().await
+            })
+            .await
+    }
+
+    async fn renew_now(&self) -> Result<Session, AppError> {
+        let session = self.renewer.renew().await?;
+        *self.session.write().await = Some(session.clone());
+        *self
+            .renewed_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Instant::now());
+        Ok(session)
+    }
+
+    /// True if the session expires within the margin
+    ///
+    /// OAuth access tokens live for about a minute and are cheap to refresh,
+    /// so they get a short fixed margin instead of the CST one.
+    fn expiring(&self, session: &Session) -> bool {
+        let margin = if session.is_oauth() {
+            OAUTH_EXPIRY_MARGIN_SECS
+        } else {
+            self.margin_secs
+        };
+        session.is_expired(Some(margin))
+    }
+
+    fn renewed_recently(&self) -> bool {
+        self.renewed_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_some_and(|at| at.elapsed() < Duration::from_millis(SESSION_RENEWAL_COOLDOWN_MS))
+    }
+}
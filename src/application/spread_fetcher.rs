@@ -0,0 +1,233 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Market details for large epic lists, spread over a time window
+//!
+//! `SpreadFetcher` splits the epics into multiple market details requests of
+//! at most 50 epics, like `Client::snapshot_all`, but starts them at an even
+//! pace so that the whole list completes within a target window instead of
+//! as fast as the rate limiter allows. Bulk enrichment jobs can then run
+//! alongside trading without eating into its request budget. Every request
+//! still goes through the rate limiter, so a window that is too short is
+//! stretched rather than breaching IG's limits.
+//!
+//! `snapshot_all` fetches the same batches back to back, as fast as the rate
+//! limiter allows, and backs `Client::snapshot_all`.
+
+use crate::application::interfaces::market::MarketService;
+use crate::constants::MAX_MARKET_DETAILS_EPICS;
+use crate::error::AppError;
+use crate::presentation::market::MarketDetails;
+use futures::Stream;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{Instant, sleep, sleep_until};
+use tracing::{debug, info, warn};
+
+/// Result of one epic, as sent by `SpreadFetcher`
+pub type EpicDetails = (String, Result<MarketDetails, AppError>);
+
+/// Fetches market details for many epics paced over a time window
+pub struct SpreadFetcher<S: MarketService> {
+    service: Arc<S>,
+    window: Duration,
+    chunk_size: usize,
+}
+
+impl<S: MarketService + 'static> SpreadFetcher<S> {
+    /// Creates a fetcher that spreads its requests over `window`
+    ///
+    /// # Arguments
+    /// * `service` - Market service used for the requests
+    /// * `window` - Time within which every chunk should have been requested
+    pub fn new(service: Arc<S>, window: Duration) -> Self {
+        Self {
+            service,
+            window,
+            chunk_size: MAX_MARKET_DETAILS_EPICS,
+        }
+    }
+
+    /// Sets how many epics each request asks for, between 1 and 50
+    ///
+    /// Smaller chunks mean more, smaller requests spread more evenly.
+    #[must_use]
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_MARKET_DETAILS_EPICS);
+        self
+    }
+
+    /// Number of requests needed for `epic_count` epics
+    pub fn chunk_count(&self, epic_count: usize) -> usize {
+        epic_count.div_ceil(self.chunk_size)
+    }
+
+    /// Time between the start of two consecutive requests
+    ///
+    /// The first request starts straight away and the last one a full
+    /// interval before the window ends, leaving it time to complete.
+    pub fn interval(&self, epic_count: usize) -> Duration {
+        match self.chunk_count(epic_count) {
+            0 | 1 => Duration::ZERO,
+            chunks => self.window / u32::try_from(chunks).unwrap_or(u32::MAX),
+        }
+    }
+
+    /// Starts fetching in the background and returns the results as they arrive
+    ///
+    /// Results are sent in input order, one per epic, as soon as their chunk
+    /// returns. A failed chunk is retried one epic at a time, spread over the
+    /// chunk's slot so the window is kept; a chunk rejected by the rate limit
+    /// or maintenance is not retried. Epics unknown to IG yield
+    /// `AppError::NotFound`. Dropping the receiver stops the remaining
+    /// requests. Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// * `epics` - Epics to fetch
+    ///
+    /// # Returns
+    /// * A receiver that closes once every epic has been sent
+    pub fn spawn(&self, epics: Vec<String>) -> mpsc::Receiver<EpicDetails> {
+        let (sender, receiver) = mpsc::channel(self.chunk_size);
+        let interval = self.interval(epics.len());
+        let batches: Vec<Vec<String>> = epics
+            .chunks(self.chunk_size)
+            .map(<[String]>::to_vec)
+            .collect();
+        info!(
+            "Fetching {} epics in {} requests, one every {:?}",
+            epics.len(),
+            batches.len(),
+            interval
+        );
+        tokio::spawn(run(Arc::clone(&self.service), batches, interval, sender));
+        receiver
+    }
+}
+
+/// Requests each batch at its slot in the window and forwards the results
+async fn run<S: MarketService>(
+    service: Arc<S>,
+    batches: Vec<Vec<String>>,
+    interval: Duration,
+    sender: mpsc::Sender<EpicDetails>,
+) {
+    let start = Instant::now();
+    let mut slot = start;
+    for batch in batches {
+        sleep_until(slot).await;
+        slot += interval;
+        if sender.is_closed() {
+            debug!("Spread fetch receiver dropped, stopping");
+            return;
+        }
+        // Individual retries of a failed chunk share the chunk's slot
+        let spacing = interval / u32::try_from(batch.len()).unwrap_or(u32::MAX);
+        for result in fetch_details_batch(service.as_ref(), batch, spacing).await {
+            if sender.send(result).await.is_err() {
+                debug!("Spread fetch receiver dropped, stopping");
+                return;
+            }
+        }
+    }
+    info!("Spread fetch completed in {:?}", start.elapsed());
+}
+
+/// Streams the market details of many epics, one batch of at most 50 at a time
+///
+/// Results are yielded in input order as soon as their batch returns. A
+/// failed batch is retried one epic at a time, unless it was rejected by the
+/// rate limit or maintenance, in which case every epic in it gets that error.
+/// Epics unknown to IG yield `AppError::NotFound`.
+///
+/// # Arguments
+/// * `service` - Market service used for the requests
+/// * `epics` - Epics to fetch
+///
+/// # Returns
+/// * A stream of `(epic, result)` pairs, one per input epic
+pub fn snapshot_all<S: MarketService + ?Sized>(
+    service: &S,
+    epics: Vec<String>,
+) -> impl Stream<Item = EpicDetails> + '_ {
+    let batches: Vec<Vec<String>> = epics
+        .chunks(MAX_MARKET_DETAILS_EPICS)
+        .map(<[String]>::to_vec)
+        .collect();
+    stream::iter(batches)
+        .then(move |batch| fetch_details_batch(service, batch, Duration::ZERO))
+        .flat_map(stream::iter)
+}
+
+/// Fetches the details of one batch of at most 50 epics
+///
+/// Falls back to one request per epic, `spacing` apart, if the batch request
+/// fails, so that a single bad epic does not fail the whole batch. A batch
+/// rejected by the rate limit or maintenance is not retried, since every
+/// individual request would be rejected too.
+async fn fetch_details_batch<S: MarketService + ?Sized>(
+    service: &S,
+    batch: Vec<String>,
+    spacing: Duration,
+) -> Vec<EpicDetails> {
+    match service.get_multiple_market_details(&batch).await {
+        Ok(response) => {
+            let by_epic: HashMap<String, MarketDetails> = response
+                .market_details
+                .into_iter()
+                .map(|details| (details.instrument.epic.clone(), details))
+                .collect();
+            batch
+                .into_iter()
+                .map(|epic| {
+                    let result = by_epic.get(&epic).cloned().ok_or(AppError::NotFound);
+                    (epic, result)
+                })
+                .collect()
+        }
+        Err(e) if batch_wide_error(&e).is_some() => {
+            warn!(
+                "Batch market details request for {} epics failed ({}), not retrying individually",
+                batch.len(),
+                e
+            );
+            batch
+                .into_iter()
+                .filter_map(|epic| Some((epic, Err(batch_wide_error(&e)?))))
+                .collect()
+        }
+        Err(e) => {
+            warn!(
+                "Batch market details request for {} epics failed ({}), fetching individually",
+                batch.len(),
+                e
+            );
+            let mut results = Vec::with_capacity(batch.len());
+            for (index, epic) in batch.into_iter().enumerate() {
+                if index > 0 && !spacing.is_zero() {
+                    sleep(spacing).await;
+                }
+                let result = service.get_market_details(&epic).await;
+                results.push((epic, result));
+            }
+            results
+        }
+    }
+}
+
+/// Copy of a batch error that would also reject every individual request
+fn batch_wide_error(error: &AppError) -> Option<AppError> {
+    match error {
+        AppError::RateLimitExceeded => Some(AppError::RateLimitExceeded),
+        AppError::Maintenance { retry_after } => Some(AppError::Maintenance {
+            retry_after: *retry_after,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,1125 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Streaming subscription manager
+//!
+//! Owns the reconnect loop around a Lightstreamer connection, shares
+//! subscriptions through a [`SubscriptionRegistry`] and publishes lifecycle
+//! events so applications can react to disconnections, released subscriptions
+//! or a connection that cannot be re-established.
+
+use crate::application::config::WebSocketConfig;
+use crate::application::interfaces::authenticator::IgAuthenticator;
+use crate::application::interfaces::listener::{
+    Listener, ListenerResult, LostUpdatesHandler, LsItem, SubscriptionErrorHandler,
+};
+use crate::application::subscription::{SubscriptionKey, SubscriptionRegistry};
+use crate::constants::SUBSCRIPTION_EVENTS_CHANNEL_CAPACITY;
+use crate::error::{AppError, StreamError, is_auth_rejection};
+use crate::presentation::market::{
+    MarketDelta, MarketField, MarketFields, MarketState, MarketTracker, PresentationMarketData,
+};
+use futures::Stream;
+use futures::stream;
+use lightstreamer_rs::client::{ClientListener, LightstreamerClient};
+use lightstreamer_rs::subscription::{Snapshot, Subscription};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Notify, broadcast, mpsc, watch};
+use tracing::{error, info, warn};
+
+pub use lightstreamer_rs::subscription::SubscriptionMode;
+
+/// Subscription mode an item needs to be delivered correctly
+///
+/// Trade items (`TRADE:*`) and tick charts (`CHART:*:TICK`) carry events where
+/// every update matters, so they need `Distinct`; `Merge` would coalesce
+/// updates and silently drop confirmations or ticks. Everything else (`MARKET`,
+/// `ACCOUNT`, candle charts) only needs the latest value and uses `Merge`.
+pub fn default_mode_for_item(item: &str) -> SubscriptionMode {
+    if is_event_item(item) {
+        SubscriptionMode::Distinct
+    } else {
+        SubscriptionMode::Merge
+    }
+}
+
+/// Returns true for items whose every update must be delivered
+fn is_event_item(item: &str) -> bool {
+    let item = item.to_ascii_uppercase();
+    item.starts_with("TRADE:") || (item.starts_with("CHART:") && item.ends_with(":TICK"))
+}
+
+/// Lifecycle events published by the [`SubscriptionManager`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionEvent {
+    /// A connection attempt is starting (1-based attempt number)
+    Connecting {
+        /// Attempt number within the current reconnect cycle
+        attempt: u32,
+    },
+    /// The connection ended in an orderly way
+    Disconnected,
+    /// A connection attempt failed and will be retried after `delay`
+    Reconnecting {
+        /// Number of the attempt that failed
+        attempt: u32,
+        /// Time to wait before the next attempt
+        delay: Duration,
+        /// Error returned by the failed attempt
+        reason: String,
+    },
+    /// Reconnect attempts are exhausted; the manager gave up
+    Failed {
+        /// Number of attempts made
+        attempts: u32,
+        /// Error returned by the last attempt
+        reason: String,
+    },
+    /// Lightstreamer dropped updates for an item because the consumer was too
+    /// slow; the local view of the item may be stale until its next snapshot
+    LostUpdates {
+        /// Item whose updates were dropped
+        item_name: String,
+        /// Number of updates dropped
+        count: u64,
+    },
+    /// The server rejected the streaming password and a new session was
+    /// obtained; the next attempt uses its credentials
+    Reauthenticated {
+        /// Number of the attempt that was rejected
+        attempt: u32,
+    },
+    /// The server refused a subscription, e.g. for an unknown epic
+    ///
+    /// Reconnecting does not help; remove or correct the item.
+    SubscriptionFailed {
+        /// The refusal, including the item and the server's error code
+        error: StreamError,
+    },
+    /// The last handle of a shared subscription was dropped
+    Released {
+        /// Subscription that is no longer referenced
+        key: SubscriptionKey,
+    },
+    /// The first update for an item after a reconnect, compared with the
+    /// last values retained before the connection was lost
+    ///
+    /// Only published when market state retention is enabled with
+    /// `SubscriptionManager::set_retain_market_state`.
+    Resynced {
+        /// Item that received fresh values
+        item_name: String,
+        /// Fields that moved while the stream was down
+        changed: Vec<MarketField>,
+    },
+}
+
+/// State of the streaming connection, for health and readiness checks
+///
+/// Published by [`SubscriptionManager::connection_state`]. Only `Connected`
+/// means updates are flowing; trading logic should not act on streamed prices
+/// in any other state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamConnectionState {
+    /// A connection is being opened or re-opened
+    Connecting,
+    /// The session is open and receiving updates
+    Connected,
+    /// The session is open but the server has gone quiet beyond its keepalive
+    Stalled,
+    /// There is no connection, including before the first attempt
+    Disconnected {
+        /// Why the connection ended, when known
+        reason: Option<String>,
+    },
+}
+
+impl StreamConnectionState {
+    /// Maps a Lightstreamer client status to a connection state
+    ///
+    /// Lightstreamer reports `CONNECTING`, `CONNECTED:<transport>`, `STALLED`
+    /// and `DISCONNECTED[:WILL-RETRY|:TRYING-RECOVERY]`; the transport suffix
+    /// is dropped and a retry suffix is kept as the reason.
+    pub fn from_lightstreamer_status(status: &str) -> Self {
+        let (state, detail) = status
+            .split_once(':')
+            .map_or((status, None), |(state, detail)| (state, Some(detail)));
+        match state.trim().to_ascii_uppercase().as_str() {
+            "CONNECTING" => StreamConnectionState::Connecting,
+            "CONNECTED" => StreamConnectionState::Connected,
+            "STALLED" => StreamConnectionState::Stalled,
+            "DISCONNECTED" => StreamConnectionState::Disconnected {
+                reason: detail.map(str::to_string),
+            },
+            _ => StreamConnectionState::Disconnected {
+                reason: Some(format!("unrecognised status: {status}")),
+            },
+        }
+    }
+
+    /// Returns true if updates are flowing
+    pub fn is_live(&self) -> bool {
+        matches!(self, StreamConnectionState::Connected)
+    }
+}
+
+/// Lightstreamer client listener that feeds a [`SubscriptionManager`]'s
+/// connection state
+///
+/// Obtained from `SubscriptionManager::connection_listener` and registered
+/// with `LightstreamerClient::add_listener`; `run_lightstreamer` does this
+/// itself.
+pub struct ConnectionStateListener {
+    state: watch::Sender<StreamConnectionState>,
+    retained: Arc<std::sync::Mutex<Option<RetainedMarkets>>>,
+    established: Arc<AtomicBool>,
+}
+
+impl ConnectionStateListener {
+    /// Records a Lightstreamer client status
+    ///
+    /// Losing or stalling the connection marks retained market state for a
+    /// resync. Reaching `Connected` tells `SubscriptionManager::run` that the
+    /// current attempt succeeded, so its attempt count starts over.
+    pub fn on_status(&self, status: &str) {
+        let state = StreamConnectionState::from_lightstreamer_status(status);
+        if state == StreamConnectionState::Connected {
+            self.established.store(true, Ordering::Release);
+        }
+        if !matches!(
+            state,
+            StreamConnectionState::Connected | StreamConnectionState::Connecting
+        ) {
+            mark_resync(&self.retained);
+        }
+        self.state.send_replace(state);
+    }
+}
+
+impl ClientListener for ConnectionStateListener {
+    fn on_status_change(&self, status: &str) {
+        self.on_status(status);
+    }
+
+    fn on_server_error(&self, code: i32, message: &str) {
+        mark_resync(&self.retained);
+        self.state
+            .send_replace(StreamConnectionState::Disconnected {
+                reason: Some(format!("server error {code}: {message}")),
+            });
+    }
+}
+
+/// Exponential backoff used between streaming reconnect attempts
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_reconnect_delay: Duration,
+    /// Maximum number of attempts before giving up (None = retry forever)
+    pub max_reconnect_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    /// Delay to wait after the given failed attempt (1-based)
+    ///
+    /// Doubles the initial delay on every attempt, capped at `max_reconnect_delay`.
+    #[must_use]
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_reconnect_delay)
+    }
+
+    /// Returns true if another attempt is allowed after `attempts` failures
+    #[must_use]
+    pub fn allows_retry(&self, attempts: u32) -> bool {
+        self.max_reconnect_attempts
+            .is_none_or(|max_attempts| attempts < max_attempts)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_reconnect_delay: Duration::from_secs(60),
+            max_reconnect_attempts: None,
+        }
+    }
+}
+
+impl From<&WebSocketConfig> for ReconnectPolicy {
+    fn from(config: &WebSocketConfig) -> Self {
+        Self {
+            initial_delay: Duration::from_secs(config.reconnect_interval),
+            max_reconnect_delay: Duration::from_secs(config.max_reconnect_delay),
+            max_reconnect_attempts: config.max_reconnect_attempts,
+        }
+    }
+}
+
+/// Manages a streaming connection and the subscriptions shared over it
+pub struct SubscriptionManager {
+    policy: ReconnectPolicy,
+    registry: SubscriptionRegistry,
+    events: broadcast::Sender<SubscriptionEvent>,
+    overflow_refresh: Arc<std::sync::RwLock<Option<OverflowRefresh>>>,
+    state: watch::Sender<StreamConnectionState>,
+    authenticator: std::sync::RwLock<Option<Arc<dyn IgAuthenticator>>>,
+    retained: Arc<std::sync::Mutex<Option<RetainedMarkets>>>,
+    established: Arc<AtomicBool>,
+    listening_client: std::sync::Mutex<Weak<Mutex<LightstreamerClient>>>,
+}
+
+/// Callback used to refresh an item after Lightstreamer reported lost updates
+type OverflowRefresh = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Last merged market fields kept across reconnects
+#[derive(Debug, Default)]
+struct RetainedMarkets {
+    tracker: MarketTracker,
+    awaiting_resync: HashSet<String>,
+}
+
+/// Merges an update and publishes `Resynced` if the item was awaiting a resync
+fn retain_update(
+    retained: &std::sync::Mutex<Option<RetainedMarkets>>,
+    events: &broadcast::Sender<SubscriptionEvent>,
+    update: &PresentationMarketData,
+) {
+    let changed = {
+        let mut retained = retained
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(retained) = retained.as_mut() else {
+            return;
+        };
+        let before = retained.tracker.get(&update.item_name).cloned();
+        let merged = retained.tracker.apply(MarketDelta {
+            item_name: update.item_name.clone(),
+            is_snapshot: update.is_snapshot,
+            changed_fields: update.changed_fields.clone(),
+        });
+        match before {
+            Some(before) if retained.awaiting_resync.remove(&update.item_name) => {
+                merged.fields.changed_from(&before)
+            }
+            _ => return,
+        }
+    };
+    let _ = events.send(SubscriptionEvent::Resynced {
+        item_name: update.item_name.clone(),
+        changed,
+    });
+}
+
+/// Marks every retained item as waiting for its first post-reconnect update
+fn mark_resync(retained: &std::sync::Mutex<Option<RetainedMarkets>>) {
+    let mut retained = retained
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(retained) = retained.as_mut() {
+        let items: Vec<String> = retained.tracker.item_names().cloned().collect();
+        retained.awaiting_resync.extend(items);
+    }
+}
+
+impl SubscriptionManager {
+    /// Creates a new manager with the given reconnect policy
+    ///
+    /// # Arguments
+    /// * `policy` - Backoff and attempt limits used by `run`
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        let (events, _) = broadcast::channel(SUBSCRIPTION_EVENTS_CHANNEL_CAPACITY);
+        let released = events.clone();
+        let registry = SubscriptionRegistry::new(move |key| {
+            let _ = released.send(SubscriptionEvent::Released { key: key.clone() });
+        });
+        Self {
+            policy,
+            registry,
+            events,
+            overflow_refresh: Arc::new(std::sync::RwLock::new(None)),
+            state: watch::Sender::new(StreamConnectionState::Disconnected { reason: None }),
+            authenticator: std::sync::RwLock::new(None),
+            retained: Arc::new(std::sync::Mutex::new(None)),
+            established: Arc::new(AtomicBool::new(false)),
+            listening_client: std::sync::Mutex::new(Weak::new()),
+        }
+    }
+
+    /// Reconnect policy used by this manager
+    pub fn policy(&self) -> &ReconnectPolicy {
+        &self.policy
+    }
+
+    /// Registry used to share and reference-count subscriptions
+    ///
+    /// When the last handle of a subscription is dropped a
+    /// `SubscriptionEvent::Released` event is published.
+    pub fn registry(&self) -> &SubscriptionRegistry {
+        &self.registry
+    }
+
+    /// Subscribes to lifecycle events
+    pub fn events(&self) -> broadcast::Receiver<SubscriptionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Sets how new credentials are obtained when the server rejects the password
+    ///
+    /// With an authenticator, `run` logs in again whenever a connection attempt
+    /// fails with an authentication rejection (see [`is_auth_rejection`]), and
+    /// `run_lightstreamer` reconnects with the new session's credentials.
+    /// Without one, rejected attempts are retried with the same credentials.
+    pub fn set_authenticator(&self, authenticator: Arc<dyn IgAuthenticator>) {
+        *self
+            .authenticator
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(authenticator);
+    }
+
+    /// Authenticator set with `set_authenticator`, if any
+    pub fn authenticator(&self) -> Option<Arc<dyn IgAuthenticator>> {
+        self.authenticator
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Logs in again after a rejected password, if an authenticator is set
+    async fn reauthenticate(&self, attempt: u32, reason: &str) {
+        if !is_auth_rejection(reason) {
+            return;
+        }
+        let Some(authenticator) = self.authenticator() else {
+            warn!("Streaming password rejected and no authenticator is set");
+            return;
+        };
+        match authenticator.refresh().await {
+            Ok(_) => {
+                info!("Streaming password rejected; logged in again");
+                self.emit(SubscriptionEvent::Reauthenticated { attempt });
+            }
+            Err(e) => error!("Could not log in again after streaming rejection: {}", e),
+        }
+    }
+
+    /// Watches the state of the streaming connection
+    ///
+    /// The receiver always holds the latest state and is notified on every
+    /// transition, e.g. `manager.connection_state().borrow().is_live()` for a
+    /// readiness check.
+    pub fn connection_state(&self) -> watch::Receiver<StreamConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Client listener that keeps `connection_state` in line with the
+    /// Lightstreamer client's own status changes
+    pub fn connection_listener(&self) -> ConnectionStateListener {
+        ConnectionStateListener {
+            state: self.state.clone(),
+            retained: Arc::clone(&self.retained),
+            established: Arc::clone(&self.established),
+        }
+    }
+
+    /// Records `client` as the one feeding `connection_state`
+    ///
+    /// Returns false if the client already has this manager's listener.
+    fn start_listening(&self, client: &Arc<Mutex<LightstreamerClient>>) -> bool {
+        let client = Arc::downgrade(client);
+        let mut listening = self
+            .listening_client
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if Weak::ptr_eq(&listening, &client) {
+            return false;
+        }
+        *listening = client;
+        true
+    }
+
+    fn set_state(&self, state: StreamConnectionState) {
+        self.state.send_replace(state);
+    }
+
+    /// Sets how items are refreshed when Lightstreamer reports lost updates
+    ///
+    /// The callback receives the item name after the `LostUpdates` event is
+    /// published, typically to force a REST snapshot, e.g.
+    /// `manager.set_overflow_refresh(move |item| prices.invalidate(item))`.
+    /// Without it lost updates are only reported.
+    pub fn set_overflow_refresh<F>(&self, refresh: F)
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        *self
+            .overflow_refresh
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(refresh));
+    }
+
+    /// Handler to attach to listeners with `Listener::with_lost_updates`
+    ///
+    /// Publishes `SubscriptionEvent::LostUpdates` and runs the overflow refresh,
+    /// if one is set, for every lost-updates notification.
+    pub fn lost_updates_handler(&self) -> LostUpdatesHandler {
+        let events = self.events.clone();
+        let refresh = Arc::clone(&self.overflow_refresh);
+        Arc::new(move |item_name: &str, count: u64| {
+            let _ = events.send(SubscriptionEvent::LostUpdates {
+                item_name: item_name.to_string(),
+                count,
+            });
+            let refresh = refresh
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone();
+            if let Some(refresh) = refresh {
+                refresh(item_name);
+            }
+        })
+    }
+
+    /// Handler to attach to the listener of `item` with `Listener::with_subscription_errors`
+    ///
+    /// Publishes `SubscriptionEvent::SubscriptionFailed` with the item and the
+    /// server's error code when the subscription is refused.
+    ///
+    /// # Arguments
+    /// * `item` - Item of the subscription, e.g. `MARKET:{epic}`
+    pub fn subscription_error_handler(&self, item: impl Into<String>) -> SubscriptionErrorHandler {
+        let events = self.events.clone();
+        let item = item.into();
+        Arc::new(move |code: i32, message: Option<&str>| {
+            let _ = events.send(SubscriptionEvent::SubscriptionFailed {
+                error: StreamError::Subscription {
+                    item: Some(item.clone()),
+                    code: Some(code),
+                    message: message.unwrap_or_default().to_string(),
+                },
+            });
+        })
+    }
+
+    /// Keeps the last merged fields of every market item across reconnects
+    ///
+    /// Attach `market_state_listener` to the market subscriptions. After a
+    /// reconnect the first update for each item publishes
+    /// `SubscriptionEvent::Resynced` with the fields that moved while the
+    /// stream was down, so stateful consumers can tell what changed during
+    /// the outage. Disabling retention forgets the retained values.
+    pub fn set_retain_market_state(&self, retain: bool) {
+        let mut retained = self
+            .retained
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match (retain, retained.is_some()) {
+            (true, false) => *retained = Some(RetainedMarkets::default()),
+            (false, _) => *retained = None,
+            (true, true) => {}
+        }
+    }
+
+    /// Listener that feeds retained market state, see `set_retain_market_state`
+    ///
+    /// Does nothing while retention is disabled.
+    pub fn market_state_listener(&self) -> Listener<PresentationMarketData> {
+        let retained = Arc::clone(&self.retained);
+        let events = self.events.clone();
+        Listener::new(move |update: &PresentationMarketData| {
+            retain_update(&retained, &events, update);
+            Ok(())
+        })
+    }
+
+    /// Merges a market update into the retained state
+    ///
+    /// What `market_state_listener` does for every update it receives.
+    pub fn retain_update(&self, update: &PresentationMarketData) {
+        retain_update(&self.retained, &self.events, update);
+    }
+
+    /// Last merged fields retained for an item, if retention is enabled
+    pub fn retained_fields(&self, item_name: &str) -> Option<MarketFields> {
+        self.retained
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .as_ref()
+            .and_then(|retained| retained.tracker.get(item_name).cloned())
+    }
+
+    /// Marks retained market state for a resync on the next update of each item
+    ///
+    /// Called on every failed connection attempt and whenever the connection
+    /// is lost or stalls; call it directly when a gap is detected otherwise.
+    pub fn mark_resync(&self) {
+        mark_resync(&self.retained);
+    }
+
+    fn emit(&self, event: SubscriptionEvent) {
+        // No receivers is not an error: events are informational
+        let _ = self.events.send(event);
+    }
+
+    /// Runs `connect` until it ends in an orderly way or attempts are exhausted
+    ///
+    /// Each failed attempt is followed by an exponential backoff delay capped at
+    /// `max_reconnect_delay`. When `max_reconnect_attempts` is reached a terminal
+    /// `SubscriptionEvent::Failed` is published and an error is returned. An
+    /// attempt that reached `Connected` before failing resets the count, so a
+    /// long-lived connection that drops starts again from the first attempt and
+    /// the initial delay.
+    ///
+    /// # Arguments
+    /// * `connect` - Opens the connection and resolves when it ends
+    ///
+    /// # Returns
+    /// * `Ok(())` - The connection ended in an orderly way
+    /// * `Err(AppError::Stream)` - Reconnect attempts were exhausted; the error is
+    ///   `StreamError::AuthRejected` if the last attempt was refused for its
+    ///   credentials, `StreamError::Connection` otherwise
+    pub async fn run<F, Fut, T, E>(&self, mut connect: F) -> Result<(), AppError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Debug,
+    {
+        let mut attempt: u32 = 0;
+        loop {
+            attempt += 1;
+            self.set_state(StreamConnectionState::Connecting);
+            self.emit(SubscriptionEvent::Connecting { attempt });
+            self.established.store(false, Ordering::Release);
+
+            match connect().await {
+                Ok(_) => {
+                    info!("Streaming connection closed");
+                    self.set_state(StreamConnectionState::Disconnected { reason: None });
+                    self.emit(SubscriptionEvent::Disconnected);
+                    return Ok(());
+                }
+                Err(e) => {
+                    if self.established.swap(false, Ordering::AcqRel) {
+                        // The connection was up, so this is a fresh drop
+                        attempt = 1;
+                    }
+                    let reason = format!("{e:?}");
+                    self.mark_resync();
+                    self.set_state(StreamConnectionState::Disconnected {
+                        reason: Some(reason.clone()),
+                    });
+                    if !self.policy.allows_retry(attempt) {
+                        error!(
+                            "Streaming connection failed after {} attempts: {}",
+                            attempt, reason
+                        );
+                        self.emit(SubscriptionEvent::Failed {
+                            attempts: attempt,
+                            reason: reason.clone(),
+                        });
+                        return Err(StreamError::connection(format!(
+                            "gave up after {attempt} attempts: {reason}"
+                        ))
+                        .into());
+                    }
+
+                    self.reauthenticate(attempt, &reason).await;
+
+                    let delay = self.policy.delay_for(attempt);
+                    warn!(
+                        "Streaming connection attempt {} failed: {}. Retrying in {:?}",
+                        attempt, reason, delay
+                    );
+                    self.emit(SubscriptionEvent::Reconnecting {
+                        attempt,
+                        delay,
+                        reason,
+                    });
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// Runs a Lightstreamer client with this manager's reconnect policy
+    ///
+    /// Registers a `connection_listener` on the client so `connection_state`
+    /// follows the client's status changes; running the same client again
+    /// reuses that listener. When an authenticator is set, each attempt uses
+    /// the current session's credentials as password.
+    ///
+    /// # Arguments
+    /// * `client` - Client with its subscriptions already registered
+    /// * `shutdown_signal` - Notified to end the connection in an orderly way
+    pub async fn run_lightstreamer(
+        &self,
+        client: Arc<Mutex<LightstreamerClient>>,
+        shutdown_signal: Arc<Notify>,
+    ) -> Result<(), AppError> {
+        if self.start_listening(&client) {
+            client
+                .lock()
+                .await
+                .add_listener(Box::new(self.connection_listener()));
+        }
+        self.run(|| {
+            let client = Arc::clone(&client);
+            let shutdown_signal = Arc::clone(&shutdown_signal);
+            let authenticator = self.authenticator();
+            async move {
+                let mut ls = client.lock().await;
+                if let Some(authenticator) = authenticator {
+                    match authenticator.websocket_info().await {
+                        Ok(info) => ls
+                            .connection_details
+                            .set_password(Some(&info.get_ws_password())),
+                        Err(e) => warn!("Could not load streaming credentials: {}", e),
+                    }
+                }
+                let result = ls.connect_direct(shutdown_signal).await;
+                if result.is_ok() {
+                    ls.disconnect().await;
+                }
+                result
+            }
+        })
+        .await
+    }
+}
+
+impl Default for SubscriptionManager {
+    fn default() -> Self {
+        Self::new(ReconnectPolicy::default())
+    }
+}
+
+/// Description of a Lightstreamer subscription
+///
+/// `max_frequency` is forwarded to the server as the requested maximum update
+/// frequency, so slow consumers such as dashboards receive fewer updates.
+#[derive(Debug)]
+pub struct SubscriptionConfig {
+    /// Subscription mode (MERGE for prices, DISTINCT for trade events)
+    pub mode: SubscriptionMode,
+    /// Items to subscribe to (e.g. `MARKET:{epic}`)
+    pub items: Vec<String>,
+    /// Fields requested for every item
+    pub fields: Vec<String>,
+    /// Whether the initial snapshot is requested
+    pub snapshot: bool,
+    /// Maximum number of updates per second per item (None = unlimited)
+    pub max_frequency: Option<f64>,
+}
+
+impl SubscriptionConfig {
+    /// Creates a new configuration requesting a snapshot and no frequency limit
+    pub fn new(mode: SubscriptionMode, items: Vec<String>, fields: Vec<String>) -> Self {
+        Self {
+            mode,
+            items,
+            fields,
+            snapshot: true,
+            max_frequency: None,
+        }
+    }
+
+    /// Creates a configuration using the mode the items need
+    ///
+    /// See [`default_mode_for_item`].
+    ///
+    /// # Returns
+    /// * `Ok(SubscriptionConfig)` - In `Distinct` mode for trade and tick items,
+    ///   `Merge` otherwise
+    /// * `Err(AppError::InvalidInput)` - If the items need different modes;
+    ///   subscribe to them separately
+    pub fn for_items(items: Vec<String>, fields: Vec<String>) -> Result<Self, AppError> {
+        let events = items.iter().filter(|item| is_event_item(item)).count();
+        if events != 0 && events != items.len() {
+            return Err(AppError::InvalidInput(
+                "trade or tick items cannot share a subscription with MERGE items".to_string(),
+            ));
+        }
+        let mode = if events == 0 {
+            SubscriptionMode::Merge
+        } else {
+            SubscriptionMode::Distinct
+        };
+        Ok(Self::new(mode, items, fields))
+    }
+
+    /// Limits the update rate to `updates_per_second` per item
+    #[must_use]
+    pub fn with_max_frequency(mut self, updates_per_second: f64) -> Self {
+        self.max_frequency = Some(updates_per_second);
+        self
+    }
+
+    /// Sets whether the initial snapshot is requested
+    #[must_use]
+    pub fn with_snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Builds the Lightstreamer subscription
+    ///
+    /// # Returns
+    /// * `Ok(Subscription)` ready to receive listeners
+    /// * `Err(AppError)` if the frequency is not positive, a trade or tick item
+    ///   is requested in `Merge` mode, or Lightstreamer rejects the settings
+    ///   (`AppError::Stream` with a `StreamError::Subscription` naming the items)
+    pub fn build(self) -> Result<Subscription, AppError> {
+        if matches!(self.mode, SubscriptionMode::Merge)
+            && let Some(item) = self.items.iter().find(|item| is_event_item(item))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "{item} must be subscribed in DISTINCT mode; MERGE would drop events"
+            )));
+        }
+
+        if let Some(frequency) = self.max_frequency
+            && !(frequency.is_finite() && frequency > 0.0)
+        {
+            return Err(AppError::InvalidInput(format!(
+                "max_frequency must be a positive number of updates per second, got {frequency}"
+            )));
+        }
+
+        let items = self.items.join(" ");
+        let mut subscription = Subscription::new(self.mode, Some(self.items), Some(self.fields))
+            .map_err(|e| subscription_refused(&items, e))?;
+        subscription
+            .set_data_adapter(None)
+            .map_err(|e| subscription_refused(&items, e))?;
+        subscription
+            .set_requested_snapshot(Some(if self.snapshot {
+                Snapshot::Yes
+            } else {
+                Snapshot::No
+            }))
+            .map_err(|e| subscription_refused(&items, e))?;
+        subscription
+            .set_requested_max_frequency(self.max_frequency)
+            .map_err(|e| subscription_refused(&items, e))?;
+        Ok(subscription)
+    }
+}
+
+/// Error for subscription settings the Lightstreamer client rejected
+fn subscription_refused(items: &str, e: impl std::fmt::Display) -> AppError {
+    StreamError::Subscription {
+        item: Some(items.to_string()),
+        code: None,
+        message: e.to_string(),
+    }
+    .into()
+}
+
+/// Client-side throttle that coalesces market updates per item
+///
+/// Updates arriving within the window are merged: the latest full field set is
+/// kept and changed fields accumulate, so no change is lost when an update is
+/// held back. Held updates are delivered with the next update after the window,
+/// or by a trailing `flush` once the window ends if no further update arrives.
+#[derive(Debug)]
+pub struct UpdateThrottle {
+    interval: Duration,
+    items: HashMap<String, ThrottledItem>,
+}
+
+#[derive(Debug, Default)]
+struct ThrottledItem {
+    last_emit: Option<Instant>,
+    pending: Option<PresentationMarketData>,
+    flush_scheduled: bool,
+}
+
+impl UpdateThrottle {
+    /// Creates a throttle allowing at most `max_frequency` updates per second per item
+    pub fn new(max_frequency: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / max_frequency.max(f64::MIN_POSITIVE)),
+            items: HashMap::new(),
+        }
+    }
+
+    /// Offers an update received at `now`
+    ///
+    /// # Returns
+    /// * `Some(PresentationMarketData)` - Coalesced update to deliver now
+    /// * `None` - The update was merged into the pending one for the item
+    pub fn offer(
+        &mut self,
+        update: &PresentationMarketData,
+        now: Instant,
+    ) -> Option<PresentationMarketData> {
+        let item = self.items.entry(update.item_name.clone()).or_default();
+        match item.pending.as_mut() {
+            Some(pending) => {
+                pending.fields = update.fields.clone();
+                pending.changed_fields.merge(&update.changed_fields);
+                pending.is_snapshot |= update.is_snapshot;
+            }
+            None => item.pending = Some(update.clone()),
+        }
+
+        let due = item
+            .last_emit
+            .is_none_or(|last| now.duration_since(last) >= self.interval);
+        if due {
+            item.last_emit = Some(now);
+            item.pending.take()
+        } else {
+            None
+        }
+    }
+
+    /// Claims the trailing flush of the update held back for `item_name`
+    ///
+    /// # Returns
+    /// * `Some(Instant)` - When the held update is due; call `flush` then
+    /// * `None` - Nothing is held back, or a flush is already scheduled
+    pub fn schedule_flush(&mut self, item_name: &str) -> Option<Instant> {
+        let item = self.items.get_mut(item_name)?;
+        if item.flush_scheduled || item.pending.is_none() {
+            return None;
+        }
+        item.flush_scheduled = true;
+        Some(
+            item.last_emit
+                .map_or_else(Instant::now, |last| last + self.interval),
+        )
+    }
+
+    /// Delivers the update held back for `item_name` if its window has ended
+    ///
+    /// Releases the flush claimed by `schedule_flush`. If an update delivered
+    /// in between restarted the window, the held update is not due yet and a
+    /// new flush has to be scheduled.
+    pub fn flush(&mut self, item_name: &str, now: Instant) -> Option<PresentationMarketData> {
+        let item = self.items.get_mut(item_name)?;
+        item.flush_scheduled = false;
+        let due = item
+            .last_emit
+            .is_none_or(|last| now.duration_since(last) >= self.interval);
+        if due && item.pending.is_some() {
+            item.last_emit = Some(now);
+            item.pending.take()
+        } else {
+            None
+        }
+    }
+}
+
+/// Creates a market listener that delivers at most `max_frequency` updates per second per item
+///
+/// An update held back by the throttle is delivered when its window ends, even
+/// if the item receives no further update, as long as the listener is called
+/// from within a Tokio runtime.
+///
+/// # Arguments
+/// * `max_frequency` - Maximum updates per second per item
+/// * `callback` - Receives the coalesced updates
+pub fn throttled_market_listener<F>(
+    max_frequency: f64,
+    callback: F,
+) -> Listener<PresentationMarketData>
+where
+    F: Fn(&PresentationMarketData) -> ListenerResult + Send + Sync + 'static,
+{
+    let throttle = Arc::new(std::sync::Mutex::new(UpdateThrottle::new(max_frequency)));
+    let callback = Arc::new(callback);
+    Listener::new(move |update: &PresentationMarketData| {
+        let (ready, flush_at) = {
+            let mut throttle = throttle
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let ready = throttle.offer(update, Instant::now());
+            // Without a Tokio runtime the held update waits for the next one
+            let flush_at = match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => throttle
+                    .schedule_flush(&update.item_name)
+                    .map(|at| (runtime, at)),
+                Err(_) => None,
+            };
+            (ready, flush_at)
+        };
+        if let Some((runtime, flush_at)) = flush_at {
+            spawn_trailing_flush(
+                &runtime,
+                Arc::clone(&throttle),
+                Arc::clone(&callback),
+                update.item_name.clone(),
+                flush_at,
+            );
+        }
+        match ready {
+            Some(coalesced) => callback(&coalesced),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Delivers an item's held-back update once its throttle window ends
+fn spawn_trailing_flush<F>(
+    runtime: &tokio::runtime::Handle,
+    throttle: Arc<std::sync::Mutex<UpdateThrottle>>,
+    callback: Arc<F>,
+    item_name: String,
+    flush_at: Instant,
+) where
+    F: Fn(&PresentationMarketData) -> ListenerResult + Send + Sync + 'static,
+{
+    runtime.spawn(async move {
+        let mut flush_at = flush_at;
+        loop {
+            tokio::time::sleep_until(flush_at.into()).await;
+            let (ready, next) = {
+                let mut throttle = throttle
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                let ready = throttle.flush(&item_name, Instant::now());
+                (ready, throttle.schedule_flush(&item_name))
+            };
+            if let Some(coalesced) = ready
+                && let Err(e) = callback(&coalesced)
+            {
+                error!("Error in throttled market callback: {}", e);
+            }
+            match next {
+                Some(next) => flush_at = next,
+                None => break,
+            }
+        }
+    });
+}
+
+/// Market state transition: epic, new state and the state it replaced
+pub type MarketStateChange = (String, MarketState, MarketState);
+
+/// Detects market state transitions in merged market updates
+///
+/// Updates are merged with a [`MarketTracker`], so an update that does not
+/// carry `MARKET_STATE` keeps the last known state. The first state seen for
+/// an item is taken as the baseline and is not reported.
+#[derive(Debug, Default)]
+pub struct MarketStateTransitions {
+    tracker: MarketTracker,
+}
+
+impl MarketStateTransitions {
+    /// Creates a detector with no known states
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges an update and returns the transition it causes, if any
+    pub fn observe(&mut self, update: &PresentationMarketData) -> Option<MarketStateChange> {
+        let previous = self
+            .tracker
+            .get(&update.item_name)
+            .and_then(|fields| fields.market_state.clone());
+        let merged = self.tracker.apply(MarketDelta {
+            item_name: update.item_name.clone(),
+            is_snapshot: update.is_snapshot,
+            changed_fields: update.changed_fields.clone(),
+        });
+        let current = merged.fields.market_state?;
+        let previous = previous?;
+        if current == previous {
+            return None;
+        }
+        let epic = update
+            .item_name
+            .strip_prefix("MARKET:")
+            .unwrap_or(&update.item_name);
+        Some((epic.to_string(), current, previous))
+    }
+}
+
+/// Subscribes to the market state of `epics` and streams only its transitions
+///
+/// The returned subscription requests just `MARKET_STATE` for every epic;
+/// add it to a connected `LightstreamerClient`. The stream yields
+/// `(epic, state, previous)` each time a market changes state, for example
+/// to and from `Tradeable`, and ends when the subscription is dropped.
+///
+/// # Returns
+/// * `Ok((Subscription, Stream))` - Subscription to register and its transitions
+/// * `Err(AppError)` - If no epic is given or the subscription cannot be built
+pub fn market_state_changes(
+    epics: &[String],
+) -> Result<(Subscription, impl Stream<Item = MarketStateChange>), AppError> {
+    if epics.is_empty() {
+        return Err(AppError::InvalidInput(
+            "at least one epic is required".to_string(),
+        ));
+    }
+    let items = epics
+        .iter()
+        .map(|epic| LsItem::market(epic.as_str()).name())
+        .collect();
+    let mut subscription = SubscriptionConfig::new(
+        SubscriptionMode::Merge,
+        items,
+        vec![MarketField::MarketState.as_str().to_string()],
+    )
+    .build()?;
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let transitions = std::sync::Mutex::new(MarketStateTransitions::new());
+    subscription.add_listener(Box::new(Listener::new(
+        move |update: &PresentationMarketData| {
+            let change = transitions
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .observe(update);
+            if let Some(change) = change {
+                // A dropped stream only means nobody is listening any more
+                let _ = sender.send(change);
+            }
+            Ok(())
+        },
+    )));
+
+    let changes = stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|change| (change, receiver))
+    });
+    Ok((subscription, changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lightstreamer_client() -> Arc<Mutex<LightstreamerClient>> {
+        Arc::new(Mutex::new(
+            LightstreamerClient::new(Some("http://127.0.0.1:1"), None, None, None).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn test_connection_listener_registered_once_per_client() {
+        let manager = SubscriptionManager::default();
+        let first = lightstreamer_client();
+        let second = lightstreamer_client();
+
+        assert!(manager.start_listening(&first));
+        assert!(!manager.start_listening(&first));
+        assert!(manager.start_listening(&second));
+        assert!(!manager.start_listening(&second));
+    }
+}
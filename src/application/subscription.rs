@@ -0,0 +1,220 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Subscription registry with reference counting
+//!
+//! Several components of an application often watch the same instruments. The
+//! registry shares one underlying Lightstreamer subscription per item and field
+//! set, counts how many handles refer to it and only tears it down when the
+//! last handle is dropped, keeping the session below IG's per-session item limits.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+/// Callback invoked when the last handle of a subscription is dropped
+pub type ReleaseCallback = Arc<dyn Fn(&SubscriptionKey) + Send + Sync>;
+
+/// Identifies a subscription by its item and requested fields
+///
+/// Fields are sorted and deduplicated so that the same field set requested in a
+/// different order maps to the same subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionKey {
+    /// Lightstreamer item name (e.g. `MARKET:IX.D.DAX.IFD.IP`)
+    pub item: String,
+    /// Normalised list of field names
+    pub fields: Vec<String>,
+}
+
+impl SubscriptionKey {
+    /// Creates a new key, normalising the field list
+    ///
+    /// # Arguments
+    /// * `item` - Lightstreamer item name
+    /// * `fields` - Fields requested for the item
+    pub fn new<I, S>(item: impl Into<String>, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut fields: Vec<String> = fields.into_iter().map(Into::into).collect();
+        fields.sort();
+        fields.dedup();
+        Self {
+            item: item.into(),
+            fields,
+        }
+    }
+}
+
+impl fmt::Display for SubscriptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]", self.item, self.fields.join(","))
+    }
+}
+
+struct RegistryInner {
+    counts: Mutex<HashMap<SubscriptionKey, usize>>,
+    on_release: ReleaseCallback,
+}
+
+/// Registry of active subscriptions keyed on (item, fields)
+///
+/// Cloning the registry is cheap and every clone shares the same state.
+#[derive(Clone)]
+pub struct SubscriptionRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+impl SubscriptionRegistry {
+    /// Creates a new registry
+    ///
+    /// # Arguments
+    /// * `on_release` - Called with the key once the last handle for it is dropped,
+    ///   typically used to unsubscribe from Lightstreamer
+    pub fn new<F>(on_release: F) -> Self
+    where
+        F: Fn(&SubscriptionKey) + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(RegistryInner {
+                counts: Mutex::new(HashMap::new()),
+                on_release: Arc::new(on_release),
+            }),
+        }
+    }
+
+    /// Acquires a handle for the given item and fields
+    ///
+    /// When `SubscriptionHandle::is_first` returns `true` the caller is responsible
+    /// for creating the underlying subscription; otherwise it is already active
+    /// and shared.
+    ///
+    /// # Arguments
+    /// * `item` - Lightstreamer item name
+    /// * `fields` - Fields requested for the item
+    ///
+    /// # Returns
+    /// * `SubscriptionHandle` that releases its reference on drop
+    pub fn acquire<I, S>(&self, item: impl Into<String>, fields: I) -> SubscriptionHandle
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let key = SubscriptionKey::new(item, fields);
+        let mut counts = self
+            .inner
+            .counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let count = counts.entry(key.clone()).or_insert(0);
+        *count += 1;
+        let is_first = *count == 1;
+        debug!("Subscription {} acquired ({} refs)", key, count);
+
+        SubscriptionHandle {
+            key,
+            is_first,
+            registry: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Number of handles currently referring to the given subscription
+    pub fn ref_count(&self, key: &SubscriptionKey) -> usize {
+        self.inner
+            .counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(key)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Number of distinct underlying subscriptions currently active
+    pub fn len(&self) -> usize {
+        self.inner
+            .counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+
+    /// Returns true if no subscription is active
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for SubscriptionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubscriptionRegistry")
+            .field("active", &self.len())
+            .finish()
+    }
+}
+
+/// Reference to a shared subscription
+///
+/// Dropping the handle decrements the reference count; the registry's release
+/// callback runs when the count reaches zero.
+pub struct SubscriptionHandle {
+    key: SubscriptionKey,
+    is_first: bool,
+    registry: Arc<RegistryInner>,
+}
+
+impl SubscriptionHandle {
+    /// Key of the subscription this handle refers to
+    pub fn key(&self) -> &SubscriptionKey {
+        &self.key
+    }
+
+    /// Returns true if this handle created the entry in the registry
+    pub fn is_first(&self) -> bool {
+        self.is_first
+    }
+}
+
+impl fmt::Debug for SubscriptionHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubscriptionHandle")
+            .field("key", &self.key)
+            .field("is_first", &self.is_first)
+            .finish()
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        let released = {
+            let mut counts = self
+                .registry
+                .counts
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            match counts.get_mut(&self.key) {
+                Some(count) if *count > 1 => {
+                    *count -= 1;
+                    debug!("Subscription {} released ({} refs)", self.key, count);
+                    false
+                }
+                Some(_) => {
+                    counts.remove(&self.key);
+                    true
+                }
+                None => false,
+            }
+        };
+
+        // Run the callback outside the lock so it may touch the registry again
+        if released {
+            debug!("Last handle for subscription {} dropped", self.key);
+            (self.registry.on_release)(&self.key);
+        }
+    }
+}
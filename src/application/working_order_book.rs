@@ -0,0 +1,184 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Live book of working orders
+//!
+//! `WorkingOrderBook` is seeded from `GET /workingorders` and kept current by
+//! the `WOU` (working order update) pushes of the trade stream. Unlike
+//! position updates, a `WOU` push describes the whole order, so orders placed
+//! elsewhere are added as they arrive; filled, cancelled and expired orders
+//! are dropped when IG reports them deleted. The book only goes stale when
+//! updates may have been missed or a push lacks the fields to build an order.
+//! Resyncs share `PositionBook`'s protection against racing updates, see
+//! `LiveBook`.
+
+use crate::application::interfaces::account::AccountService;
+use crate::application::interfaces::listener::Listener;
+use crate::application::live_book::LiveBook;
+use crate::error::AppError;
+use crate::model::responses::WorkingOrdersResponse;
+use crate::presentation::account::WorkingOrderData;
+use crate::presentation::order::Status;
+use crate::presentation::trade::{TradeData, WorkingOrderUpdate};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Working orders kept current by the trade stream
+pub struct WorkingOrderBook<S: AccountService> {
+    service: Arc<S>,
+    book: LiveBook<WorkingOrderData>,
+}
+
+impl<S: AccountService> WorkingOrderBook<S> {
+    /// Creates an empty book that resyncs from REST on first read
+    ///
+    /// # Arguments
+    /// * `service` - Account service used to seed and resync the book
+    pub fn new(service: Arc<S>) -> Self {
+        Self {
+            service,
+            book: LiveBook::new(),
+        }
+    }
+
+    /// Creates a listener that applies trade stream updates to this book
+    ///
+    /// Attach it to the `TRADE:{account_id}` subscription. Only `WOU` pushes
+    /// are used; confirmations and position updates are ignored.
+    pub fn trade_listener(&self) -> Listener<TradeData> {
+        let book = self.book.clone();
+        Listener::new(move |update: &TradeData| {
+            if let Some(wou) = update.fields.wou.as_ref() {
+                book.update(|orders| apply_update(orders, wou));
+            }
+            Ok(())
+        })
+    }
+
+    /// Applies one working order update
+    ///
+    /// Deleted orders are removed, known orders get the amended fields and new
+    /// orders are added. An update that is too sparse to build a new order
+    /// from marks the book stale instead.
+    pub fn apply(&self, update: &WorkingOrderUpdate) {
+        self.book.update(|orders| apply_update(orders, update));
+    }
+
+    /// Marks the book stale so that the next `working_orders` call resyncs it
+    ///
+    /// Call it when the streaming connection drops, since updates may have
+    /// been missed.
+    pub fn mark_stale(&self) {
+        self.book.mark_stale();
+    }
+
+    /// Returns true if the book needs a REST resync before it can be trusted
+    pub fn is_stale(&self) -> bool {
+        self.book.is_stale()
+    }
+
+    /// Replaces the book with a full working orders snapshot and clears the stale flag
+    pub fn seed(&self, response: WorkingOrdersResponse) {
+        self.book.seed(by_deal_id(response));
+    }
+
+    /// Reloads the book from `GET /workingorders`
+    ///
+    /// If a stream update or `mark_stale` arrives while the request is in
+    /// flight the snapshot is discarded, so a fill or cancellation pushed
+    /// meanwhile is not undone, and the book stays stale for the next read.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of working orders in the snapshot
+    /// * `Err(AppError)` - If the request fails; the book stays stale
+    pub async fn resync(&self) -> Result<usize, AppError> {
+        let generation = self.book.generation();
+        let response = self.service.get_working_orders().await?;
+        let count = response.working_orders.len();
+        if self
+            .book
+            .seed_if_unchanged(generation, by_deal_id(response))
+        {
+            info!("Working order book resynced: {} working orders", count);
+        } else {
+            debug!("Working orders changed during resync, discarding the snapshot");
+        }
+        Ok(count)
+    }
+
+    /// Working orders as currently held, without any REST call
+    ///
+    /// May be out of date while `is_stale` is true. Orders are ordered by
+    /// creation time.
+    pub fn current_orders(&self) -> Vec<WorkingOrderData> {
+        let mut orders = self.book.values();
+        orders.sort_by(|a, b| {
+            a.created_date_utc
+                .cmp(&b.created_date_utc)
+                .then_with(|| a.deal_id.cmp(&b.deal_id))
+        });
+        orders
+    }
+
+    /// Working orders, resyncing from REST first if the book is stale
+    ///
+    /// # Returns
+    /// * `Ok(Vec<WorkingOrderData>)` - Current working orders
+    /// * `Err(AppError)` - If a needed resync fails
+    pub async fn working_orders(&self) -> Result<Vec<WorkingOrderData>, AppError> {
+        if self.is_stale() {
+            self.resync().await?;
+        }
+        Ok(self.current_orders())
+    }
+}
+
+/// Keys a working orders snapshot by deal ID
+fn by_deal_id(response: WorkingOrdersResponse) -> HashMap<String, WorkingOrderData> {
+    response
+        .working_orders
+        .into_iter()
+        .map(|order| {
+            (
+                order.working_order_data.deal_id.clone(),
+                order.working_order_data,
+            )
+        })
+        .collect()
+}
+
+/// Applies an update to the orders, returning false if it could not be applied
+fn apply_update(
+    orders: &mut HashMap<String, WorkingOrderData>,
+    update: &WorkingOrderUpdate,
+) -> bool {
+    if update.deal_status == Some(Status::Rejected) {
+        return true;
+    }
+    let Some(deal_id) = update.deal_id.as_deref() else {
+        debug!("Ignoring working order update without a deal ID");
+        return true;
+    };
+    if update.is_deleted() {
+        orders.remove(deal_id);
+        return true;
+    }
+    if let Some(order) = orders.get_mut(deal_id) {
+        update.apply_to(order);
+        return true;
+    }
+    match update.to_working_order_data() {
+        Some(order) => {
+            orders.insert(deal_id.to_string(), order);
+            true
+        }
+        None => {
+            debug!("Working order {} cannot be built from the update", deal_id);
+            false
+        }
+    }
+}
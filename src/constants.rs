@@ -27,3 +27,71 @@ pub const DEFAULT_ORDER_SELL_LEVEL: f64 = 0.0;
 /// Developers can use this constant to ensure uniformity and consistency when working with order buy levels
 /// across the application.
 pub const DEFAULT_ORDER_BUY_LEVEL: f64 = 10000.0;
+/// Initial delay in milliseconds between polls of the deal confirmation endpoint
+pub const CONFIRM_POLL_INITIAL_DELAY_MS: u64 = 250;
+/// Maximum delay in milliseconds between polls of the deal confirmation endpoint
+pub const CONFIRM_POLL_MAX_DELAY_MS: u64 = 2000;
+/// Capacity of the in-process channel used to fan out streamed trade updates
+pub const TRADE_UPDATES_CHANNEL_CAPACITY: usize = 256;
+/// Header used to send the per-request correlation ID to the IG Markets API
+pub const REQUEST_ID_HEADER: &str = "X-Request-ID";
+/// Time in milliseconds a REST market snapshot is reused by `PriceSource`
+pub const PRICE_SNAPSHOT_CACHE_TTL_MS: u64 = 2000;
+/// Default upper bound in seconds for the streaming reconnect delay
+pub const DEFAULT_MAX_RECONNECT_DELAY_SECS: u64 = 60;
+/// Capacity of the channel used to publish subscription manager events
+pub const SUBSCRIPTION_EVENTS_CHANNEL_CAPACITY: usize = 64;
+/// Maximum number of epics accepted by a single multiple market details request
+pub const MAX_MARKET_DETAILS_EPICS: usize = 50;
+/// Default deal size increment used when rounding computed position sizes
+pub const DEFAULT_SIZE_STEP: f64 = 0.01;
+/// Maximum number of candles requested per call by ranged historical price queries
+pub const HISTORICAL_PRICES_MAX_POINTS_PER_REQUEST: i64 = 1000;
+/// Default time in seconds market details are served from the in-process cache
+pub const DEFAULT_MARKET_DETAILS_CACHE_TTL_SECS: u64 = 300;
+/// Maximum number of concurrent market details requests when resolving DB entry expiries
+pub const DB_ENTRIES_EXPIRY_CONCURRENCY: usize = 4;
+/// Single rate limiter waits at or above this many milliseconds are logged
+pub const RATE_LIMITER_SLOW_WAIT_THRESHOLD_MS: u64 = 500;
+/// Upper bound in seconds for the computed backoff between rate-limit retries
+pub const MAX_RETRY_BACKOFF_SECS: u64 = 300;
+/// How far back `realized_pnl_for` searches the transaction history, in hours
+pub const REALIZED_PNL_LOOKBACK_HOURS: i64 = 24;
+/// Date format accepted by the `history/transactions` endpoint
+pub const TRANSACTIONS_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+/// Maximum time in seconds `partial_close_and_protect` waits for the close to be confirmed
+pub const PARTIAL_CLOSE_CONFIRM_TIMEOUT_SECS: u64 = 10;
+/// Maximum length IG accepts for a client-generated deal reference
+pub const DEAL_REFERENCE_MAX_LEN: usize = 30;
+/// Minimum number of random characters kept after a deal reference prefix
+pub const DEAL_REFERENCE_MIN_SUFFIX_LEN: usize = 8;
+/// Account ID used by `Config::new` when `IG_ACCOUNT_ID` is not set
+pub const DEFAULT_ACCOUNT_ID_PLACEHOLDER: &str = "default_account_id";
+/// Maximum time in seconds `place_strategy` waits for each leg to be confirmed
+pub const STRATEGY_LEG_CONFIRM_TIMEOUT_SECS: u64 = 10;
+/// Time in milliseconds a market state fetched over REST is reused by `Client::market_state`
+pub const MARKET_STATE_CACHE_TTL_MS: u64 = 1000;
+/// Fraction of the size step above which rounding an order size is logged as a warning
+pub const SIZE_ROUNDING_WARN_STEP_FRACTION: f64 = 0.1;
+/// Tolerance below which two deal sizes are treated as equal
+pub const SIZE_EPSILON: f64 = 1e-9;
+/// Time in seconds an FX rate fetched by `FxConverter` is reused
+pub const FX_RATE_CACHE_TTL_SECS: u64 = 60;
+/// Maximum number of ticks `TickStore` writes per insert
+pub const TICK_STORE_BATCH_SIZE: usize = 500;
+/// Longest time in milliseconds a tick waits in the `TickStore` queue before being written
+pub const TICK_STORE_FLUSH_INTERVAL_MS: u64 = 1000;
+/// Maximum number of ticks `TickStore` holds in memory while the database catches up
+pub const TICK_STORE_CAPACITY: usize = 100_000;
+/// Seconds before expiry at which `SessionGuard` renews a session
+pub const SESSION_EXPIRY_MARGIN_SECS: u64 = 300;
+/// Milliseconds after a renewal during which a rejected session is not renewed again
+pub const SESSION_RENEWAL_COOLDOWN_MS: u64 = 5000;
+/// Seconds requests are held back after IG reports maintenance without a `Retry-After`
+pub const MAINTENANCE_COOLDOWN_SECS: u64 = 600;
+/// Error code IG returns on 400 or 404 when an endpoint does not serve the requested version
+pub const UNSUPPORTED_VERSION_ERROR_CODE: &str = "invalid.version";
+/// Seconds before an OAuth access token expires at which it is refreshed
+pub const OAUTH_EXPIRY_MARGIN_SECS: u64 = 5;
+/// Maximum number of epics kept in the client's market details cache
+pub const MAX_MARKET_DETAILS_CACHE_ENTRIES: usize = 1000;
@@ -122,6 +122,47 @@ impl From<AppError> for AuthError {
     }
 }
 
+/// Error raised when a configuration is incomplete or malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A required field is empty or still holds a placeholder value
+    MissingField(&'static str),
+    /// A URL field could not be parsed
+    InvalidUrl {
+        /// Name of the offending field
+        field: &'static str,
+        /// Value that failed to parse
+        value: String,
+        /// Parser error message
+        reason: String,
+    },
+    /// A field holds a value outside of its accepted range
+    InvalidValue {
+        /// Name of the offending field
+        field: &'static str,
+        /// Description of the accepted values
+        reason: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingField(field) => write!(f, "missing required config field: {field}"),
+            ConfigError::InvalidUrl {
+                field,
+                value,
+                reason,
+            } => write!(f, "invalid url in {field} ({value}): {reason}"),
+            ConfigError::InvalidValue { field, reason } => {
+                write!(f, "invalid value for {field}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
 /// General application error type
 #[derive(Debug)]
 pub enum AppError {
@@ -161,6 +202,35 @@ pub enum AppError {
     ///     that were violated.
     ///
     InvalidInput(String),
+    /// Operation did not complete within the allotted time
+    Timeout(String),
+    /// The account cannot deal, e.g. it is disabled or view-only
+    TradingNotAllowed(String),
+    /// Missing or malformed configuration
+    Config(ConfigError),
+    /// Streaming connection, subscription or update error
+    Stream(StreamError),
+    /// IG is down for scheduled maintenance
+    Maintenance {
+        /// How long IG asked clients to wait, from `Retry-After` when sent,
+        /// or what remains of the client's own maintenance cooldown
+        retry_after: Option<std::time::Duration>,
+    },
+    /// IG does not serve the endpoint in the requested `Version`
+    UnsupportedVersion {
+        /// Path of the endpoint, e.g. `markets/{epic}`
+        endpoint: String,
+        /// Version sent in the `Version` header
+        version: u8,
+    },
+    /// A market has no bid or offer to price a deal or P&L with, typically
+    /// because it is suspended or closed
+    PriceUnavailable {
+        /// Epic of the market
+        epic: String,
+        /// Status IG reported for the market, e.g. `SUSPENDED`
+        market_status: String,
+    },
 }
 
 impl Display for AppError {
@@ -179,6 +249,21 @@ impl Display for AppError {
             AppError::WebSocketError(s) => write!(f, "websocket error: {s}"),
             AppError::Deserialization(s) => write!(f, "deserialization error: {s}"),
             AppError::InvalidInput(s) => write!(f, "invalid input: {s}"),
+            AppError::Timeout(s) => write!(f, "timeout: {s}"),
+            AppError::TradingNotAllowed(s) => write!(f, "trading not allowed: {s}"),
+            AppError::Config(e) => write!(f, "config error: {e}"),
+            AppError::Stream(e) => write!(f, "stream error: {e}"),
+            AppError::Maintenance {
+                retry_after: Some(delay),
+            } => write!(f, "IG is under maintenance, retry in {}s", delay.as_secs()),
+            AppError::Maintenance { retry_after: None } => write!(f, "IG is under maintenance"),
+            AppError::UnsupportedVersion { endpoint, version } => {
+                write!(f, "{endpoint} is not available in API version {version}")
+            }
+            AppError::PriceUnavailable {
+                epic,
+                market_status,
+            } => write!(f, "no price available for {epic} (market {market_status})"),
         }
     }
 }
@@ -190,6 +275,16 @@ impl From<reqwest::Error> for AppError {
         AppError::Network(e)
     }
 }
+impl From<ConfigError> for AppError {
+    fn from(e: ConfigError) -> Self {
+        AppError::Config(e)
+    }
+}
+impl From<StreamError> for AppError {
+    fn from(e: StreamError) -> Self {
+        AppError::Stream(e)
+    }
+}
 impl From<io::Error> for AppError {
     fn from(e: io::Error) -> Self {
         AppError::Io(e)
@@ -200,6 +295,12 @@ impl From<serde_json::Error> for AppError {
         AppError::Json(e)
     }
 }
+#[cfg(feature = "csv")]
+impl From<csv::Error> for AppError {
+    fn from(e: csv::Error) -> Self {
+        AppError::Io(e.into())
+    }
+}
 impl From<sqlx::Error> for AppError {
     fn from(e: sqlx::Error) -> Self {
         AppError::Db(e)
@@ -232,3 +333,136 @@ impl From<Box<dyn std::error::Error>> for AppError {
         }
     }
 }
+
+/// Returns true if a connection error means Lightstreamer rejected the password
+///
+/// Lightstreamer reports a failed credential check as server error 1
+/// (`user/password check failed`), which is what happens once IG's session
+/// tokens expire.
+pub fn is_auth_rejection(reason: &str) -> bool {
+    let reason = reason.to_ascii_lowercase();
+    reason.contains("user/password")
+        || reason.contains("unauthorized")
+        || reason.contains("authentication failed")
+        || reason.contains("server error 1:")
+}
+
+/// Error raised by the streaming layer
+///
+/// Tells apart failures that call for a reconnect (`Connection`,
+/// `AuthRejected`) from those tied to one item, such as an unknown epic
+/// (`Subscription`) or a value IG sent that could not be read (`Parse`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamError {
+    /// The connection could not be opened or was lost
+    Connection(String),
+    /// The server rejected the streaming credentials
+    AuthRejected(String),
+    /// The server refused a subscription
+    Subscription {
+        /// Item of the refused subscription, e.g. `MARKET:{epic}`
+        item: Option<String>,
+        /// Lightstreamer error code, when reported
+        code: Option<i32>,
+        /// Error message from the server or the client library
+        message: String,
+    },
+    /// A field of an update could not be parsed
+    Parse {
+        /// Item the update belongs to
+        item: Option<String>,
+        /// Name of the field, e.g. `BID`
+        field: String,
+        /// Value as received
+        raw: String,
+    },
+}
+
+impl StreamError {
+    /// Classifies a failed connection attempt from its reason
+    ///
+    /// Rejected credentials become `AuthRejected`, anything else `Connection`.
+    pub fn connection(reason: impl Into<String>) -> Self {
+        let reason = reason.into();
+        if is_auth_rejection(&reason) {
+            StreamError::AuthRejected(reason)
+        } else {
+            StreamError::Connection(reason)
+        }
+    }
+
+    /// Creates a parse error for a field, without the item
+    pub fn parse(field: &str, raw: &str) -> Self {
+        StreamError::Parse {
+            item: None,
+            field: field.to_string(),
+            raw: raw.to_string(),
+        }
+    }
+
+    /// Sets the item of a subscription or parse error that has none
+    #[must_use]
+    pub fn for_item(mut self, item_name: &str) -> Self {
+        if let StreamError::Subscription { item, .. } | StreamError::Parse { item, .. } = &mut self
+            && item.is_none()
+            && !item_name.is_empty()
+        {
+            *item = Some(item_name.to_string());
+        }
+        self
+    }
+
+    /// Item the error relates to, if any
+    pub fn item(&self) -> Option<&str> {
+        match self {
+            StreamError::Subscription { item, .. } | StreamError::Parse { item, .. } => {
+                item.as_deref()
+            }
+            StreamError::Connection(_) | StreamError::AuthRejected(_) => None,
+        }
+    }
+
+    /// Returns true if reconnecting may resolve the error
+    ///
+    /// Subscription and parse errors concern one item and recur on a new
+    /// connection; drop or fix the item instead.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            StreamError::Connection(_) | StreamError::AuthRejected(_)
+        )
+    }
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Connection(reason) => write!(f, "connection failed: {reason}"),
+            StreamError::AuthRejected(reason) => write!(f, "credentials rejected: {reason}"),
+            StreamError::Subscription {
+                item,
+                code,
+                message,
+            } => {
+                write!(f, "subscription")?;
+                if let Some(item) = item {
+                    write!(f, " to {item}")?;
+                }
+                write!(f, " refused")?;
+                if let Some(code) = code {
+                    write!(f, " (code {code})")?;
+                }
+                write!(f, ": {message}")
+            }
+            StreamError::Parse { item, field, raw } => {
+                write!(f, "cannot parse {field} value {raw:?}")?;
+                if let Some(item) = item {
+                    write!(f, " for {item}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
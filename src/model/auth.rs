@@ -43,6 +43,7 @@ impl SessionResponse {
                 oauth_token: Some(v.oauth_token.clone()),
                 api_version: 3,
                 expires_at: v.oauth_token.expire_at(1),
+                can_trade: None,
             },
             SessionResponse::V2(v) => {
                 let (cst, x_security_token) = match v.security_headers.as_ref() {
@@ -62,6 +63,7 @@ impl SessionResponse {
                     oauth_token: None,
                     api_version: 2,
                     expires_at,
+                    can_trade: None,
                 }
             }
         }
@@ -235,6 +237,27 @@ impl V2Response {
     }
 }
 
+/// Details of the active session returned by `GET /session`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDetails {
+    /// Client identifier
+    pub client_id: String,
+    /// Account the session is currently using
+    pub account_id: String,
+    /// Offset of the account's timezone from UTC, in hours
+    #[serde(default)]
+    pub timezone_offset: i32,
+    /// Locale of the client, e.g. `en_GB`
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Currency of the account
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Lightstreamer endpoint to use for new streaming connections
+    pub lightstreamer_endpoint: String,
+}
+
 /// Security headers for API v2 authentication
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SecurityHeaders {
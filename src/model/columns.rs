@@ -0,0 +1,102 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Columnar view of historical prices
+//!
+//! Available with the `analytics` feature. `PriceColumns` holds one `Vec` per
+//! field, all the same length, which maps directly onto polars `Series` or
+//! arrow arrays without walking the row structs by hand.
+
+use crate::model::responses::HistoricalPricesResponse;
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+/// Historical prices as parallel columns, one entry per candle
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceColumns {
+    /// Candle timestamps in UTC
+    pub timestamps: Vec<DateTime<Utc>>,
+    /// Opening bid
+    pub open_bid: Vec<Option<f64>>,
+    /// Opening ask
+    pub open_ask: Vec<Option<f64>>,
+    /// Highest bid
+    pub high_bid: Vec<Option<f64>>,
+    /// Highest ask
+    pub high_ask: Vec<Option<f64>>,
+    /// Lowest bid
+    pub low_bid: Vec<Option<f64>>,
+    /// Lowest ask
+    pub low_ask: Vec<Option<f64>>,
+    /// Closing bid
+    pub close_bid: Vec<Option<f64>>,
+    /// Closing ask
+    pub close_ask: Vec<Option<f64>>,
+    /// Last traded volume
+    pub volume: Vec<Option<i64>>,
+}
+
+impl PriceColumns {
+    /// Creates empty columns with room for `capacity` candles
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            timestamps: Vec::with_capacity(capacity),
+            open_bid: Vec::with_capacity(capacity),
+            open_ask: Vec::with_capacity(capacity),
+            high_bid: Vec::with_capacity(capacity),
+            high_ask: Vec::with_capacity(capacity),
+            low_bid: Vec::with_capacity(capacity),
+            low_ask: Vec::with_capacity(capacity),
+            close_bid: Vec::with_capacity(capacity),
+            close_ask: Vec::with_capacity(capacity),
+            volume: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of candles
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// Returns true if there are no candles
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+}
+
+impl HistoricalPricesResponse {
+    /// Converts the candles into parallel columns
+    ///
+    /// Timestamps come from `HistoricalPrice::snapshot_datetime`. Candles whose
+    /// snapshot time cannot be parsed are skipped with a warning so every
+    /// column stays aligned with `timestamps`.
+    ///
+    /// # Returns
+    /// Columns in the order IG returned the candles
+    pub fn to_columns(&self) -> PriceColumns {
+        let mut columns = PriceColumns::with_capacity(self.prices.len());
+        for price in &self.prices {
+            let Some(timestamp) = price.snapshot_datetime() else {
+                warn!(
+                    "Skipping candle with unparseable snapshot time {}",
+                    price.snapshot_time
+                );
+                continue;
+            };
+            columns.timestamps.push(timestamp);
+            columns.open_bid.push(price.open_price.bid);
+            columns.open_ask.push(price.open_price.ask);
+            columns.high_bid.push(price.high_price.bid);
+            columns.high_ask.push(price.high_price.ask);
+            columns.low_bid.push(price.low_price.bid);
+            columns.low_ask.push(price.low_price.ask);
+            columns.close_bid.push(price.close_price.bid);
+            columns.close_ask.push(price.close_price.ask);
+            columns.volume.push(price.last_traded_volume);
+        }
+        columns
+    }
+}
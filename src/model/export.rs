@@ -0,0 +1,159 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! CSV export of positions and transactions
+//!
+//! Available with the `csv` feature. IG reports transaction amounts as text
+//! with currency markers and thousands separators (`E-1,234.50`); the exports
+//! write them as plain numbers and resolve currencies to ISO 4217 codes so the
+//! files can be summed directly in a spreadsheet.
+
+use crate::error::AppError;
+use crate::model::responses::{PositionsResponse, TransactionHistoryResponse};
+use crate::presentation::account::AccountTransaction;
+use crate::presentation::currency::CurrencyCode;
+use crate::presentation::order::Direction;
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Serialize)]
+struct PositionRow<'a> {
+    epic: &'a str,
+    instrument_name: &'a str,
+    deal_id: &'a str,
+    deal_reference: &'a str,
+    created_date_utc: &'a str,
+    direction: &'a Direction,
+    size: f64,
+    contract_size: f64,
+    level: f64,
+    currency: &'a str,
+    stop_level: Option<f64>,
+    limit_level: Option<f64>,
+    bid: Option<f64>,
+    offer: Option<f64>,
+    pnl: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct TransactionRow<'a> {
+    date: &'a str,
+    date_utc: &'a str,
+    open_date_utc: &'a str,
+    instrument_name: &'a str,
+    period: &'a str,
+    transaction_type: &'a str,
+    reference: &'a str,
+    open_level: Option<f64>,
+    close_level: Option<f64>,
+    size: Option<f64>,
+    currency: String,
+    profit_and_loss: Option<f64>,
+    cash_transaction: bool,
+}
+
+/// Parses IG's textual numbers (`+1.5`, `1,234.5`, `-`) into a value
+fn parse_number(raw: &str) -> Option<f64> {
+    let cleaned: String = raw
+        .trim()
+        .trim_start_matches('+')
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+    cleaned
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+impl<'a> From<&'a AccountTransaction> for TransactionRow<'a> {
+    fn from(transaction: &'a AccountTransaction) -> Self {
+        let currency = transaction
+            .profit_and_loss_currency()
+            .map_or_else(|| transaction.currency.clone(), |code| code.to_string());
+        Self {
+            date: &transaction.date,
+            date_utc: &transaction.date_utc,
+            open_date_utc: &transaction.open_date_utc,
+            instrument_name: &transaction.instrument_name,
+            period: &transaction.period,
+            transaction_type: &transaction.transaction_type,
+            reference: &transaction.reference,
+            open_level: parse_number(&transaction.open_level),
+            close_level: parse_number(&transaction.close_level),
+            size: parse_number(&transaction.size),
+            currency,
+            profit_and_loss: transaction.profit_and_loss_value(),
+            cash_transaction: transaction.cash_transaction,
+        }
+    }
+}
+
+impl PositionsResponse {
+    /// Writes the positions as CSV with a header row
+    ///
+    /// One row per position; numeric fields are written as numbers and
+    /// missing values as empty cells. Nothing is written for an empty list.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination, e.g. a file or a `Vec<u8>`
+    ///
+    /// # Returns
+    /// * `Ok(())` - When every row was written
+    /// * `Err(AppError::Io)` - If writing fails
+    pub fn to_csv<W: Write>(&self, writer: W) -> Result<(), AppError> {
+        let mut csv = csv::Writer::from_writer(writer);
+        for position in &self.positions {
+            let details = &position.position;
+            let currency = CurrencyCode::parse(&details.currency);
+            csv.serialize(PositionRow {
+                epic: &position.market.epic,
+                instrument_name: &position.market.instrument_name,
+                deal_id: &details.deal_id,
+                deal_reference: &details.deal_reference,
+                created_date_utc: &details.created_date_utc,
+                direction: &details.direction,
+                size: details.size,
+                contract_size: details.contract_size,
+                level: details.level,
+                currency: currency
+                    .as_ref()
+                    .map_or(details.currency.as_str(), CurrencyCode::code),
+                stop_level: details.stop_level,
+                limit_level: details.limit_level,
+                bid: position.market.bid,
+                offer: position.market.offer,
+                pnl: position.pnl,
+            })?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+}
+
+impl TransactionHistoryResponse {
+    /// Writes the transactions as CSV with a header row
+    ///
+    /// Levels, sizes and profit and loss are parsed from IG's text into
+    /// numbers; the currency is written as an ISO 4217 code when the symbol
+    /// IG uses can be resolved, otherwise as reported. Nothing is written for
+    /// an empty list.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination, e.g. a file or a `Vec<u8>`
+    ///
+    /// # Returns
+    /// * `Ok(())` - When every row was written
+    /// * `Err(AppError::Io)` - If writing fails
+    pub fn to_csv<W: Write>(&self, writer: W) -> Result<(), AppError> {
+        let mut csv = csv::Writer::from_writer(writer);
+        for transaction in &self.transactions {
+            csv.serialize(TransactionRow::from(transaction))?;
+        }
+        csv.flush()?;
+        Ok(())
+    }
+}
@@ -6,19 +6,103 @@
 
 use crate::application::auth::{Auth, Session, WebsocketInfo};
 use crate::application::config::Config;
-use crate::application::rate_limiter::RateLimiter;
+use crate::application::rate_limiter::{RateLimiter, RateLimiterRegistry, RateLimiterStats};
+use crate::application::session_guard::SessionGuard;
+use crate::constants::{
+    MAINTENANCE_COOLDOWN_SECS, REQUEST_ID_HEADER, UNSUPPORTED_VERSION_ERROR_CODE,
+};
 use crate::error::AppError;
-use crate::model::retry::RetryConfig;
+use crate::model::auth::SessionDetails;
+use crate::model::retry::{RetryConfig, parse_retry_after};
+use crate::utils::id::generate_request_id;
+use crate::utils::parsing::parse_http_date;
+use crate::utils::redact::{trace_request, trace_response};
+use crate::utils::single_flight::SingleFlight;
+use chrono::{DateTime, Utc};
 use reqwest::Client as HttpInternalClient;
+use reqwest::header::{DATE, RETRY_AFTER};
 use reqwest::{Client, Method, Response, StatusCode};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::{debug, error, warn};
+use tracing::{Instrument, debug, error, info_span, warn};
 
 const USER_AGENT: &str = "ig-client/0.6.0";
 
+/// Summary of a completed HTTP request, passed to the request observer
+#[derive(Debug, Clone)]
+pub struct RequestRecord {
+    /// Correlation ID generated for the request
+    pub request_id: String,
+    /// HTTP method
+    pub method: Method,
+    /// Full request URL
+    pub url: String,
+    /// Response status, if a response was received
+    pub status: Option<StatusCode>,
+    /// Time from the first attempt until the final response or error
+    pub elapsed: Duration,
+    /// Error message if the request failed
+    pub error: Option<String>,
+    /// Time spent waiting on the rate limiter, summed over all attempts
+    pub rate_limit_wait: Duration,
+}
+
+/// Per-request settings for `HttpClient::request_with_options`
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Extra headers sent with the request
+    pub headers: Vec<(String, String)>,
+    /// Maximum time for the request, including retries (None = no limit)
+    pub timeout: Option<Duration>,
+    /// Account sent in `IG-ACCOUNT-ID` instead of the session's (OAuth only)
+    pub account_id: Option<String>,
+}
+
+impl RequestOptions {
+    /// Creates empty options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header to send with the request
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Limits the time the request may take
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Acts on another account of the same user for this request only
+    #[must_use]
+    pub fn for_account(mut self, account_id: impl Into<String>) -> Self {
+        self.account_id = Some(account_id.into());
+        self
+    }
+}
+
+/// How a request waits for the rate limiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pacing {
+    /// Waits for a token before every attempt and retries rate-limit rejections
+    Blocking,
+    /// Sends once with a token the caller already took; a rate-limit rejection
+    /// is returned as `AppError::RateLimitExceeded`
+    NonBlocking,
+}
+
+/// Callback invoked after every request made by `HttpClient`
+pub type RequestObserver = Arc<dyn Fn(&RequestRecord) + Send + Sync>;
+
 /// Simplified client for IG Markets API with automatic authentication
 ///
 /// This client handles all authentication complexity internally, including:
@@ -29,9 +113,13 @@ const USER_AGENT: &str = "ig-client/0.6.0";
 /// - Rate limiting for all API requests
 pub struct HttpClient {
     auth: Arc<Auth>,
+    session_guard: SessionGuard,
     http_client: HttpInternalClient,
     config: Arc<Config>,
     rate_limiter: Arc<RwLock<RateLimiter>>,
+    observer: std::sync::RwLock<Option<RequestObserver>>,
+    get_flights: std::sync::Mutex<HashMap<String, Arc<SingleFlight<Arc<[u8]>>>>>,
+    maintenance_until: std::sync::Mutex<Option<Instant>>,
 }
 
 impl HttpClient {
@@ -50,7 +138,7 @@ impl HttpClient {
         let http_client = HttpInternalClient::builder()
             .user_agent(USER_AGENT)
             .build()?;
-        let rate_limiter = Arc::new(RwLock::new(RateLimiter::new(&config.rate_limiter)));
+        let rate_limiter = Arc::new(RwLock::new(RateLimiter::for_config(&config)));
 
         // Create Auth instance
         let auth = Arc::new(Auth::new(config.clone()));
@@ -59,10 +147,14 @@ impl HttpClient {
         auth.login().await?;
 
         Ok(Self {
+            session_guard: SessionGuard::new(auth.session_slot(), auth.clone()),
             auth,
             http_client,
             config,
             rate_limiter,
+            observer: std::sync::RwLock::new(None),
+            get_flights: std::sync::Mutex::new(HashMap::new()),
+            maintenance_until: std::sync::Mutex::new(None),
         })
     }
 
@@ -75,16 +167,55 @@ impl HttpClient {
             .user_agent(USER_AGENT)
             .build()
             .expect("Failed to create HTTP client");
-        let rate_limiter = Arc::new(RwLock::new(RateLimiter::new(&config.rate_limiter)));
+        let rate_limiter = Arc::new(RwLock::new(RateLimiter::for_config(&config)));
 
         // Create Auth instance
         let auth = Arc::new(Auth::new(config.clone()));
 
         Self {
+            session_guard: SessionGuard::new(auth.session_slot(), auth.clone()),
             auth,
             http_client,
             config,
             rate_limiter,
+            observer: std::sync::RwLock::new(None),
+            get_flights: std::sync::Mutex::new(HashMap::new()),
+            maintenance_until: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Creates a new client without performing initial authentication, taking
+    /// its rate limiter from a registry shared with other clients
+    ///
+    /// Clients built from the same registry with the same API key share one
+    /// request budget, including for login and session requests.
+    ///
+    /// # Arguments
+    /// * `config` - Configuration containing credentials and API settings
+    /// * `registry` - Rate limiters keyed by API key
+    pub fn with_rate_limiters(config: Config, registry: &RateLimiterRegistry) -> Self {
+        let config = Arc::new(config);
+
+        let http_client = HttpInternalClient::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("Failed to create HTTP client");
+        let rate_limiter = registry.limiter_for(&config);
+
+        let auth = Arc::new(Auth::with_rate_limiter(
+            config.clone(),
+            rate_limiter.clone(),
+        ));
+
+        Self {
+            session_guard: SessionGuard::new(auth.session_slot(), auth.clone()),
+            auth,
+            http_client,
+            config,
+            rate_limiter,
+            observer: std::sync::RwLock::new(None),
+            get_flights: std::sync::Mutex::new(HashMap::new()),
+            maintenance_until: std::sync::Mutex::new(None),
         }
     }
 
@@ -96,13 +227,109 @@ impl HttpClient {
         self.auth.get_ws_info().await
     }
 
+    /// Configuration used by this client
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Time left before requests are sent again after IG reported maintenance
+    ///
+    /// While this is `Some`, every request fails with `AppError::Maintenance`
+    /// without reaching IG.
+    pub fn maintenance_remaining(&self) -> Option<Duration> {
+        self.maintenance_until
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .and_then(|until| until.checked_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Registers a callback invoked after every request with its correlation ID,
+    /// status and timing
+    ///
+    /// Replaces any previously registered observer.
+    pub fn set_request_observer<F>(&self, observer: F)
+    where
+        F: Fn(&RequestRecord) + Send + Sync + 'static,
+    {
+        *self
+            .observer
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(observer));
+    }
+
+    /// Returns the time spent waiting on the request rate limiter so far
+    pub async fn rate_limiter_stats(&self) -> RateLimiterStats {
+        self.rate_limiter.read().await.stats()
+    }
+
+    /// Removes the registered request observer, if any
+    pub fn clear_request_observer(&self) {
+        *self
+            .observer
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+
     /// Makes a GET request
+    ///
+    /// Concurrent GETs for the same path, version and session account share
+    /// one request: the callers that arrive while it is in flight get a copy
+    /// of its response body instead of sending their own. A failed request is not shared;
+    /// callers that were waiting on it send their own request instead.
     pub async fn get<T: DeserializeOwned>(
         &self,
         path: &str,
         version: Option<u8>,
     ) -> Result<T, AppError> {
-        self.request(Method::GET, path, None::<()>, version).await
+        // Responses depend on the account, which `switch_account` can change
+        let account_id = self.session_guard.valid_session().await?.account_id;
+        let key = format!("{}#v{}@{}", path, version.unwrap_or(1), account_id);
+        let flight = Arc::clone(
+            self.get_flights
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .entry(key.clone())
+                .or_default(),
+        );
+
+        let body = flight.run(|| self.get_body(path, version)).await;
+
+        drop(flight);
+        let mut flights = self
+            .get_flights
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if flights
+            .get(&key)
+            .is_some_and(|flight| Arc::strong_count(flight) == 1)
+        {
+            flights.remove(&key);
+        }
+        drop(flights);
+
+        parse_json_body(&body?)
+    }
+
+    /// Sends a GET request and reads the whole response body
+    async fn get_body(&self, path: &str, version: Option<u8>) -> Result<Arc<[u8]>, AppError> {
+        let response = self
+            .send_with_refresh(
+                Method::GET,
+                path,
+                &None::<()>,
+                version,
+                &RequestOptions::default(),
+                Pacing::Blocking,
+            )
+            .await?;
+
+        let url = response.url().to_string();
+        let bytes = response.bytes().await?;
+        if self.config.rest_api.log_bodies {
+            trace_response(&url, &bytes);
+        }
+        Ok(Arc::from(bytes.as_ref()))
     }
 
     /// Makes a POST request
@@ -115,6 +342,40 @@ impl HttpClient {
         self.request(Method::POST, path, Some(body), version).await
     }
 
+    /// Makes a POST request only if the rate limiter has capacity right now
+    ///
+    /// Never waits on the rate limiter and never retries a rate-limit
+    /// rejection: if IG rejects the request as over its allowance, the error
+    /// is returned straight away. Lower API versions are not tried either.
+    ///
+    /// # Returns
+    /// * `Ok(None)` - If no rate limiter token was available; nothing was sent
+    /// * `Ok(Some(T))` - Deserialized response
+    /// * `Err(AppError::RateLimitExceeded)` - If IG rejected the request as
+    ///   rate limited, or no token was left to resend it after renewing the session
+    pub async fn try_post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: B,
+        version: Option<u8>,
+    ) -> Result<Option<T>, AppError> {
+        if !self.rate_limiter.read().await.try_acquire() {
+            debug!("No rate limiter token available for POST {}", path);
+            return Ok(None);
+        }
+        let response = self
+            .send_with_refresh(
+                Method::POST,
+                path,
+                &Some(body),
+                version,
+                &RequestOptions::default(),
+                Pacing::NonBlocking,
+            )
+            .await?;
+        self.parse_response(response).await.map(Some)
+    }
+
     /// Makes a PUT request
     pub async fn put<B: Serialize, T: DeserializeOwned>(
         &self,
@@ -154,8 +415,8 @@ impl HttpClient {
         {
             Ok(response) => self.parse_response(response).await,
             Err(AppError::OAuthTokenExpired) => {
-                warn!("OAuth token expired, refreshing and retrying");
-                self.auth.refresh_token().await?;
+                warn!("OAuth token rejected, renewing session and retrying");
+                self.session_guard.renew_rejected().await?;
                 let response = self
                     .request_internal_with_delete_method(path, &body, version)
                     .await?;
@@ -173,30 +434,139 @@ impl HttpClient {
         body: Option<B>,
         version: Option<u8>,
     ) -> Result<T, AppError> {
+        self.send_request(method, path, &body, version, &RequestOptions::default())
+            .await
+    }
+
+    /// Makes a request with per-request headers, timeout or account
+    ///
+    /// An escape hatch for endpoints and headers the typed API does not model.
+    /// Extra headers never replace the authentication, API key, version or
+    /// content headers the client sets; a conflicting header is skipped with a
+    /// warning.
+    ///
+    /// # Arguments
+    /// * `method` - HTTP method
+    /// * `path` - Path relative to the REST base URL, or a full URL
+    /// * `body` - Optional JSON body
+    /// * `version` - API version sent in the `Version` header (default 1)
+    /// * `options` - Extra headers, timeout and account for this request
+    ///
+    /// # Returns
+    /// * `Ok(T)` - Deserialized response
+    /// * `Err(AppError::Timeout)` - If `options.timeout` elapsed first
+    pub async fn request_with_options<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<B>,
+        version: Option<u8>,
+        options: &RequestOptions,
+    ) -> Result<T, AppError> {
+        let request = self.send_request(method, path, &body, version, options);
+        match options.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, request).await.map_err(|_| {
+                AppError::Timeout(format!("{path} did not respond within {timeout:?}"))
+            })?,
+            None => request.await,
+        }
+    }
+
+    /// Sends a request and parses its response, refreshing an expired OAuth token once
+    async fn send_request<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &Option<B>,
+        version: Option<u8>,
+        options: &RequestOptions,
+    ) -> Result<T, AppError> {
+        let response = self
+            .send_with_refresh(method, path, body, version, options, Pacing::Blocking)
+            .await?;
+        self.parse_response(response).await
+    }
+
+    /// Sends a request, renewing the session and resending once if IG rejects
+    /// the OAuth token
+    ///
+    /// A non-blocking request is only resent if a rate limiter token is
+    /// available straight away.
+    async fn send_with_refresh<B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &Option<B>,
+        version: Option<u8>,
+        options: &RequestOptions,
+        pacing: Pacing,
+    ) -> Result<Response, AppError> {
         match self
-            .request_internal(method.clone(), path, &body, version)
+            .request_internal(method.clone(), path, body, version, options, pacing)
             .await
         {
-            Ok(response) => self.parse_response(response).await,
             Err(AppError::OAuthTokenExpired) => {
-                warn!("OAuth token expired, refreshing and retrying");
-                self.auth.refresh_token().await?;
-                let response = self.request_internal(method, path, &body, version).await?;
-                self.parse_response(response).await
+                warn!("OAuth token rejected, renewing session and retrying");
+                self.session_guard.renew_rejected().await?;
+                if pacing == Pacing::NonBlocking && !self.rate_limiter.read().await.try_acquire() {
+                    debug!(
+                        "No rate limiter token available to resend {} {}",
+                        method, path
+                    );
+                    return Err(AppError::RateLimitExceeded);
+                }
+                self.request_internal(method, path, body, version, options, pacing)
+                    .await
             }
-            Err(e) => Err(e),
+            result => result,
         }
     }
 
+    /// Makes a POST request on behalf of another account of the same user
+    ///
+    /// Sends `IG-ACCOUNT-ID` for this request only, leaving the session's
+    /// default account unchanged. Only OAuth (v3) sessions honour the header;
+    /// CST sessions are bound to one account and need `switch_account`.
+    ///
+    /// # Returns
+    /// * `Err(AppError::InvalidInput)` - If the session is not OAuth and the account differs
+    pub async fn post_for_account<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: B,
+        version: Option<u8>,
+        account_id: &str,
+    ) -> Result<T, AppError> {
+        let options = RequestOptions::new().for_account(account_id);
+        self.send_request(Method::POST, path, &Some(body), version, &options)
+            .await
+    }
+
     /// Internal method to make HTTP requests
+    ///
+    /// `options.account_id` replaces the session account in the `IG-ACCOUNT-ID`
+    /// header and `options.headers` are appended unless they clash with a
+    /// header set here. A non-blocking request has already taken its rate
+    /// limiter token.
     async fn request_internal<B: Serialize>(
         &self,
         method: Method,
         path: &str,
         body: &Option<B>,
         version: Option<u8>,
+        options: &RequestOptions,
+        pacing: Pacing,
     ) -> Result<Response, AppError> {
-        let session = self.auth.get_session().await?;
+        let account_override = options.account_id.as_deref();
+        let session = self.session_guard.valid_session().await?;
+        if session.oauth_token.is_none()
+            && let Some(account) = account_override
+            && account != session.account_id
+        {
+            return Err(AppError::InvalidInput(format!(
+                "cannot act on account {account} without switching: only OAuth sessions accept IG-ACCOUNT-ID"
+            )));
+        }
 
         let url = if path.starts_with("http") {
             path.to_string()
@@ -221,7 +591,8 @@ impl HttpClient {
 
         if let Some(oauth) = &session.oauth_token {
             auth_header_value = format!("Bearer {}", oauth.access_token);
-            account_id = session.account_id.clone();
+            account_id =
+                account_override.map_or_else(|| session.account_id.clone(), str::to_string);
             headers.push(("Authorization", auth_header_value.as_str()));
             headers.push(("IG-ACCOUNT-ID", account_id.as_str()));
         } else if let (Some(cst_val), Some(token_val)) = (&session.cst, &session.x_security_token) {
@@ -231,16 +602,29 @@ impl HttpClient {
             headers.push(("X-SECURITY-TOKEN", x_security_token.as_str()));
         }
 
-        make_http_request(
-            &self.http_client,
-            self.rate_limiter.clone(),
-            method,
-            &url,
-            headers,
-            body,
-            RetryConfig::infinite(),
-        )
-        .await
+        // `execute` adds the correlation ID header after these
+        let request_id_header = self
+            .config
+            .rest_api
+            .request_id_header
+            .then_some(REQUEST_ID_HEADER);
+        for (name, value) in &options.headers {
+            if headers
+                .iter()
+                .map(|(set, _)| *set)
+                .chain(request_id_header)
+                .any(|set| set.eq_ignore_ascii_case(name))
+            {
+                warn!(
+                    "Ignoring custom header {} on {}: it would override a header set by the client",
+                    name, path
+                );
+                continue;
+            }
+            headers.push((name.as_str(), value.as_str()));
+        }
+
+        self.execute(method, &url, headers, body, pacing).await
     }
 
     /// Internal method to make POST requests with _method: DELETE header
@@ -252,7 +636,7 @@ impl HttpClient {
         body: &B,
         version: Option<u8>,
     ) -> Result<Response, AppError> {
-        let session = self.auth.get_session().await?;
+        let session = self.session_guard.valid_session().await?;
 
         let url = if path.starts_with("http") {
             path.to_string()
@@ -288,21 +672,144 @@ impl HttpClient {
             headers.push(("X-SECURITY-TOKEN", x_security_token.as_str()));
         }
 
-        make_http_request(
-            &self.http_client,
-            self.rate_limiter.clone(),
-            Method::POST, // Always POST for this method
-            &url,
-            headers,
-            &Some(body),
-            RetryConfig::infinite(),
-        )
-        .await
+        // Always POST for this method
+        self.execute(Method::POST, &url, headers, &Some(body), Pacing::Blocking)
+            .await
+    }
+
+    /// Sends a request tagged with a fresh correlation ID
+    ///
+    /// The ID is recorded in a `tracing` span around the request, optionally sent
+    /// in the `X-Request-ID` header and reported to the request observer.
+    async fn execute<B: Serialize>(
+        &self,
+        method: Method,
+        url: &str,
+        mut headers: Vec<(&str, &str)>,
+        body: &Option<B>,
+        pacing: Pacing,
+    ) -> Result<Response, AppError> {
+        if let Some(remaining) = self.maintenance_remaining() {
+            debug!(
+                "Not sending {} {}: IG under maintenance for another {:?}",
+                method, url, remaining
+            );
+            return Err(AppError::Maintenance {
+                retry_after: Some(remaining),
+            });
+        }
+
+        let request_id = generate_request_id();
+        if self.config.rest_api.request_id_header {
+            headers.push((REQUEST_ID_HEADER, request_id.as_str()));
+        }
+
+        if self.config.rest_api.log_bodies {
+            trace_request(method.as_str(), url, &headers, body);
+        }
+
+        let span = info_span!("ig_request", request_id = %request_id, method = %method, url = %url);
+        let started = Instant::now();
+        let mut rate_limit_wait = Duration::ZERO;
+        // Versions to fall back to, highest first, when IG rejects the requested
+        // one; a non-blocking request has no token for another attempt
+        let lower_versions: Vec<String> = match version_header(&headers) {
+            Some(version)
+                if self.config.rest_api.api_versions.fallback_to_lower
+                    && pacing == Pacing::Blocking =>
+            {
+                (1..version).rev().map(|v| v.to_string()).collect()
+            }
+            _ => Vec::new(),
+        };
+        let mut lower_versions = lower_versions.iter();
+        let mut attempt_headers = headers.clone();
+        let result = loop {
+            let result = send_with_retries(
+                &self.http_client,
+                self.rate_limiter.clone(),
+                method.clone(),
+                url,
+                attempt_headers.clone(),
+                body,
+                RetryConfig::infinite(),
+                pacing,
+                &mut rate_limit_wait,
+            )
+            .instrument(span.clone())
+            .await;
+            match (&result, lower_versions.next()) {
+                (Err(AppError::UnsupportedVersion { endpoint, version }), Some(lower)) => {
+                    warn!(
+                        "Retrying {} with API version {} instead of {}",
+                        endpoint, lower, version
+                    );
+                    for header in attempt_headers.iter_mut() {
+                        if header.0.eq_ignore_ascii_case("Version") {
+                            header.1 = lower.as_str();
+                        }
+                    }
+                }
+                _ => break result,
+            }
+        };
+
+        if let Err(AppError::Maintenance { retry_after }) = &result {
+            // Maintenance lasts far longer than a transient 5xx, so hold every
+            // request back instead of letting callers retry straight away
+            let cooldown =
+                retry_after.unwrap_or_else(|| Duration::from_secs(MAINTENANCE_COOLDOWN_SECS));
+            warn!(
+                "IG is under maintenance, holding requests back for {:?}",
+                cooldown
+            );
+            *self
+                .maintenance_until
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Instant::now() + cooldown);
+        }
+
+        let observer = self
+            .observer
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if let Some(observer) = observer {
+            let (status, error) = match &result {
+                Ok(response) => (Some(response.status()), None),
+                Err(AppError::Unexpected(status)) => (Some(*status), Some(status.to_string())),
+                Err(AppError::Unauthorized | AppError::OAuthTokenExpired) => (
+                    Some(StatusCode::UNAUTHORIZED),
+                    Some("unauthorized".to_string()),
+                ),
+                Err(e @ AppError::Maintenance { .. }) => {
+                    (Some(StatusCode::SERVICE_UNAVAILABLE), Some(e.to_string()))
+                }
+                Err(e) => (None, Some(e.to_string())),
+            };
+            observer(&RequestRecord {
+                request_id,
+                method,
+                url: url.to_string(),
+                status,
+                elapsed: started.elapsed(),
+                error,
+                rate_limit_wait,
+            });
+        }
+
+        result
     }
 
     /// Parses response
     async fn parse_response<T: DeserializeOwned>(&self, response: Response) -> Result<T, AppError> {
-        Ok(response.json().await?)
+        if !self.config.rest_api.log_bodies {
+            return read_json(response).await;
+        }
+        let url = response.url().to_string();
+        let bytes = response.bytes().await?;
+        trace_response(&url, &bytes);
+        parse_json_body(&bytes)
     }
 
     /// Switches to a different trading account
@@ -319,10 +826,48 @@ impl HttpClient {
 
     /// Gets the current session
     pub async fn get_session(&self) -> Result<Session, AppError> {
-        self.auth.get_session().await
+        self.session_guard.valid_session().await
+    }
+
+    /// Gets the details of the current session from IG
+    ///
+    /// Also refreshes the stored Lightstreamer endpoint if IG reports a new one.
+    pub async fn get_session_details(&self) -> Result<SessionDetails, AppError> {
+        let session = self.session_guard.valid_session().await?;
+        self.auth.get_session_details(&session).await
+    }
+
+    /// Time on IG's servers, read from the `Date` header of `GET /session`
+    ///
+    /// The header has a resolution of one second and is stamped before the
+    /// response travels back, so the result can lag the true server time by
+    /// up to a second plus the response latency.
+    ///
+    /// # Returns
+    /// * `Ok(DateTime<Utc>)` - Server time when the response was sent
+    /// * `Err(AppError::Deserialization)` - If the response has no valid `Date` header
+    pub async fn server_time(&self) -> Result<DateTime<Utc>, AppError> {
+        let response = self
+            .send_with_refresh(
+                Method::GET,
+                "session",
+                &None::<()>,
+                Some(1),
+                &RequestOptions::default(),
+                Pacing::Blocking,
+            )
+            .await?;
+        response
+            .headers()
+            .get(DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_http_date)
+            .ok_or_else(|| {
+                AppError::Deserialization("response has no valid Date header".to_string())
+            })
     }
 
-    /// Logs out
+    /// Logs out with `DELETE /session` and clears the stored session
     pub async fn logout(&self) -> Result<(), AppError> {
         self.auth.logout().await
     }
@@ -340,6 +885,37 @@ impl Default for HttpClient {
     }
 }
 
+/// UTF-8 byte order mark occasionally prefixed to IG response bodies
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Removes a leading UTF-8 byte order mark, if present
+pub fn strip_utf8_bom(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes)
+}
+
+/// Deserializes a JSON response body given as raw bytes
+///
+/// The body is decoded as UTF-8 after stripping a leading byte order mark, so
+/// non-ASCII content such as the `£` and `€` currency symbols is preserved.
+///
+/// # Arguments
+///
+/// * `bytes` - Raw response body
+///
+/// # Returns
+///
+/// * `Ok(T)` - Deserialized value
+/// * `Err(AppError::Json)` - If the body is not valid JSON for `T`
+pub fn parse_json_body<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, AppError> {
+    Ok(serde_json::from_slice(strip_utf8_bom(bytes))?)
+}
+
+/// Reads a response body as bytes and deserializes it with `parse_json_body`
+pub async fn read_json<T: DeserializeOwned>(response: Response) -> Result<T, AppError> {
+    let bytes = response.bytes().await?;
+    parse_json_body(&bytes)
+}
+
 /// Makes an HTTP request with automatic rate limiting and retry on rate limit errors
 ///
 /// This function provides a centralized way to make HTTP requests to the IG Markets API
@@ -428,15 +1004,117 @@ pub async fn make_http_request<B: Serialize>(
     body: &Option<B>,
     retry_config: RetryConfig,
 ) -> Result<Response, AppError> {
+    let mut rate_limit_wait = Duration::ZERO;
+    send_with_retries(
+        client,
+        rate_limiter,
+        method,
+        url,
+        headers,
+        body,
+        retry_config,
+        Pacing::Blocking,
+        &mut rate_limit_wait,
+    )
+    .await
+}
+
+/// Returns the category a request is counted under in the rate limiter stats
+///
+/// This is the first path segment after the API base, e.g. `markets` for
+/// `.../gateway/deal/markets/EPIC` or `session` for `.../gateway/deal/session`.
+pub fn request_category(url: &str) -> &str {
+    let path = url
+        .split_once("/gateway/deal/")
+        .map_or(url, |(_, path)| path);
+    path.split(['/', '?'])
+        .next()
+        .filter(|segment| !segment.is_empty())
+        .unwrap_or("other")
+}
+
+/// Returns the `errorCode` of an IG error body
+///
+/// # Returns
+/// * `Some(String)` - The code, e.g. `error.public-api.failure.maintenance`
+/// * `None` - If the body is not JSON or has no string `errorCode`
+pub fn error_code(body: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get("errorCode")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Returns true if an error body reports IG's scheduled maintenance
+///
+/// Reads the body's `errorCode`; a body without one, such as a maintenance
+/// page, is searched as plain text.
+pub fn is_maintenance_error(body: &str) -> bool {
+    error_code(body)
+        .unwrap_or_else(|| body.to_string())
+        .to_ascii_lowercase()
+        .contains("maintenance")
+}
+
+/// Returns true if an error body reports that the `Version` header is not supported
+///
+/// IG answers an endpoint called with a version it does not serve with the
+/// error code `invalid.version`, possibly with a prefix such as
+/// `error.invalid.version`. Only the parsed `errorCode` is checked, so other
+/// errors that merely mention a version are not mistaken for it.
+pub fn is_unsupported_version_error(body: &str) -> bool {
+    error_code(body).is_some_and(|code| {
+        let code = code.to_ascii_lowercase();
+        code == UNSUPPORTED_VERSION_ERROR_CODE
+            || code.ends_with(&format!(".{UNSUPPORTED_VERSION_ERROR_CODE}"))
+    })
+}
+
+/// Returns the endpoint path of a request URL, without the API base or query
+///
+/// E.g. `markets/EPIC` for `.../gateway/deal/markets/EPIC?x=1`.
+fn endpoint_path(url: &str) -> &str {
+    let path = url
+        .split_once("/gateway/deal/")
+        .map_or(url, |(_, path)| path);
+    path.split_once('?').map_or(path, |(path, _)| path)
+}
+
+/// Returns the API version sent in the `Version` header, if any
+fn version_header(headers: &[(&str, &str)]) -> Option<u8> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Version"))
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// Implementation of `make_http_request` that also reports rate limiter waits
+#[allow(clippy::too_many_arguments)]
+async fn send_with_retries<B: Serialize>(
+    client: &Client,
+    rate_limiter: Arc<RwLock<RateLimiter>>,
+    method: Method,
+    url: &str,
+    headers: Vec<(&str, &str)>,
+    body: &Option<B>,
+    retry_config: RetryConfig,
+    pacing: Pacing,
+    rate_limit_wait: &mut Duration,
+) -> Result<Response, AppError> {
+    let mut token_acquired = pacing == Pacing::NonBlocking;
     let mut retry_count = 0;
     let max_retries = retry_config.max_retries();
-    let delay_secs = retry_config.delay_secs();
+    let category = request_category(url);
 
     loop {
-        // Wait for rate limiter before making request
-        {
+        // Wait for rate limiter before making request, unless the caller
+        // already holds a token for this attempt
+        if token_acquired {
+            token_acquired = false;
+        } else {
             let limiter = rate_limiter.read().await;
-            limiter.wait().await;
+            *rate_limit_wait += limiter.wait_for(category).await;
         }
 
         debug!("{} {}", method, url);
@@ -463,14 +1141,25 @@ pub async fn make_http_request<B: Serialize>(
             return Ok(response);
         }
 
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, Utc::now()));
+
         match status {
-            StatusCode::FORBIDDEN => {
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => {
                 let body_text = response.text().await.unwrap_or_default();
-                if body_text.contains("exceeded-api-key-allowance")
+                if status == StatusCode::TOO_MANY_REQUESTS
+                    || body_text.contains("exceeded-api-key-allowance")
                     || body_text.contains("exceeded-account-allowance")
                     || body_text.contains("exceeded-account-trading-allowance")
                     || body_text.contains("exceeded-account-historical-data-allowance")
                 {
+                    if pacing == Pacing::NonBlocking {
+                        warn!("Rate limit exceeded, not retrying: {}", body_text);
+                        return Err(AppError::RateLimitExceeded);
+                    }
                     retry_count += 1;
 
                     // Check if we've exceeded max retries (0 = infinite)
@@ -483,11 +1172,20 @@ pub async fn make_http_request<B: Serialize>(
                         return Err(AppError::RateLimitExceeded);
                     }
 
+                    // Prefer IG's suggested delay when it is longer than our own backoff
+                    let delay = retry_config.retry_delay(retry_count, retry_after);
                     warn!(
-                        "Rate limit exceeded (attempt {}): {}. Waiting {} seconds before retry...",
-                        retry_count, body_text, delay_secs
+                        "Rate limit exceeded (attempt {}): {}. Waiting {:?} before retry{}...",
+                        retry_count,
+                        body_text,
+                        delay,
+                        if retry_after.is_some() {
+                            " (Retry-After)"
+                        } else {
+                            ""
+                        }
                     );
-                    tokio::time::sleep(tokio::time::Duration::from_secs(delay_secs)).await;
+                    tokio::time::sleep(delay).await;
                     continue; // Retry the request
                 }
                 error!("Forbidden: {}", body_text);
@@ -501,6 +1199,30 @@ pub async fn make_http_request<B: Serialize>(
                 error!("Unauthorized: {}", body_text);
                 return Err(AppError::Unauthorized);
             }
+            StatusCode::SERVICE_UNAVAILABLE => {
+                let body_text = response.text().await.unwrap_or_default();
+                if is_maintenance_error(&body_text) {
+                    warn!("IG maintenance: {}", body_text);
+                    return Err(AppError::Maintenance { retry_after });
+                }
+                error!("Service unavailable: {}", body_text);
+                return Err(AppError::Unexpected(status));
+            }
+            StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST => {
+                let body_text = response.text().await.unwrap_or_default();
+                if is_unsupported_version_error(&body_text)
+                    && let Some(version) = version_header(&headers)
+                {
+                    let endpoint = endpoint_path(url).to_string();
+                    warn!(
+                        "{} is not available in API version {}: {}",
+                        endpoint, version, body_text
+                    );
+                    return Err(AppError::UnsupportedVersion { endpoint, version });
+                }
+                error!("Request failed with status {}: {}", status, body_text);
+                return Err(AppError::Unexpected(status));
+            }
             _ => {
                 let body = response.text().await.unwrap_or_default();
                 error!("Request failed with status {}: {}", status, body);
@@ -509,3 +1231,220 @@ pub async fn make_http_request<B: Serialize>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::auth::OAuthToken;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves `status` with an empty JSON body to every connection after
+    /// `delay`, counting them and keeping each request head
+    fn serve(
+        status: &'static str,
+        delay: Duration,
+    ) -> (String, Arc<AtomicUsize>, Arc<std::sync::Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind test server");
+        let url = format!(
+            "http://{}/gateway/deal/positions/otc",
+            listener.local_addr().unwrap()
+        );
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+        let heads = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = Arc::clone(&heads);
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => head.extend_from_slice(&buf[..read]),
+                    }
+                }
+                received
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&head).to_lowercase());
+                std::thread::sleep(delay);
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}"
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (url, requests, heads)
+    }
+
+    fn cst_session() -> Session {
+        Session {
+            account_id: "ABC123".to_string(),
+            client_id: String::new(),
+            lightstreamer_endpoint: String::new(),
+            cst: Some("cst".to_string()),
+            x_security_token: Some("token".to_string()),
+            oauth_token: None,
+            api_version: 2,
+            expires_at: Utc::now().timestamp() as u64 + 21_600,
+            can_trade: None,
+        }
+    }
+
+    fn oauth_session() -> Session {
+        Session {
+            cst: None,
+            x_security_token: None,
+            oauth_token: Some(OAuthToken {
+                access_token: "access".to_string(),
+                refresh_token: "refresh".to_string(),
+                scope: "profile".to_string(),
+                token_type: "Bearer".to_string(),
+                expires_in: "60".to_string(),
+                created_at: Utc::now(),
+            }),
+            api_version: 3,
+            ..cst_session()
+        }
+    }
+
+    /// Client without rate limiting, logged in with `session`
+    async fn client_with_session(session: Session) -> HttpClient {
+        client_with_config(Config::builder(), session).await
+    }
+
+    /// Client built from `builder` whose session is already established
+    async fn client_with_config(
+        builder: crate::application::config::ConfigBuilder,
+        session: Session,
+    ) -> HttpClient {
+        let mut config = builder
+            .username("user")
+            .password("secret")
+            .api_key("key")
+            .account_id("ABC123")
+            .build()
+            .unwrap();
+        config.disable_rate_limiting();
+        let client = HttpClient::new_lazy(config);
+        *client.auth.session_slot().write().await = Some(session);
+        client
+    }
+
+    #[tokio::test]
+    async fn test_non_blocking_request_returns_rate_limit_without_retrying() {
+        let (url, requests, _) = serve("429 Too Many Requests", Duration::ZERO);
+        let mut rate_limit_wait = Duration::ZERO;
+
+        let result = send_with_retries(
+            &Client::new(),
+            Arc::new(RwLock::new(RateLimiter::noop())),
+            Method::GET,
+            &url,
+            vec![("Version", "2")],
+            &None::<()>,
+            RetryConfig::infinite(),
+            Pacing::NonBlocking,
+            &mut rate_limit_wait,
+        )
+        .await;
+
+        assert!(matches!(result, Err(AppError::RateLimitExceeded)));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(rate_limit_wait, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_identical_gets_share_one_request() {
+        let (url, requests, _) = serve("200 OK", Duration::from_millis(100));
+        let client = client_with_session(cst_session()).await;
+
+        let (first, second) = tokio::join!(
+            client.get::<serde_json::Value>(&url, Some(1)),
+            client.get::<serde_json::Value>(&url, Some(1))
+        );
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // A GET after the shared one completed sends its own request
+        client
+            .get::<serde_json::Value>(&url, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_post_for_account_sends_the_account_header_on_oauth_sessions() {
+        let (url, requests, heads) = serve("200 OK", Duration::ZERO);
+        let client = client_with_session(oauth_session()).await;
+
+        client
+            .post_for_account::<_, serde_json::Value>(
+                &url,
+                serde_json::json!({}),
+                Some(2),
+                "XYZ789",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        let heads = heads.lock().unwrap();
+        assert!(heads[0].contains("ig-account-id: xyz789"));
+        assert!(!heads[0].contains("ig-account-id: abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_post_for_account_rejects_other_accounts_on_cst_sessions() {
+        let (url, requests, _) = serve("200 OK", Duration::ZERO);
+        let client = client_with_session(cst_session()).await;
+
+        let result = client
+            .post_for_account::<_, serde_json::Value>(
+                &url,
+                serde_json::json!({}),
+                Some(2),
+                "XYZ789",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_with_options_skips_headers_that_clash_with_the_client() {
+        let (url, _, heads) = serve("200 OK", Duration::ZERO);
+        let client =
+            client_with_config(Config::builder().request_id_header(true), cst_session()).await;
+        let options = RequestOptions::new()
+            .with_header("X-Custom-Trace", "trace-1")
+            .with_header("version", "9")
+            .with_header("X-SECURITY-TOKEN", "forged")
+            .with_header("x-request-id", "forged-id");
+
+        client
+            .request_with_options::<(), serde_json::Value>(
+                Method::GET,
+                &url,
+                None,
+                Some(2),
+                &options,
+            )
+            .await
+            .unwrap();
+
+        let heads = heads.lock().unwrap();
+        let head = &heads[0];
+        assert!(head.contains("x-custom-trace: trace-1"));
+        assert!(head.contains("version: 2"));
+        assert!(!head.contains("version: 9"));
+        assert!(head.contains("x-security-token: token"));
+        assert!(!head.contains("forged"));
+        assert_eq!(head.matches("x-request-id:").count(), 1);
+    }
+}
@@ -5,6 +5,12 @@
 ******************************************************************************/
 /// Authentication models and session management
 pub mod auth;
+/// Columnar view of historical prices for analytics
+#[cfg(feature = "analytics")]
+pub mod columns;
+/// CSV export of positions and transactions
+#[cfg(feature = "csv")]
+pub mod export;
 /// HTTP request utilities with rate limiting and retry
 pub mod http;
 /// Request models for API calls
@@ -3,10 +3,20 @@
    Email: jb@taunais.com
    Date: 19/10/25
 ******************************************************************************/
-use crate::constants::{DEFAULT_ORDER_BUY_LEVEL, DEFAULT_ORDER_SELL_LEVEL};
+use crate::constants::{
+    DEFAULT_ORDER_BUY_LEVEL, DEFAULT_ORDER_SELL_LEVEL, DEFAULT_SIZE_STEP,
+    SIZE_ROUNDING_WARN_STEP_FRACTION,
+};
+use crate::error::AppError;
 use crate::prelude::{Deserialize, Serialize};
+use crate::presentation::account::ApplicationStatus;
+use crate::presentation::instrument::ContractMonth;
+use crate::presentation::market::{DealingRules, MarketDetails, StepUnit};
 use crate::presentation::order::{Direction, OrderType, TimeInForce};
+use crate::utils::finance::{RoundingMode, round_to_step};
+use crate::utils::id::generate_deal_reference;
 use pretty_simple_display::DisplaySimple;
+use tracing::warn;
 
 /// Parameters for getting recent prices (API v3)
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -130,6 +140,25 @@ pub struct CreateOrderRequest {
         skip_serializing_if = "Option::is_none"
     )]
     pub trailing_stop_increment: Option<f64>,
+    /// Route the order to the underlying exchange (direct market access)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dma: Option<bool>,
+}
+
+/// Rounds an order size to the default deal size step
+///
+/// Logs a warning when the rounding moves the size by more than a small
+/// fraction of the step, so a lossy rounding of the requested exposure does
+/// not go unnoticed while floating point noise stays quiet.
+fn round_order_size(size: f64, mode: RoundingMode) -> f64 {
+    let rounded = round_to_step(size, DEFAULT_SIZE_STEP, mode);
+    if (rounded - size).abs() > DEFAULT_SIZE_STEP * SIZE_ROUNDING_WARN_STEP_FRACTION {
+        warn!(
+            "Order size {} rounded to {} ({:?} to a step of {})",
+            size, rounded, mode, DEFAULT_SIZE_STEP
+        );
+    }
+    rounded
 }
 
 impl CreateOrderRequest {
@@ -141,7 +170,7 @@ impl CreateOrderRequest {
         currency_code: Option<String>,
         deal_reference: Option<String>,
     ) -> Self {
-        let rounded_size = (size * 100.0).floor() / 100.0;
+        let rounded_size = round_order_size(size, RoundingMode::Floor);
 
         let currency_code = currency_code.unwrap_or_else(|| "EUR".to_string());
 
@@ -164,6 +193,7 @@ impl CreateOrderRequest {
             quote_id: None,
             trailing_stop: Some(false),
             trailing_stop_increment: None,
+            dma: None,
         }
     }
 
@@ -176,7 +206,7 @@ impl CreateOrderRequest {
         currency_code: Option<String>,
         deal_reference: Option<String>,
     ) -> Self {
-        let rounded_size = (size * 100.0).floor() / 100.0;
+        let rounded_size = round_order_size(size, RoundingMode::Floor);
 
         let currency_code = currency_code.unwrap_or_else(|| "EUR".to_string());
 
@@ -199,6 +229,84 @@ impl CreateOrderRequest {
             quote_id: None,
             trailing_stop: Some(false),
             trailing_stop_increment: None,
+            dma: None,
+        }
+    }
+
+    /// Creates an order that deals immediately using an order type the market accepts
+    ///
+    /// The order type comes from `OrderType::best_for`. Where market orders are
+    /// accepted a plain market order is built. Otherwise a fill-or-kill limit
+    /// order is built at a marketable level: the default option levels for options
+    /// markets, or the current offer (buy) or bid (sell) for other markets.
+    ///
+    /// # Arguments
+    /// * `market_details` - Details of the market to deal on
+    /// * `direction` - Direction of the order
+    /// * `size` - Size of the order, rounded down to two decimals
+    ///
+    /// # Returns
+    /// * `Ok(CreateOrderRequest)` - Using the instrument's epic, expiry and default currency
+    /// * `Err(AppError::InvalidInput)` - If a limit order is needed on a market
+    ///   other than options and the snapshot has no offer (buy) or bid (sell),
+    ///   as the option placeholder levels would leave it without price protection
+    pub fn smart(
+        market_details: &MarketDetails,
+        direction: Direction,
+        size: f64,
+    ) -> Result<Self, AppError> {
+        let instrument = &market_details.instrument;
+        let currency_code = instrument.currencies.as_ref().and_then(|currencies| {
+            currencies
+                .iter()
+                .find(|c| c.is_default == Some(true))
+                .or_else(|| currencies.first())
+                .map(|c| c.code.clone())
+        });
+        let expiry = Some(instrument.expiry.clone());
+
+        match OrderType::best_for(instrument.instrument_type, &market_details.dealing_rules) {
+            OrderType::Market => {
+                let mut order = Self::market(
+                    instrument.epic.clone(),
+                    direction,
+                    size,
+                    currency_code,
+                    None,
+                );
+                order.expiry = expiry;
+                Ok(order)
+            }
+            _ => {
+                let is_option = instrument.instrument_type.is_some_and(|t| t.is_option());
+                let level = match (&direction, is_option) {
+                    (Direction::Buy, true) => DEFAULT_ORDER_BUY_LEVEL,
+                    (Direction::Sell, true) => DEFAULT_ORDER_SELL_LEVEL,
+                    (Direction::Buy, false) => market_details.snapshot.offer.ok_or_else(|| {
+                        AppError::InvalidInput(format!(
+                            "market {} has no offer to price a buy limit order",
+                            instrument.epic
+                        ))
+                    })?,
+                    (Direction::Sell, false) => market_details.snapshot.bid.ok_or_else(|| {
+                        AppError::InvalidInput(format!(
+                            "market {} has no bid to price a sell limit order",
+                            instrument.epic
+                        ))
+                    })?,
+                };
+                let mut order = Self::limit(
+                    instrument.epic.clone(),
+                    direction,
+                    size,
+                    level,
+                    currency_code,
+                    None,
+                );
+                order.time_in_force = TimeInForce::FillOrKill;
+                order.expiry = expiry;
+                Ok(order)
+            }
         }
     }
 
@@ -241,7 +349,7 @@ impl CreateOrderRequest {
         deal_reference: Option<String>,
         currency_code: Option<String>,
     ) -> Self {
-        let rounded_size = (size * 100.0).floor() / 100.0;
+        let rounded_size = round_order_size(size, RoundingMode::Floor);
 
         let currency_code = currency_code.unwrap_or_else(|| "EUR".to_string());
 
@@ -267,6 +375,7 @@ impl CreateOrderRequest {
             quote_id: None,
             trailing_stop: Some(false),
             trailing_stop_increment: None,
+            dma: None,
         }
     }
 
@@ -310,7 +419,7 @@ impl CreateOrderRequest {
         currency_code: Option<String>,
         force_open: bool, // Compensate position if it is already open
     ) -> Self {
-        let rounded_size = (size * 100.0).floor() / 100.0;
+        let rounded_size = round_order_size(size, RoundingMode::Floor);
 
         let currency_code = currency_code.unwrap_or_else(|| "EUR".to_string());
 
@@ -336,6 +445,7 @@ impl CreateOrderRequest {
             quote_id: None,
             trailing_stop: Some(false),
             trailing_stop_increment: None,
+            dma: None,
         }
     }
 
@@ -365,7 +475,7 @@ impl CreateOrderRequest {
         deal_reference: Option<String>,
         currency_code: Option<String>,
     ) -> Self {
-        let rounded_size = (size * 100.0).floor() / 100.0;
+        let rounded_size = round_order_size(size, RoundingMode::Floor);
 
         let currency_code = currency_code.unwrap_or_else(|| "EUR".to_string());
 
@@ -391,6 +501,7 @@ impl CreateOrderRequest {
             quote_id: None,
             trailing_stop: Some(false),
             trailing_stop_increment: None,
+            dma: None,
         }
     }
 
@@ -429,7 +540,7 @@ impl CreateOrderRequest {
         currency_code: Option<String>,
         force_open: bool,
     ) -> Self {
-        let rounded_size = (size * 100.0).floor() / 100.0;
+        let rounded_size = round_order_size(size, RoundingMode::Floor);
 
         let currency_code = currency_code.unwrap_or_else(|| "EUR".to_string());
 
@@ -455,9 +566,21 @@ impl CreateOrderRequest {
             quote_id: None,
             trailing_stop: Some(false),
             trailing_stop_increment: None,
+            dma: None,
         }
     }
 
+    /// Sets the order size, rounded with an explicit mode
+    ///
+    /// Overrides the floor rounding the constructors use, e.g.
+    /// `RoundingMode::Nearest` turns a requested 0.259 into 0.26 rather than
+    /// 0.25. `Client::order_for_epic` applies `RestApiConfig::size_rounding`
+    /// this way.
+    pub fn with_size(mut self, size: f64, mode: RoundingMode) -> Self {
+        self.size = round_order_size(size, mode);
+        self
+    }
+
     /// Adds a stop loss to the order
     pub fn with_stop_loss(mut self, stop_level: f64) -> Self {
         self.stop_level = Some(stop_level);
@@ -470,13 +593,112 @@ impl CreateOrderRequest {
         self
     }
 
+    /// Requests direct market access routing for the order
+    ///
+    /// DMA orders are sent to the underlying exchange instead of IG's OTC book.
+    pub fn with_dma(mut self, dma: bool) -> Self {
+        self.dma = Some(dma);
+        self
+    }
+
+    /// Sets the expiry to a monthly contract, formatted as IG expects (`JUL-25`)
+    pub fn with_contract_month(mut self, contract: ContractMonth) -> Self {
+        self.expiry = Some(contract.to_string());
+        self
+    }
+
+    /// Sets the expiry to a monthly contract after checking that it is listed
+    ///
+    /// The expiry string of the matching market is used verbatim, so dated
+    /// contracts such as `20-AUG-25` keep their day.
+    ///
+    /// # Arguments
+    /// * `contract` - Contract month to trade
+    /// * `markets` - Market details of the available expiries of the instrument
+    ///
+    /// # Returns
+    /// * `Err(AppError::InvalidInput)` - If no listed market expires in that month
+    pub fn with_listed_contract_month(
+        mut self,
+        contract: ContractMonth,
+        markets: &[MarketDetails],
+    ) -> Result<Self, AppError> {
+        let listed = contract.find_in(markets).ok_or_else(|| {
+            AppError::InvalidInput(format!("no listed expiry for contract month {contract}"))
+        })?;
+        self.expiry = Some(listed.instrument.expiry.clone());
+        Ok(self)
+    }
+
     /// Adds a trailing stop loss to the order
+    ///
+    /// IG expects `trailingStopIncrement` to be a whole number of points that is
+    /// at least the market's minimum step distance; fractional values such as
+    /// `1.5` are rejected by the API. Use `validate_trailing_stop` with the
+    /// market's `DealingRules` to catch this before sending the order.
     pub fn with_trailing_stop_loss(mut self, trailing_stop_increment: f64) -> Self {
         self.trailing_stop = Some(true);
         self.trailing_stop_increment = Some(trailing_stop_increment);
         self
     }
 
+    /// Validates the trailing stop increment against the market dealing rules
+    ///
+    /// The increment must be a positive whole number of points and, when the
+    /// minimum step distance is expressed in points, no smaller than it.
+    /// Orders without a trailing stop are always valid.
+    ///
+    /// # Arguments
+    /// * `dealing_rules` - Dealing rules of the market the order targets
+    ///
+    /// # Returns
+    /// * `Ok(())` if the trailing stop settings are acceptable
+    /// * `Err(AppError::InvalidInput)` describing the violated constraint
+    pub fn validate_trailing_stop(&self, dealing_rules: &DealingRules) -> Result<(), AppError> {
+        if self.trailing_stop != Some(true) {
+            return Ok(());
+        }
+
+        let increment = self.trailing_stop_increment.ok_or_else(|| {
+            AppError::InvalidInput(
+                "trailingStopIncrement is required when trailingStop is enabled".to_string(),
+            )
+        })?;
+
+        if !increment.is_finite() || increment <= 0.0 {
+            return Err(AppError::InvalidInput(format!(
+                "trailingStopIncrement must be a positive number of points, got {increment}"
+            )));
+        }
+
+        if increment.fract() != 0.0 {
+            return Err(AppError::InvalidInput(format!(
+                "trailingStopIncrement must be a whole number of points, got {increment}"
+            )));
+        }
+
+        let min_step = &dealing_rules.min_step_distance;
+        if let (Some(StepUnit::Points), Some(min)) = (&min_step.unit, min_step.value)
+            && increment < min
+        {
+            return Err(AppError::InvalidInput(format!(
+                "trailingStopIncrement {increment} is below the minimum step distance of {min} points"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the deal reference with one that starts with `prefix`
+    ///
+    /// Lets orders placed by a given strategy be recognised by their reference
+    /// in confirmations and activity. The random suffix is shortened so the
+    /// reference fits IG's 30 character limit; see `generate_deal_reference`.
+    pub fn with_reference_prefix(mut self, prefix: &str) -> Self {
+        self.deal_reference = Some(generate_deal_reference(prefix));
+        self
+    }
+
     /// Adds a reference to the order
     pub fn with_reference(mut self, reference: String) -> Self {
         self.deal_reference = Some(reference);
@@ -502,6 +724,130 @@ impl CreateOrderRequest {
     }
 }
 
+/// One leg of a multi-leg option strategy
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyLeg {
+    /// Option epic to trade
+    pub epic: String,
+    /// Buy or sell
+    pub direction: Direction,
+    /// Number of contracts
+    pub size: f64,
+    /// Expiry of the option, e.g. `DEC-25`
+    pub expiry: Option<String>,
+}
+
+/// Composes a multi-leg option strategy into a batch of orders
+///
+/// Each leg becomes a `buy_option_to_market` or `sell_option_to_market` order
+/// whose deal reference starts with the strategy's prefix, so the legs of one
+/// strategy can be told apart in confirmations and activity. Submit the batch
+/// with `OrderService::place_strategy`.
+///
+/// ```rust,ignore
+/// // Bull call spread: buy the lower strike, sell the higher one
+/// let orders = StrategyBuilder::new("BULLCS")
+///     .leg("OP.D.OTCDAX1.21000C.IP", Direction::Buy, 1.0, Some("DEC-25".into()))
+///     .leg("OP.D.OTCDAX1.21200C.IP", Direction::Sell, 1.0, Some("DEC-25".into()))
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StrategyBuilder {
+    reference_prefix: String,
+    currency_code: Option<String>,
+    legs: Vec<StrategyLeg>,
+}
+
+impl StrategyBuilder {
+    /// Starts a strategy whose legs share the deal reference prefix `reference_prefix`
+    pub fn new(reference_prefix: &str) -> Self {
+        Self {
+            reference_prefix: reference_prefix.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the currency of every leg (`EUR` when not set)
+    pub fn currency_code(mut self, currency_code: &str) -> Self {
+        self.currency_code = Some(currency_code.to_string());
+        self
+    }
+
+    /// Adds a leg; legs are placed in the order they are added
+    pub fn leg(
+        mut self,
+        epic: &str,
+        direction: Direction,
+        size: f64,
+        expiry: Option<String>,
+    ) -> Self {
+        self.legs.push(StrategyLeg {
+            epic: epic.to_string(),
+            direction,
+            size,
+            expiry,
+        });
+        self
+    }
+
+    /// Legs added so far
+    pub fn legs(&self) -> &[StrategyLeg] {
+        &self.legs
+    }
+
+    /// Builds one order per leg
+    ///
+    /// # Returns
+    /// * `Ok(Vec<CreateOrderRequest>)` - Orders in leg order, each with a
+    ///   distinct deal reference starting with the strategy prefix
+    /// * `Err(AppError::InvalidInput)` - If there are no legs, a leg has no
+    ///   epic or its size is not positive
+    pub fn build(self) -> Result<Vec<CreateOrderRequest>, AppError> {
+        if self.legs.is_empty() {
+            return Err(AppError::InvalidInput(
+                "a strategy needs at least one leg".to_string(),
+            ));
+        }
+        self.legs
+            .into_iter()
+            .enumerate()
+            .map(|(index, leg)| {
+                if leg.epic.trim().is_empty() {
+                    return Err(AppError::InvalidInput(format!(
+                        "leg {} has no epic",
+                        index + 1
+                    )));
+                }
+                if !(leg.size.is_finite() && leg.size > 0.0) {
+                    return Err(AppError::InvalidInput(format!(
+                        "leg {} size {} must be positive",
+                        index + 1,
+                        leg.size
+                    )));
+                }
+                let deal_reference = Some(generate_deal_reference(&self.reference_prefix));
+                let currency_code = self.currency_code.clone();
+                Ok(match leg.direction {
+                    Direction::Buy => CreateOrderRequest::buy_option_to_market(
+                        leg.epic,
+                        leg.size,
+                        leg.expiry,
+                        deal_reference,
+                        currency_code,
+                    ),
+                    Direction::Sell => CreateOrderRequest::sell_option_to_market(
+                        leg.epic,
+                        leg.size,
+                        leg.expiry,
+                        deal_reference,
+                        currency_code,
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
 /// Model for updating an existing position
 #[derive(Debug, Clone, DisplaySimple, Serialize, Deserialize)]
 pub struct UpdatePositionRequest {
@@ -585,6 +931,69 @@ impl ClosePositionRequest {
         }
     }
 
+    /// Creates a request to close a position against a dealer quote
+    ///
+    /// Required by instruments that only accept quote-dependent orders.
+    ///
+    /// # Arguments
+    /// * `deal_id` - The ID of the deal to close
+    /// * `direction` - The direction of the closing order (opposite of the position direction)
+    /// * `size` - The size of the position to close
+    /// * `quote_id` - Identifier of the quote being dealt on
+    /// * `level` - Price of the quote
+    pub fn quote(
+        deal_id: String,
+        direction: Direction,
+        size: f64,
+        quote_id: String,
+        level: f64,
+    ) -> Self {
+        Self {
+            deal_id: Some(deal_id),
+            direction,
+            size,
+            order_type: OrderType::Quote,
+            time_in_force: TimeInForce::FillOrKill,
+            level: Some(level),
+            expiry: None,
+            epic: None,
+            quote_id: Some(quote_id),
+        }
+    }
+
+    /// Sets the quote identifier for the closing order
+    ///
+    /// A quote order also needs `level` set to the quoted price.
+    pub fn with_quote_id(mut self, quote_id: String) -> Self {
+        self.quote_id = Some(quote_id);
+        self
+    }
+
+    /// Checks that the request carries the fields required by its order type
+    ///
+    /// # Returns
+    /// * `Ok(())` if the request can be sent
+    /// * `Err(AppError::InvalidInput)` if a quote order lacks `quote_id` or `level`
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.order_type == OrderType::Quote {
+            if self
+                .quote_id
+                .as_deref()
+                .is_none_or(|id| id.trim().is_empty())
+            {
+                return Err(AppError::InvalidInput(
+                    "quote close orders require a quote_id".to_string(),
+                ));
+            }
+            if self.level.is_none() {
+                return Err(AppError::InvalidInput(
+                    "quote close orders require a level".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Creates a request to close an option position by deal ID using a limit order with predefined price levels
     ///
     /// This is specifically designed for options trading where market orders are not supported
@@ -694,6 +1103,9 @@ pub struct CreateWorkingOrderRequest {
     /// Currency code for the order (e.g., "USD", "EUR")
     #[serde(rename = "currencyCode", skip_serializing_if = "Option::is_none")]
     pub currency_code: Option<String>,
+    /// Route the order to the underlying exchange (direct market access)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dma: Option<bool>,
 }
 
 impl CreateWorkingOrderRequest {
@@ -714,6 +1126,7 @@ impl CreateWorkingOrderRequest {
             good_till_date: None,
             deal_reference: None,
             currency_code: None,
+            dma: None,
         }
     }
 
@@ -734,6 +1147,7 @@ impl CreateWorkingOrderRequest {
             good_till_date: None,
             deal_reference: None,
             currency_code: None,
+            dma: None,
         }
     }
 
@@ -749,6 +1163,16 @@ impl CreateWorkingOrderRequest {
         self
     }
 
+    /// Replaces the deal reference with one that starts with `prefix`
+    ///
+    /// Lets orders placed by a given strategy be recognised by their reference
+    /// in confirmations and activity. The random suffix is shortened so the
+    /// reference fits IG's 30 character limit; see `generate_deal_reference`.
+    pub fn with_reference_prefix(mut self, prefix: &str) -> Self {
+        self.deal_reference = Some(generate_deal_reference(prefix));
+        self
+    }
+
     /// Adds a reference to the working order
     pub fn with_reference(mut self, reference: String) -> Self {
         self.deal_reference = Some(reference);
@@ -761,4 +1185,168 @@ impl CreateWorkingOrderRequest {
         self.good_till_date = Some(date);
         self
     }
+
+    /// Sets how long the order remains valid
+    ///
+    /// # Arguments
+    /// * `time_in_force` - Order duration
+    /// * `good_till_date` - Expiry date, required for `GoodTillDate` and
+    ///   ignored otherwise
+    ///
+    /// # Returns
+    /// * `Err(AppError::InvalidInput)` - If `GoodTillDate` is given without a date
+    pub fn with_time_in_force(
+        mut self,
+        time_in_force: TimeInForce,
+        good_till_date: Option<String>,
+    ) -> Result<Self, AppError> {
+        self.good_till_date = match time_in_force {
+            TimeInForce::GoodTillDate => good_till_date,
+            _ => None,
+        };
+        self.time_in_force = time_in_force;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Checks the time in force before the request is sent
+    ///
+    /// A working order rests until its level is reached, so only
+    /// `GoodTillCancelled` and `GoodTillDate` apply; the immediate-execution
+    /// variants are for market orders. IG rejects a `GOOD_TILL_DATE` order
+    /// without `goodTillDate` with no useful reason, so that is refused too.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the request can be sent
+    /// * `Err(AppError::InvalidInput)` if the time in force is an
+    ///   immediate-execution one, or `GoodTillDate` is set without a date
+    pub fn validate(&self) -> Result<(), AppError> {
+        if matches!(
+            self.time_in_force,
+            TimeInForce::ImmediateOrCancel
+                | TimeInForce::FillOrKill
+                | TimeInForce::ExecuteAndEliminate
+        ) {
+            return Err(AppError::InvalidInput(format!(
+                "working orders cannot use {:?} time in force",
+                self.time_in_force
+            )));
+        }
+        if self.time_in_force == TimeInForce::GoodTillDate
+            && self
+                .good_till_date
+                .as_deref()
+                .is_none_or(|date| date.trim().is_empty())
+        {
+            return Err(AppError::InvalidInput(
+                "good till date working orders require a good_till_date".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Requests direct market access routing for the working order
+    ///
+    /// DMA orders are sent to the underlying exchange instead of IG's OTC book.
+    pub fn with_dma(mut self, dma: bool) -> Self {
+        self.dma = Some(dma);
+        self
+    }
+}
+
+/// Request for `PUT operations/application` to change an API key's status or allowances
+///
+/// Disabling a key locks out every client using it, including this one, so a
+/// request that disables or revokes the key is rejected by `validate` unless
+/// `confirm_disable` has been called.
+#[derive(Debug, Clone, DisplaySimple, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateApplicationRequest {
+    /// API key of the application to update
+    pub api_key: String,
+    /// New status for the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ApplicationStatus>,
+    /// New per-minute allowance for all account requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowance_account_overall: Option<u32>,
+    /// New per-minute allowance for trading requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowance_account_trading: Option<u32>,
+    /// Explicit opt-in required to disable the key
+    #[serde(skip)]
+    disable_confirmed: bool,
+}
+
+impl UpdateApplicationRequest {
+    /// Creates an update for the given API key that changes nothing yet
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            status: None,
+            allowance_account_overall: None,
+            allowance_account_trading: None,
+            disable_confirmed: false,
+        }
+    }
+
+    /// Sets the status of the key
+    ///
+    /// Setting anything other than `Enabled` also requires `confirm_disable`.
+    pub fn with_status(mut self, status: ApplicationStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Sets the overall per-minute account allowance
+    pub fn with_allowance_account_overall(mut self, allowance: u32) -> Self {
+        self.allowance_account_overall = Some(allowance);
+        self
+    }
+
+    /// Sets the per-minute trading allowance
+    pub fn with_allowance_account_trading(mut self, allowance: u32) -> Self {
+        self.allowance_account_trading = Some(allowance);
+        self
+    }
+
+    /// Confirms that the caller really means to disable the key
+    pub fn confirm_disable(mut self) -> Self {
+        self.disable_confirmed = true;
+        self
+    }
+
+    /// Returns true if the request would disable or revoke the key
+    pub fn disables_key(&self) -> bool {
+        self.status
+            .is_some_and(|status| status != ApplicationStatus::Enabled)
+    }
+
+    /// Validates the request before it is sent
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the request can be sent
+    /// * `Err(AppError::InvalidInput)` - If the key is empty, nothing would change,
+    ///   or the key would be disabled without `confirm_disable`
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.api_key.trim().is_empty() {
+            return Err(AppError::InvalidInput(
+                "apiKey must not be empty".to_string(),
+            ));
+        }
+        if self.status.is_none()
+            && self.allowance_account_overall.is_none()
+            && self.allowance_account_trading.is_none()
+        {
+            return Err(AppError::InvalidInput(
+                "application update does not change anything".to_string(),
+            ));
+        }
+        if self.disables_key() && !self.disable_confirmed {
+            return Err(AppError::InvalidInput(
+                "disabling an API key requires confirm_disable()".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
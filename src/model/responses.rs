@@ -3,9 +3,13 @@
    Email: jb@taunais.com
    Date: 19/10/25
 ******************************************************************************/
+use crate::constants::SIZE_EPSILON;
+use crate::error::AppError;
+use crate::model::requests::ClosePositionRequest;
 use crate::prelude::{Account, Activity, MarketDetails};
 use crate::presentation::account::{
-    AccountTransaction, ActivityMetadata, Position, TransactionMetadata, WorkingOrder,
+    AccountTransaction, ActivityMetadata, ApplicationStatus, Channel, Position,
+    SprintMarketPosition, TransactionMetadata, WorkingOrder,
 };
 use crate::presentation::instrument::InstrumentType;
 use crate::presentation::market::{
@@ -39,6 +43,116 @@ pub struct DBEntryResponse {
     pub last_update: DateTime<Utc>,
 }
 
+/// Database entries together with the symbols whose expiry could not be refreshed
+#[derive(Debug, Default)]
+pub struct DBEntriesReport {
+    /// Entries built from the market hierarchy
+    pub entries: Vec<DBEntryResponse>,
+    /// Symbols whose market details request failed, with the error returned.
+    /// Entries for these symbols keep the expiry reported by the navigation.
+    pub failures: Vec<(String, AppError)>,
+}
+
+impl DBEntriesReport {
+    /// Returns true if the expiry of every symbol was refreshed
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Outcome of `OrderService::partial_close_and_protect`
+#[derive(Debug)]
+pub struct PartialCloseResult {
+    /// Confirmation of the partial close
+    pub close: OrderConfirmationResponse,
+    /// Result of amending the stop/limit on the remaining position.
+    /// `None` if the close was not accepted, in which case nothing was amended.
+    pub amend: Option<Result<UpdatePositionResponse, AppError>>,
+}
+
+impl PartialCloseResult {
+    /// Returns true if the close was accepted
+    pub fn is_closed(&self) -> bool {
+        self.close.is_accepted()
+    }
+
+    /// Returns true if both the close and the amendment succeeded
+    pub fn is_complete(&self) -> bool {
+        matches!(self.amend, Some(Ok(_)))
+    }
+
+    /// Returns true if part of the position was closed but the remainder
+    /// could not be protected and needs attention
+    pub fn is_remainder_unprotected(&self) -> bool {
+        matches!(self.amend, Some(Err(_)))
+    }
+}
+
+/// Outcome of `OrderService::place_strategy`
+///
+/// Legs are placed one at a time and placement stops at the first leg that
+/// is rejected or cannot be submitted, so `legs` may be shorter than
+/// `requested`.
+#[derive(Debug)]
+pub struct StrategyResult {
+    /// Number of legs in the strategy
+    pub requested: usize,
+    /// Outcome of each leg that was sent, in leg order
+    pub legs: Vec<Result<OrderConfirmationResponse, AppError>>,
+    /// Open positions of legs whose confirmation timed out
+    ///
+    /// A leg that was sent but not confirmed in time may still have filled.
+    /// Its outcome in `legs` stays the timeout error, and the position found
+    /// for its deal reference, if any, is recorded here so it is unwound too.
+    pub unconfirmed_fills: Vec<Position>,
+}
+
+impl StrategyResult {
+    /// Returns true if every leg was sent and accepted
+    pub fn is_complete(&self) -> bool {
+        self.legs.len() == self.requested
+            && self.legs.iter().all(|leg| {
+                leg.as_ref()
+                    .is_ok_and(OrderConfirmationResponse::is_accepted)
+            })
+    }
+
+    /// Confirmations of the legs that were accepted
+    pub fn filled(&self) -> impl Iterator<Item = &OrderConfirmationResponse> {
+        self.legs
+            .iter()
+            .filter_map(|leg| leg.as_ref().ok())
+            .filter(|confirmation| confirmation.is_accepted())
+    }
+
+    /// Returns true if some legs filled and others did not, leaving a partial strategy
+    pub fn needs_unwind(&self) -> bool {
+        !self.is_complete()
+            && (self.filled().next().is_some() || !self.unconfirmed_fills.is_empty())
+    }
+
+    /// Orders closing every filled leg, for unwinding a partial strategy
+    ///
+    /// Covers both confirmed legs and `unconfirmed_fills`. Confirmed legs
+    /// whose confirmation lacks a deal ID, direction or size are skipped.
+    pub fn unwind_requests(&self) -> Vec<ClosePositionRequest> {
+        self.filled()
+            .filter_map(|confirmation| {
+                Some(ClosePositionRequest::close_option_to_market_by_id(
+                    confirmation.deal_id.clone()?,
+                    confirmation.direction.as_ref()?.opposite(),
+                    confirmation.size?,
+                ))
+            })
+            .chain(
+                self.unconfirmed_fills
+                    .iter()
+                    .map(|position| position.to_close_request(None)),
+            )
+            .collect()
+    }
+}
+
 impl From<MarketNode> for DBEntryResponse {
     fn from(value: MarketNode) -> Self {
         let mut entry = DBEntryResponse::default();
@@ -303,6 +417,32 @@ impl HistoricalPricesResponse {
     pub fn iter(&self) -> impl Iterator<Item = &HistoricalPrice> {
         self.prices.iter()
     }
+
+    /// Appends the prices of another response, skipping candles already present
+    ///
+    /// Candles are identified by `snapshot_time`. The allowance is replaced by
+    /// the one reported in `other`, as it is the most recent.
+    ///
+    /// # Returns
+    /// Number of candles appended
+    pub fn extend_dedup(&mut self, other: HistoricalPricesResponse) -> usize {
+        let mut seen: std::collections::HashSet<String> = self
+            .prices
+            .iter()
+            .map(|price| price.snapshot_time.clone())
+            .collect();
+        let before = self.prices.len();
+        self.prices.extend(
+            other
+                .prices
+                .into_iter()
+                .filter(|price| seen.insert(price.snapshot_time.clone())),
+        );
+        if other.allowance.is_some() {
+            self.allowance = other.allowance;
+        }
+        self.prices.len() - before
+    }
 }
 
 impl std::fmt::Display for HistoricalPricesResponse {
@@ -457,6 +597,16 @@ impl MarketSearchResponse {
     pub fn iter(&self) -> impl Iterator<Item = &MarketData> {
         self.markets.iter()
     }
+
+    /// Reorders the markets best match first, see `MarketData::search_score`
+    ///
+    /// The sort is stable, so markets that score the same keep IG's order.
+    #[must_use]
+    pub fn ranked(mut self, search_term: &str) -> Self {
+        self.markets
+            .sort_by_cached_key(|market| std::cmp::Reverse(market.search_score(search_term)));
+        self
+    }
 }
 
 impl std::fmt::Display for MarketSearchResponse {
@@ -563,6 +713,17 @@ pub struct AccountsResponse {
     pub accounts: Vec<Account>,
 }
 
+impl AccountsResponse {
+    /// Returns the account flagged as preferred (the user's default account)
+    ///
+    /// # Returns
+    /// * `Some(&Account)` - The first account with `preferred == true`
+    /// * `None` - If no account is flagged as preferred
+    pub fn preferred(&self) -> Option<&Account> {
+        self.accounts.iter().find(|account| account.preferred)
+    }
+}
+
 /// Open positions
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize, Default)]
 pub struct PositionsResponse {
@@ -571,23 +732,81 @@ pub struct PositionsResponse {
 }
 
 impl PositionsResponse {
+    /// Returns the number of positions in the response
+    ///
+    /// # Returns
+    /// Number of positions
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns true if the response contains no positions
+    ///
+    /// # Returns
+    /// True if empty, false otherwise
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Size-weighted average entry level of the positions open on an epic
+    ///
+    /// An order filled in parts shows up as several positions with their own
+    /// deal IDs and levels. Their combined entry is `Σ(level × size) / Σ size`,
+    /// unlike the plain mean of levels used when netting with `Position::add`.
+    ///
+    /// # Arguments
+    /// * `epic` - Instrument to average over
+    ///
+    /// # Returns
+    /// * `Some(f64)` - The weighted entry level
+    /// * `None` - If there is no position on the epic, the positions are in
+    ///   both directions, or their total size is zero
+    pub fn average_entry(&self, epic: &str) -> Option<f64> {
+        let mut fills = self
+            .positions
+            .iter()
+            .filter(|position| position.market.epic == epic)
+            .map(|position| &position.position)
+            .peekable();
+        let direction = fills.peek()?.direction.clone();
+
+        let mut total_size = 0.0;
+        let mut weighted_levels = 0.0;
+        for fill in fills {
+            if fill.direction != direction {
+                return None;
+            }
+            total_size += fill.size;
+            weighted_levels += fill.level * fill.size;
+        }
+
+        (total_size > 0.0).then(|| weighted_levels / total_size)
+    }
+
     /// Compact positions by epic, combining positions with the same epic
     ///
     /// This method takes a vector of positions and returns a new vector where
     /// positions with the same epic have been combined into a single position.
+    /// Positions on the same epic in different currencies cannot be netted and
+    /// are kept apart, one per currency.
     ///
     /// # Arguments
     /// * `positions` - A vector of positions to compact
     ///
     /// # Returns
-    /// A vector of positions with unique epics
+    /// A vector of positions with unique epic and currency pairs
     pub fn compact_by_epic(positions: Vec<Position>) -> Vec<Position> {
-        let mut epic_map: HashMap<String, Position> = std::collections::HashMap::new();
+        let mut epic_map: HashMap<(String, String), Position> = std::collections::HashMap::new();
 
         for position in positions {
-            let epic = position.market.epic.clone();
+            let key = (
+                position.market.epic.clone(),
+                position.position.currency.clone(),
+            );
             epic_map
-                .entry(epic)
+                .entry(key)
                 .and_modify(|existing| {
                     *existing = existing.clone() + position.clone();
                 })
@@ -598,6 +817,18 @@ impl PositionsResponse {
     }
 }
 
+/// Open positions on sprint markets
+#[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize, Default)]
+pub struct SprintPositionsResponse {
+    /// List of open sprint market positions
+    #[serde(
+        rename = "sprintMarketPositions",
+        default,
+        deserialize_with = "deserialize_null_as_empty_vec"
+    )]
+    pub sprint_market_positions: Vec<SprintMarketPosition>,
+}
+
 /// Working orders
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
 pub struct WorkingOrdersResponse {
@@ -606,6 +837,26 @@ pub struct WorkingOrdersResponse {
     pub working_orders: Vec<WorkingOrder>,
 }
 
+impl WorkingOrdersResponse {
+    /// Returns the number of working orders in the response
+    ///
+    /// # Returns
+    /// Number of working orders
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.working_orders.len()
+    }
+
+    /// Returns true if the response contains no working orders
+    ///
+    /// # Returns
+    /// True if empty, false otherwise
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.working_orders.is_empty()
+    }
+}
+
 /// Account activity
 #[derive(Debug, Clone, Deserialize)]
 pub struct AccountActivityResponse {
@@ -615,6 +866,36 @@ pub struct AccountActivityResponse {
     pub metadata: Option<ActivityMetadata>,
 }
 
+impl AccountActivityResponse {
+    /// Returns the number of activities in the response
+    ///
+    /// # Returns
+    /// Number of activities
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.activities.len()
+    }
+
+    /// Returns true if the response contains no activities
+    ///
+    /// # Returns
+    /// True if empty, false otherwise
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.activities.is_empty()
+    }
+
+    /// Activities placed through the given channel
+    ///
+    /// Use `Channel::PublicWebApi` to keep what was placed over the REST API,
+    /// including by this crate, apart from web, mobile and dealer activity.
+    pub fn by_channel<'a>(&'a self, channel: &'a Channel) -> impl Iterator<Item = &'a Activity> {
+        self.activities
+            .iter()
+            .filter(move |activity| activity.channel_typed().as_ref() == Some(channel))
+    }
+}
+
 /// Transaction history
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
 pub struct TransactionHistoryResponse {
@@ -624,6 +905,40 @@ pub struct TransactionHistoryResponse {
     pub metadata: TransactionMetadata,
 }
 
+/// API key details returned by `operations/application`
+#[derive(Debug, Clone, DisplaySimple, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationDetails {
+    /// API key identifying the application
+    pub api_key: String,
+    /// Client identifier owning the application
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Application name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Current status of the key
+    pub status: ApplicationStatus,
+    /// Requests per minute allowed for all account requests
+    #[serde(default)]
+    pub allowance_account_overall: Option<u32>,
+    /// Requests per minute allowed for trading requests
+    #[serde(default)]
+    pub allowance_account_trading: Option<u32>,
+    /// Historical price data points allowed per week
+    #[serde(default)]
+    pub allowance_account_historical_data: Option<u32>,
+    /// Requests per minute allowed for the application across all accounts
+    #[serde(default)]
+    pub allowance_application_overall: Option<u32>,
+    /// Maximum number of concurrent Lightstreamer subscriptions
+    #[serde(default)]
+    pub concurrent_subscriptions_limit: Option<u32>,
+    /// Date the application was created
+    #[serde(default)]
+    pub created_date: Option<String>,
+}
+
 /// Response to order creation
 #[derive(Debug, Clone, DisplaySimple, Serialize, Deserialize)]
 pub struct CreateOrderResponse {
@@ -656,10 +971,22 @@ pub struct CreateWorkingOrderResponse {
     pub deal_reference: String,
 }
 
+/// Deal affected by a confirmed order, e.g. the position opened or closed
+#[derive(Debug, Clone, PartialEq, DisplaySimple, Serialize, Deserialize)]
+pub struct AffectedDeal {
+    /// Deal ID of the affected position or working order
+    #[serde(rename = "dealId")]
+    pub deal_id: String,
+    /// What happened to the deal (`OPENED`, `AMENDED`, `PARTIALLY_CLOSED`...)
+    #[serde(deserialize_with = "deserialize_nullable_status")]
+    pub status: Status,
+}
+
 /// Details of a confirmed order
 #[derive(Debug, Clone, DisplaySimple, Serialize, Deserialize)]
 pub struct OrderConfirmationResponse {
     /// Date and time of the confirmation
+    #[serde(default)]
     pub date: String,
     /// Status of the order (accepted, rejected, etc.)
     /// This can be null in some responses (e.g., when market is closed)
@@ -706,4 +1033,81 @@ pub struct OrderConfirmationResponse {
     pub trailing_stop: Option<bool>,
     /// Direction of the order (buy or sell)
     pub direction: Option<Direction>,
+    /// Realised profit or loss, reported on confirmations of closing deals
+    #[serde(default)]
+    pub profit: Option<f64>,
+    /// Currency of `profit`
+    #[serde(rename = "profitCurrency", default)]
+    pub profit_currency: Option<String>,
+    /// Deals opened, amended or closed by this order
+    #[serde(
+        rename = "affectedDeals",
+        default,
+        deserialize_with = "deserialize_null_as_empty_vec"
+    )]
+    pub affected_deals: Vec<AffectedDeal>,
+}
+
+impl OrderConfirmationResponse {
+    /// Returns true if IG accepted the deal
+    pub fn is_accepted(&self) -> bool {
+        self.deal_status.as_deref() == Some("ACCEPTED")
+    }
+
+    /// Returns true if the deal reached its final state
+    ///
+    /// Rejected deals are final whatever their `status`, which IG leaves null
+    /// (read as `Open`) on rejections.
+    pub fn is_terminal(&self) -> bool {
+        self.deal_status.as_deref() == Some("REJECTED") || self.status.is_terminal()
+    }
+
+    /// Size IG actually dealt
+    ///
+    /// Zero for a rejected deal, otherwise the confirmed `size`, which can be
+    /// smaller than requested when the order was only partly filled.
+    ///
+    /// # Returns
+    /// * `None` - If an accepted confirmation does not report a size
+    pub fn filled_size(&self) -> Option<f64> {
+        if self.deal_status.as_deref() == Some("REJECTED") {
+            return Some(0.0);
+        }
+        self.size
+    }
+
+    /// Returns true if the deal executed for less than `requested_size`
+    ///
+    /// A rejected deal is not a partial fill; nothing executed. False when the
+    /// filled size is not reported.
+    pub fn is_partial_fill(&self, requested_size: f64) -> bool {
+        self.filled_size()
+            .is_some_and(|filled| filled > 0.0 && requested_size - filled > SIZE_EPSILON)
+    }
+
+    /// Size left to deal to reach `requested_size`, e.g. to re-submit the remainder
+    ///
+    /// # Returns
+    /// * `Some(f64)` - The unfilled size, zero when fully filled
+    /// * `None` - If the filled size is not reported
+    pub fn unfilled_size(&self, requested_size: f64) -> Option<f64> {
+        let filled = self.filled_size()?;
+        let remaining = requested_size - filled;
+        Some(if remaining > SIZE_EPSILON {
+            remaining
+        } else {
+            0.0
+        })
+    }
+
+    /// Deal IDs affected with the given status
+    ///
+    /// For example `Status::FullyClosed` lists the positions a closing order
+    /// closed completely.
+    pub fn affected_deal_ids(&self, status: Status) -> impl Iterator<Item = &str> {
+        self.affected_deals
+            .iter()
+            .filter(move |deal| deal.status == status)
+            .map(|deal| deal.deal_id.as_str())
+    }
 }
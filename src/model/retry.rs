@@ -3,7 +3,11 @@
    Email: jb@taunais.com
    Date: 20/10/25
 ******************************************************************************/
+use crate::constants::MAX_RETRY_BACKOFF_SECS;
 use crate::utils::config::get_env_or_none;
+use crate::utils::parsing::parse_http_date;
+use chrono::{DateTime, Utc};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Configuration for HTTP request retry behavior
 #[derive(Debug, Clone)]
@@ -68,6 +72,58 @@ impl RetryConfig {
     pub fn delay_secs(&self) -> u64 {
         self.retry_delay_secs.unwrap_or(10)
     }
+
+    /// Computed backoff before the given retry (1-based) when IG suggests no delay
+    ///
+    /// Doubles `delay_secs` on every attempt up to `MAX_RETRY_BACKOFF_SECS` (or
+    /// `delay_secs` itself if that is larger) and adds up to 20% jitter so that
+    /// clients throttled together do not retry in lockstep.
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.delay_secs();
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let capped = base
+            .saturating_mul(factor)
+            .min(MAX_RETRY_BACKOFF_SECS.max(base));
+        let delay = Duration::from_secs(capped);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.subsec_nanos());
+        delay + delay.mul_f64(f64::from(nanos % 1000) / 5000.0)
+    }
+
+    /// Delay before the given retry, honouring IG's `Retry-After` when it is longer
+    ///
+    /// # Arguments
+    /// * `attempt` - Retry number, starting at 1
+    /// * `retry_after` - Delay parsed from the `Retry-After` header, if any
+    #[must_use]
+    pub fn retry_delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self.backoff(attempt);
+        retry_after.map_or(backoff, |suggested| suggested.max(backoff))
+    }
+}
+
+/// Parses a `Retry-After` header value
+///
+/// Both forms allowed by RFC 9110 are accepted: a number of seconds (`"120"`)
+/// and an HTTP date (`"Wed, 21 Oct 2015 07:28:00 GMT"`). Dates in the past
+/// yield a zero delay.
+///
+/// # Arguments
+/// * `value` - Raw header value
+/// * `now` - Current time, used to turn a date into a delay
+///
+/// # Returns
+/// * `Some(Duration)` - Delay suggested by the server
+/// * `None` - If the value is neither form
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = parse_http_date(value)?;
+    Some((date - now).to_std().unwrap_or(Duration::ZERO))
 }
 
 impl Default for RetryConfig {
@@ -6,6 +6,7 @@
 use crate::prelude::{
     AppError, Client, IgResult, MarketData, MarketNavigationResponse, MarketNode, MarketService,
 };
+use chrono::{Duration, NaiveDateTime};
 use std::future::Future;
 use std::pin::Pin;
 use tracing::{debug, error, info};
@@ -143,3 +144,83 @@ pub fn extract_markets_from_hierarchy(nodes: &[MarketNode]) -> Vec<MarketData> {
 
     all_markets
 }
+
+/// Date format used by the v3 prices endpoint for `from` and `to`
+pub const HISTORICAL_PRICES_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Duration of one candle for an IG price resolution
+///
+/// `MONTH` is approximated as 31 days, which only matters for range splitting.
+///
+/// # Returns
+/// `None` if the resolution is not recognised
+pub fn resolution_duration(resolution: &str) -> Option<Duration> {
+    let duration = match resolution.to_ascii_uppercase().as_str() {
+        "SECOND" => Duration::seconds(1),
+        "MINUTE" => Duration::minutes(1),
+        "MINUTE_2" => Duration::minutes(2),
+        "MINUTE_3" => Duration::minutes(3),
+        "MINUTE_5" => Duration::minutes(5),
+        "MINUTE_10" => Duration::minutes(10),
+        "MINUTE_15" => Duration::minutes(15),
+        "MINUTE_30" => Duration::minutes(30),
+        "HOUR" => Duration::hours(1),
+        "HOUR_2" => Duration::hours(2),
+        "HOUR_3" => Duration::hours(3),
+        "HOUR_4" => Duration::hours(4),
+        "DAY" => Duration::days(1),
+        "WEEK" => Duration::weeks(1),
+        "MONTH" => Duration::days(31),
+        _ => return None,
+    };
+    Some(duration)
+}
+
+/// Splits a date range into sub-ranges holding at most `max_points` candles each
+///
+/// Dates use the `yyyy-MM-ddTHH:mm:ss` format of the v3 prices endpoint (a space
+/// instead of `T` is also accepted). Consecutive sub-ranges share their boundary,
+/// so callers should deduplicate candles.
+///
+/// # Arguments
+/// * `resolution` - Price resolution (e.g. `MINUTE`, `HOUR`, `DAY`)
+/// * `from` - Start of the range
+/// * `to` - End of the range, later than `from`
+/// * `max_points` - Maximum number of candles per sub-range
+///
+/// # Returns
+/// * `Ok(Vec<(String, String)>)` - Sub-ranges formatted for the prices endpoint
+/// * `Err(AppError::InvalidInput)` - If a date or the resolution cannot be parsed,
+///   or the range is empty
+pub fn split_historical_range(
+    resolution: &str,
+    from: &str,
+    to: &str,
+    max_points: i64,
+) -> IgResult<Vec<(String, String)>> {
+    let parse = |value: &str| {
+        NaiveDateTime::parse_from_str(&value.replacen(' ', "T", 1), HISTORICAL_PRICES_DATE_FORMAT)
+            .map_err(|e| AppError::InvalidInput(format!("invalid date '{value}': {e}")))
+    };
+    let (start, end) = (parse(from)?, parse(to)?);
+    if end <= start {
+        return Err(AppError::InvalidInput(format!(
+            "end date {to} must be later than start date {from}"
+        )));
+    }
+    let step = resolution_duration(resolution)
+        .ok_or_else(|| AppError::InvalidInput(format!("unknown resolution {resolution}")))?;
+    let span = step * max_points.max(1) as i32;
+
+    let mut ranges = Vec::new();
+    let mut current = start;
+    while current < end {
+        let next = (current + span).min(end);
+        ranges.push((
+            current.format(HISTORICAL_PRICES_DATE_FORMAT).to_string(),
+            next.format(HISTORICAL_PRICES_DATE_FORMAT).to_string(),
+        ));
+        current = next;
+    }
+    Ok(ranges)
+}
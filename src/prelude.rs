@@ -28,11 +28,11 @@ pub use crate::application::auth::{Auth, Session};
 
 // Configuration
 pub use crate::application::config::{
-    Config, Credentials, RateLimiterConfig, RestApiConfig, WebSocketConfig,
+    ApiVersions, Config, Credentials, RateLimiterConfig, RestApiConfig, WebSocketConfig,
 };
 
 // Rate limiter
-pub use crate::application::rate_limiter::RateLimiter;
+pub use crate::application::rate_limiter::{RateLimiter, RateLimiterStats};
 
 // Service interfaces
 pub use crate::application::interfaces::account::AccountService;
@@ -40,12 +40,16 @@ pub use crate::application::interfaces::listener::ListenerResult;
 pub use crate::application::interfaces::market::MarketService;
 pub use crate::application::interfaces::order::OrderService;
 
+// Cancellation of long-running requests
+pub use tokio_util::sync::CancellationToken;
+
 // Error handling
 pub use crate::error::AppError;
 
 // Common presentation models
 pub use crate::presentation::account::*;
 pub use crate::presentation::chart::*;
+pub use crate::presentation::currency::*;
 pub use crate::presentation::instrument::*;
 pub use crate::presentation::market::*;
 pub use crate::presentation::order::*;
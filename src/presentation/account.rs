@@ -1,13 +1,19 @@
-use crate::presentation::instrument::InstrumentType;
-use crate::presentation::market::MarketState;
+use crate::error::{AppError, StreamError};
+use crate::model::requests::ClosePositionRequest;
+use crate::presentation::currency::CurrencyCode;
+use crate::presentation::instrument::{Expiry, InstrumentType};
+use crate::presentation::market::{MarketDetails, MarketField, MarketFields, MarketState};
 use crate::presentation::order::{Direction, OrderType, Status, TimeInForce};
-use crate::presentation::serialization::string_as_float_opt;
+use crate::presentation::serialization::{null_as_zero, string_as_float_opt};
+use crate::utils::parsing::parse_ig_datetime;
+use chrono::{DateTime, Duration, Utc};
 use lightstreamer_rs::subscription::ItemUpdate;
 use pretty_simple_display::DisplaySimple;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::ops::Add;
+use std::str::FromStr;
 
 /// Account information
 #[derive(Debug, Clone, Deserialize)]
@@ -38,6 +44,18 @@ pub struct Account {
     pub preferred: bool,
 }
 
+impl Account {
+    /// Returns true if orders can be placed on this account through the API
+    ///
+    /// IG reports `status` as `ENABLED`, `DISABLED` or `SUSPENDED_FROM_DEALING`;
+    /// only enabled accounts can deal. `PHYSICAL` share dealing accounts are
+    /// not dealt through the OTC endpoints and are also excluded.
+    pub fn can_trade(&self) -> bool {
+        self.status.eq_ignore_ascii_case("ENABLED")
+            && !self.account_type.eq_ignore_ascii_case("PHYSICAL")
+    }
+}
+
 /// Account balance information
 #[derive(Debug, Clone, Deserialize)]
 pub struct AccountBalance {
@@ -52,6 +70,57 @@ pub struct AccountBalance {
     pub available: f64,
 }
 
+/// Balances of one currency summed over every account held in it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CurrencyTotals {
+    /// Number of accounts in this currency
+    pub accounts: usize,
+    /// Sum of account balances
+    pub balance: f64,
+    /// Sum of deposits (margin in use)
+    pub deposit: f64,
+    /// Sum of running profit and loss on open positions
+    pub profit_loss: f64,
+    /// Sum of funds available for trading
+    pub available: f64,
+}
+
+/// Aggregate view of every account the user holds
+///
+/// Totals are grouped by account currency; amounts in different currencies
+/// are never added together.
+#[derive(Debug, Clone, Default)]
+pub struct PortfolioSummary {
+    /// Accounts the summary was built from
+    pub accounts: Vec<Account>,
+    /// Totals keyed by account currency, in code order
+    pub by_currency: BTreeMap<String, CurrencyTotals>,
+}
+
+impl PortfolioSummary {
+    /// Builds the summary from the accounts returned by `GET /accounts`
+    pub fn from_accounts(accounts: Vec<Account>) -> Self {
+        let mut by_currency: BTreeMap<String, CurrencyTotals> = BTreeMap::new();
+        for account in &accounts {
+            let totals = by_currency.entry(account.currency.clone()).or_default();
+            totals.accounts += 1;
+            totals.balance += account.balance.balance;
+            totals.deposit += account.balance.deposit;
+            totals.profit_loss += account.balance.profit_loss;
+            totals.available += account.balance.available;
+        }
+        Self {
+            accounts,
+            by_currency,
+        }
+    }
+
+    /// Totals for one currency, if any account is held in it
+    pub fn totals_for(&self, currency: &str) -> Option<&CurrencyTotals> {
+        self.by_currency.get(currency)
+    }
+}
+
 /// Metadata for activity pagination
 #[derive(Debug, Clone, Deserialize)]
 pub struct ActivityMetadata {
@@ -85,6 +154,90 @@ pub enum ActivityType {
     WorkingOrder,
 }
 
+/// Status of an API key as reported by `operations/application`
+#[derive(Debug, Copy, Clone, DisplaySimple, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ApplicationStatus {
+    /// The key can be used to log in and trade
+    Enabled,
+    /// The key is disabled and every request made with it is rejected
+    Disabled,
+    /// The key has been revoked by IG
+    Revoked,
+}
+
+/// Channel a deal or order was placed through, as reported by IG
+///
+/// IG sets the channel server-side: everything sent through the REST API,
+/// including orders placed with this crate, shows up as `PublicWebApi`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// IG web platform
+    Web,
+    /// IG mobile apps
+    Mobile,
+    /// REST API (`PUBLIC_WEB_API`)
+    PublicWebApi,
+    /// FIX API (`PUBLIC_FIX_API`, also reported as `PublicFIXAPI`)
+    PublicFixApi,
+    /// Placed by an IG dealer, e.g. over the phone
+    Dealer,
+    /// Generated by IG itself (rollovers, expiries, margin closeouts)
+    System,
+    /// Any channel not recognised by the parser, kept verbatim
+    Unknown(String),
+}
+
+impl Channel {
+    /// Parses a raw channel string
+    ///
+    /// Matching ignores case and underscores, since IG spells the same channel
+    /// differently across endpoints (`PUBLIC_FIX_API` vs `PublicFIXAPI`).
+    pub fn parse(raw: &str) -> Self {
+        let normalised: String = raw
+            .chars()
+            .filter(|c| *c != '_' && !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_uppercase();
+        match normalised.as_str() {
+            "WEB" => Channel::Web,
+            "MOBILE" => Channel::Mobile,
+            "PUBLICWEBAPI" => Channel::PublicWebApi,
+            "PUBLICFIXAPI" => Channel::PublicFixApi,
+            "DEALER" => Channel::Dealer,
+            "SYSTEM" => Channel::System,
+            _ => Channel::Unknown(raw.to_string()),
+        }
+    }
+
+    /// Returns true for the REST and FIX APIs, i.e. activity placed programmatically
+    pub fn is_api(&self) -> bool {
+        matches!(self, Channel::PublicWebApi | Channel::PublicFixApi)
+    }
+}
+
+impl FromStr for Channel {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Channel::parse(s))
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Channel::Web => write!(f, "WEB"),
+            Channel::Mobile => write!(f, "MOBILE"),
+            Channel::PublicWebApi => write!(f, "PUBLIC_WEB_API"),
+            Channel::PublicFixApi => write!(f, "PUBLIC_FIX_API"),
+            Channel::Dealer => write!(f, "DEALER"),
+            Channel::System => write!(f, "SYSTEM"),
+            Channel::Unknown(raw) => write!(f, "{raw}"),
+        }
+    }
+}
+
 /// Individual activity record
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
 pub struct Activity {
@@ -126,6 +279,22 @@ pub struct Activity {
     pub level: Option<String>,
 }
 
+impl Activity {
+    /// Contract period parsed from the raw `period` field
+    ///
+    /// The raw string is kept in `period` for display.
+    pub fn expiry(&self) -> Expiry {
+        self.period
+            .as_deref()
+            .map_or(Expiry::NotApplicable, Expiry::parse)
+    }
+
+    /// Channel parsed from the raw `channel` field, if IG reported one
+    pub fn channel_typed(&self) -> Option<Channel> {
+        self.channel.as_deref().map(Channel::parse)
+    }
+}
+
 /// Detailed information about an activity
 /// Only available when using the detailed=true parameter
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
@@ -238,15 +407,206 @@ pub struct Position {
     pub pnl: Option<f64>,
 }
 
-impl Add for Position {
-    type Output = Position;
+impl Position {
+    /// Closing price at which the position breaks even after costs
+    ///
+    /// The result is expressed on the closing side of the market (bid for a buy,
+    /// offer for a sell), so the spread paid on entry is already accounted for
+    /// by comparing it against the opening `level`. For guaranteed-stop
+    /// (controlled risk) positions the `limited_risk_premium`, in points, is
+    /// added to the distance the market must travel.
+    ///
+    /// # Returns
+    /// * `Some(f64)` - Break-even closing price
+    /// * `None` - If size or contract size are not positive, or a controlled-risk
+    ///   position has no premium reported
+    pub fn break_even(&self) -> Option<f64> {
+        let details = &self.position;
+        if details.size <= 0.0 || details.contract_size <= 0.0 {
+            return None;
+        }
 
-    fn add(self, other: Position) -> Position {
+        // The premium is charged per point of exposure, so it translates directly
+        // into the extra points the market must move regardless of size.
+        let premium_points = if details.controlled_risk {
+            details.limited_risk_premium?
+        } else {
+            details.limited_risk_premium.unwrap_or(0.0)
+        };
+
+        Some(match details.direction {
+            Direction::Buy => details.level + premium_points,
+            Direction::Sell => details.level - premium_points,
+        })
+    }
+
+    /// Epic and market fields to subscribe to in order to stream this position's P&L
+    ///
+    /// Subscribe to the `MARKET:{epic}` item with the returned fields and feed
+    /// every merged update to `live_pnl`.
+    pub fn pnl_stream_spec(&self) -> (String, Vec<MarketField>) {
+        (
+            self.market.epic.clone(),
+            vec![
+                MarketField::Bid,
+                MarketField::Offer,
+                MarketField::MarketState,
+            ],
+        )
+    }
+
+    /// Unrealised P&L of the position from a streaming market update
+    ///
+    /// Valued on the closing side of the market (bid for a buy, offer for a
+    /// sell), as in `utils::finance::calculate_pnl`.
+    ///
+    /// # Arguments
+    /// * `market_fields` - Latest merged fields of the position's market
+    ///
+    /// # Returns
+    /// * `Some(f64)` - Unrealised P&L
+    /// * `None` - If the closing price is not present in the update
+    pub fn live_pnl(&self, market_fields: &MarketFields) -> Option<f64> {
+        let details = &self.position;
+        let price_diff = match details.direction {
+            Direction::Buy => market_fields.bid? - details.level,
+            Direction::Sell => details.level - market_fields.offer?,
+        };
+        Some(price_diff * details.size)
+    }
+
+    /// Price the position would currently close at
+    ///
+    /// The bid for a buy and the offer for a sell.
+    ///
+    /// # Returns
+    /// * `Ok(f64)` - Closing price
+    /// * `Err(AppError::PriceUnavailable)` - If the market has no price on
+    ///   that side, e.g. while it is suspended
+    pub fn closing_price(&self) -> Result<f64, AppError> {
+        self.market
+            .current_price(&self.position.direction.opposite())
+            .ok_or_else(|| self.market.price_unavailable())
+    }
+
+    /// Unrealised P&L of the position at the current closing price
+    ///
+    /// Same value as `utils::finance::calculate_pnl`, with a missing price
+    /// reported as an error instead of `None`.
+    ///
+    /// # Returns
+    /// * `Ok(f64)` - Unrealised P&L
+    /// * `Err(AppError::PriceUnavailable)` - If the market has no closing price
+    pub fn unrealised_pnl(&self) -> Result<f64, AppError> {
+        let closing_price = self.closing_price()?;
+        let details = &self.position;
+        let price_diff = match details.direction {
+            Direction::Buy => closing_price - details.level,
+            Direction::Sell => details.level - closing_price,
+        };
+        Ok(price_diff * details.size)
+    }
+
+    /// Request closing the whole position with a limit at the current closing price
+    ///
+    /// For instruments that do not take market orders. No request is built
+    /// while the market has no price, rather than guessing a level.
+    ///
+    /// # Returns
+    /// * `Ok(ClosePositionRequest)` - Fill-or-kill limit close
+    /// * `Err(AppError::PriceUnavailable)` - If the market has no closing price
+    pub fn to_limit_close_request(&self) -> Result<ClosePositionRequest, AppError> {
+        Ok(ClosePositionRequest::limit(
+            self.position.deal_id.clone(),
+            self.position.direction.opposite(),
+            self.position.size,
+            self.closing_price()?,
+        ))
+    }
+
+    /// Request closing the whole position with an order its market accepts
+    ///
+    /// IG does not take market orders on options, so they are closed with
+    /// `ClosePositionRequest::close_option_to_market_by_id`; everything else
+    /// gets a market close. The instrument type is read from `market_details`
+    /// when given, otherwise from the position's own market data.
+    ///
+    /// # Arguments
+    /// * `market_details` - Details of the position's market, if already fetched
+    pub fn to_close_request(&self, market_details: Option<&MarketDetails>) -> ClosePositionRequest {
+        let instrument_type = market_details
+            .and_then(|details| details.instrument.instrument_type)
+            .or_else(|| parse_instrument_type(&self.market.instrument_type));
+        let deal_id = self.position.deal_id.clone();
+        let direction = self.position.direction.opposite();
+        let size = self.position.size;
+        if instrument_type.is_some_and(|kind| kind.is_option()) {
+            ClosePositionRequest::close_option_to_market_by_id(deal_id, direction, size)
+        } else {
+            ClosePositionRequest::market(deal_id, direction, size)
+        }
+    }
+}
+
+/// Parses an IG instrument type name such as `OPT_INDICES`
+fn parse_instrument_type(name: &str) -> Option<InstrumentType> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+/// Reason two positions cannot be netted into one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// The positions are on different markets
+    EpicMismatch {
+        /// Epic of the left-hand position
+        left: String,
+        /// Epic of the right-hand position
+        right: String,
+    },
+    /// The positions are denominated in different currencies, so their sizes
+    /// and P&L cannot be added
+    CurrencyMismatch {
+        /// Currency of the left-hand position
+        left: String,
+        /// Currency of the right-hand position
+        right: String,
+    },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::EpicMismatch { left, right } => {
+                write!(
+                    f,
+                    "cannot add positions from different markets ({left} and {right})"
+                )
+            }
+            MergeError::CurrencyMismatch { left, right } => write!(
+                f,
+                "cannot add positions in different currencies ({left} and {right})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+impl Position {
+    /// Nets two positions on the same market and in the same currency
+    ///
+    /// # Returns
+    /// * `Ok(Position)` - The combined position
+    /// * `Err(MergeError)` - If the epics or currencies differ
+    pub fn try_add(self, other: Position) -> Result<Position, MergeError> {
         if self.market.epic != other.market.epic {
-            panic!("Cannot add positions from different markets");
+            return Err(MergeError::EpicMismatch {
+                left: self.market.epic,
+                right: other.market.epic,
+            });
         }
-        Position {
-            position: self.position + other.position,
+        Ok(Position {
+            position: self.position.try_add(other.position)?,
             market: self.market,
             pnl: match (self.pnl, other.pnl) {
                 (Some(a), Some(b)) => Some(a + b),
@@ -254,7 +614,19 @@ impl Add for Position {
                 (None, Some(b)) => Some(b),
                 (None, None) => None,
             },
-        }
+        })
+    }
+}
+
+impl Add for Position {
+    type Output = Position;
+
+    /// Nets two positions
+    ///
+    /// # Panics
+    /// If the epics or currencies differ; use `try_add` to handle that case.
+    fn add(self, other: Position) -> Position {
+        self.try_add(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -304,10 +676,39 @@ pub struct PositionDetails {
     pub limited_risk_premium: Option<f64>,
 }
 
-impl Add for PositionDetails {
-    type Output = PositionDetails;
+impl PositionDetails {
+    /// Time the position has been open, relative to `now`
+    ///
+    /// # Arguments
+    /// * `now` - Reference time, usually `Utc::now()`
+    ///
+    /// # Returns
+    /// * `Some(Duration)` clamped at zero, or `None` if `created_date_utc`
+    ///   cannot be parsed
+    pub fn holding_duration(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let held = now - parse_ig_datetime(&self.created_date_utc)?;
+        Some(held.max(Duration::zero()))
+    }
 
-    fn add(self, other: PositionDetails) -> PositionDetails {
+    /// Nets two positions denominated in the same currency
+    ///
+    /// Contract sizes are combined like sizes, as exposure, so positions with
+    /// different contract sizes can be netted.
+    ///
+    /// # Returns
+    /// * `Ok(PositionDetails)` - The combined details
+    /// * `Err(MergeError::CurrencyMismatch)` - If the currencies differ
+    pub fn try_add(self, other: PositionDetails) -> Result<PositionDetails, MergeError> {
+        if self.currency != other.currency {
+            return Err(MergeError::CurrencyMismatch {
+                left: self.currency,
+                right: other.currency,
+            });
+        }
+        Ok(self.add_unchecked(other))
+    }
+
+    fn add_unchecked(self, other: PositionDetails) -> PositionDetails {
         let (contract_size, size) = if self.direction != other.direction {
             (
                 (self.contract_size - other.contract_size).abs(),
@@ -340,6 +741,18 @@ impl Add for PositionDetails {
     }
 }
 
+impl Add for PositionDetails {
+    type Output = PositionDetails;
+
+    /// Nets two positions
+    ///
+    /// # Panics
+    /// If the currencies differ; use `try_add` to handle that case.
+    fn add(self, other: PositionDetails) -> PositionDetails {
+        self.try_add(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
 /// Market information for a position
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
 pub struct PositionMarket {
@@ -357,18 +770,26 @@ pub struct PositionMarket {
     #[serde(rename = "lotSize")]
     pub lot_size: f64,
     /// Highest price of the current trading session
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub high: Option<f64>,
     /// Lowest price of the current trading session
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub low: Option<f64>,
-    /// Percentage change in price since previous close
-    #[serde(rename = "percentageChange")]
+    /// Percentage change in price since previous close, 0 when IG sends null
+    #[serde(rename = "percentageChange", with = "null_as_zero", default)]
     pub percentage_change: f64,
-    /// Net change in price since previous close
-    #[serde(rename = "netChange")]
+    /// Net change in price since previous close, 0 when IG sends null
+    #[serde(rename = "netChange", with = "null_as_zero", default)]
     pub net_change: f64,
     /// Current bid price
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub bid: Option<f64>,
     /// Current offer/ask price
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub offer: Option<f64>,
     /// Time of the last price update
     #[serde(rename = "updateTime")]
@@ -390,6 +811,89 @@ pub struct PositionMarket {
     pub scaling_factor: i64,
 }
 
+impl PositionMarket {
+    /// Price a deal in `direction` would currently get on this market
+    ///
+    /// The offer to buy and the bid to sell. IG reports no bid or offer while
+    /// a market is suspended or closed.
+    ///
+    /// # Returns
+    /// * `None` - If the market has no price on that side
+    pub fn current_price(&self, direction: &Direction) -> Option<f64> {
+        match direction {
+            Direction::Buy => self.offer,
+            Direction::Sell => self.bid,
+        }
+    }
+
+    /// Error reporting that this market cannot be priced
+    pub fn price_unavailable(&self) -> AppError {
+        AppError::PriceUnavailable {
+            epic: self.epic.clone(),
+            market_status: self.market_status.clone(),
+        }
+    }
+}
+
+/// Open position on a sprint market
+#[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
+pub struct SprintMarketPosition {
+    /// Unique identifier for the deal
+    #[serde(rename = "dealId")]
+    pub deal_id: String,
+    /// Instrument EPIC identifier
+    pub epic: String,
+    /// Human-readable name of the instrument
+    #[serde(rename = "instrumentName")]
+    pub instrument_name: Option<String>,
+    /// Description of the sprint market
+    pub description: Option<String>,
+    /// Direction of the position (buy or sell)
+    pub direction: Direction,
+    /// Stake of the position
+    pub size: f64,
+    /// Currency of the stake and payout
+    pub currency: Option<String>,
+    /// Amount paid out if the position expires in the money
+    #[serde(rename = "payoutAmount")]
+    pub payout_amount: f64,
+    /// Market level at which the position was opened
+    #[serde(rename = "strikeLevel")]
+    pub strike_level: f64,
+    /// Date and time the position was created
+    #[serde(rename = "createdDate")]
+    pub created_date: Option<String>,
+    /// Date and time at which the position expires
+    #[serde(rename = "expiryTime")]
+    pub expiry_time: String,
+    /// Current status of the market
+    #[serde(rename = "marketStatus")]
+    pub market_status: Option<String>,
+}
+
+impl SprintMarketPosition {
+    /// Parses `expiry_time` as a UTC timestamp
+    ///
+    /// # Returns
+    /// * `Some(DateTime<Utc>)` if the expiry is in one of IG's date formats
+    pub fn expiry(&self) -> Option<DateTime<Utc>> {
+        parse_ig_datetime(&self.expiry_time)
+    }
+
+    /// Time left until the position expires, relative to `now`
+    ///
+    /// # Arguments
+    /// * `now` - Reference time, usually `Utc::now()`
+    ///
+    /// # Returns
+    /// * `Some(Duration)` clamped at zero once the position has expired,
+    ///   or `None` if the expiry time cannot be parsed
+    pub fn time_remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let remaining = self.expiry()? - now;
+        Some(remaining.max(Duration::zero()))
+    }
+}
+
 /// Working order
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
 pub struct WorkingOrder {
@@ -489,11 +993,11 @@ pub struct AccountMarketData {
     pub high: Option<f64>,
     /// Lowest price of the current trading session
     pub low: Option<f64>,
-    /// Percentage change in price since previous close
-    #[serde(rename = "percentageChange")]
+    /// Percentage change in price since previous close, 0 when IG sends null
+    #[serde(rename = "percentageChange", with = "null_as_zero", default)]
     pub percentage_change: f64,
-    /// Net change in price since previous close
-    #[serde(rename = "netChange")]
+    /// Net change in price since previous close, 0 when IG sends null
+    #[serde(rename = "netChange", with = "null_as_zero", default)]
     pub net_change: f64,
     /// Current bid price
     pub bid: Option<f64>,
@@ -540,6 +1044,39 @@ pub struct PageData {
     pub total_pages: i32,
 }
 
+/// Kind of entry in the transaction history, parsed from `transactionType`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TransactionType {
+    /// Profit or loss realised by closing a deal (`TRADE`, also `DEAL`)
+    Trade,
+    /// Funds paid into the account (`DEPO`)
+    Deposit,
+    /// Funds taken out of the account, including charges and fees (`WITH`)
+    Withdrawal,
+    /// Any type not recognised by the parser, kept verbatim
+    Unknown(String),
+}
+
+impl TransactionType {
+    /// Parses a raw transaction type
+    ///
+    /// Matching ignores case and accepts both IG's short codes and the full
+    /// names used by the history filter (`DEPOSIT`, `WITHDRAWAL`).
+    pub fn parse(raw: &str) -> Self {
+        match raw.trim().to_ascii_uppercase().as_str() {
+            "TRADE" | "DEAL" => TransactionType::Trade,
+            "DEPO" | "DEPOSIT" => TransactionType::Deposit,
+            "WITH" | "WITHDRAWAL" => TransactionType::Withdrawal,
+            _ => TransactionType::Unknown(raw.to_string()),
+        }
+    }
+
+    /// Returns true for deposits and withdrawals, which move cash without a deal
+    pub fn is_cash_movement(&self) -> bool {
+        matches!(self, TransactionType::Deposit | TransactionType::Withdrawal)
+    }
+}
+
 /// Individual transaction
 #[derive(Debug, Clone, DisplaySimple, Deserialize, Serialize)]
 pub struct AccountTransaction {
@@ -579,6 +1116,98 @@ pub struct AccountTransaction {
     pub cash_transaction: bool,
 }
 
+impl AccountTransaction {
+    /// Time between opening and closing the position this transaction settles
+    ///
+    /// # Returns
+    /// * `Some(Duration)` from `open_date_utc` to `date_utc`, or `None` if
+    ///   either cannot be parsed, as for cash transactions
+    pub fn holding_duration(&self) -> Option<Duration> {
+        let opened = parse_ig_datetime(&self.open_date_utc)?;
+        let closed = parse_ig_datetime(&self.date_utc)?;
+        Some(closed - opened)
+    }
+
+    /// Contract period parsed from the raw `period` field
+    ///
+    /// The raw string is kept in `period` for display.
+    pub fn expiry(&self) -> Expiry {
+        Expiry::parse(&self.period)
+    }
+
+    /// Transaction type parsed from the raw `transactionType` field
+    pub fn transaction_type_typed(&self) -> TransactionType {
+        TransactionType::parse(&self.transaction_type)
+    }
+
+    /// Profit or loss parsed from the raw `profitAndLoss` field
+    ///
+    /// IG prefixes the amount with a currency marker (`E`, `$`, `£`, `A$`...)
+    /// and uses thousands separators. The sign may come after the marker
+    /// (`E-1,234.50`) or before it (`-£1.50`); both are read as negative.
+    ///
+    /// # Returns
+    /// * `Some(f64)` - The amount with the sign IG reported
+    /// * `None` - If no number could be read
+    pub fn profit_and_loss_value(&self) -> Option<f64> {
+        let raw = self.profit_and_loss.trim();
+        let (marker, amount) = raw.split_at(raw.find(|c: char| c.is_ascii_digit())?);
+        let amount: String = amount.chars().filter(|c| *c != ',').collect();
+        let value = amount
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())?;
+        Some(if marker.contains('-') { -value } else { value })
+    }
+
+    /// Profit or loss with the sign of its effect on the account balance
+    ///
+    /// IG is not consistent about signs in the history: trades carry the sign
+    /// of the realised result, but withdrawals may be reported either as a
+    /// positive amount or with a minus sign. This normalises them so that
+    /// gains and deposits are positive and losses, withdrawals and charges are
+    /// negative, and summing `signed_pnl` over a history gives the net change
+    /// in cash:
+    ///
+    /// * `Deposit` - always positive
+    /// * `Withdrawal` - always negative
+    /// * `Trade` and unknown types - the sign IG reported
+    ///
+    /// # Returns
+    /// * `Some(f64)` - The normalised amount
+    /// * `None` - If `profitAndLoss` holds no number
+    pub fn signed_pnl(&self) -> Option<f64> {
+        let value = self.profit_and_loss_value()?;
+        Some(match self.transaction_type_typed() {
+            TransactionType::Deposit => value.abs(),
+            TransactionType::Withdrawal => -value.abs(),
+            TransactionType::Trade | TransactionType::Unknown(_) => value,
+        })
+    }
+
+    /// Currency of the profit or loss, read from the marker in `profitAndLoss`
+    ///
+    /// Falls back to the `currency` field, which IG also fills with a symbol.
+    pub fn profit_and_loss_currency(&self) -> Option<CurrencyCode> {
+        let marker: String = self
+            .profit_and_loss
+            .chars()
+            .take_while(|c| !c.is_ascii_digit())
+            .filter(|c| *c != '-' && *c != '+')
+            .collect();
+        CurrencyCode::from_symbol_or_code(&marker)
+            .or_else(|| CurrencyCode::from_symbol_or_code(&self.currency))
+    }
+
+    /// Returns true if this transaction was booked for the given deal
+    ///
+    /// Transactions carry the deal ID without IG's `DIAAAA`-style prefix in
+    /// `reference`, so the deal ID is matched on its suffix.
+    pub fn is_for_deal(&self, deal_id: &str) -> bool {
+        !self.reference.is_empty() && deal_id.ends_with(&self.reference)
+    }
+}
+
 /// Representation of account data received from the IG Markets streaming API
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AccountData {
@@ -665,8 +1294,8 @@ impl AccountData {
     /// * `item_update` - The ItemUpdate received from the Lightstreamer API
     ///
     /// # Returns
-    /// * `Result<Self, String>` - The converted AccountData or an error message
-    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, String> {
+    /// * `Result<Self, StreamError>` - The converted AccountData or an error message
+    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, StreamError> {
         // Extract the item_name, defaulting to an empty string if None
         let item_name = item_update.item_name.clone().unwrap_or_default();
 
@@ -677,14 +1306,16 @@ impl AccountData {
         let is_snapshot = item_update.is_snapshot;
 
         // Convert fields
-        let fields = Self::create_account_fields(&item_update.fields)?;
+        let fields =
+            Self::create_account_fields(&item_update.fields).map_err(|e| e.for_item(&item_name))?;
 
         // Convert changed_fields by first creating a HashMap<String, Option<String>>
         let mut changed_fields_map: HashMap<String, Option<String>> = HashMap::new();
         for (key, value) in &item_update.changed_fields {
             changed_fields_map.insert(key.clone(), Some(value.clone()));
         }
-        let changed_fields = Self::create_account_fields(&changed_fields_map)?;
+        let changed_fields =
+            Self::create_account_fields(&changed_fields_map).map_err(|e| e.for_item(&item_name))?;
 
         Ok(AccountData {
             item_name,
@@ -701,20 +1332,20 @@ impl AccountData {
     /// * `fields_map` - HashMap containing field names and their string values
     ///
     /// # Returns
-    /// * `Result<AccountFields, String>` - The parsed AccountFields or an error message
+    /// * `Result<AccountFields, StreamError>` - The parsed AccountFields or an error message
     fn create_account_fields(
         fields_map: &HashMap<String, Option<String>>,
-    ) -> Result<AccountFields, String> {
+    ) -> Result<AccountFields, StreamError> {
         // Helper function to safely get a field value
         let get_field = |key: &str| -> Option<String> { fields_map.get(key).cloned().flatten() };
 
         // Helper function to parse float values
-        let parse_float = |key: &str| -> Result<Option<f64>, String> {
+        let parse_float = |key: &str| -> Result<Option<f64>, StreamError> {
             match get_field(key) {
                 Some(val) if !val.is_empty() => val
                     .parse::<f64>()
                     .map(Some)
-                    .map_err(|_| format!("Failed to parse {key} as float: {val}")),
+                    .map_err(|_| StreamError::parse(key, &val)),
                 _ => Ok(None),
             }
         };
@@ -1,3 +1,4 @@
+use crate::error::StreamError;
 use crate::presentation::serialization::string_as_float_opt;
 use lightstreamer_rs::subscription::ItemUpdate;
 use serde::{Deserialize, Serialize};
@@ -5,7 +6,7 @@ use std::collections::HashMap;
 use std::fmt;
 
 /// Time scale for chart data aggregation
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 pub enum ChartScale {
     /// Second-level aggregation
     #[serde(rename = "SECOND")]
@@ -25,6 +26,31 @@ pub enum ChartScale {
     Tick,
 }
 
+impl ChartScale {
+    /// Scale as written in a `CHART:{epic}:{scale}` item name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChartScale::Second => "SECOND",
+            ChartScale::OneMinute => "1MINUTE",
+            ChartScale::FiveMinute => "5MINUTE",
+            ChartScale::Hour => "HOUR",
+            ChartScale::Tick => "TICK",
+        }
+    }
+
+    /// Parses the scale of a chart item name, ignoring case
+    pub fn parse(scale: &str) -> Option<ChartScale> {
+        match scale.to_ascii_uppercase().as_str() {
+            "SECOND" => Some(ChartScale::Second),
+            "1MINUTE" => Some(ChartScale::OneMinute),
+            "5MINUTE" => Some(ChartScale::FiveMinute),
+            "HOUR" => Some(ChartScale::Hour),
+            "TICK" => Some(ChartScale::Tick),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 /// Chart data structure that represents price chart information
 /// Contains both tick and candle data depending on the chart scale
@@ -180,7 +206,7 @@ impl ChartData {
     /// # Returns
     ///
     /// A Result containing either the parsed ChartData or an error message
-    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, String> {
+    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, StreamError> {
         // Extract the item_name, defaulting to an empty string if None
         let item_name = item_update.item_name.clone().unwrap_or_default();
 
@@ -217,14 +243,16 @@ impl ChartData {
         let is_snapshot = item_update.is_snapshot;
 
         // Convert fields
-        let fields = Self::create_chart_fields(&item_update.fields)?;
+        let fields =
+            Self::create_chart_fields(&item_update.fields).map_err(|e| e.for_item(&item_name))?;
 
         // Convert changed_fields by first creating a HashMap<String, Option<String>>
         let mut changed_fields_map: HashMap<String, Option<String>> = HashMap::new();
         for (key, value) in &item_update.changed_fields {
             changed_fields_map.insert(key.clone(), Some(value.clone()));
         }
-        let changed_fields = Self::create_chart_fields(&changed_fields_map)?;
+        let changed_fields =
+            Self::create_chart_fields(&changed_fields_map).map_err(|e| e.for_item(&item_name))?;
 
         Ok(ChartData {
             item_name,
@@ -239,17 +267,17 @@ impl ChartData {
     // Helper method to create ChartFields from a HashMap
     fn create_chart_fields(
         fields_map: &HashMap<String, Option<String>>,
-    ) -> Result<ChartFields, String> {
+    ) -> Result<ChartFields, StreamError> {
         // Helper function to safely get a field value
         let get_field = |key: &str| -> Option<String> { fields_map.get(key).cloned().flatten() };
 
         // Helper function to parse float values
-        let parse_float = |key: &str| -> Result<Option<f64>, String> {
+        let parse_float = |key: &str| -> Result<Option<f64>, StreamError> {
             match get_field(key) {
                 Some(val) if !val.is_empty() => val
                     .parse::<f64>()
                     .map(Some)
-                    .map_err(|_| format!("Failed to parse {key} as float: {val}")),
+                    .map_err(|_| StreamError::parse(key, &val)),
                 _ => Ok(None),
             }
         };
@@ -0,0 +1,165 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Typed ISO 4217 currency codes
+//!
+//! IG reports currencies as codes in market and account data (`"GBP"`) but as
+//! symbols in the transaction history (`"£"`, `"E"`, `"A$"`). `CurrencyCode`
+//! parses both so callers can compare currencies across models.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// ISO 4217 currency code
+///
+/// The currencies IG commonly deals in have their own variant; any other valid
+/// three-letter code is kept in `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CurrencyCode {
+    /// US dollar
+    Usd,
+    /// Euro
+    Eur,
+    /// Pound sterling
+    Gbp,
+    /// Japanese yen
+    Jpy,
+    /// Swiss franc
+    Chf,
+    /// Australian dollar
+    Aud,
+    /// Canadian dollar
+    Cad,
+    /// New Zealand dollar
+    Nzd,
+    /// Hong Kong dollar
+    Hkd,
+    /// Singapore dollar
+    Sgd,
+    /// Swedish krona
+    Sek,
+    /// Norwegian krone
+    Nok,
+    /// Danish krone
+    Dkk,
+    /// South African rand
+    Zar,
+    /// Any other valid ISO 4217 code, upper-cased
+    Other(String),
+}
+
+impl CurrencyCode {
+    /// Parses an ISO 4217 code
+    ///
+    /// Matching ignores case and surrounding whitespace.
+    ///
+    /// # Returns
+    /// * `Some(CurrencyCode)` - If `raw` is three ASCII letters
+    /// * `None` - Otherwise
+    pub fn parse(raw: &str) -> Option<Self> {
+        let code = raw.trim().to_ascii_uppercase();
+        if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_uppercase()) {
+            return None;
+        }
+        Some(match code.as_str() {
+            "USD" => CurrencyCode::Usd,
+            "EUR" => CurrencyCode::Eur,
+            "GBP" => CurrencyCode::Gbp,
+            "JPY" => CurrencyCode::Jpy,
+            "CHF" => CurrencyCode::Chf,
+            "AUD" => CurrencyCode::Aud,
+            "CAD" => CurrencyCode::Cad,
+            "NZD" => CurrencyCode::Nzd,
+            "HKD" => CurrencyCode::Hkd,
+            "SGD" => CurrencyCode::Sgd,
+            "SEK" => CurrencyCode::Sek,
+            "NOK" => CurrencyCode::Nok,
+            "DKK" => CurrencyCode::Dkk,
+            "ZAR" => CurrencyCode::Zar,
+            _ => CurrencyCode::Other(code),
+        })
+    }
+
+    /// Resolves a currency symbol as used in IG's transaction history
+    ///
+    /// IG writes euros as `E` there; `$` is taken to be the US dollar.
+    /// Ambiguous symbols such as `kr` are not resolved.
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol.trim() {
+            "$" | "US$" => Some(CurrencyCode::Usd),
+            "€" | "E" => Some(CurrencyCode::Eur),
+            "£" => Some(CurrencyCode::Gbp),
+            "¥" => Some(CurrencyCode::Jpy),
+            "SF" | "Fr" => Some(CurrencyCode::Chf),
+            "A$" => Some(CurrencyCode::Aud),
+            "C$" => Some(CurrencyCode::Cad),
+            "NZ$" => Some(CurrencyCode::Nzd),
+            "HK$" => Some(CurrencyCode::Hkd),
+            "S$" => Some(CurrencyCode::Sgd),
+            "R" => Some(CurrencyCode::Zar),
+            _ => None,
+        }
+    }
+
+    /// Resolves either a symbol or an ISO code, trying the symbol first
+    pub fn from_symbol_or_code(raw: &str) -> Option<Self> {
+        CurrencyCode::from_symbol(raw).or_else(|| CurrencyCode::parse(raw))
+    }
+
+    /// Three-letter ISO 4217 code
+    pub fn code(&self) -> &str {
+        match self {
+            CurrencyCode::Usd => "USD",
+            CurrencyCode::Eur => "EUR",
+            CurrencyCode::Gbp => "GBP",
+            CurrencyCode::Jpy => "JPY",
+            CurrencyCode::Chf => "CHF",
+            CurrencyCode::Aud => "AUD",
+            CurrencyCode::Cad => "CAD",
+            CurrencyCode::Nzd => "NZD",
+            CurrencyCode::Hkd => "HKD",
+            CurrencyCode::Sgd => "SGD",
+            CurrencyCode::Sek => "SEK",
+            CurrencyCode::Nok => "NOK",
+            CurrencyCode::Dkk => "DKK",
+            CurrencyCode::Zar => "ZAR",
+            CurrencyCode::Other(code) => code,
+        }
+    }
+
+    /// Usual display symbol, if known
+    pub fn symbol(&self) -> Option<&'static str> {
+        match self {
+            CurrencyCode::Usd => Some("$"),
+            CurrencyCode::Eur => Some("€"),
+            CurrencyCode::Gbp => Some("£"),
+            CurrencyCode::Jpy => Some("¥"),
+            CurrencyCode::Chf => Some("Fr"),
+            CurrencyCode::Aud => Some("A$"),
+            CurrencyCode::Cad => Some("C$"),
+            CurrencyCode::Nzd => Some("NZ$"),
+            CurrencyCode::Hkd => Some("HK$"),
+            CurrencyCode::Sgd => Some("S$"),
+            CurrencyCode::Sek | CurrencyCode::Nok | CurrencyCode::Dkk => Some("kr"),
+            CurrencyCode::Zar => Some("R"),
+            CurrencyCode::Other(_) => None,
+        }
+    }
+}
+
+impl FromStr for CurrencyCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CurrencyCode::parse(s).ok_or_else(|| format!("not an ISO 4217 currency code: {s}"))
+    }
+}
+
+impl fmt::Display for CurrencyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
@@ -1,4 +1,8 @@
+use crate::presentation::market::MarketDetails;
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all = "UPPERCASE")]
@@ -69,3 +73,230 @@ pub enum InstrumentType {
     #[default]
     Options,
 }
+
+impl InstrumentType {
+    /// Returns true for option instruments, which IG only deals with limit orders
+    pub fn is_option(&self) -> bool {
+        matches!(
+            self,
+            InstrumentType::OptCommodities
+                | InstrumentType::OptCurrencies
+                | InstrumentType::OptIndices
+                | InstrumentType::OptRates
+                | InstrumentType::OptShares
+                | InstrumentType::Options
+        )
+    }
+}
+
+/// Contract period of an instrument, as found in the `period` field of
+/// activities and transactions (e.g. `DFB`, `20-AUG-25`, `SEP-25` or `-`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expiry {
+    /// Rolling daily funded bet with no fixed expiry
+    Dfb,
+    /// No period applies (shown as `-`)
+    NotApplicable,
+    /// Monthly or quarterly contract, e.g. `SEP-25`
+    Month {
+        /// Calendar year of the contract
+        year: i32,
+        /// Month of the contract (1-12)
+        month: u32,
+    },
+    /// Contract expiring on a specific day, e.g. `20-AUG-25`
+    Date(NaiveDate),
+    /// Any period not recognised by the parser, kept verbatim
+    Other(String),
+}
+
+impl Expiry {
+    /// Parses a period string
+    ///
+    /// Unrecognised values are returned as `Expiry::Other` so that no
+    /// information is lost.
+    pub fn parse(period: &str) -> Self {
+        let period = period.trim();
+        match period.to_ascii_uppercase().as_str() {
+            "DFB" => return Expiry::Dfb,
+            "" | "-" => return Expiry::NotApplicable,
+            _ => {}
+        }
+
+        let parts: Vec<&str> = period.split('-').collect();
+        let parsed = match parts.as_slice() {
+            [day, month, year] => day.parse::<u32>().ok().and_then(|day| {
+                let (year, month) = (parse_year(year)?, parse_month(month)?);
+                NaiveDate::from_ymd_opt(year, month, day).map(Expiry::Date)
+            }),
+            [month, year] => parse_month(month)
+                .zip(parse_year(year))
+                .map(|(month, year)| Expiry::Month { year, month }),
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| Expiry::Other(period.to_string()))
+    }
+
+    /// Date used to order contract periods chronologically
+    ///
+    /// Monthly contracts sort on the first day of the month. Returns `None`
+    /// for periods without a date (`DFB`, `-` and unrecognised values).
+    pub fn sort_date(&self) -> Option<NaiveDate> {
+        match self {
+            Expiry::Month { year, month } => NaiveDate::from_ymd_opt(*year, *month, 1),
+            Expiry::Date(date) => Some(*date),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the contract has a fixed expiry
+    pub fn is_dated(&self) -> bool {
+        self.sort_date().is_some()
+    }
+}
+
+impl FromStr for Expiry {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Expiry::parse(s))
+    }
+}
+
+impl fmt::Display for Expiry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expiry::Dfb => write!(f, "DFB"),
+            Expiry::NotApplicable => write!(f, "-"),
+            Expiry::Month { year, month } => {
+                write!(f, "{}-{:02}", month_abbreviation(*month), year % 100)
+            }
+            Expiry::Date(date) => write!(
+                f,
+                "{:02}-{}-{:02}",
+                date.day(),
+                month_abbreviation(date.month()),
+                date.year() % 100
+            ),
+            Expiry::Other(raw) => write!(f, "{raw}"),
+        }
+    }
+}
+
+/// Monthly contract of a future or option, formatted by IG as `MON-YY`
+///
+/// Building expiries from a year and month avoids hand-formatted strings
+/// such as `"JUL-25"` that IG rejects when mistyped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContractMonth {
+    /// Calendar year of the contract
+    pub year: i32,
+    /// Month of the contract (1-12)
+    pub month: u32,
+}
+
+impl ContractMonth {
+    /// Creates a contract month
+    ///
+    /// # Returns
+    /// * `None` if `month` is not between 1 and 12
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        (1..=12)
+            .contains(&month)
+            .then_some(ContractMonth { year, month })
+    }
+
+    /// Parses an IG expiry string such as `JUL-25`
+    ///
+    /// Dated expiries (`20-AUG-25`) resolve to their month.
+    ///
+    /// # Returns
+    /// * `None` if the string has no month, e.g. `DFB` or `-`
+    pub fn parse(expiry: &str) -> Option<Self> {
+        ContractMonth::try_from(&Expiry::parse(expiry)).ok()
+    }
+
+    /// Returns true if `expiry` refers to this contract month
+    pub fn matches(&self, expiry: &str) -> bool {
+        ContractMonth::parse(expiry) == Some(*self)
+    }
+
+    /// Finds the market listed for this contract month
+    ///
+    /// # Arguments
+    /// * `markets` - Market details of the available expiries of an instrument
+    pub fn find_in<'a>(&self, markets: &'a [MarketDetails]) -> Option<&'a MarketDetails> {
+        markets
+            .iter()
+            .find(|details| self.matches(&details.instrument.expiry))
+    }
+}
+
+impl fmt::Display for ContractMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{:02}",
+            month_abbreviation(self.month),
+            self.year % 100
+        )
+    }
+}
+
+impl FromStr for ContractMonth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ContractMonth::parse(s).ok_or_else(|| format!("not a contract month: {s}"))
+    }
+}
+
+impl TryFrom<&Expiry> for ContractMonth {
+    type Error = ();
+
+    fn try_from(expiry: &Expiry) -> Result<Self, Self::Error> {
+        match expiry {
+            Expiry::Month { year, month } => Ok(ContractMonth {
+                year: *year,
+                month: *month,
+            }),
+            Expiry::Date(date) => Ok(ContractMonth {
+                year: date.year(),
+                month: date.month(),
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<ContractMonth> for Expiry {
+    fn from(contract: ContractMonth) -> Self {
+        Expiry::Month {
+            year: contract.year,
+            month: contract.month,
+        }
+    }
+}
+
+fn parse_month(month: &str) -> Option<u32> {
+    chrono::Month::from_str(month)
+        .ok()
+        .map(|month| month.number_from_month())
+}
+
+fn parse_year(year: &str) -> Option<i32> {
+    if !year.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    match year.len() {
+        2 => year.parse::<i32>().ok().map(|year| 2000 + year),
+        4 => year.parse().ok(),
+        _ => None,
+    }
+}
+
+fn month_abbreviation(month: u32) -> String {
+    chrono::Month::try_from(month as u8)
+        .map(|month| month.name()[..3].to_ascii_uppercase())
+        .unwrap_or_default()
+}
@@ -1,8 +1,13 @@
+use crate::error::StreamError;
+use crate::presentation::currency::CurrencyCode;
 use crate::presentation::instrument::InstrumentType;
+use crate::presentation::order::Direction;
 use crate::presentation::serialization::{string_as_bool_opt, string_as_float_opt};
+use crate::utils::parsing::parse_ig_datetime;
+use chrono::{DateTime, Utc};
 use lightstreamer_rs::subscription::ItemUpdate;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fmt::Display;
 
@@ -58,6 +63,39 @@ pub struct Instrument {
     pub chart_code: Option<String>,
 }
 
+impl Instrument {
+    /// Parses `contract_size` into a number
+    ///
+    /// IG sends the contract size as a string (e.g. "1" or "100"); thousands
+    /// separators and surrounding whitespace are ignored.
+    ///
+    /// # Returns
+    /// * `Some(f64)` if the value is a finite number, `None` otherwise
+    pub fn contract_size_value(&self) -> Option<f64> {
+        let cleaned: String = self
+            .contract_size
+            .chars()
+            .filter(|c| !matches!(c, ',' | '_') && !c.is_whitespace())
+            .collect();
+        cleaned.parse::<f64>().ok().filter(|v| v.is_finite())
+    }
+
+    /// Last dealing date parsed from `expiryDetails`, if IG reported one
+    pub fn last_dealing_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_ig_datetime(&self.expiry_details.as_ref()?.last_dealing_date)
+    }
+
+    /// Days from `now` until the last dealing date, as a fraction
+    ///
+    /// # Returns
+    /// * `Some(f64)` - Days to expiry, `0.0` once the date has passed
+    /// * `None` - If the instrument has no parseable last dealing date
+    pub fn days_to_expiry(&self, now: DateTime<Utc>) -> Option<f64> {
+        let remaining = self.last_dealing_datetime()? - now;
+        Some((remaining.num_seconds() as f64 / 86_400.0).max(0.0))
+    }
+}
+
 /// Model for an instrument's currency
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Currency {
@@ -76,6 +114,13 @@ pub struct Currency {
     pub is_default: Option<bool>,
 }
 
+impl Currency {
+    /// Typed ISO 4217 code, or `None` if `code` is not a valid code
+    pub fn code_typed(&self) -> Option<CurrencyCode> {
+        CurrencyCode::parse(&self.code)
+    }
+}
+
 /// Model for market data with enhanced deserialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketDetails {
@@ -88,6 +133,114 @@ pub struct MarketDetails {
     pub dealing_rules: DealingRules,
 }
 
+impl MarketDetails {
+    /// Stop level `distance_points` away from the price a position would close at
+    ///
+    /// A long position (`Direction::Buy`) closes at the bid, so its stop sits
+    /// below the bid; a short one closes at the offer and its stop sits above.
+    /// Points are converted to price with `point_size` and the level is
+    /// rounded to the market's decimal places.
+    ///
+    /// # Arguments
+    /// * `direction` - Direction of the position the stop protects
+    /// * `distance_points` - Distance from the closing price, in points
+    ///
+    /// # Returns
+    /// * `None` - If the closing price is missing, the distance is not
+    ///   positive, or it is shorter than the market's minimum stop distance
+    pub fn stop_level_for_distance(
+        &self,
+        direction: Direction,
+        distance_points: f64,
+    ) -> Option<f64> {
+        if !(distance_points.is_finite() && distance_points > 0.0) {
+            return None;
+        }
+        let price = self.closing_price(&direction)?;
+        if self
+            .min_stop_distance_points(price)
+            .is_some_and(|min| distance_points < min)
+        {
+            return None;
+        }
+        let offset = distance_points * self.point_size();
+        let level = match direction {
+            Direction::Buy => price - offset,
+            Direction::Sell => price + offset,
+        };
+        Some(self.round_level(level))
+    }
+
+    /// Distance in points from the closing price to a stop level
+    ///
+    /// The inverse of `stop_level_for_distance`.
+    ///
+    /// # Returns
+    /// * `None` - If the closing price is missing or the level is not on the
+    ///   losing side of it
+    pub fn distance_for_level(&self, direction: Direction, level: f64) -> Option<f64> {
+        let price = self.closing_price(&direction)?;
+        let offset = match direction {
+            Direction::Buy => price - level,
+            Direction::Sell => level - price,
+        };
+        let distance = offset / self.point_size();
+        // Undo the float noise of the subtraction, points are never finer than this
+        let distance = (distance * 1e6).round() / 1e6;
+        (distance.is_finite() && distance > 0.0).then_some(distance)
+    }
+
+    /// Price change of one point
+    ///
+    /// Most markets are quoted in points, so a point is one unit of the
+    /// quoted price. A market quoted in real units with more decimal places
+    /// than its scaling factor accounts for (EUR/USD at `1.16432` with a
+    /// scaling factor of 10000) has points of `1 / scaling_factor`.
+    pub fn point_size(&self) -> f64 {
+        let scale = self
+            .snapshot
+            .scaling_factor
+            .filter(|factor| *factor > 1)
+            .unwrap_or(1);
+        let scale_decimals = (scale as f64).log10().round() as i64;
+        match self.snapshot.decimal_places_factor {
+            Some(places) if scale > 1 && places > scale_decimals => 1.0 / scale as f64,
+            _ => 1.0,
+        }
+    }
+
+    /// Minimum normal stop distance in points at `price`
+    ///
+    /// IG gives the minimum either in points or as a percentage of the price.
+    pub fn min_stop_distance_points(&self, price: f64) -> Option<f64> {
+        let min = &self.dealing_rules.min_normal_stop_or_limit_distance;
+        let value = min.value?;
+        match min.unit {
+            Some(StepUnit::Percentage | StepUnit::Pct) => {
+                Some(price.abs() * value / 100.0 / self.point_size())
+            }
+            Some(StepUnit::Points) | None => Some(value),
+        }
+    }
+
+    fn closing_price(&self, direction: &Direction) -> Option<f64> {
+        match direction {
+            Direction::Buy => self.snapshot.bid,
+            Direction::Sell => self.snapshot.offer,
+        }
+    }
+
+    fn round_level(&self, value: f64) -> f64 {
+        match self.snapshot.decimal_places_factor {
+            Some(places) if (0..=10).contains(&places) => {
+                let factor = 10f64.powi(places as i32);
+                (value * factor).round() / factor
+            }
+            _ => value,
+        }
+    }
+}
+
 /// Trading rules for a market with enhanced deserialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DealingRules {
@@ -137,10 +290,14 @@ pub struct MarketSnapshot {
 
     /// Net change in price since previous close
     #[serde(rename = "netChange")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub net_change: Option<f64>,
 
     /// Percentage change in price since previous close
     #[serde(rename = "percentageChange")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub percentage_change: Option<f64>,
 
     /// Time of the last price update
@@ -152,19 +309,29 @@ pub struct MarketSnapshot {
     pub delay_time: Option<i64>,
 
     /// Current bid price
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub bid: Option<f64>,
 
     /// Current offer/ask price
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub offer: Option<f64>,
 
     /// Highest price of the current trading session
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub high: Option<f64>,
 
     /// Lowest price of the current trading session
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub low: Option<f64>,
 
     /// Odds for binary markets
     #[serde(rename = "binaryOdds")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub binary_odds: Option<f64>,
 
     /// Factor for decimal places in price display
@@ -177,9 +344,136 @@ pub struct MarketSnapshot {
 
     /// Extra spread for controlled risk trades
     #[serde(rename = "controlledRiskExtraSpread")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub controlled_risk_extra_spread: Option<f64>,
 }
 
+impl MarketSnapshot {
+    /// Net change since the previous close, `0.0` when IG reports none
+    pub fn net_change_or_zero(&self) -> f64 {
+        self.net_change.unwrap_or(0.0)
+    }
+
+    /// Percentage change since the previous close, `0.0` when IG reports none
+    pub fn percentage_change_or_zero(&self) -> f64 {
+        self.percentage_change.unwrap_or(0.0)
+    }
+
+    /// Compares this snapshot with a later one
+    ///
+    /// # Arguments
+    /// * `other` - The newer snapshot
+    ///
+    /// # Returns
+    /// A `SnapshotDiff` listing the changed fields, price deltas and any market
+    /// state transition from `self` to `other`
+    pub fn diff(&self, other: &MarketSnapshot) -> SnapshotDiff {
+        let mut changed_fields = Vec::new();
+        let mut track = |field: MarketField, changed: bool| {
+            if changed {
+                changed_fields.push(field);
+            }
+        };
+        track(MarketField::Bid, self.bid != other.bid);
+        track(MarketField::Offer, self.offer != other.offer);
+        track(MarketField::High, self.high != other.high);
+        track(MarketField::Low, self.low != other.low);
+        track(MarketField::Change, self.net_change != other.net_change);
+        track(
+            MarketField::ChangePct,
+            self.percentage_change != other.percentage_change,
+        );
+        track(
+            MarketField::MarketState,
+            self.market_status != other.market_status,
+        );
+        track(
+            MarketField::UpdateTime,
+            self.update_time != other.update_time,
+        );
+
+        let state_transition = match (
+            MarketState::parse(&self.market_status),
+            MarketState::parse(&other.market_status),
+        ) {
+            (Some(from), Some(to)) if from != to => Some(MarketStateTransition { from, to }),
+            _ => None,
+        };
+
+        SnapshotDiff {
+            changed_fields,
+            bid_delta: price_delta(self.bid, other.bid),
+            offer_delta: price_delta(self.offer, other.offer),
+            high_delta: price_delta(self.high, other.high),
+            low_delta: price_delta(self.low, other.low),
+            state_transition,
+        }
+    }
+}
+
+fn price_delta(before: Option<f64>, after: Option<f64>) -> Option<f64> {
+    match (before, after) {
+        (Some(before), Some(after)) if before != after => Some(after - before),
+        _ => None,
+    }
+}
+
+/// Differences between two market snapshots, as returned by [`MarketSnapshot::diff`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SnapshotDiff {
+    /// Fields whose value differs between the snapshots
+    pub changed_fields: Vec<MarketField>,
+    /// Change of the bid price, if both snapshots carry one and it moved
+    pub bid_delta: Option<f64>,
+    /// Change of the offer price, if both snapshots carry one and it moved
+    pub offer_delta: Option<f64>,
+    /// Change of the session high, if both snapshots carry one and it moved
+    pub high_delta: Option<f64>,
+    /// Change of the session low, if both snapshots carry one and it moved
+    pub low_delta: Option<f64>,
+    /// Market state transition, if the market status changed
+    pub state_transition: Option<MarketStateTransition>,
+}
+
+impl SnapshotDiff {
+    /// Returns true if no field changed
+    pub fn is_empty(&self) -> bool {
+        self.changed_fields.is_empty()
+    }
+
+    /// Returns true if the given field changed
+    pub fn changed(&self, field: MarketField) -> bool {
+        self.changed_fields.contains(&field)
+    }
+}
+
+/// Change of market state between two observations
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketStateTransition {
+    /// State before the change
+    pub from: MarketState,
+    /// State after the change
+    pub to: MarketState,
+}
+
+impl MarketStateTransition {
+    /// Returns true if the market stopped being tradeable
+    pub fn left_tradeable(&self) -> bool {
+        self.from == MarketState::Tradeable && self.to != MarketState::Tradeable
+    }
+
+    /// Returns true if the market became tradeable
+    pub fn became_tradeable(&self) -> bool {
+        self.from != MarketState::Tradeable && self.to == MarketState::Tradeable
+    }
+
+    /// Returns true if the market was suspended
+    pub fn is_suspension(&self) -> bool {
+        self.to == MarketState::Suspended
+    }
+}
+
 /// Basic market data
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MarketData {
@@ -195,18 +489,26 @@ pub struct MarketData {
     pub expiry: String,
     /// Upper price limit for the market
     #[serde(rename = "highLimitPrice")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub high_limit_price: Option<f64>,
     /// Lower price limit for the market
     #[serde(rename = "lowLimitPrice")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub low_limit_price: Option<f64>,
     /// Current status of the market
     #[serde(rename = "marketStatus")]
     pub market_status: String,
     /// Net change in price since previous close
     #[serde(rename = "netChange")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub net_change: Option<f64>,
     /// Percentage change in price since previous close
     #[serde(rename = "percentageChange")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub percentage_change: Option<f64>,
     /// Time of the last price update
     #[serde(rename = "updateTime")]
@@ -215,11 +517,67 @@ pub struct MarketData {
     #[serde(rename = "updateTimeUTC")]
     pub update_time_utc: Option<String>,
     /// Current bid price
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub bid: Option<f64>,
     /// Current offer/ask price
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub offer: Option<f64>,
 }
 
+impl MarketData {
+    /// Net change since the previous close, `0.0` when IG reports none
+    pub fn net_change_or_zero(&self) -> f64 {
+        self.net_change.unwrap_or(0.0)
+    }
+
+    /// Percentage change since the previous close, `0.0` when IG reports none
+    pub fn percentage_change_or_zero(&self) -> f64 {
+        self.percentage_change.unwrap_or(0.0)
+    }
+
+    /// How well this market matches a search term, higher is better
+    ///
+    /// Case-insensitive. From best to worst: exact epic, exact name, epic
+    /// prefix, name prefix, prefix of a word in the name, epic substring and
+    /// name substring; `0` means no match. Tradeable markets score slightly
+    /// higher than others in the same band.
+    pub fn search_score(&self, term: &str) -> u32 {
+        let term = term.trim().to_lowercase();
+        if term.is_empty() {
+            return 0;
+        }
+        let epic = self.epic.to_lowercase();
+        let name = self.instrument_name.to_lowercase();
+        let score = if epic == term {
+            100
+        } else if name == term {
+            90
+        } else if epic.starts_with(&term) {
+            70
+        } else if name.starts_with(&term) {
+            60
+        } else if name
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word.starts_with(&term))
+        {
+            50
+        } else if epic.contains(&term) {
+            40
+        } else if name.contains(&term) {
+            30
+        } else {
+            0
+        };
+        if score > 0 && self.market_status.eq_ignore_ascii_case("TRADEABLE") {
+            score + 5
+        } else {
+            score
+        }
+    }
+}
+
 impl Display for MarketData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let json = serde_json::to_string(self).unwrap_or_else(|_| "Invalid JSON".to_string());
@@ -227,12 +585,51 @@ impl Display for MarketData {
     }
 }
 
+/// How `get_all_markets` orders and deduplicates the markets it collects
+///
+/// The same market can be listed under several navigation nodes, and the
+/// traversal order depends on how IG returns the nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarketOrdering {
+    /// Traversal order, duplicates included
+    Raw,
+    /// Traversal order, keeping only the first occurrence of each epic
+    #[default]
+    Deduplicated,
+    /// One market per epic, sorted by epic, stable across runs
+    SortedByEpic,
+}
+
+impl MarketOrdering {
+    /// Applies this ordering to markets collected in traversal order
+    ///
+    /// Deduplication keeps the first occurrence of each epic, so the node
+    /// visited first wins.
+    pub fn apply(self, markets: Vec<MarketData>) -> Vec<MarketData> {
+        if self == MarketOrdering::Raw {
+            return markets;
+        }
+        let mut seen = HashSet::with_capacity(markets.len());
+        let mut unique: Vec<MarketData> = markets
+            .into_iter()
+            .filter(|market| seen.insert(market.epic.clone()))
+            .collect();
+        if self == MarketOrdering::SortedByEpic {
+            unique.sort_by(|a, b| a.epic.cmp(&b.epic));
+        }
+        unique
+    }
+}
+
 /// Historical price data point
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoricalPrice {
     /// Timestamp of the price data point
     #[serde(rename = "snapshotTime")]
     pub snapshot_time: String,
+    /// Timestamp of the price data point in UTC, returned by the v3 endpoint
+    #[serde(rename = "snapshotTimeUTC", default)]
+    pub snapshot_time_utc: Option<String>,
     /// Opening price for the period
     #[serde(rename = "openPrice")]
     pub open_price: PricePoint,
@@ -250,15 +647,39 @@ pub struct HistoricalPrice {
     pub last_traded_volume: Option<i64>,
 }
 
+impl HistoricalPrice {
+    /// Timestamp of the data point as a UTC date time
+    ///
+    /// Uses `snapshot_time_utc` when IG provides it. Otherwise falls back to
+    /// `snapshot_time`, read as UTC although IG reports it in the account's
+    /// time zone, which is what the storage layer has always done.
+    ///
+    /// # Returns
+    /// * `Some(DateTime<Utc>)` - If either timestamp could be parsed
+    /// * `None` - If neither could
+    pub fn snapshot_datetime(&self) -> Option<DateTime<Utc>> {
+        self.snapshot_time_utc
+            .as_deref()
+            .and_then(parse_ig_datetime)
+            .or_else(|| parse_ig_datetime(&self.snapshot_time))
+    }
+}
+
 /// Price point with bid, ask and last traded prices
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricePoint {
     /// Bid price at this point
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub bid: Option<f64>,
     /// Ask/offer price at this point
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub ask: Option<f64>,
     /// Last traded price at this point
     #[serde(rename = "lastTraded")]
+    #[serde(with = "string_as_float_opt")]
+    #[serde(default)]
     pub last_traded: Option<f64>,
 }
 
@@ -356,6 +777,37 @@ pub enum MarketState {
     Suspended,
 }
 
+impl MarketState {
+    /// Parses a market status as reported by IG (e.g. `TRADEABLE`, `auction_no_edit`)
+    ///
+    /// Matching is case-insensitive. Returns `None` for unknown statuses.
+    pub fn parse(status: &str) -> Option<MarketState> {
+        match status.to_ascii_uppercase().as_str() {
+            "CLOSED" => Some(MarketState::Closed),
+            "OFFLINE" => Some(MarketState::Offline),
+            "TRADEABLE" => Some(MarketState::Tradeable),
+            "EDIT" | "EDITS_ONLY" => Some(MarketState::Edit),
+            "AUCTION" => Some(MarketState::Auction),
+            "AUCTION_NO_EDIT" | "AUCTIONNOEDIT" => Some(MarketState::AuctionNoEdit),
+            "SUSPENDED" => Some(MarketState::Suspended),
+            _ => None,
+        }
+    }
+
+    /// Status name as reported by IG, accepted back by `parse`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketState::Closed => "CLOSED",
+            MarketState::Offline => "OFFLINE",
+            MarketState::Tradeable => "TRADEABLE",
+            MarketState::Edit => "EDIT",
+            MarketState::Auction => "AUCTION",
+            MarketState::AuctionNoEdit => "AUCTION_NO_EDIT",
+            MarketState::Suspended => "SUSPENDED",
+        }
+    }
+}
+
 /// Representation of market data received from the IG Markets streaming API
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PresentationMarketData {
@@ -378,8 +830,8 @@ impl PresentationMarketData {
     /// * `item_update` - The ItemUpdate received from the Lightstreamer API
     ///
     /// # Returns
-    /// * `Result<Self, String>` - The converted MarketData or an error message
-    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, String> {
+    /// * `Result<Self, StreamError>` - The converted MarketData or an error message
+    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, StreamError> {
         // Extract the item_name, defaulting to an empty string if None
         let item_name = item_update.item_name.clone().unwrap_or_default();
 
@@ -390,14 +842,16 @@ impl PresentationMarketData {
         let is_snapshot = item_update.is_snapshot;
 
         // Convert fields
-        let fields = Self::create_market_fields(&item_update.fields)?;
+        let fields =
+            Self::create_market_fields(&item_update.fields).map_err(|e| e.for_item(&item_name))?;
 
         // Convert changed_fields by first creating a HashMap<String, Option<String>>
         let mut changed_fields_map: HashMap<String, Option<String>> = HashMap::new();
         for (key, value) in &item_update.changed_fields {
             changed_fields_map.insert(key.clone(), Some(value.clone()));
         }
-        let changed_fields = Self::create_market_fields(&changed_fields_map)?;
+        let changed_fields =
+            Self::create_market_fields(&changed_fields_map).map_err(|e| e.for_item(&item_name))?;
 
         Ok(PresentationMarketData {
             item_name,
@@ -414,10 +868,10 @@ impl PresentationMarketData {
     /// * `fields_map` - HashMap containing field names and their string values
     ///
     /// # Returns
-    /// * `Result<MarketFields, String>` - The parsed MarketFields or an error message
+    /// * `Result<MarketFields, StreamError>` - The parsed MarketFields or an error message
     fn create_market_fields(
         fields_map: &HashMap<String, Option<String>>,
-    ) -> Result<MarketFields, String> {
+    ) -> Result<MarketFields, StreamError> {
         // Helper function to safely get a field value
         let get_field = |key: &str| -> Option<String> { fields_map.get(key).cloned().flatten() };
 
@@ -430,7 +884,7 @@ impl PresentationMarketData {
             Some("auction") => Some(MarketState::Auction),
             Some("auction_no_edit") => Some(MarketState::AuctionNoEdit),
             Some("suspended") => Some(MarketState::Suspended),
-            Some(unknown) => return Err(format!("Unknown market state: {unknown}")),
+            Some(unknown) => return Err(StreamError::parse("MARKET_STATE", unknown)),
             None => None,
         };
 
@@ -438,17 +892,17 @@ impl PresentationMarketData {
         let market_delay = match get_field("MARKET_DELAY").as_deref() {
             Some("0") => Some(false),
             Some("1") => Some(true),
-            Some(val) => return Err(format!("Invalid MARKET_DELAY value: {val}")),
+            Some(val) => return Err(StreamError::parse("MARKET_DELAY", val)),
             None => None,
         };
 
         // Helper function to parse float values
-        let parse_float = |key: &str| -> Result<Option<f64>, String> {
+        let parse_float = |key: &str| -> Result<Option<f64>, StreamError> {
             match get_field(key) {
                 Some(val) if !val.is_empty() => val
                     .parse::<f64>()
                     .map(Some)
-                    .map_err(|_| format!("Failed to parse {key} as float: {val}")),
+                    .map_err(|_| StreamError::parse(key, &val)),
                 _ => Ok(None),
             }
         };
@@ -468,6 +922,132 @@ impl PresentationMarketData {
     }
 }
 
+impl PresentationMarketData {
+    /// Serializes only the changed fields of this update for forwarding downstream
+    ///
+    /// The delta contains `item_name`, `is_snapshot` and the non-null entries of
+    /// `changed_fields`. Use [`MarketTracker::apply_delta`] on the receiving side
+    /// to rebuild the full market state.
+    ///
+    /// # Returns
+    /// * `Result<String, serde_json::Error>` - The compact delta JSON or a serialization error
+    pub fn to_delta_json(&self) -> Result<String, serde_json::Error> {
+        let mut changed = serde_json::to_value(&self.changed_fields)?;
+        if let serde_json::Value::Object(map) = &mut changed {
+            map.retain(|_, value| !value.is_null());
+        }
+        let delta = serde_json::json!({
+            "item_name": self.item_name,
+            "is_snapshot": self.is_snapshot,
+            "changed_fields": changed,
+        });
+        serde_json::to_string(&delta)
+    }
+}
+
+/// Compact representation of a streaming market update containing only changed fields
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MarketDelta {
+    /// Name of the item this delta belongs to
+    pub item_name: String,
+    /// Whether the originating update was a snapshot
+    #[serde(default)]
+    pub is_snapshot: bool,
+    /// Fields that changed in the originating update
+    #[serde(default)]
+    pub changed_fields: MarketFields,
+}
+
+impl From<&MarketSnapshot> for MarketFields {
+    /// Maps a REST market snapshot onto the streaming field layout
+    fn from(snapshot: &MarketSnapshot) -> Self {
+        MarketFields {
+            mid_open: None,
+            high: snapshot.high,
+            offer: snapshot.offer,
+            change: snapshot.net_change,
+            market_delay: snapshot.delay_time.map(|delay| delay > 0),
+            low: snapshot.low,
+            bid: snapshot.bid,
+            change_pct: snapshot.percentage_change,
+            market_state: MarketState::parse(&snapshot.market_status),
+            update_time: snapshot.update_time.clone(),
+        }
+    }
+}
+
+/// Rebuilds full market state from deltas produced by [`PresentationMarketData::to_delta_json`]
+///
+/// Keeps the last known fields for every item and merges incoming changes on top.
+#[derive(Debug, Clone, Default)]
+pub struct MarketTracker {
+    markets: HashMap<String, MarketFields>,
+}
+
+impl MarketTracker {
+    /// Creates an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a JSON delta and returns the reconstructed market data
+    ///
+    /// # Arguments
+    /// * `json` - Delta produced by `PresentationMarketData::to_delta_json`
+    ///
+    /// # Returns
+    /// * `Result<PresentationMarketData, serde_json::Error>` - Full market state after the delta
+    pub fn apply_delta(&mut self, json: &str) -> Result<PresentationMarketData, serde_json::Error> {
+        let delta: MarketDelta = serde_json::from_str(json)?;
+        Ok(self.apply(delta))
+    }
+
+    /// Applies an already-parsed delta and returns the reconstructed market data
+    ///
+    /// Snapshots replace the stored state for the item; regular updates are merged.
+    pub fn apply(&mut self, delta: MarketDelta) -> PresentationMarketData {
+        let fields = self.markets.entry(delta.item_name.clone()).or_default();
+        if delta.is_snapshot {
+            *fields = delta.changed_fields.clone();
+        } else {
+            fields.merge(&delta.changed_fields);
+        }
+
+        PresentationMarketData {
+            item_name: delta.item_name,
+            item_pos: 0,
+            fields: fields.clone(),
+            changed_fields: delta.changed_fields,
+            is_snapshot: delta.is_snapshot,
+        }
+    }
+
+    /// Returns the last known fields for an item, if any
+    pub fn get(&self, item_name: &str) -> Option<&MarketFields> {
+        self.markets.get(item_name)
+    }
+
+    /// Forgets the stored state of an item, e.g. after updates were lost
+    pub fn remove(&mut self, item_name: &str) -> Option<MarketFields> {
+        self.markets.remove(item_name)
+    }
+
+    /// Names of the tracked items
+    pub fn item_names(&self) -> impl Iterator<Item = &String> {
+        self.markets.keys()
+    }
+
+    /// Returns the number of tracked items
+    pub fn len(&self) -> usize {
+        self.markets.len()
+    }
+
+    /// Returns true if no items are tracked
+    pub fn is_empty(&self) -> bool {
+        self.markets.is_empty()
+    }
+}
+
 impl fmt::Display for PresentationMarketData {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
@@ -487,6 +1067,55 @@ impl From<&ItemUpdate> for PresentationMarketData {
     }
 }
 
+/// Field names available on a Lightstreamer `MARKET:{epic}` subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarketField {
+    /// Mid-open price (`MID_OPEN`)
+    MidOpen,
+    /// Session high (`HIGH`)
+    High,
+    /// Session low (`LOW`)
+    Low,
+    /// Offer price (`OFFER`)
+    Offer,
+    /// Bid price (`BID`)
+    Bid,
+    /// Net change (`CHANGE`)
+    Change,
+    /// Percentage change (`CHANGE_PCT`)
+    ChangePct,
+    /// Whether prices are delayed (`MARKET_DELAY`)
+    MarketDelay,
+    /// Market state (`MARKET_STATE`)
+    MarketState,
+    /// Time of the last update (`UPDATE_TIME`)
+    UpdateTime,
+}
+
+impl MarketField {
+    /// Field name as expected by Lightstreamer
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketField::MidOpen => "MID_OPEN",
+            MarketField::High => "HIGH",
+            MarketField::Low => "LOW",
+            MarketField::Offer => "OFFER",
+            MarketField::Bid => "BID",
+            MarketField::Change => "CHANGE",
+            MarketField::ChangePct => "CHANGE_PCT",
+            MarketField::MarketDelay => "MARKET_DELAY",
+            MarketField::MarketState => "MARKET_STATE",
+            MarketField::UpdateTime => "UPDATE_TIME",
+        }
+    }
+}
+
+impl Display for MarketField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Fields containing market price and status information
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct MarketFields {
@@ -548,3 +1177,63 @@ pub struct MarketFields {
     #[serde(default)]
     pub update_time: Option<String>,
 }
+
+impl MarketFields {
+    /// Overwrites fields with every value present in `changes`, keeping the rest
+    pub fn merge(&mut self, changes: &MarketFields) {
+        macro_rules! merge_field {
+            ($($field:ident),*) => {
+                $(
+                    if changes.$field.is_some() {
+                        self.$field = changes.$field.clone();
+                    }
+                )*
+            };
+        }
+        merge_field!(
+            mid_open,
+            high,
+            offer,
+            change,
+            market_delay,
+            low,
+            bid,
+            change_pct,
+            market_state,
+            update_time
+        );
+    }
+
+    /// Fields whose value differs from `previous`
+    pub fn changed_from(&self, previous: &MarketFields) -> Vec<MarketField> {
+        let mut changed = Vec::new();
+        let mut track = |field: MarketField, differs: bool| {
+            if differs {
+                changed.push(field);
+            }
+        };
+        track(MarketField::MidOpen, self.mid_open != previous.mid_open);
+        track(MarketField::High, self.high != previous.high);
+        track(MarketField::Low, self.low != previous.low);
+        track(MarketField::Offer, self.offer != previous.offer);
+        track(MarketField::Bid, self.bid != previous.bid);
+        track(MarketField::Change, self.change != previous.change);
+        track(
+            MarketField::ChangePct,
+            self.change_pct != previous.change_pct,
+        );
+        track(
+            MarketField::MarketDelay,
+            self.market_delay != previous.market_delay,
+        );
+        track(
+            MarketField::MarketState,
+            self.market_state != previous.market_state,
+        );
+        track(
+            MarketField::UpdateTime,
+            self.update_time != previous.update_time,
+        );
+        changed
+    }
+}
@@ -2,6 +2,8 @@
 pub mod account;
 /// Chart and candlestick data models
 pub mod chart;
+/// Currency codes and symbols
+pub mod currency;
 /// Financial instrument models
 pub mod instrument;
 /// Market data and navigation models
@@ -3,6 +3,8 @@
    Email: jb@taunais.com
    Date: 13/5/25
 ******************************************************************************/
+use crate::presentation::instrument::InstrumentType;
+use crate::presentation::market::DealingRules;
 use pretty_simple_display::DisplaySimple;
 use serde::{Deserialize, Serialize};
 
@@ -17,6 +19,16 @@ pub enum Direction {
     Sell,
 }
 
+impl Direction {
+    /// Direction of the deal that closes a position opened in this direction
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Buy => Direction::Sell,
+            Direction::Sell => Direction::Buy,
+        }
+    }
+}
+
 /// Order type
 #[derive(Debug, Clone, DisplaySimple, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "UPPERCASE")]
@@ -34,6 +46,32 @@ pub enum OrderType {
     StopLimit,
 }
 
+impl OrderType {
+    /// Picks an order type the given market will accept for an immediate deal
+    ///
+    /// Options markets reject `Market` orders, as do markets whose dealing rules
+    /// report the market order preference as `NOT_AVAILABLE`. Those get `Limit`,
+    /// everything else gets `Market`.
+    ///
+    /// # Arguments
+    /// * `instrument_type` - Type of the instrument, if known
+    /// * `dealing_rules` - Dealing rules returned with the market details
+    ///
+    /// # Returns
+    /// * `OrderType::Limit` or `OrderType::Market`
+    pub fn best_for(
+        instrument_type: Option<InstrumentType>,
+        dealing_rules: &DealingRules,
+    ) -> OrderType {
+        let is_option = instrument_type.is_some_and(|t| t.is_option());
+        if is_option || dealing_rules.market_order_preference == "NOT_AVAILABLE" {
+            OrderType::Limit
+        } else {
+            OrderType::Market
+        }
+    }
+}
+
 /// Represents the status of an order or transaction in the system.
 ///
 /// This enum covers various states an order can be in throughout its lifecycle,
@@ -74,6 +112,31 @@ pub enum Status {
     Expired,
 }
 
+impl Status {
+    /// Returns true for states a deal does not move on from by itself
+    ///
+    /// `Accepted` is transient: IG may report it before the deal settles into
+    /// `Open` (or is closed or rejected).
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Status::Amended
+            | Status::Deleted
+            | Status::FullyClosed
+            | Status::Opened
+            | Status::PartiallyClosed
+            | Status::Closed
+            | Status::Open
+            | Status::Updated
+            | Status::Rejected
+            | Status::Working
+            | Status::Filled
+            | Status::Cancelled
+            | Status::Expired => true,
+            Status::Accepted => false,
+        }
+    }
+}
+
 /// Order duration (time in force)
 #[derive(Debug, Clone, DisplaySimple, Serialize, Deserialize, PartialEq, Default)]
 pub enum TimeInForce {
@@ -90,4 +153,10 @@ pub enum TimeInForce {
     /// Order must be filled completely immediately or cancelled
     #[serde(rename = "FILL_OR_KILL")]
     FillOrKill,
+    /// Order is filled as far as possible immediately and the rest is dropped
+    ///
+    /// IG's name for immediate-or-cancel on positions, offered for some
+    /// instruments only.
+    #[serde(rename = "EXECUTE_AND_ELIMINATE")]
+    ExecuteAndEliminate,
 }
@@ -1,3 +1,4 @@
+use crate::error::StreamError;
 use crate::presentation::serialization::string_as_float_opt;
 use lightstreamer_rs::subscription::ItemUpdate;
 use pretty_simple_display::DisplaySimple;
@@ -280,7 +281,7 @@ impl PriceData {
     /// # Returns
     ///
     /// A Result containing either the parsed PriceData or an error message
-    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, String> {
+    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, StreamError> {
         // Extract the item_name, defaulting to an empty string if None
         let item_name = item_update.item_name.clone().unwrap_or_default();
 
@@ -291,14 +292,16 @@ impl PriceData {
         let is_snapshot = item_update.is_snapshot;
 
         // Convert fields
-        let fields = Self::create_price_fields(&item_update.fields)?;
+        let fields =
+            Self::create_price_fields(&item_update.fields).map_err(|e| e.for_item(&item_name))?;
 
         // Convert changed_fields by first creating a HashMap<String, Option<String>>
         let mut changed_fields_map: HashMap<String, Option<String>> = HashMap::new();
         for (key, value) in &item_update.changed_fields {
             changed_fields_map.insert(key.clone(), Some(value.clone()));
         }
-        let changed_fields = Self::create_price_fields(&changed_fields_map)?;
+        let changed_fields =
+            Self::create_price_fields(&changed_fields_map).map_err(|e| e.for_item(&item_name))?;
 
         Ok(PriceData {
             item_name,
@@ -312,17 +315,17 @@ impl PriceData {
     // Helper method to create PriceFields from a HashMap
     fn create_price_fields(
         fields_map: &HashMap<String, Option<String>>,
-    ) -> Result<PriceFields, String> {
+    ) -> Result<PriceFields, StreamError> {
         // Helper function to safely get a field value
         let get_field = |key: &str| -> Option<String> { fields_map.get(key).cloned().flatten() };
 
         // Helper function to parse float values
-        let parse_float = |key: &str| -> Result<Option<f64>, String> {
+        let parse_float = |key: &str| -> Result<Option<f64>, StreamError> {
             match get_field(key) {
                 Some(val) if !val.is_empty() => val
                     .parse::<f64>()
                     .map(Some)
-                    .map_err(|_| format!("Failed to parse {key} as float: {val}")),
+                    .map_err(|_| StreamError::parse(key, &val)),
                 _ => Ok(None),
             }
         };
@@ -338,7 +341,7 @@ impl PriceData {
             Some("AUCTION") => Some(DealingFlag::Auction),
             Some("AUCTIONNOEDIT") => Some(DealingFlag::AuctionNoEdit),
             Some("SUSPEND") => Some(DealingFlag::Suspend),
-            Some(unknown) => return Err(format!("Unknown dealing flag: {unknown}")),
+            Some(unknown) => return Err(StreamError::parse("DLG_FLAG", unknown)),
             None => None,
         };
 
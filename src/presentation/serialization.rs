@@ -59,6 +59,48 @@ pub mod string_as_float_opt {
     }
 }
 
+/// Module for reading nullable numbers as `0.0`
+///
+/// For plain `f64` fields that should default to zero instead of being
+/// optional. Accepts the same inputs as `string_as_float_opt`; `null`, an
+/// empty string and (with `#[serde(default)]`) a missing field all become
+/// `0.0`. Used for the change fields of position and working order markets,
+/// which IG leaves null while a market is closed; models that need to tell a
+/// missing value from zero keep `Option<f64>` and expose `*_or_zero`
+/// accessors instead.
+pub mod null_as_zero {
+    use serde::{Deserializer, Serializer};
+
+    /// Serializes the value as a JSON number
+    ///
+    /// # Arguments
+    /// * `value` - The float value to serialize
+    /// * `serializer` - The serializer to use
+    ///
+    /// # Returns
+    /// A Result containing the serialized value or an error
+    pub fn serialize<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_f64(*value)
+    }
+
+    /// Deserializes a number, numeric string or null, mapping null to `0.0`
+    ///
+    /// # Arguments
+    /// * `deserializer` - The deserializer to use
+    ///
+    /// # Returns
+    /// A Result containing the deserialized float value or an error
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::string_as_float_opt::deserialize(deserializer).map(|value| value.unwrap_or(0.0))
+    }
+}
+
 /// Module for handling the conversion between string and optional boolean values
 ///
 /// This module provides serialization and deserialization functions for converting
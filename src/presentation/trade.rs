@@ -1,3 +1,5 @@
+use crate::error::StreamError;
+use crate::presentation::account::{Channel, WorkingOrderData};
 use crate::presentation::order::{Direction, OrderType, Status, TimeInForce};
 use crate::presentation::serialization::{option_string_empty_as_none, string_as_float_opt};
 use lightstreamer_rs::subscription::ItemUpdate;
@@ -177,6 +179,87 @@ pub struct WorkingOrderUpdate {
     pub good_till_date: Option<String>,
 }
 
+impl OpenPositionUpdate {
+    /// Channel parsed from the raw `channel` field, if present
+    pub fn channel_typed(&self) -> Option<Channel> {
+        self.channel.as_deref().map(Channel::parse)
+    }
+}
+
+impl WorkingOrderUpdate {
+    /// Channel parsed from the raw `channel` field, if present
+    pub fn channel_typed(&self) -> Option<Channel> {
+        self.channel.as_deref().map(Channel::parse)
+    }
+
+    /// Returns true if the order is no longer working
+    ///
+    /// IG reports filled, cancelled and expired working orders alike as
+    /// `DELETED`.
+    pub fn is_deleted(&self) -> bool {
+        matches!(
+            self.status,
+            Some(Status::Deleted | Status::Cancelled | Status::Filled | Status::Expired)
+        )
+    }
+
+    /// Builds the working order described by this update
+    ///
+    /// Fields the push does not carry, such as the creation date in UTC or
+    /// stop and limit levels, are left empty; the timestamp of the update
+    /// stands in for the creation date.
+    ///
+    /// # Returns
+    /// * `None` - If the deal ID, epic, direction, level or size is missing
+    pub fn to_working_order_data(&self) -> Option<WorkingOrderData> {
+        let created_date = self.timestamp.clone().unwrap_or_default();
+        Some(WorkingOrderData {
+            deal_id: self.deal_id.clone()?,
+            direction: self.direction.clone()?,
+            epic: self.epic.clone()?,
+            order_size: self.size?,
+            order_level: self.level?,
+            time_in_force: self.time_in_force.clone().unwrap_or_default(),
+            good_till_date: self.good_till_date.clone(),
+            good_till_date_iso: None,
+            created_date_utc: created_date.clone(),
+            created_date,
+            guaranteed_stop: self.guaranteed_stop.unwrap_or(false),
+            order_type: self.order_type.clone().unwrap_or_default(),
+            stop_distance: self.stop_distance,
+            limit_distance: self.limit_distance,
+            currency_code: self.currency.clone().unwrap_or_default(),
+            dma: false,
+            limited_risk_premium: None,
+            limit_level: None,
+            stop_level: None,
+            deal_reference: self.deal_reference.clone(),
+        })
+    }
+
+    /// Applies the changes carried by this update to a tracked order
+    pub fn apply_to(&self, order: &mut WorkingOrderData) {
+        if let Some(level) = self.level {
+            order.order_level = level;
+        }
+        if let Some(size) = self.size {
+            order.order_size = size;
+        }
+        if let Some(time_in_force) = &self.time_in_force {
+            order.time_in_force = time_in_force.clone();
+        }
+        if self.good_till_date.is_some() {
+            order.good_till_date = self.good_till_date.clone();
+        }
+        if let Some(guaranteed_stop) = self.guaranteed_stop {
+            order.guaranteed_stop = guaranteed_stop;
+        }
+        // Distances are replaced as a whole; IG sends them empty once removed
+        order.stop_distance = self.stop_distance;
+        order.limit_distance = self.limit_distance;
+    }
+}
+
 impl TradeData {
     /// Converts a Lightstreamer ItemUpdate to a TradeData object
     ///
@@ -187,7 +270,7 @@ impl TradeData {
     /// # Returns
     ///
     /// A Result containing either the parsed TradeData or an error message
-    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, String> {
+    pub fn from_item_update(item_update: &ItemUpdate) -> Result<Self, StreamError> {
         // Extract the item_name, defaulting to an empty string if None
         let item_name = item_update.item_name.clone().unwrap_or_default();
 
@@ -198,14 +281,16 @@ impl TradeData {
         let is_snapshot = item_update.is_snapshot;
 
         // Convert fields
-        let fields = Self::create_trade_fields(&item_update.fields)?;
+        let fields =
+            Self::create_trade_fields(&item_update.fields).map_err(|e| e.for_item(&item_name))?;
 
         // Convert changed_fields by first creating a HashMap<String, Option<String>>
         let mut changed_fields_map: HashMap<String, Option<String>> = HashMap::new();
         for (key, value) in &item_update.changed_fields {
             changed_fields_map.insert(key.clone(), Some(value.clone()));
         }
-        let changed_fields = Self::create_trade_fields(&changed_fields_map)?;
+        let changed_fields =
+            Self::create_trade_fields(&changed_fields_map).map_err(|e| e.for_item(&item_name))?;
 
         Ok(TradeData {
             item_name,
@@ -219,7 +304,7 @@ impl TradeData {
     // Helper method to create TradeFields from a HashMap
     fn create_trade_fields(
         fields_map: &HashMap<String, Option<String>>,
-    ) -> Result<TradeFields, String> {
+    ) -> Result<TradeFields, StreamError> {
         // Helper function to safely get a field value
         let get_field = |key: &str| -> Option<String> {
             let field = fields_map.get(key).cloned().flatten();
@@ -238,7 +323,7 @@ impl TradeData {
             if !opu_json.is_empty() {
                 match serde_json::from_str::<OpenPositionUpdate>(&opu_json) {
                     Ok(parsed_opu) => Some(parsed_opu),
-                    Err(e) => return Err(format!("Failed to parse OPU JSON: {e}")),
+                    Err(_) => return Err(StreamError::parse("OPU", &opu_json)),
                 }
             } else {
                 None
@@ -252,7 +337,7 @@ impl TradeData {
             if !wou_json.is_empty() {
                 match serde_json::from_str::<WorkingOrderUpdate>(&wou_json) {
                     Ok(parsed_wou) => Some(parsed_wou),
-                    Err(e) => return Err(format!("Failed to parse WOU JSON: {e}")),
+                    Err(_) => return Err(StreamError::parse("WOU", &wou_json)),
                 }
             } else {
                 None
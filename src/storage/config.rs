@@ -1,5 +1,14 @@
 use pretty_simple_display::DisplaySimple;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use std::time::Duration;
+
+/// Default time in seconds to wait for a connection from the pool
+pub const DEFAULT_ACQUIRE_TIMEOUT_SECS: u64 = 30;
+
+/// Default time in seconds an idle connection is kept before being closed
+pub const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 600;
 
 /// Configuration for database connections
 #[derive(Debug, DisplaySimple, Serialize, Deserialize, Clone)]
@@ -8,4 +17,57 @@ pub struct DatabaseConfig {
     pub url: String,
     /// Maximum number of connections in the connection pool
     pub max_connections: u32,
+    /// Maximum time in seconds to wait when acquiring a connection from the pool
+    #[serde(default = "default_acquire_timeout")]
+    pub acquire_timeout: u64,
+    /// Time in seconds after which an idle connection is closed (None = never)
+    #[serde(default = "default_idle_timeout")]
+    pub idle_timeout: Option<u64>,
+}
+
+fn default_acquire_timeout() -> u64 {
+    DEFAULT_ACQUIRE_TIMEOUT_SECS
+}
+
+fn default_idle_timeout() -> Option<u64> {
+    Some(DEFAULT_IDLE_TIMEOUT_SECS)
+}
+
+impl DatabaseConfig {
+    /// Creates a new database configuration with default timeouts
+    ///
+    /// # Arguments
+    /// * `url` - Database connection URL
+    /// * `max_connections` - Maximum number of connections in the pool
+    pub fn new(url: String, max_connections: u32) -> Self {
+        Self {
+            url,
+            max_connections,
+            acquire_timeout: DEFAULT_ACQUIRE_TIMEOUT_SECS,
+            idle_timeout: Some(DEFAULT_IDLE_TIMEOUT_SECS),
+        }
+    }
+
+    /// Builds the pool options from this configuration without connecting
+    ///
+    /// # Returns
+    /// `PgPoolOptions` with max connections, acquire timeout and idle timeout applied
+    #[must_use]
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout))
+            .idle_timeout(self.idle_timeout.map(Duration::from_secs))
+    }
+}
+
+/// Creates a PostgreSQL connection pool using the sizing and timeouts from the configuration
+///
+/// # Arguments
+/// * `config` - Database configuration containing URL, pool size and timeouts
+///
+/// # Returns
+/// * `Result<PgPool, sqlx::Error>` - Connection pool or the error raised while connecting
+pub async fn connect(config: &DatabaseConfig) -> Result<PgPool, sqlx::Error> {
+    config.pool_options().connect(&config.url).await
 }
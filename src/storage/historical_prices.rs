@@ -116,15 +116,17 @@ pub async fn store_historical_prices(
     for (i, price) in prices.iter().enumerate() {
         stats.total_processed += 1;
 
-        // Parse snapshot time
-        let snapshot_time = match parse_snapshot_time(&price.snapshot_time) {
-            Ok(time) => time,
-            Err(e) => {
+        // Parse snapshot time, preferring the UTC timestamp when IG sends it
+        let snapshot_time = match price.snapshot_datetime() {
+            Some(time) => time,
+            None => {
                 warn!(
-                    "⚠️  Skipping record {}: Invalid timestamp '{}': {}",
+                    "⚠️  Skipping record {}: Invalid timestamp '{}'",
                     i + 1,
-                    price.snapshot_time,
-                    e
+                    price
+                        .snapshot_time_utc
+                        .as_deref()
+                        .unwrap_or(&price.snapshot_time)
                 );
                 stats.skipped += 1;
                 continue;
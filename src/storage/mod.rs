@@ -6,5 +6,7 @@ pub mod historical_prices;
 pub mod market_database;
 /// Market hierarchy persistence models
 pub mod market_persistence;
+/// Streamed market tick recording in TimescaleDB
+pub mod tick_store;
 /// Storage utility functions
 pub mod utils;
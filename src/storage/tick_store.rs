@@ -0,0 +1,490 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Recording of streamed market ticks in TimescaleDB
+//!
+//! `TickStore` queues every market update it is given and writes them in
+//! batches from a background task, so the streaming callback never waits on
+//! the database. Ticks go to the `market_ticks` table, made a hypertable on
+//! `ts` when the TimescaleDB extension is installed and left as a plain
+//! PostgreSQL table otherwise. When the writer falls behind, the queue either
+//! drops its oldest ticks or makes the producer wait, see [`TickBackpressure`].
+
+use crate::application::interfaces::listener::Listener;
+use crate::constants::{TICK_STORE_BATCH_SIZE, TICK_STORE_CAPACITY, TICK_STORE_FLUSH_INTERVAL_MS};
+use crate::presentation::market::{MarketFields, MarketState, PresentationMarketData};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// One recorded market update
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    /// Instrument epic, without the `MARKET:` prefix
+    pub epic: String,
+    /// When the update was received
+    pub ts: DateTime<Utc>,
+    /// Bid, offer and market state of the update; other fields are not stored
+    pub fields: MarketFields,
+}
+
+impl Tick {
+    /// Builds a tick from the merged fields of a streamed market update
+    pub fn from_update(update: &PresentationMarketData, ts: DateTime<Utc>) -> Self {
+        let epic = update
+            .item_name
+            .strip_prefix("MARKET:")
+            .unwrap_or(&update.item_name);
+        Self {
+            epic: epic.to_string(),
+            ts,
+            fields: MarketFields {
+                bid: update.fields.bid,
+                offer: update.fields.offer,
+                market_state: update.fields.market_state.clone(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// What `TickStore` does with a new tick when its queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TickBackpressure {
+    /// Discard the oldest queued tick; the stream is never held up
+    #[default]
+    DropOldest,
+    /// Block the producer until the writer has made room
+    ///
+    /// Needs a multi-threaded Tokio runtime, where the blocked worker hands
+    /// its other tasks over so the writer keeps running. `TickStore` falls
+    /// back to `DropOldest` on a current-thread runtime, which would deadlock.
+    Block,
+}
+
+/// Tuning of the `TickStore` writer
+#[derive(Debug, Clone)]
+pub struct TickStoreOptions {
+    /// Maximum number of ticks written per insert
+    pub batch_size: usize,
+    /// Longest time a queued tick waits before being written
+    pub flush_interval: Duration,
+    /// Maximum number of ticks held in memory
+    pub capacity: usize,
+    /// Behaviour when `capacity` is reached
+    pub backpressure: TickBackpressure,
+}
+
+impl Default for TickStoreOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: TICK_STORE_BATCH_SIZE,
+            flush_interval: Duration::from_millis(TICK_STORE_FLUSH_INTERVAL_MS),
+            capacity: TICK_STORE_CAPACITY,
+            backpressure: TickBackpressure::default(),
+        }
+    }
+}
+
+/// Bounded queue of ticks waiting to be written
+#[derive(Debug)]
+pub struct TickBuffer {
+    ticks: Mutex<VecDeque<Tick>>,
+    space: Condvar,
+    capacity: usize,
+    backpressure: TickBackpressure,
+    dropped: AtomicU64,
+    closed: AtomicBool,
+}
+
+impl TickBuffer {
+    /// Creates an empty buffer holding at most `capacity` ticks
+    pub fn new(capacity: usize, backpressure: TickBackpressure) -> Self {
+        Self {
+            ticks: Mutex::new(VecDeque::new()),
+            space: Condvar::new(),
+            capacity: capacity.max(1),
+            backpressure,
+            dropped: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Queues a tick, applying the backpressure policy when full
+    ///
+    /// With `TickBackpressure::Block` this blocks the calling thread until
+    /// the writer makes room. On a multi-threaded Tokio runtime the wait goes
+    /// through `block_in_place`, so the worker's other tasks, including the
+    /// writer, keep running.
+    ///
+    /// # Returns
+    /// * The number of ticks now queued
+    pub fn push(&self, tick: Tick) -> usize {
+        let mut ticks = self
+            .ticks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while ticks.len() >= self.capacity {
+            match self.backpressure {
+                TickBackpressure::DropOldest => {
+                    ticks.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                TickBackpressure::Block if self.closed.load(Ordering::Acquire) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return ticks.len();
+                }
+                TickBackpressure::Block => {
+                    ticks = self.wait_for_space(ticks);
+                }
+            }
+        }
+        ticks.push_back(tick);
+        ticks.len()
+    }
+
+    fn wait_for_space<'a>(
+        &self,
+        ticks: MutexGuard<'a, VecDeque<Tick>>,
+    ) -> MutexGuard<'a, VecDeque<Tick>> {
+        let wait = || {
+            self.space
+                .wait(ticks)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+        };
+        match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+            Ok(RuntimeFlavor::MultiThread) => tokio::task::block_in_place(wait),
+            _ => wait(),
+        }
+    }
+
+    /// Removes up to `max` ticks from the front of the queue
+    pub fn drain(&self, max: usize) -> Vec<Tick> {
+        let mut ticks = self
+            .ticks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let count = max.min(ticks.len());
+        let batch = ticks.drain(..count).collect();
+        self.space.notify_all();
+        batch
+    }
+
+    /// Number of ticks waiting to be written
+    pub fn len(&self) -> usize {
+        self.ticks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+
+    /// Returns true if no tick is waiting to be written
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of ticks discarded because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Releases blocked producers; ticks pushed afterwards to a full queue are dropped
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.space.notify_all();
+    }
+}
+
+/// Writes streamed market ticks to the `market_ticks` table in batches
+pub struct TickStore {
+    pool: PgPool,
+    buffer: Arc<TickBuffer>,
+    wake: Arc<Notify>,
+    batch_size: usize,
+    written: Arc<AtomicU64>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl TickStore {
+    /// Creates the table and starts the background writer with default options
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn start(pool: PgPool) -> Result<Self, sqlx::Error> {
+        Self::start_with_options(pool, TickStoreOptions::default()).await
+    }
+
+    /// Creates the table and starts the background writer
+    ///
+    /// # Arguments
+    /// * `pool` - PostgreSQL pool, ideally to a database with TimescaleDB
+    /// * `options` - Batch size, flush interval and backpressure policy
+    ///
+    /// # Returns
+    /// * `Ok(TickStore)` - Store ready to record ticks
+    /// * `Err(sqlx::Error)` - If the table cannot be created
+    pub async fn start_with_options(
+        pool: PgPool,
+        options: TickStoreOptions,
+    ) -> Result<Self, sqlx::Error> {
+        initialize_ticks_table(&pool).await?;
+
+        // Blocking the only worker thread would also stop the writer
+        let backpressure = match options.backpressure {
+            TickBackpressure::Block
+                if Handle::current().runtime_flavor() != RuntimeFlavor::MultiThread =>
+            {
+                warn!(
+                    "Tick store needs a multi-threaded runtime to block, dropping oldest ticks instead"
+                );
+                TickBackpressure::DropOldest
+            }
+            backpressure => backpressure,
+        };
+        let buffer = Arc::new(TickBuffer::new(options.capacity, backpressure));
+        let wake = Arc::new(Notify::new());
+        let written = Arc::new(AtomicU64::new(0));
+        let batch_size = options.batch_size.max(1);
+        let writer = tokio::spawn(run_writer(
+            pool.clone(),
+            Arc::clone(&buffer),
+            Arc::clone(&wake),
+            Arc::clone(&written),
+            batch_size,
+            options.flush_interval,
+        ));
+        Ok(Self {
+            pool,
+            buffer,
+            wake,
+            batch_size,
+            written,
+            writer: Some(writer),
+        })
+    }
+
+    /// Queues a tick for writing
+    pub fn record(&self, tick: Tick) {
+        if self.buffer.push(tick) >= self.batch_size {
+            self.wake.notify_one();
+        }
+    }
+
+    /// Creates a listener that records every update of a market subscription
+    ///
+    /// Updates are stamped with the time they are received.
+    pub fn listener(&self) -> Listener<PresentationMarketData> {
+        let buffer = Arc::clone(&self.buffer);
+        let wake = Arc::clone(&self.wake);
+        let batch_size = self.batch_size;
+        Listener::new(move |update: &PresentationMarketData| {
+            if buffer.push(Tick::from_update(update, Utc::now())) >= batch_size {
+                wake.notify_one();
+            }
+            Ok(())
+        })
+    }
+
+    /// Number of ticks written to the database so far
+    pub fn written(&self) -> u64 {
+        self.written.load(Ordering::Relaxed)
+    }
+
+    /// Number of ticks discarded because the queue was full or a write failed
+    pub fn dropped(&self) -> u64 {
+        self.buffer.dropped()
+    }
+
+    /// Number of ticks waiting to be written
+    pub fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Recorded ticks of an epic with `from <= ts < to`, oldest first
+    pub async fn query_ticks(
+        &self,
+        epic: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Tick>, sqlx::Error> {
+        query_ticks(&self.pool, epic, from, to).await
+    }
+
+    /// Writes every queued tick and stops the background writer
+    pub async fn shutdown(mut self) {
+        self.buffer.close();
+        self.wake.notify_one();
+        if let Some(writer) = self.writer.take()
+            && let Err(e) = writer.await
+        {
+            warn!("Tick writer ended abnormally: {}", e);
+        }
+        info!(
+            "Tick store stopped: {} ticks written, {} dropped",
+            self.written(),
+            self.dropped()
+        );
+    }
+}
+
+impl Drop for TickStore {
+    fn drop(&mut self) {
+        // Lets the writer flush what is queued and exit on its own
+        self.buffer.close();
+        self.wake.notify_one();
+    }
+}
+
+async fn run_writer(
+    pool: PgPool,
+    buffer: Arc<TickBuffer>,
+    wake: Arc<Notify>,
+    written: Arc<AtomicU64>,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    loop {
+        let _ = tokio::time::timeout(flush_interval, wake.notified()).await;
+        loop {
+            let batch = buffer.drain(batch_size);
+            if batch.is_empty() {
+                break;
+            }
+            match insert_ticks(&pool, &batch).await {
+                Ok(()) => {
+                    written.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    warn!(
+                        "Dropping {} ticks that could not be written: {}",
+                        batch.len(),
+                        e
+                    );
+                    buffer
+                        .dropped
+                        .fetch_add(batch.len() as u64, Ordering::Relaxed);
+                }
+            }
+        }
+        if buffer.closed.load(Ordering::Acquire) && buffer.is_empty() {
+            break;
+        }
+    }
+}
+
+/// Creates the `market_ticks` table, as a hypertable when TimescaleDB is available
+pub async fn initialize_ticks_table(pool: &PgPool) -> Result<(), sqlx::Error> {
+    info!("Initializing market_ticks table...");
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS market_ticks (
+            epic VARCHAR(255) NOT NULL,
+            ts TIMESTAMPTZ NOT NULL,
+            bid DOUBLE PRECISION,
+            offer DOUBLE PRECISION,
+            state VARCHAR(50)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    if let Err(e) =
+        sqlx::query("SELECT create_hypertable('market_ticks', 'ts', if_not_exists => TRUE)")
+            .execute(pool)
+            .await
+    {
+        warn!(
+            "market_ticks is a plain table, TimescaleDB hypertable not created: {}",
+            e
+        );
+    }
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_market_ticks_epic_ts
+        ON market_ticks(epic, ts DESC)
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    info!("✅ Market ticks table initialized successfully");
+    Ok(())
+}
+
+/// Inserts a batch of ticks with a single statement
+pub async fn insert_ticks(pool: &PgPool, ticks: &[Tick]) -> Result<(), sqlx::Error> {
+    let epics: Vec<&str> = ticks.iter().map(|tick| tick.epic.as_str()).collect();
+    let timestamps: Vec<DateTime<Utc>> = ticks.iter().map(|tick| tick.ts).collect();
+    let bids: Vec<Option<f64>> = ticks.iter().map(|tick| tick.fields.bid).collect();
+    let offers: Vec<Option<f64>> = ticks.iter().map(|tick| tick.fields.offer).collect();
+    let states: Vec<Option<&str>> = ticks
+        .iter()
+        .map(|tick| tick.fields.market_state.as_ref().map(MarketState::as_str))
+        .collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO market_ticks (epic, ts, bid, offer, state)
+        SELECT * FROM UNNEST($1::varchar[], $2::timestamptz[], $3::float8[], $4::float8[], $5::varchar[])
+        "#,
+    )
+    .bind(epics)
+    .bind(timestamps)
+    .bind(bids)
+    .bind(offers)
+    .bind(states)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Recorded ticks of an epic with `from <= ts < to`, oldest first
+pub async fn query_ticks(
+    pool: &PgPool,
+    epic: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Tick>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT epic, ts, bid, offer, state
+        FROM market_ticks
+        WHERE epic = $1 AND ts >= $2 AND ts < $3
+        ORDER BY ts
+        "#,
+    )
+    .bind(epic)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| Tick {
+            epic: row.get("epic"),
+            ts: row.get("ts"),
+            fields: MarketFields {
+                bid: row.get("bid"),
+                offer: row.get("offer"),
+                market_state: row
+                    .get::<Option<String>, _>("state")
+                    .as_deref()
+                    .and_then(MarketState::parse),
+                ..Default::default()
+            },
+        })
+        .collect())
+}
@@ -1,6 +1,8 @@
 use crate::error::AppError;
 use crate::presentation::transaction::StoreTransaction;
-use crate::storage::config::DatabaseConfig;
+use crate::storage::config::{
+    DEFAULT_ACQUIRE_TIMEOUT_SECS, DEFAULT_IDLE_TIMEOUT_SECS, DatabaseConfig, connect,
+};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use serde_json;
@@ -68,21 +70,17 @@ pub fn deserialize_from_json<T: DeserializeOwned>(s: &str) -> Result<T, serde_js
 /// Creates a PostgreSQL connection pool from database configuration
 ///
 /// # Arguments
-/// * `config` - Database configuration containing URL and max connections
+/// * `config` - Database configuration containing URL, max connections and timeouts
 ///
 /// # Returns
 /// * `Result<PgPool, AppError>` - Connection pool or an error
 pub async fn create_connection_pool(config: &DatabaseConfig) -> Result<PgPool, AppError> {
     info!(
-        "Creating PostgreSQL connection pool with max {} connections",
-        config.max_connections
+        "Creating PostgreSQL connection pool with max {} connections (acquire timeout {}s)",
+        config.max_connections, config.acquire_timeout
     );
 
-    let pool = sqlx::postgres::PgPoolOptions::new()
-        .max_connections(config.max_connections)
-        .connect(&config.url)
-        .await
-        .map_err(AppError::Db)?;
+    let pool = connect(config).await.map_err(AppError::Db)?;
 
     info!("PostgreSQL connection pool created successfully");
     Ok(pool)
@@ -105,8 +103,26 @@ pub fn create_database_config_from_env() -> Result<DatabaseConfig, AppError> {
             AppError::InvalidInput(format!("Invalid DATABASE_MAX_CONNECTIONS value: {e}"))
         })?;
 
+    let acquire_timeout = std::env::var("DATABASE_ACQUIRE_TIMEOUT")
+        .ok()
+        .map(|v| v.parse::<u64>())
+        .transpose()
+        .map_err(|e| {
+            AppError::InvalidInput(format!("Invalid DATABASE_ACQUIRE_TIMEOUT value: {e}"))
+        })?
+        .unwrap_or(DEFAULT_ACQUIRE_TIMEOUT_SECS);
+
+    let idle_timeout = std::env::var("DATABASE_IDLE_TIMEOUT")
+        .ok()
+        .map(|v| v.parse::<u64>())
+        .transpose()
+        .map_err(|e| AppError::InvalidInput(format!("Invalid DATABASE_IDLE_TIMEOUT value: {e}")))?
+        .unwrap_or(DEFAULT_IDLE_TIMEOUT_SECS);
+
     Ok(DatabaseConfig {
         url,
         max_connections,
+        acquire_timeout,
+        idle_timeout: Some(idle_timeout),
     })
 }
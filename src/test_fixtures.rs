@@ -0,0 +1,386 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Sample IG payloads and ready-made models for tests
+//!
+//! Available with the `testing` feature. Each `*_JSON` constant holds a payload
+//! shaped like a real IG response (anonymised, with the extra fields IG sends
+//! that the models ignore), and the function of the same name returns it
+//! deserialized. Downstream crates can use them to test code built on
+//! `ig-client` without hand-rolling fixtures:
+//!
+//! ```rust,ignore
+//! use ig_client::test_fixtures;
+//!
+//! let details = test_fixtures::market_details();
+//! assert_eq!(details.instrument.epic, "CS.D.EURUSD.CFD.IP");
+//! ```
+
+use crate::model::responses::{
+    AccountActivityResponse, OrderConfirmationResponse, PositionsResponse,
+    TransactionHistoryResponse,
+};
+use crate::presentation::account::{AccountTransaction, Position};
+use crate::presentation::market::MarketDetails;
+use serde::de::DeserializeOwned;
+
+/// `GET /markets/{epic}` (v3) for a EUR/USD CFD
+pub const MARKET_DETAILS_JSON: &str = r#"{
+  "instrument": {
+    "epic": "CS.D.EURUSD.CFD.IP",
+    "expiry": "-",
+    "name": "EUR/USD",
+    "forceOpenAllowed": true,
+    "stopsLimitsAllowed": true,
+    "lotSize": 1.0,
+    "unit": "CONTRACTS",
+    "type": "CURRENCIES",
+    "controlledRiskAllowed": true,
+    "streamingPricesAvailable": true,
+    "marketId": "EURUSD",
+    "currencies": [
+      {
+        "code": "USD",
+        "symbol": "$",
+        "baseExchangeRate": 1.0,
+        "exchangeRate": 0.86,
+        "isDefault": true
+      }
+    ],
+    "sprintMarketsMinimumExpiryTime": null,
+    "sprintMarketsMaximumExpiryTime": null,
+    "marginDepositBands": [
+      { "min": 0, "max": 124, "margin": 3.33, "currency": "USD" }
+    ],
+    "marginFactor": 3.33,
+    "marginFactorUnit": "PERCENTAGE",
+    "slippageFactor": { "unit": "pct", "value": 50.0 },
+    "limitedRiskPremium": { "value": 0.3, "unit": "POINTS" },
+    "openingHours": null,
+    "expiryDetails": null,
+    "rolloverDetails": null,
+    "newsCode": "EUR=",
+    "chartCode": "EURUSD",
+    "country": null,
+    "valueOfOnePip": "10.00",
+    "onePipMeans": "0.0001 USD/EUR",
+    "contractSize": "100000",
+    "specialInfo": ["MAX KNOCK OUT LEVEL DISTANCE", "DEFAULT KNOCK OUT LEVEL DISTANCE"],
+    "instrumentType": "CURRENCIES"
+  },
+  "dealingRules": {
+    "minStepDistance": { "unit": "POINTS", "value": 1.0 },
+    "minDealSize": { "unit": "POINTS", "value": 0.5 },
+    "minControlledRiskStopDistance": { "unit": "POINTS", "value": 10.0 },
+    "minNormalStopOrLimitDistance": { "unit": "POINTS", "value": 2.0 },
+    "maxStopOrLimitDistance": { "unit": "PERCENTAGE", "value": 75.0 },
+    "controlledRiskSpacing": { "unit": "POINTS", "value": 0.0 },
+    "marketOrderPreference": "AVAILABLE_DEFAULT_OFF",
+    "trailingStopsPreference": "AVAILABLE",
+    "maxDealSize": 1000.0
+  },
+  "snapshot": {
+    "marketStatus": "TRADEABLE",
+    "netChange": 0.0012,
+    "percentageChange": 0.11,
+    "updateTime": "14:32:05",
+    "delayTime": 0,
+    "bid": 1.16432,
+    "offer": 1.16441,
+    "high": 1.16587,
+    "low": 1.16214,
+    "binaryOdds": null,
+    "decimalPlacesFactor": 5,
+    "scalingFactor": 10000,
+    "controlledRiskExtraSpread": 0.4
+  }
+}"#;
+
+/// `GET /positions` (v2) with one long CFD position
+pub const POSITIONS_RESPONSE_JSON: &str = r#"{
+  "positions": [
+    {
+      "position": {
+        "contractSize": 100000.0,
+        "createdDate": "2025/10/14 09:12:44:000",
+        "createdDateUTC": "2025-10-14T08:12:44",
+        "dealId": "DIAAAAVJVM7Y9AR",
+        "dealReference": "XR4H8ZRF3NQTYQ5",
+        "workingOrderId": null,
+        "size": 1.5,
+        "leverage": 30.0,
+        "upl": 12.3,
+        "direction": "BUY",
+        "level": 1.16112,
+        "currency": "USD",
+        "controlledRisk": false,
+        "stopLevel": 1.15512,
+        "trailingStep": null,
+        "trailingStopDistance": null,
+        "limitedRiskPremium": null,
+        "limitLevel": 1.17112
+      },
+      "market": {
+        "instrumentName": "EUR/USD",
+        "expiry": "-",
+        "epic": "CS.D.EURUSD.CFD.IP",
+        "instrumentType": "CURRENCIES",
+        "lotSize": 1.0,
+        "high": 1.16587,
+        "low": 1.16214,
+        "percentageChange": 0.11,
+        "netChange": 0.0012,
+        "bid": 1.16432,
+        "offer": 1.16441,
+        "updateTime": "14:32:05",
+        "updateTimeUTC": "13:32:05",
+        "delayTime": 0,
+        "streamingPricesAvailable": true,
+        "marketStatus": "TRADEABLE",
+        "scalingFactor": 10000
+      }
+    }
+  ]
+}"#;
+
+/// `GET /confirms/{dealReference}` for an accepted market order
+pub const ORDER_CONFIRMATION_JSON: &str = r#"{
+  "date": "2025-10-14T08:12:44.421",
+  "status": "OPEN",
+  "reason": "SUCCESS",
+  "dealStatus": "ACCEPTED",
+  "epic": "CS.D.EURUSD.CFD.IP",
+  "expiry": "-",
+  "dealReference": "XR4H8ZRF3NQTYQ5",
+  "dealId": "DIAAAAVJVM7Y9AR",
+  "affectedDeals": [
+    { "dealId": "DIAAAAVJVM7Y9AR", "status": "OPENED" }
+  ],
+  "level": 1.16112,
+  "size": 1.5,
+  "direction": "BUY",
+  "stopLevel": 1.15512,
+  "limitLevel": 1.17112,
+  "stopDistance": null,
+  "limitDistance": null,
+  "guaranteedStop": false,
+  "trailingStop": false,
+  "profit": null,
+  "profitCurrency": null
+}"#;
+
+/// `GET /history/activity` (v3, `detailed=true`) with an opened position
+pub const ACCOUNT_ACTIVITY_JSON: &str = r#"{
+  "activities": [
+    {
+      "date": "2025-10-14T09:12:44",
+      "epic": "CS.D.EURUSD.CFD.IP",
+      "period": "-",
+      "dealId": "DIAAAAVJVM7Y9AR",
+      "channel": "PUBLIC_WEB_API",
+      "type": "POSITION",
+      "status": "ACCEPTED",
+      "description": "Position opened: VJVM7Y9AR",
+      "details": {
+        "dealReference": "XR4H8ZRF3NQTYQ5",
+        "actions": [
+          { "actionType": "POSITION_OPENED", "affectedDealId": "DIAAAAVJVM7Y9AR" }
+        ],
+        "marketName": "EUR/USD",
+        "goodTillDate": null,
+        "currency": "USD",
+        "size": 1.5,
+        "direction": "BUY",
+        "level": 1.16112,
+        "stopLevel": 1.15512,
+        "stopDistance": null,
+        "guaranteedStop": false,
+        "trailingStopDistance": null,
+        "trailingStep": null,
+        "limitLevel": 1.17112,
+        "limitDistance": null
+      }
+    }
+  ],
+  "metadata": {
+    "paging": { "size": 1, "next": null }
+  }
+}"#;
+
+/// `GET /history/transactions` (v2) with a closed trade
+pub const TRANSACTION_HISTORY_JSON: &str = r#"{
+  "transactions": [
+    {
+      "date": "2025-10-14",
+      "dateUtc": "2025-10-14T15:40:02",
+      "openDateUtc": "2025-10-14T08:12:44",
+      "instrumentName": "EUR/USD",
+      "period": "-",
+      "profitAndLoss": "$48.00",
+      "transactionType": "TRADE",
+      "reference": "VJVM7Y9AR",
+      "openLevel": "1.16112",
+      "closeLevel": "1.16432",
+      "size": "+1.5",
+      "currency": "$",
+      "cashTransaction": false
+    }
+  ],
+  "metadata": {
+    "size": 1,
+    "pageData": { "pageNumber": 1, "pageSize": 50, "totalPages": 1 }
+  }
+}"#;
+
+/// `GET /history/transactions` (v2) mixing trades with cash movements
+///
+/// Holds a gain and a loss written with the sign on either side of the
+/// currency marker, a deposit, a withdrawal IG reported as a positive amount
+/// and a charge reported as a negative one. Their `signed_pnl` sum is `-3.10`.
+pub const CASH_TRANSACTION_HISTORY_JSON: &str = r#"{
+  "transactions": [
+    {
+      "date": "2025-10-13",
+      "dateUtc": "2025-10-13T09:00:00",
+      "openDateUtc": "-",
+      "instrumentName": "Bank Deposit",
+      "period": "-",
+      "profitAndLoss": "£1,000.00",
+      "transactionType": "DEPO",
+      "reference": "DP1000001",
+      "openLevel": "-",
+      "closeLevel": "-",
+      "size": "-",
+      "currency": "£",
+      "cashTransaction": true
+    },
+    {
+      "date": "2025-10-14",
+      "dateUtc": "2025-10-14T15:40:02",
+      "openDateUtc": "2025-10-14T08:12:44",
+      "instrumentName": "FTSE 100",
+      "period": "DFB",
+      "profitAndLoss": "£1.50",
+      "transactionType": "TRADE",
+      "reference": "VJVM7Y9AR",
+      "openLevel": "9400.5",
+      "closeLevel": "9402",
+      "size": "+1",
+      "currency": "£",
+      "cashTransaction": false
+    },
+    {
+      "date": "2025-10-14",
+      "dateUtc": "2025-10-14T16:05:11",
+      "openDateUtc": "2025-10-14T10:30:00",
+      "instrumentName": "FTSE 100",
+      "period": "DFB",
+      "profitAndLoss": "-£4.10",
+      "transactionType": "TRADE",
+      "reference": "VJVM8A2BS",
+      "openLevel": "9405.1",
+      "closeLevel": "9401",
+      "size": "+1",
+      "currency": "£",
+      "cashTransaction": false
+    },
+    {
+      "date": "2025-10-15",
+      "dateUtc": "2025-10-15T00:00:00",
+      "openDateUtc": "-",
+      "instrumentName": "Overnight Funding",
+      "period": "-",
+      "profitAndLoss": "£-0.50",
+      "transactionType": "WITH",
+      "reference": "FN0000001",
+      "openLevel": "-",
+      "closeLevel": "-",
+      "size": "-",
+      "currency": "£",
+      "cashTransaction": true
+    },
+    {
+      "date": "2025-10-16",
+      "dateUtc": "2025-10-16T11:20:00",
+      "openDateUtc": "-",
+      "instrumentName": "Bank Withdrawal",
+      "period": "-",
+      "profitAndLoss": "£1,000.00",
+      "transactionType": "WITH",
+      "reference": "WD1000001",
+      "openLevel": "-",
+      "closeLevel": "-",
+      "size": "-",
+      "currency": "£",
+      "cashTransaction": true
+    }
+  ],
+  "metadata": {
+    "size": 5,
+    "pageData": { "pageNumber": 1, "pageSize": 50, "totalPages": 1 }
+  }
+}"#;
+
+fn parse<T: DeserializeOwned>(name: &str, json: &str) -> T {
+    serde_json::from_str(json)
+        .unwrap_or_else(|e| panic!("{name} fixture no longer matches the model: {e}"))
+}
+
+/// Market details for `CS.D.EURUSD.CFD.IP`, parsed from `MARKET_DETAILS_JSON`
+pub fn market_details() -> MarketDetails {
+    parse("MARKET_DETAILS_JSON", MARKET_DETAILS_JSON)
+}
+
+/// Open positions parsed from `POSITIONS_RESPONSE_JSON`
+pub fn positions_response() -> PositionsResponse {
+    parse("POSITIONS_RESPONSE_JSON", POSITIONS_RESPONSE_JSON)
+}
+
+/// The single long EUR/USD position from `positions_response`
+pub fn position() -> Position {
+    positions_response()
+        .positions
+        .into_iter()
+        .next()
+        .expect("POSITIONS_RESPONSE_JSON has one position")
+}
+
+/// Accepted deal confirmation parsed from `ORDER_CONFIRMATION_JSON`
+pub fn order_confirmation() -> OrderConfirmationResponse {
+    parse("ORDER_CONFIRMATION_JSON", ORDER_CONFIRMATION_JSON)
+}
+
+/// Detailed account activity parsed from `ACCOUNT_ACTIVITY_JSON`
+pub fn account_activity() -> AccountActivityResponse {
+    parse("ACCOUNT_ACTIVITY_JSON", ACCOUNT_ACTIVITY_JSON)
+}
+
+/// Transaction history parsed from `TRANSACTION_HISTORY_JSON`
+pub fn transaction_history() -> TransactionHistoryResponse {
+    parse("TRANSACTION_HISTORY_JSON", TRANSACTION_HISTORY_JSON)
+}
+
+/// The EUR/USD trade from `transaction_history` with another type and P&L
+///
+/// Use struct update syntax to vary the remaining fields.
+pub fn account_transaction(transaction_type: &str, profit_and_loss: &str) -> AccountTransaction {
+    let mut transaction = transaction_history()
+        .transactions
+        .into_iter()
+        .next()
+        .expect("TRANSACTION_HISTORY_JSON has one transaction");
+    transaction.transaction_type = transaction_type.to_string();
+    transaction.profit_and_loss = profit_and_loss.to_string();
+    transaction
+}
+
+/// Trades, deposits and withdrawals parsed from `CASH_TRANSACTION_HISTORY_JSON`
+pub fn cash_transaction_history() -> TransactionHistoryResponse {
+    parse(
+        "CASH_TRANSACTION_HISTORY_JSON",
+        CASH_TRANSACTION_HISTORY_JSON,
+    )
+}
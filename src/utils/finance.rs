@@ -2,8 +2,17 @@
 //
 // Financial calculation utilities for the IG client
 
+use crate::constants::DEFAULT_SIZE_STEP;
 use crate::presentation::account::Position;
+#[cfg(feature = "options")]
+use crate::presentation::market::MarketDetails;
+use crate::presentation::market::{HistoricalPrice, Instrument, PricePoint};
 use crate::presentation::order::Direction;
+#[cfg(feature = "options")]
+use crate::utils::parsing::{OptionEpic, OptionSide};
+#[cfg(feature = "options")]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 /// Calculate the Profit and Loss (P&L) for a position based on current market prices
 ///
@@ -13,16 +22,14 @@ use crate::presentation::order::Direction;
 ///
 /// # Returns
 ///
-/// * `Option<f64>` - The calculated P&L if market prices are available, None otherwise
+/// * `Option<f64>` - The calculated P&L if market prices are available, None otherwise;
+///   `Position::unrealised_pnl` reports a missing price as an error instead
 ///
 pub fn calculate_pnl(position: &Position) -> Option<f64> {
-    let (bid, offer) = (position.market.bid, position.market.offer);
-
-    // Get the appropriate price based on direction
-    let current_price = match position.position.direction {
-        Direction::Buy => bid?,
-        Direction::Sell => offer?,
-    };
+    // Valued at the price the position would close at
+    let current_price = position
+        .market
+        .current_price(&position.position.direction.opposite())?;
 
     // Calculate price difference
     let price_diff = match position.position.direction {
@@ -54,3 +61,409 @@ pub fn calculate_percentage_return(position: &Position) -> Option<f64> {
 
     Some((pnl / initial_value) * 100.0)
 }
+
+/// Calculate the P&L of a trade on an instrument from entry and exit levels
+///
+/// # Arguments
+///
+/// * `instrument` - Instrument traded, providing the contract size
+/// * `direction` - Direction of the trade
+/// * `entry_level` - Level at which the trade was opened
+/// * `exit_level` - Level at which the trade is valued or closed
+/// * `size` - Number of contracts
+///
+/// # Returns
+///
+/// * `Option<f64>` - The P&L in the instrument currency, None if the contract size cannot be parsed
+pub fn calculate_instrument_pnl(
+    instrument: &Instrument,
+    direction: &Direction,
+    entry_level: f64,
+    exit_level: f64,
+    size: f64,
+) -> Option<f64> {
+    let contract_size = instrument.contract_size_value()?;
+    let price_diff = match direction {
+        Direction::Buy => exit_level - entry_level,
+        Direction::Sell => entry_level - exit_level,
+    };
+    Some(price_diff * size * contract_size)
+}
+
+/// Calculate the margin required to open a position on an instrument
+///
+/// A `PERCENTAGE` margin factor is applied to the notional value
+/// (`size * contract_size * level`); a `POINTS` margin factor is applied per
+/// contract (`size * contract_size * margin_factor`).
+///
+/// # Arguments
+///
+/// * `instrument` - Instrument traded, providing contract size and margin factor
+/// * `size` - Number of contracts
+/// * `level` - Price level at which the position would be opened
+///
+/// # Returns
+///
+/// * `Option<f64>` - The required margin, None if the contract size or margin factor is missing
+pub fn calculate_margin(instrument: &Instrument, size: f64, level: f64) -> Option<f64> {
+    let contract_size = instrument.contract_size_value()?;
+    let margin_factor = instrument.margin_factor?;
+    match instrument.margin_factor_unit.as_deref() {
+        Some("POINTS") => Some(size * contract_size * margin_factor),
+        _ => Some(size * contract_size * level * margin_factor / 100.0),
+    }
+}
+
+/// Resolve a single price from a price point
+///
+/// Uses the last traded price when available, otherwise the bid/ask mid,
+/// falling back to whichever side is present.
+fn reference_price(point: &PricePoint) -> Option<f64> {
+    match (point.last_traded, point.bid, point.ask) {
+        (Some(last), _, _) => Some(last),
+        (None, Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+        (None, bid, ask) => bid.or(ask),
+    }
+}
+
+/// Calculate the volume-weighted average price (VWAP) of a price series
+///
+/// Each candle contributes its close price weighted by `last_traded_volume`.
+/// Candles without volume (missing or zero) or without a close price are skipped.
+///
+/// # Arguments
+///
+/// * `prices` - Historical candles to aggregate
+///
+/// # Returns
+///
+/// * `Option<f64>` - The VWAP, or None if no candle carries volume data
+pub fn vwap(prices: &[HistoricalPrice]) -> Option<f64> {
+    let (weighted_sum, volume_sum) = prices
+        .iter()
+        .filter_map(|price| {
+            let volume = price.last_traded_volume.filter(|v| *v > 0)? as f64;
+            let close = reference_price(&price.close_price)?;
+            Some((close * volume, volume))
+        })
+        .fold((0.0, 0.0), |(sum, vol), (pv, v)| (sum + pv, vol + v));
+
+    if volume_sum == 0.0 {
+        return None;
+    }
+
+    Some(weighted_sum / volume_sum)
+}
+
+/// Calculate the total traded volume of a price series
+///
+/// # Arguments
+///
+/// * `prices` - Historical candles to aggregate
+///
+/// # Returns
+///
+/// * `i64` - Sum of `last_traded_volume`, treating missing values as zero
+pub fn total_volume(prices: &[HistoricalPrice]) -> i64 {
+    prices
+        .iter()
+        .filter_map(|price| price.last_traded_volume)
+        .sum()
+}
+
+/// Calculate the position size that risks a percentage of equity if the stop is hit
+///
+/// The size is rounded down to `DEFAULT_SIZE_STEP` so the risk never exceeds the
+/// requested percentage. Use `size_for_risk_with_step` for instruments with a
+/// different deal size increment.
+///
+/// # Arguments
+///
+/// * `equity` - Account equity in the account currency
+/// * `risk_pct` - Percentage of equity to risk (e.g. `1.0` for 1%)
+/// * `stop_distance_points` - Distance to the stop in points
+/// * `pip_value` - Value of one point per unit of size, in the account currency
+///
+/// # Returns
+///
+/// * `f64` - The position size, or `0.0` if any input is not positive
+pub fn size_for_risk(equity: f64, risk_pct: f64, stop_distance_points: f64, pip_value: f64) -> f64 {
+    size_for_risk_with_step(
+        equity,
+        risk_pct,
+        stop_distance_points,
+        pip_value,
+        DEFAULT_SIZE_STEP,
+    )
+}
+
+/// Calculate the position size for a risk percentage, rounded down to `size_step`
+///
+/// # Arguments
+///
+/// * `equity` - Account equity in the account currency
+/// * `risk_pct` - Percentage of equity to risk (e.g. `1.0` for 1%)
+/// * `stop_distance_points` - Distance to the stop in points
+/// * `pip_value` - Value of one point per unit of size, in the account currency
+/// * `size_step` - Deal size increment of the instrument
+///
+/// # Returns
+///
+/// * `f64` - The position size, or `0.0` if any input is not positive
+pub fn size_for_risk_with_step(
+    equity: f64,
+    risk_pct: f64,
+    stop_distance_points: f64,
+    pip_value: f64,
+    size_step: f64,
+) -> f64 {
+    if [equity, risk_pct, stop_distance_points, pip_value]
+        .iter()
+        .any(|value| !value.is_finite() || *value <= 0.0)
+    {
+        return 0.0;
+    }
+
+    let risk_amount = equity * risk_pct / 100.0;
+    let size = risk_amount / (stop_distance_points * pip_value);
+    round_down_to_step(size, size_step)
+}
+
+/// Round a size down to a multiple of `step`
+///
+/// A small tolerance absorbs floating point error so that exact multiples are
+/// not rounded down by one step. A non-positive step leaves the size unchanged.
+pub fn round_down_to_step(size: f64, step: f64) -> f64 {
+    round_to_step(size, step, RoundingMode::Floor)
+}
+
+/// Round a size to a multiple of `step` in the given direction
+///
+/// Exact multiples are kept as they are despite floating point error, and the
+/// result carries no representation noise (`0.3`, not `0.30000000000000004`).
+/// A non-positive step leaves the size unchanged.
+pub fn round_to_step(size: f64, step: f64, mode: RoundingMode) -> f64 {
+    if !(step.is_finite() && step > 0.0) {
+        return size;
+    }
+    let ratio = size / step;
+    let steps = match mode {
+        RoundingMode::Floor => (ratio + 1e-9).floor(),
+        RoundingMode::Nearest => ratio.round(),
+        RoundingMode::Ceil => (ratio - 1e-9).ceil(),
+    };
+    // Round away representation noise (e.g. 0.30000000000000004)
+    let factor = 10f64.powi(step_decimals(step));
+    (steps * step * factor).round() / factor
+}
+
+/// Number of decimals in the shortest decimal representation of `step`
+///
+/// `0.25` has two and `2.5` one, so a multiple of the step is exact at that
+/// precision. Capped at 15, beyond which an `f64` holds no more digits.
+fn step_decimals(step: f64) -> i32 {
+    let repr = step.to_string();
+    let decimals = repr
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    i32::try_from(decimals).unwrap_or(i32::MAX).min(15)
+}
+
+/// Direction in which order sizes are rounded to the deal size step
+///
+/// `Floor` is the default: the rounded order never exposes more than was
+/// asked for. `Nearest` keeps the size closest to the request and `Ceil`
+/// never exposes less.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RoundingMode {
+    /// Round towards zero exposure, never above the requested size
+    #[default]
+    Floor,
+    /// Round to the nearest step, halves away from zero
+    Nearest,
+    /// Round up, never below the requested size
+    Ceil,
+}
+
+/// Option pricing models used to back out implied volatility
+///
+/// Available with the `options` feature. IG quotes option prices but no
+/// Greeks; implement this trait to plug in a model, or use [`BlackScholes`].
+#[cfg(feature = "options")]
+pub trait OptionPricing {
+    /// Implied volatility of an option from its price
+    ///
+    /// # Arguments
+    /// * `option` - Strike and side of the option
+    /// * `underlying_price` - Current level of the underlying
+    /// * `option_price` - Price of the option, in the same units
+    /// * `days_to_expiry` - Calendar days left until expiry
+    /// * `rate` - Continuously compounded risk-free rate, e.g. `0.03`
+    ///
+    /// # Returns
+    /// * `Some(f64)` - Annualised volatility, e.g. `0.2` for 20%
+    /// * `None` - If the inputs admit no volatility, e.g. a price below
+    ///   intrinsic value
+    fn implied_vol(
+        &self,
+        option: &OptionEpic,
+        underlying_price: f64,
+        option_price: f64,
+        days_to_expiry: f64,
+        rate: f64,
+    ) -> Option<f64>;
+
+    /// Implied volatility of the option described by `details`
+    ///
+    /// Strike and side are parsed from the epic and the time to expiry from
+    /// the last dealing date.
+    ///
+    /// # Returns
+    /// * `None` - If the epic is not an option epic, the market has no last
+    ///   dealing date or `implied_vol` finds no solution
+    fn implied_vol_for_market(
+        &self,
+        details: &MarketDetails,
+        underlying_price: f64,
+        option_price: f64,
+        rate: f64,
+        now: DateTime<Utc>,
+    ) -> Option<f64> {
+        let option = OptionEpic::parse(&details.instrument.epic)?;
+        let days_to_expiry = details.instrument.days_to_expiry(now)?;
+        self.implied_vol(
+            &option,
+            underlying_price,
+            option_price,
+            days_to_expiry,
+            rate,
+        )
+    }
+}
+
+/// European option pricing with the Black-Scholes model
+///
+/// Available with the `options` feature. IG's options are European and cash
+/// settled, so this is a reasonable default; dividends are not modelled.
+#[cfg(feature = "options")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlackScholes;
+
+#[cfg(feature = "options")]
+impl BlackScholes {
+    /// Lowest volatility searched by `implied_vol`
+    const MIN_VOL: f64 = 1e-6;
+    /// Highest volatility searched by `implied_vol` (1000%)
+    const MAX_VOL: f64 = 10.0;
+    /// Price tolerance at which the search stops
+    const TOLERANCE: f64 = 1e-10;
+
+    /// Theoretical price of a European option
+    ///
+    /// # Arguments
+    /// * `side` - Call or put
+    /// * `underlying_price` - Current level of the underlying
+    /// * `strike` - Strike price
+    /// * `years` - Time to expiry in years
+    /// * `rate` - Continuously compounded risk-free rate
+    /// * `vol` - Annualised volatility
+    pub fn price(
+        side: OptionSide,
+        underlying_price: f64,
+        strike: f64,
+        years: f64,
+        rate: f64,
+        vol: f64,
+    ) -> f64 {
+        let discount = (-rate * years).exp();
+        if years <= 0.0 || vol <= 0.0 {
+            return match side {
+                OptionSide::Call => (underlying_price - strike * discount).max(0.0),
+                OptionSide::Put => (strike * discount - underlying_price).max(0.0),
+            };
+        }
+        let sd = vol * years.sqrt();
+        let d1 = ((underlying_price / strike).ln() + (rate + vol * vol / 2.0) * years) / sd;
+        let d2 = d1 - sd;
+        match side {
+            OptionSide::Call => {
+                underlying_price * normal_cdf(d1) - strike * discount * normal_cdf(d2)
+            }
+            OptionSide::Put => {
+                strike * discount * normal_cdf(-d2) - underlying_price * normal_cdf(-d1)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "options")]
+impl OptionPricing for BlackScholes {
+    /// Solves for volatility by bisection, which always converges since the
+    /// price increases with volatility
+    fn implied_vol(
+        &self,
+        option: &OptionEpic,
+        underlying_price: f64,
+        option_price: f64,
+        days_to_expiry: f64,
+        rate: f64,
+    ) -> Option<f64> {
+        let inputs = [underlying_price, option_price, days_to_expiry, rate];
+        if inputs.iter().any(|value| !value.is_finite())
+            || underlying_price <= 0.0
+            || option_price <= 0.0
+            || days_to_expiry <= 0.0
+        {
+            return None;
+        }
+        let years = days_to_expiry / 365.0;
+        let price = |vol| {
+            BlackScholes::price(
+                option.side,
+                underlying_price,
+                option.strike,
+                years,
+                rate,
+                vol,
+            )
+        };
+
+        let (mut low, mut high) = (Self::MIN_VOL, Self::MAX_VOL);
+        if option_price < price(low) || option_price > price(high) {
+            return None;
+        }
+        for _ in 0..200 {
+            let mid = (low + high) / 2.0;
+            let diff = price(mid) - option_price;
+            if diff.abs() < Self::TOLERANCE {
+                return Some(mid);
+            }
+            if diff > 0.0 {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Some((low + high) / 2.0)
+    }
+}
+
+/// Standard normal cumulative distribution function
+///
+/// Uses the Abramowitz and Stegun approximation 7.1.26 of `erf`, accurate to
+/// about 1e-7, which is well below the precision of quoted option prices.
+#[cfg(feature = "options")]
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * z);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
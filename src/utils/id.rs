@@ -1,3 +1,5 @@
+use crate::constants::{DEAL_REFERENCE_MAX_LEN, DEAL_REFERENCE_MIN_SUFFIX_LEN};
+
 /// Generates a unique identifier as an optional `String`.
 ///
 /// This function creates a 30-character long unique identifier composed of
@@ -32,3 +34,61 @@ pub fn get_id() -> Option<String> {
     let alphabet: Vec<char> = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789".chars().collect();
     Some(nanoid::nanoid!(30, &alphabet))
 }
+
+/// Generates a deal reference that starts with `prefix`
+///
+/// The prefix is followed by random characters from nanoid's URL-safe
+/// alphabet, as used for the references the order constructors generate, so
+/// that the whole reference is `DEAL_REFERENCE_MAX_LEN` (30) characters long.
+/// A prefix longer than `DEAL_REFERENCE_MAX_LEN - DEAL_REFERENCE_MIN_SUFFIX_LEN`
+/// characters is cut to that length so every reference keeps a random part.
+///
+/// # Examples
+/// ```
+/// use ig_client::utils::id::generate_deal_reference;
+/// let reference = generate_deal_reference("mystrat-");
+/// assert!(reference.starts_with("mystrat-"));
+/// assert_eq!(reference.len(), 30);
+/// ```
+pub fn generate_deal_reference(prefix: &str) -> String {
+    let prefix: String = prefix
+        .chars()
+        .take(DEAL_REFERENCE_MAX_LEN - DEAL_REFERENCE_MIN_SUFFIX_LEN)
+        .collect();
+    let suffix_len = DEAL_REFERENCE_MAX_LEN - prefix.chars().count();
+    format!(
+        "{prefix}{}",
+        nanoid::nanoid!(suffix_len, &nanoid::alphabet::SAFE)
+    )
+}
+
+/// Generates a random UUID (version 4) string used to correlate HTTP requests.
+///
+/// The identifier is built from 32 random hexadecimal digits produced by
+/// `nanoid`, with the version and variant nibbles set as required by RFC 4122,
+/// e.g. `3f2b8c1e-9d4a-4c6b-a1e2-7f0d5b3c9e81`.
+///
+/// # Returns
+/// A lowercase, hyphenated UUID v4 string.
+///
+/// # Examples
+/// ```
+/// use ig_client::utils::id::generate_request_id;
+/// let request_id = generate_request_id();
+/// assert_eq!(request_id.len(), 36);
+/// ```
+pub fn generate_request_id() -> String {
+    let alphabet: Vec<char> = "0123456789abcdef".chars().collect();
+    let mut hex: Vec<char> = nanoid::nanoid!(32, &alphabet).chars().collect();
+    hex[12] = '4';
+    hex[16] = ['8', '9', 'a', 'b'][hex[16].to_digit(16).unwrap_or(0) as usize % 4];
+    let hex: String = hex.into_iter().collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
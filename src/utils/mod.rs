@@ -8,6 +8,10 @@ pub mod id;
 pub mod logger;
 /// Module containing parsing utilities for instrument names and other data
 pub mod parsing;
+/// Module containing redaction of credentials for request logging
+pub mod redact;
+/// Module containing coalescing of concurrent async operations
+pub mod single_flight;
 
 pub use finance::*;
 pub use id::*;
@@ -1,4 +1,6 @@
+use crate::presentation::instrument::InstrumentType;
 use crate::presentation::order::Status;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
@@ -200,6 +202,186 @@ pub fn parse_instrument_name(instrument_name: &str) -> ParsedOptionInfo {
     }
 }
 
+/// Date time formats used across IG's REST responses, without a time zone
+const IG_DATETIME_FORMATS: [&str; 8] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y/%m/%d %H:%M:%S:%3f",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y:%m:%d-%H:%M:%S",
+    "%Y/%m/%d %H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parses a date time as IG writes it in REST responses
+///
+/// Accepts RFC 3339 and the zone-less formats IG uses across endpoints and
+/// API versions (`2024-01-31T10:15:00`, `2024/01/31 10:15:00`,
+/// `2024:01:31-10:15:00`, ...). Zone-less values are taken to be UTC, so pass
+/// the `...UTC` variant of a field when IG provides one.
+///
+/// # Returns
+/// * `Some(DateTime<Utc>)` - If the value is in one of the known formats
+/// * `None` - Otherwise, e.g. for IG's `-` placeholder
+///
+/// # Examples
+///
+/// ```
+/// use ig_client::utils::parsing::parse_ig_datetime;
+///
+/// let parsed = parse_ig_datetime("2024/01/31 10:15:00").unwrap();
+/// assert_eq!(parsed.to_rfc3339(), "2024-01-31T10:15:00+00:00");
+/// assert!(parse_ig_datetime("-").is_none());
+/// ```
+pub fn parse_ig_datetime(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(raw) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    IG_DATETIME_FORMATS.iter().find_map(|format| {
+        NaiveDateTime::parse_from_str(raw, format)
+            .ok()
+            .map(|naive| naive.and_utc())
+    })
+}
+
+/// Parses an HTTP date such as the `Date` response header
+///
+/// # Returns
+/// * `Some(DateTime<Utc>)` - For an RFC 9110 date (`Wed, 21 Oct 2015 07:28:00 GMT`)
+/// * `None` - Otherwise
+pub fn parse_http_date(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(raw.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Side of an option contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OptionSide {
+    /// Right to buy the underlying
+    Call,
+    /// Right to sell the underlying
+    Put,
+}
+
+/// Strike and side of an IG option, parsed from its epic
+///
+/// IG option epics encode the contract in the fourth segment as the strike
+/// followed by `C` or `P`, e.g. `OP.D.OTCDAX1.021100P.IP` is a 21100 put on
+/// the `OTCDAX1` series.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionEpic {
+    /// Epic as given
+    pub epic: String,
+    /// Option series the contract belongs to, e.g. `OTCDAX1`
+    pub series: String,
+    /// Strike price
+    pub strike: f64,
+    /// Call or put
+    pub side: OptionSide,
+}
+
+impl OptionEpic {
+    /// Parses an option epic
+    ///
+    /// # Returns
+    /// * `Some(OptionEpic)` - For `OP` epics whose fourth segment is a strike
+    ///   followed by `C` or `P`
+    /// * `None` - Otherwise, including for digital options (`DO`), whose epics
+    ///   carry no strike
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ig_client::utils::parsing::{OptionEpic, OptionSide};
+    ///
+    /// let option = OptionEpic::parse("OP.D.OTCASX1.7725C.IP").unwrap();
+    /// assert_eq!(option.series, "OTCASX1");
+    /// assert_eq!(option.strike, 7725.0);
+    /// assert_eq!(option.side, OptionSide::Call);
+    /// assert!(OptionEpic::parse("IX.D.DAX.DAILY.IP").is_none());
+    /// ```
+    pub fn parse(epic: &str) -> Option<Self> {
+        let segments: Vec<&str> = epic.trim().split('.').collect();
+        let [prefix, _, series, contract, _] = segments.as_slice() else {
+            return None;
+        };
+        if !prefix.eq_ignore_ascii_case("OP") {
+            return None;
+        }
+        let (strike, side) = contract.split_at(contract.len().checked_sub(1)?);
+        let side = match side.to_ascii_uppercase().as_str() {
+            "C" => OptionSide::Call,
+            "P" => OptionSide::Put,
+            _ => return None,
+        };
+        let strike = strike
+            .parse::<f64>()
+            .ok()
+            .filter(|strike| strike.is_finite() && *strike > 0.0)?;
+        Some(Self {
+            epic: epic.trim().to_string(),
+            series: series.to_string(),
+            strike,
+            side,
+        })
+    }
+}
+
+/// Spot metals quoted under the `CS` (currencies) prefix, e.g. `CS.D.USCGC.TODAY.IP`
+const SPOT_METAL_EPIC_CODES: [&str; 2] = ["USCGC", "USCSI"];
+
+/// Infers the instrument type from an epic's prefix, without calling the API
+///
+/// IG groups epics by their first segment. The recognised prefixes are:
+///
+/// | Prefix | Instrument type | Example |
+/// |--------|-----------------|---------|
+/// | `IX` | `Indices` | `IX.D.DAX.DAILY.IP` |
+/// | `CS` | `Currencies` (spot gold and silver: `Commodities`) | `CS.D.EURUSD.TODAY.IP` |
+/// | `CC`, `EN`, `MT` | `Commodities` | `EN.D.CL.Month1.IP` |
+/// | `IR` | `Rates` | `IR.D.10YEAR100.Month2.IP` |
+/// | `UA`, `UB`, `UC`, `UD`, `KA`, `KB` | `Shares` | `UA.D.AAPL.CASH.IP` |
+/// | `OP`, `DO` | `Options` | `OP.D.OTCDAX1.021100P.IP` |
+///
+/// The prefix does not tell what an option is written on, so options map to
+/// the generic `Options` type. Matching ignores case.
+///
+/// # Returns
+/// * `Some(InstrumentType)` - If the prefix is one of the above
+/// * `None` - For any other prefix; fetch the market details instead
+///
+/// # Examples
+///
+/// ```
+/// use ig_client::presentation::instrument::InstrumentType;
+/// use ig_client::utils::parsing::instrument_type_from_epic;
+///
+/// assert_eq!(
+///     instrument_type_from_epic("IX.D.DAX.DAILY.IP"),
+///     Some(InstrumentType::Indices)
+/// );
+/// assert_eq!(instrument_type_from_epic("ZZ.D.UNKNOWN.IP"), None);
+/// ```
+pub fn instrument_type_from_epic(epic: &str) -> Option<InstrumentType> {
+    let mut segments = epic.trim().split('.');
+    let prefix = segments.next()?.to_ascii_uppercase();
+    let code = segments.nth(1).unwrap_or_default().to_ascii_uppercase();
+
+    match prefix.as_str() {
+        "IX" => Some(InstrumentType::Indices),
+        "CS" if SPOT_METAL_EPIC_CODES.contains(&code.as_str()) => Some(InstrumentType::Commodities),
+        "CS" => Some(InstrumentType::Currencies),
+        "CC" | "EN" | "MT" => Some(InstrumentType::Commodities),
+        "IR" => Some(InstrumentType::Rates),
+        "UA" | "UB" | "UC" | "UD" | "KA" | "KB" => Some(InstrumentType::Shares),
+        "OP" | "DO" => Some(InstrumentType::Options),
+        _ => None,
+    }
+}
+
 /// Helper function to deserialize null values as empty vectors
 pub fn deserialize_null_as_empty_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
 where
@@ -0,0 +1,131 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Redaction of credentials for request and response logging
+//!
+//! Used by `HttpClient` when `RestApiConfig::log_bodies` is enabled. Headers
+//! and JSON fields that carry credentials or session tokens are replaced with
+//! `[REDACTED]` before anything is written to the log.
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::trace;
+
+/// Placeholder written in place of a redacted value
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers that carry the API key or session tokens
+const SENSITIVE_HEADERS: [&str; 4] = ["x-ig-api-key", "cst", "x-security-token", "authorization"];
+
+/// Returns true if a header value must not be logged
+pub fn is_sensitive_header(name: &str) -> bool {
+    SENSITIVE_HEADERS
+        .iter()
+        .any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+}
+
+/// Returns true if a JSON field value must not be logged
+///
+/// Matching ignores case, `_` and `-`, and covers anything that looks like a
+/// password, token, secret or key, plus the login `identifier`.
+pub fn is_sensitive_field(name: &str) -> bool {
+    let normalised: String = name
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    normalised.contains("password")
+        || normalised.contains("token")
+        || normalised.contains("secret")
+        || normalised.ends_with("key")
+        || normalised == "cst"
+        || normalised == "identifier"
+}
+
+/// Formats headers for logging with sensitive values redacted
+pub fn redact_headers(headers: &[(&str, &str)]) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            if is_sensitive_header(name) {
+                format!("{name}: {REDACTED}")
+            } else {
+                format!("{name}: {value}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Replaces the values of sensitive fields, at any depth, with `[REDACTED]`
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_sensitive_field(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Serializes a request body for logging with sensitive fields redacted
+pub fn redact_body<B: Serialize>(body: &B) -> String {
+    match serde_json::to_value(body) {
+        Ok(mut value) => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        Err(_) => "<unserializable body>".to_string(),
+    }
+}
+
+/// Formats a response body for logging with sensitive fields redacted
+///
+/// Bodies that are not JSON are summarised by length only, since they cannot
+/// be redacted field by field.
+pub fn redact_response_body(bytes: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut value) => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes, not JSON>", bytes.len()),
+    }
+}
+
+/// Logs a request at TRACE level with credentials redacted
+pub fn trace_request<B: Serialize>(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &Option<B>,
+) {
+    let body = body
+        .as_ref()
+        .map_or_else(|| "<empty>".to_string(), redact_body);
+    trace!(
+        "Request {} {} headers: [{}] body: {}",
+        method,
+        url,
+        redact_headers(headers),
+        body
+    );
+}
+
+/// Logs a response body at TRACE level with credentials redacted
+pub fn trace_response(url: &str, bytes: &[u8]) {
+    trace!(
+        "Response from {} body: {}",
+        url,
+        redact_response_body(bytes)
+    );
+}
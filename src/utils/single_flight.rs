@@ -0,0 +1,77 @@
+/******************************************************************************
+   Author: Joaquín Béjar García
+   Email: jb@taunais.com
+   Date: 16/10/26
+******************************************************************************/
+
+//! Coalescing of concurrent calls to the same async operation
+//!
+//! Used by `Auth` so that many tasks noticing an expired session at the same
+//! time trigger a single login instead of one each.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::Mutex;
+
+/// Runs an async operation at most once for a group of concurrent callers
+///
+/// Callers that arrive while the operation is in flight wait for it and get a
+/// clone of its result instead of running it again. Callers arriving after it
+/// completed start a new run. Failures are not shared: a caller whose run
+/// failed gets the error and the next caller tries again.
+///
+/// The lock is a `tokio::sync::Mutex`, so it is safe to hold across the
+/// operation's `.await` points and is never poisoned.
+#[derive(Debug)]
+pub struct SingleFlight<T> {
+    last: Mutex<Option<T>>,
+    completed: AtomicU64,
+}
+
+impl<T: Clone> SingleFlight<T> {
+    /// Creates a new, idle instance
+    pub fn new() -> Self {
+        Self {
+            last: Mutex::new(None),
+            completed: AtomicU64::new(0),
+        }
+    }
+
+    /// Runs `operation`, or joins a run already in flight
+    ///
+    /// # Arguments
+    /// * `operation` - Produces the future to run if no other caller is running it
+    ///
+    /// # Returns
+    /// * `Ok(T)` - Result of this run or of the run this call joined
+    /// * `Err(E)` - If this caller's own run failed
+    pub async fn run<F, Fut, E>(&self, operation: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let seen = self.completed.load(Ordering::Acquire);
+        let mut last = self.last.lock().await;
+        if self.completed.load(Ordering::Acquire) != seen
+            && let Some(value) = last.as_ref()
+        {
+            return Ok(value.clone());
+        }
+
+        let value = operation().await?;
+        *last = Some(value.clone());
+        self.completed.fetch_add(1, Ordering::AcqRel);
+        Ok(value)
+    }
+
+    /// Number of runs that completed successfully
+    pub fn completed_runs(&self) -> u64 {
+        self.completed.load(Ordering::Acquire)
+    }
+}
+
+impl<T: Clone> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,7 +2,9 @@
 
 use crate::common;
 use ig_client::prelude::*;
-use ig_client::presentation::order::{ClosePositionRequest, CreateOrderRequest, Direction, TimeInForce, UpdatePositionRequest};
+use ig_client::presentation::order::{
+    ClosePositionRequest, CreateOrderRequest, Direction, TimeInForce, UpdatePositionRequest,
+};
 use tokio::runtime::Runtime;
 use tracing::{info, warn};
 
@@ -62,7 +64,10 @@ fn test_create_and_close_position() {
         };
 
         // Get current price and set limit price slightly higher for a buy order
-        let current_price = market_details.snapshot.offer.unwrap_or(100.0);
+        let current_price = market_details
+            .snapshot
+            .offer
+            .expect("market has no price, it may be suspended");
         let limit_price = current_price + 5.0; // Set limit price 5 points above current price
 
         info!(
@@ -82,7 +87,8 @@ fn test_create_and_close_position() {
         .with_reference(format!("test_{}", chrono::Utc::now().timestamp()));
 
         // Set required fields
-        create_order.expiry = Some("JUL-25".to_string()); // Use actual expiry date for options
+        // Expiry of the epic traded
+        create_order.expiry = Some(market_details.instrument.expiry.clone());
         create_order.guaranteed_stop = false; // Specify whether to use a guaranteed stop
         create_order.currency_code = "EUR".to_string(); // Set the currency code for the order
         create_order.time_in_force = TimeInForce::FillOrKill; // Use fill or kill
@@ -147,15 +153,35 @@ fn test_create_and_close_position() {
 
                 // Get current price and set limit price based on direction
                 let close_price = match create_order.direction {
-                    Direction::Buy => market_details.snapshot.bid.unwrap_or(100.0) - 5.0, // Set lower for selling
-                    Direction::Sell => market_details.snapshot.offer.unwrap_or(100.0) + 5.0, // Set higher for buying
+                    // Set lower for selling
+                    Direction::Buy => {
+                        market_details
+                            .snapshot
+                            .bid
+                            .expect("market has no price, it may be suspended")
+                            - 5.0
+                    }
+                    // Set higher for buying
+                    Direction::Sell => {
+                        market_details
+                            .snapshot
+                            .offer
+                            .expect("market has no price, it may be suspended")
+                            + 5.0
+                    }
                 };
 
                 info!(
                     "Current price for closing: {}, setting limit price to: {}",
                     match create_order.direction {
-                        Direction::Buy => market_details.snapshot.bid.unwrap_or(100.0),
-                        Direction::Sell => market_details.snapshot.offer.unwrap_or(100.0),
+                        Direction::Buy => market_details
+                            .snapshot
+                            .bid
+                            .expect("market has no price, it may be suspended"),
+                        Direction::Sell => market_details
+                            .snapshot
+                            .offer
+                            .expect("market has no price, it may be suspended"),
                     },
                     close_price
                 );
@@ -380,7 +406,10 @@ fn test_update_position() {
             };
 
             // Get current price and set limit price slightly higher for a buy order
-            let current_price = market_details.snapshot.offer.unwrap_or(100.0);
+            let current_price = market_details
+                .snapshot
+                .offer
+                .expect("market has no price, it may be suspended");
             let limit_price = current_price + 5.0; // Set limit price 5 points above current price
 
             info!(
@@ -400,7 +429,8 @@ fn test_update_position() {
             .with_reference(format!("test_{}", chrono::Utc::now().timestamp()));
 
             // Set required fields
-            create_order.expiry = Some("JUL-25".to_string()); // Use actual expiry date for options
+            // Expiry of the epic traded
+            create_order.expiry = Some(market_details.instrument.expiry.clone());
             create_order.guaranteed_stop = false; // Specify whether to use a guaranteed stop
             create_order.currency_code = "EUR".to_string(); // Set the currency code for the order
             create_order.time_in_force =
@@ -458,8 +488,14 @@ fn test_update_position() {
 
                     // Unwrap the price values with defaults if they're None
                     let current_price = match create_order.direction {
-                        Direction::Buy => market_details.snapshot.offer.unwrap_or(100.0),
-                        Direction::Sell => market_details.snapshot.bid.unwrap_or(100.0),
+                        Direction::Buy => market_details
+                            .snapshot
+                            .offer
+                            .expect("market has no price, it may be suspended"),
+                        Direction::Sell => market_details
+                            .snapshot
+                            .bid
+                            .expect("market has no price, it may be suspended"),
                     };
 
                     info!("Current price: {}", current_price);
@@ -498,19 +534,32 @@ fn test_update_position() {
                             // Get current price and set limit price based on direction
                             let close_price = match create_order.direction {
                                 Direction::Buy => {
-                                    market_details.snapshot.bid.unwrap_or(100.0) - 5.0
+                                    market_details
+                                        .snapshot
+                                        .bid
+                                        .expect("market has no price, it may be suspended")
+                                        - 5.0
                                 } // Set lower for selling
                                 Direction::Sell => {
-                                    market_details.snapshot.offer.unwrap_or(100.0) + 5.0
+                                    market_details
+                                        .snapshot
+                                        .offer
+                                        .expect("market has no price, it may be suspended")
+                                        + 5.0
                                 } // Set higher for buying
                             };
 
                             info!(
                                 "Current price for closing: {}, setting limit price to: {}",
                                 match create_order.direction {
-                                    Direction::Buy => market_details.snapshot.bid.unwrap_or(100.0),
-                                    Direction::Sell =>
-                                        market_details.snapshot.offer.unwrap_or(100.0),
+                                    Direction::Buy => market_details
+                                        .snapshot
+                                        .bid
+                                        .expect("market has no price, it may be suspended"),
+                                    Direction::Sell => market_details
+                                        .snapshot
+                                        .offer
+                                        .expect("market has no price, it may be suspended"),
                                 },
                                 close_price
                             );
@@ -548,19 +597,32 @@ fn test_update_position() {
                             // Get current price and set limit price based on direction
                             let close_price = match create_order.direction {
                                 Direction::Buy => {
-                                    market_details.snapshot.bid.unwrap_or(100.0) - 5.0
+                                    market_details
+                                        .snapshot
+                                        .bid
+                                        .expect("market has no price, it may be suspended")
+                                        - 5.0
                                 } // Set lower for selling
                                 Direction::Sell => {
-                                    market_details.snapshot.offer.unwrap_or(100.0) + 5.0
+                                    market_details
+                                        .snapshot
+                                        .offer
+                                        .expect("market has no price, it may be suspended")
+                                        + 5.0
                                 } // Set higher for buying
                             };
 
                             info!(
                                 "Current price for closing: {}, setting limit price to: {}",
                                 match create_order.direction {
-                                    Direction::Buy => market_details.snapshot.bid.unwrap_or(100.0),
-                                    Direction::Sell =>
-                                        market_details.snapshot.offer.unwrap_or(100.0),
+                                    Direction::Buy => market_details
+                                        .snapshot
+                                        .bid
+                                        .expect("market has no price, it may be suspended"),
+                                    Direction::Sell => market_details
+                                        .snapshot
+                                        .offer
+                                        .expect("market has no price, it may be suspended"),
                                 },
                                 close_price
                             );
@@ -595,11 +657,8 @@ fn test_update_position() {
         let deal_id = &position.position.deal_id;
         info!("Updating position with deal ID: {}", deal_id);
 
-        // Get current price
-        let current_price_opt = match position.position.direction {
-            Direction::Buy => position.market.offer,
-            Direction::Sell => position.market.bid,
-        };
+        // Get current price, absent while the market is suspended
+        let current_price_opt = position.market.current_price(&position.position.direction);
 
         // Only proceed if current price is available
         if let Some(current_price) = current_price_opt {
@@ -57,7 +57,10 @@ pub fn test_working_orders() {
             .expect("Failed to get market details");
 
         // Set a limit price well below current price to avoid execution
-        let current_price = market_details.snapshot.offer.unwrap_or(100.0);
+        let current_price = market_details
+            .snapshot
+            .offer
+            .expect("market has no price, it may be suspended");
         let limit_price = current_price * 0.5; // 50% of current price
 
         info!(
@@ -2,4 +2,16 @@ mod models;
 mod services;
 mod test_auth;
 mod test_client;
+mod test_fx;
 mod test_listener;
+mod test_live_book;
+#[cfg(feature = "testing")]
+mod test_order_journal;
+mod test_order_service;
+#[cfg(feature = "testing")]
+mod test_position_book;
+mod test_session_guard;
+mod test_spread_fetcher;
+mod test_streaming;
+mod test_subscription;
+mod test_working_order_book;
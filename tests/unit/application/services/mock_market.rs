@@ -0,0 +1,201 @@
+//! In-memory `MarketService` for code that is generic over it
+
+use async_trait::async_trait;
+use ig_client::application::interfaces::market::MarketService;
+use ig_client::error::AppError;
+use ig_client::model::requests::RecentPricesRequest;
+use ig_client::model::responses::{
+    DBEntriesReport, DBEntryResponse, HistoricalPricesResponse, MarketNavigationResponse,
+    MarketSearchResponse, MultipleMarketDetailsResponse,
+};
+use ig_client::presentation::market::{MarketData, MarketDetails, MarketOrdering, MarketState};
+use ig_client::test_fixtures;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Market details request received by `MockMarketService`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarketRequest {
+    /// `get_multiple_market_details` with these epics
+    Batch(Vec<String>),
+    /// `get_market_details` for this epic
+    Single(String),
+}
+
+/// Serves market details for a fixed set of epics and records every request
+pub struct MockMarketService {
+    details: HashMap<String, MarketDetails>,
+    batch_error: Option<fn() -> AppError>,
+    requests: Mutex<Vec<MarketRequest>>,
+}
+
+impl MockMarketService {
+    /// Knows the given epics, each with a copy of `test_fixtures::market_details`
+    pub fn with_epics(epics: &[&str]) -> Self {
+        let details = epics
+            .iter()
+            .map(|epic| {
+                let mut details = test_fixtures::market_details();
+                details.instrument.epic = epic.to_string();
+                (epic.to_string(), details)
+            })
+            .collect();
+        Self {
+            details,
+            batch_error: None,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Fails every batch request with the error built by `error`
+    pub fn failing_batches(mut self, error: fn() -> AppError) -> Self {
+        self.batch_error = Some(error);
+        self
+    }
+
+    /// Requests received so far, in order
+    pub fn requests(&self) -> Vec<MarketRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn record(&self, request: MarketRequest) {
+        self.requests.lock().unwrap().push(request);
+    }
+}
+
+#[async_trait]
+impl MarketService for MockMarketService {
+    async fn search_markets(&self, _search_term: &str) -> Result<MarketSearchResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_market_details(&self, epic: &str) -> Result<MarketDetails, AppError> {
+        self.record(MarketRequest::Single(epic.to_string()));
+        self.details.get(epic).cloned().ok_or(AppError::NotFound)
+    }
+
+    async fn get_market_details_fresh(&self, epic: &str) -> Result<MarketDetails, AppError> {
+        self.get_market_details(epic).await
+    }
+
+    async fn get_multiple_market_details(
+        &self,
+        epics: &[String],
+    ) -> Result<MultipleMarketDetailsResponse, AppError> {
+        self.record(MarketRequest::Batch(epics.to_vec()));
+        if let Some(error) = self.batch_error {
+            return Err(error());
+        }
+        Ok(MultipleMarketDetailsResponse {
+            market_details: epics
+                .iter()
+                .filter_map(|epic| self.details.get(epic).cloned())
+                .collect(),
+        })
+    }
+
+    async fn market_state(&self, _epic: &str) -> Result<MarketState, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_historical_prices(
+        &self,
+        _epic: &str,
+        _resolution: &str,
+        _from: &str,
+        _to: &str,
+    ) -> Result<HistoricalPricesResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_historical_prices_ranged(
+        &self,
+        _epic: &str,
+        _resolution: &str,
+        _from: &str,
+        _to: &str,
+    ) -> Result<HistoricalPricesResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_historical_prices_by_date_range(
+        &self,
+        _epic: &str,
+        _resolution: &str,
+        _start_date: &str,
+        _end_date: &str,
+    ) -> Result<HistoricalPricesResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_recent_prices(
+        &self,
+        _params: &RecentPricesRequest<'_>,
+    ) -> Result<HistoricalPricesResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_historical_prices_by_count_v1(
+        &self,
+        _epic: &str,
+        _resolution: &str,
+        _num_points: i32,
+    ) -> Result<HistoricalPricesResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_historical_prices_by_count_v2(
+        &self,
+        _epic: &str,
+        _resolution: &str,
+        _num_points: i32,
+    ) -> Result<HistoricalPricesResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_market_navigation(&self) -> Result<MarketNavigationResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_market_navigation_node(
+        &self,
+        _node_id: &str,
+    ) -> Result<MarketNavigationResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_node_markets_detailed(
+        &self,
+        _node_id: &str,
+    ) -> Result<Vec<MarketDetails>, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_all_markets(&self) -> Result<Vec<MarketData>, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_all_markets_cancellable(
+        &self,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<MarketData>, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_all_markets_ordered(
+        &self,
+        _ordering: MarketOrdering,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<MarketData>, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_vec_db_entries(&self) -> Result<Vec<DBEntryResponse>, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_vec_db_entries_with_report(&self) -> Result<DBEntriesReport, AppError> {
+        unimplemented!("not used by the mock")
+    }
+}
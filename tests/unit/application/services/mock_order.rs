@@ -0,0 +1,176 @@
+//! In-memory `OrderService` for the trait's default methods
+
+use async_trait::async_trait;
+use ig_client::application::interfaces::order::OrderService;
+use ig_client::error::AppError;
+use ig_client::model::requests::{
+    ClosePositionRequest, CreateOrderRequest, CreateWorkingOrderRequest, UpdatePositionRequest,
+};
+use ig_client::model::responses::{
+    ClosePositionResponse, CreateOrderResponse, CreateWorkingOrderResponse,
+    OrderConfirmationResponse, PartialCloseResult, StrategyResult, UpdatePositionResponse,
+};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Accepts every order except those on the given epics and records the epics sent
+pub struct MockOrderService {
+    rejected_epics: HashSet<String>,
+    rejected_deals: HashSet<String>,
+    sent: Mutex<Vec<String>>,
+}
+
+impl MockOrderService {
+    /// Rejects orders on `epics` with `AppError::InvalidInput`
+    pub fn rejecting(epics: &[&str]) -> Self {
+        Self {
+            rejected_epics: epics.iter().map(|epic| epic.to_string()).collect(),
+            rejected_deals: HashSet::new(),
+            sent: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Confirms deals on `epics` as rejected for insufficient funds
+    pub fn rejecting_deals(mut self, epics: &[&str]) -> Self {
+        self.rejected_deals = epics.iter().map(|epic| epic.to_string()).collect();
+        self
+    }
+
+    /// Epics of the orders sent so far, in order
+    pub fn sent(&self) -> Vec<String> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl OrderService for MockOrderService {
+    async fn create_order(
+        &self,
+        order: &CreateOrderRequest,
+    ) -> Result<CreateOrderResponse, AppError> {
+        self.sent.lock().unwrap().push(order.epic.clone());
+        if self.rejected_epics.contains(&order.epic) {
+            return Err(AppError::InvalidInput(format!("{} rejected", order.epic)));
+        }
+        Ok(CreateOrderResponse {
+            deal_reference: format!("REF-{}", order.epic),
+        })
+    }
+
+    async fn create_order_for_account(
+        &self,
+        _account_id: &str,
+        _order: &CreateOrderRequest,
+    ) -> Result<CreateOrderResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn try_create_order(
+        &self,
+        _order: &CreateOrderRequest,
+    ) -> Result<Option<CreateOrderResponse>, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn get_order_confirmation(
+        &self,
+        _deal_reference: &str,
+    ) -> Result<OrderConfirmationResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn confirm_deal(
+        &self,
+        deal_reference: &str,
+        _timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError> {
+        let epic = deal_reference.trim_start_matches("REF-");
+        let confirmation = if self.rejected_deals.contains(epic) {
+            serde_json::json!({
+                "status": null,
+                "reason": "INSUFFICIENT_FUNDS",
+                "dealStatus": "REJECTED",
+                "dealReference": deal_reference,
+                "dealId": null,
+                "epic": epic
+            })
+        } else {
+            serde_json::json!({
+                "status": "OPEN",
+                "reason": "SUCCESS",
+                "dealStatus": "ACCEPTED",
+                "dealReference": deal_reference,
+                "dealId": "DIAAAAMOCK",
+                "epic": epic
+            })
+        };
+        Ok(serde_json::from_value(confirmation)?)
+    }
+
+    async fn track_until_terminal(
+        &self,
+        _deal_reference: &str,
+        _timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn place_and_confirm(
+        &self,
+        _order: &CreateOrderRequest,
+        _timeout: Duration,
+    ) -> Result<OrderConfirmationResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn update_position(
+        &self,
+        _deal_id: &str,
+        _update: &UpdatePositionRequest,
+    ) -> Result<UpdatePositionResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn close_position(
+        &self,
+        _close_request: &ClosePositionRequest,
+    ) -> Result<ClosePositionResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn partial_close_and_protect(
+        &self,
+        _deal_id: &str,
+        _close_size: f64,
+        _new_stop: Option<f64>,
+        _new_limit: Option<f64>,
+    ) -> Result<PartialCloseResult, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn place_strategy(
+        &self,
+        _legs: &[CreateOrderRequest],
+    ) -> Result<StrategyResult, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn unwind_strategy(
+        &self,
+        _result: &StrategyResult,
+    ) -> Vec<Result<ClosePositionResponse, AppError>> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn create_working_order(
+        &self,
+        _order: &CreateWorkingOrderRequest,
+    ) -> Result<CreateWorkingOrderResponse, AppError> {
+        unimplemented!("not used by the mock")
+    }
+
+    async fn realized_pnl_for(&self, _deal_reference: &str) -> Result<Option<f64>, AppError> {
+        unimplemented!("not used by the mock")
+    }
+}
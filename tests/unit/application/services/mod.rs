@@ -1 +1,3 @@
-
+#[cfg(feature = "testing")]
+pub mod mock_market;
+pub mod mock_order;
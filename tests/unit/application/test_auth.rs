@@ -1,6 +1,8 @@
 use chrono::Utc;
-use ig_client::application::auth::{Session, WebsocketInfo};
+use ig_client::application::auth::{Auth, Session, WebsocketInfo};
+use ig_client::application::config::Config;
 use ig_client::model::auth::OAuthToken;
+use std::sync::Arc;
 
 fn make_session(expires_in_secs: i64, with_oauth: bool) -> Session {
     let now = Utc::now().timestamp() as u64;
@@ -24,6 +26,7 @@ fn make_session(expires_in_secs: i64, with_oauth: bool) -> Session {
         },
         api_version: if with_oauth { 3 } else { 2 },
         expires_at: now + (expires_in_secs as u64),
+        can_trade: None,
     }
 }
 
@@ -80,3 +83,9 @@ fn session_expiry_checks_and_alias() {
     let secs = s.seconds_until_expiry();
     assert!(secs <= 120 && secs > 0);
 }
+
+#[tokio::test]
+async fn logout_without_session_sends_nothing() {
+    let auth = Auth::new(Arc::new(Config::default()));
+    assert!(auth.logout().await.is_ok());
+}
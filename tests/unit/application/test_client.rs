@@ -35,3 +35,20 @@ fn client_default_new_equivalence() {
     let _c2: Client = Default::default();
     // Construction should not panic; no further assertions needed
 }
+
+#[test]
+fn server_now_falls_back_to_local_clock_before_measuring() {
+    let client = Client::new();
+    assert!(client.clock_offset().is_none());
+    let before = chrono::Utc::now();
+    let now = client.server_now();
+    assert!(now >= before && now <= chrono::Utc::now());
+}
+
+#[tokio::test]
+async fn create_orders_with_no_orders_sends_nothing() {
+    use ig_client::application::interfaces::order::OrderService;
+
+    let client = Client::new();
+    assert!(client.create_orders(Vec::new(), true).await.is_empty());
+}
@@ -0,0 +1,56 @@
+use ig_client::application::client::Client;
+use ig_client::application::fx::{FxConverter, fx_epic, rate_from_snapshot};
+use ig_client::presentation::currency::CurrencyCode;
+use ig_client::presentation::market::MarketSnapshot;
+use std::sync::Arc;
+
+#[test]
+fn test_fx_epic_and_scaled_snapshot_rate() {
+    assert_eq!(
+        fx_epic(&CurrencyCode::Eur, &CurrencyCode::Usd),
+        "CS.D.EURUSD.TODAY.IP"
+    );
+
+    let snapshot: MarketSnapshot = serde_json::from_value(serde_json::json!({
+        "marketStatus": "TRADEABLE",
+        "bid": 10850.0,
+        "offer": 10851.0,
+        "scalingFactor": 10000
+    }))
+    .unwrap();
+    let rate = rate_from_snapshot(&snapshot).unwrap();
+    assert!((rate - 1.08505).abs() < 1e-9);
+
+    let unpriced: MarketSnapshot = serde_json::from_value(serde_json::json!({
+        "marketStatus": "CLOSED",
+        "bid": null,
+        "offer": 1.1
+    }))
+    .unwrap();
+    assert_eq!(rate_from_snapshot(&unpriced), None);
+}
+
+#[tokio::test]
+async fn test_fx_converter_inverts_cached_rates() {
+    let converter = FxConverter::new(Arc::new(Client::new()));
+    converter.set_rate(&CurrencyCode::Eur, &CurrencyCode::Usd, 1.25);
+
+    let usd = converter
+        .convert(100.0, &CurrencyCode::Eur, &CurrencyCode::Usd)
+        .await
+        .unwrap();
+    assert!((usd - 125.0).abs() < 1e-9);
+
+    let eur = converter
+        .convert(125.0, &CurrencyCode::Usd, &CurrencyCode::Eur)
+        .await
+        .unwrap();
+    assert!((eur - 100.0).abs() < 1e-9);
+
+    assert_eq!(
+        converter
+            .convert(42.0, &CurrencyCode::Gbp, &CurrencyCode::Gbp)
+            .await,
+        Some(42.0)
+    );
+}
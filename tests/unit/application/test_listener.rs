@@ -206,3 +206,27 @@ fn test_listener_error_handling() {
     // Should not panic even with error
     listener.on_item_update(&update);
 }
+
+#[test]
+fn test_ls_item_names_and_fields() {
+    use ig_client::application::interfaces::listener::{
+        CHART_CANDLE_FIELDS, LsItem, MARKET_FIELDS, TRADE_FIELDS,
+    };
+    use ig_client::presentation::chart::ChartScale;
+
+    let market = LsItem::market("IX.D.DAX.DAILY.IP");
+    assert_eq!(market.name(), "MARKET:IX.D.DAX.DAILY.IP");
+    assert_eq!(market.fields(), &MARKET_FIELDS);
+    assert_eq!(LsItem::account("ABC123").to_string(), "ACCOUNT:ABC123");
+    assert_eq!(LsItem::trade("ABC123").fields(), &TRADE_FIELDS);
+
+    let candles = LsItem::chart("CS.D.EURUSD.CFD.IP", ChartScale::OneMinute);
+    assert_eq!(candles.name(), "CHART:CS.D.EURUSD.CFD.IP:1MINUTE");
+    assert_eq!(candles.fields(), &CHART_CANDLE_FIELDS);
+
+    assert_eq!(LsItem::parse(&candles.name()), Some(candles));
+    assert_eq!(LsItem::parse("market:IX.D.DAX.DAILY.IP"), Some(market));
+    assert_eq!(LsItem::parse("CHART:CS.D.EURUSD.CFD.IP:2MINUTE"), None);
+    assert_eq!(LsItem::parse("PRICE:IX.D.DAX.DAILY.IP"), None);
+    assert_eq!(LsItem::parse("MARKET:"), None);
+}
@@ -0,0 +1,37 @@
+use ig_client::application::live_book::LiveBook;
+use std::collections::HashMap;
+
+fn entries(deal_ids: &[&str]) -> HashMap<String, u32> {
+    deal_ids.iter().map(|id| (id.to_string(), 1)).collect()
+}
+
+#[test]
+fn test_live_book_discards_snapshot_that_raced_an_update() {
+    let book: LiveBook<u32> = LiveBook::new();
+    assert!(book.is_stale());
+    book.seed(entries(&["DEAL1", "DEAL2"]));
+    assert!(!book.is_stale());
+
+    // A close is pushed while the REST snapshot, still holding it, is in flight
+    let generation = book.generation();
+    book.update(|entries| entries.remove("DEAL2").is_some());
+    assert!(!book.seed_if_unchanged(generation, entries(&["DEAL1", "DEAL2"])));
+    assert!(book.is_stale());
+    assert_eq!(book.values().len(), 1);
+
+    // Marking stale during the request discards the snapshot as well
+    let generation = book.generation();
+    book.mark_stale();
+    assert!(!book.seed_if_unchanged(generation, entries(&["DEAL1"])));
+    assert!(book.is_stale());
+
+    // Nothing in between: the snapshot is installed
+    let generation = book.generation();
+    assert!(book.seed_if_unchanged(generation, entries(&["DEAL1", "DEAL3"])));
+    assert!(!book.is_stale());
+    assert_eq!(book.values().len(), 2);
+
+    // An update that cannot be applied marks the book stale
+    book.update(|_| false);
+    assert!(book.is_stale());
+}
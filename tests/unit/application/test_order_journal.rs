@@ -0,0 +1,119 @@
+use ig_client::application::order_journal::{JournalOrder, OrderJournal, is_definite_rejection};
+use ig_client::error::AppError;
+use ig_client::model::requests::{ClosePositionRequest, CreateOrderRequest};
+use ig_client::presentation::order::Direction;
+use std::io::Write;
+use std::path::PathBuf;
+
+fn journal_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "ig_client_journal_{}_{}.jsonl",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn market_order(deal_reference: &str) -> JournalOrder {
+    JournalOrder::CreateOrder(CreateOrderRequest::market(
+        "CS.D.EURUSD.CFD.IP".to_string(),
+        Direction::Buy,
+        1.0,
+        None,
+        Some(deal_reference.to_string()),
+    ))
+}
+
+#[test]
+fn test_order_journal_replay_lists_unfinished_orders() {
+    let path = journal_path("replay");
+    let journal = OrderJournal::open(&path).unwrap();
+
+    let confirmed = journal
+        .record_submitted(market_order("REF-CONFIRMED"))
+        .unwrap();
+    journal.record_accepted(confirmed, "REF-CONFIRMED").unwrap();
+    let mut confirmation = ig_client::test_fixtures::order_confirmation();
+    confirmation.deal_reference = "REF-CONFIRMED".to_string();
+    journal.record_confirmed(confirmed, &confirmation).unwrap();
+
+    let failed = journal
+        .record_submitted(market_order("REF-FAILED"))
+        .unwrap();
+    journal
+        .record_failed(failed, &AppError::RateLimitExceeded)
+        .unwrap();
+
+    // Crash after sending, before the response was recorded
+    let pending = journal
+        .record_submitted(market_order("REF-PENDING"))
+        .unwrap();
+    let close = journal
+        .record_submitted(JournalOrder::ClosePosition(ClosePositionRequest::market(
+            "DIAAAAPENDING".to_string(),
+            Direction::Sell,
+            1.0,
+        )))
+        .unwrap();
+    drop(journal);
+
+    // A line cut short by the crash is skipped
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(b"{\"id\":99,\"recorded_at\":").unwrap();
+    drop(file);
+
+    let journal = OrderJournal::open(&path).unwrap();
+    let in_flight = journal.replay().unwrap();
+    assert_eq!(in_flight.len(), 2);
+    assert_eq!(in_flight[0].id, pending);
+    assert_eq!(in_flight[0].deal_reference(), Some("REF-PENDING"));
+    assert_eq!(in_flight[1].id, close);
+    assert_eq!(in_flight[1].deal_reference(), None);
+
+    journal.record_accepted(close, "REF-CLOSE").unwrap();
+    let next = journal.record_submitted(market_order("REF-NEXT")).unwrap();
+    assert!(next > close);
+    let in_flight = journal.replay().unwrap();
+    assert_eq!(in_flight[1].deal_reference(), Some("REF-CLOSE"));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_order_journal_keeps_ambiguous_failures_in_flight() {
+    use reqwest::StatusCode;
+
+    assert!(is_definite_rejection(&AppError::Unexpected(
+        StatusCode::BAD_REQUEST
+    )));
+    assert!(is_definite_rejection(&AppError::RateLimitExceeded));
+    assert!(!is_definite_rejection(&AppError::Timeout(
+        "no response".to_string()
+    )));
+    assert!(!is_definite_rejection(&AppError::Unexpected(
+        StatusCode::BAD_GATEWAY
+    )));
+    assert!(!is_definite_rejection(&AppError::Deserialization(
+        "truncated body".to_string()
+    )));
+
+    let path = journal_path("unknown");
+    let journal = OrderJournal::open(&path).unwrap();
+    let timed_out = journal
+        .record_submitted(market_order("REF-TIMEOUT"))
+        .unwrap();
+    journal
+        .record_unknown(timed_out, &AppError::Timeout("no response".to_string()))
+        .unwrap();
+
+    let in_flight = journal.replay().unwrap();
+    assert_eq!(in_flight.len(), 1);
+    assert_eq!(in_flight[0].id, timed_out);
+    assert_eq!(in_flight[0].deal_reference(), Some("REF-TIMEOUT"));
+
+    let _ = std::fs::remove_file(&path);
+}
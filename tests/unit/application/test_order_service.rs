@@ -0,0 +1,74 @@
+use crate::application::services::mock_order::MockOrderService;
+use ig_client::application::interfaces::order::OrderService;
+use ig_client::error::AppError;
+use ig_client::model::requests::CreateOrderRequest;
+use ig_client::presentation::order::{Direction, Status};
+use std::time::Duration;
+
+fn orders(epics: &[&str]) -> Vec<CreateOrderRequest> {
+    epics
+        .iter()
+        .map(|epic| CreateOrderRequest::market(epic.to_string(), Direction::Buy, 1.0, None, None))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_create_orders_reports_each_order_and_continues_after_failures() {
+    let service = MockOrderService::rejecting(&["EPIC.B"]);
+
+    let results = service
+        .create_orders(orders(&["EPIC.A", "EPIC.B", "EPIC.C"]), false)
+        .await;
+
+    assert_eq!(service.sent(), vec!["EPIC.A", "EPIC.B", "EPIC.C"]);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().deal_reference, "REF-EPIC.A");
+    assert!(matches!(results[1], Err(AppError::InvalidInput(_))));
+    assert_eq!(results[2].as_ref().unwrap().deal_reference, "REF-EPIC.C");
+}
+
+#[tokio::test]
+async fn test_create_orders_stops_at_the_first_failure_when_asked() {
+    let service = MockOrderService::rejecting(&["EPIC.B"]);
+
+    let results = service
+        .create_orders(orders(&["EPIC.A", "EPIC.B", "EPIC.C"]), true)
+        .await;
+
+    assert_eq!(service.sent(), vec!["EPIC.A", "EPIC.B"]);
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+}
+
+#[tokio::test]
+async fn test_place_and_confirm_returns_rejected_deals_as_ok() {
+    let service = MockOrderService::rejecting(&[]).rejecting_deals(&["EPIC.B"]);
+    let timeout = Duration::from_secs(1);
+
+    let accepted = service
+        .place_and_confirm(&orders(&["EPIC.A"])[0], timeout)
+        .await
+        .unwrap();
+    assert!(accepted.is_accepted());
+    assert_eq!(accepted.status, Status::Open);
+
+    let rejected = service
+        .place_and_confirm(&orders(&["EPIC.B"])[0], timeout)
+        .await
+        .unwrap();
+    assert_eq!(rejected.deal_status.as_deref(), Some("REJECTED"));
+    assert_eq!(rejected.reason.as_deref(), Some("INSUFFICIENT_FUNDS"));
+    assert_eq!(rejected.deal_reference, "REF-EPIC.B");
+}
+
+#[tokio::test]
+async fn test_place_and_confirm_fails_when_the_order_is_not_submitted() {
+    let service = MockOrderService::rejecting(&["EPIC.A"]);
+
+    let result = service
+        .place_and_confirm(&orders(&["EPIC.A"])[0], Duration::from_secs(1))
+        .await;
+
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+}
@@ -0,0 +1,46 @@
+use ig_client::application::client::Client;
+use ig_client::application::position_book::PositionBook;
+use ig_client::presentation::order::{Direction, Status};
+use ig_client::presentation::trade::OpenPositionUpdate;
+use ig_client::test_fixtures;
+use std::sync::Arc;
+
+fn update(deal_id: &str, status: Status, size: Option<f64>) -> OpenPositionUpdate {
+    OpenPositionUpdate {
+        deal_id: Some(deal_id.to_string()),
+        direction: Some(Direction::Buy),
+        status: Some(status),
+        deal_status: Some(Status::Accepted),
+        size,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_position_book_applies_stream_updates_and_goes_stale_on_gaps() {
+    let book = PositionBook::new(Arc::new(Client::new()));
+    assert!(book.is_stale());
+
+    book.seed(test_fixtures::positions_response());
+    assert!(!book.is_stale());
+    let positions = book.current_positions();
+    assert_eq!(positions.len(), 1);
+    let deal_id = positions[0].position.deal_id.clone();
+
+    book.apply(&update(&deal_id, Status::Updated, Some(0.5)));
+    assert_eq!(book.current_positions()[0].position.size, 0.5);
+    assert!(!book.is_stale());
+
+    // A position opened elsewhere has no market data in the OPU push
+    book.apply(&update("DIAAAANEWDEAL1", Status::Open, Some(1.0)));
+    assert!(book.is_stale());
+    assert_eq!(book.current_positions().len(), 1);
+
+    book.seed(test_fixtures::positions_response());
+    book.apply(&update(&deal_id, Status::Deleted, None));
+    assert!(book.current_positions().is_empty());
+    assert!(!book.is_stale());
+
+    book.mark_stale();
+    assert!(book.is_stale());
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use ig_client::application::auth::Session;
+use ig_client::application::session_guard::{SessionGuard, SessionRenewer};
+use ig_client::error::AppError;
+use ig_client::model::auth::OAuthToken;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+fn make_session(account_id: &str, expires_in_secs: u64) -> Session {
+    Session {
+        account_id: account_id.to_string(),
+        client_id: "CLIENT1".to_string(),
+        lightstreamer_endpoint: "https://ls.example.com".to_string(),
+        cst: Some("CSTTOKEN".to_string()),
+        x_security_token: Some("XSTOKEN".to_string()),
+        oauth_token: None,
+        api_version: 2,
+        expires_at: Utc::now().timestamp() as u64 + expires_in_secs,
+        can_trade: None,
+    }
+}
+
+fn make_oauth_session(expires_in_secs: u64) -> Session {
+    Session {
+        cst: None,
+        x_security_token: None,
+        oauth_token: Some(OAuthToken {
+            access_token: "ACCESS".to_string(),
+            refresh_token: "REFRESH".to_string(),
+            scope: "profile".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: "60".to_string(),
+            created_at: Utc::now(),
+        }),
+        api_version: 3,
+        ..make_session("OAUTH", expires_in_secs)
+    }
+}
+
+struct CountingRenewer {
+    renewals: AtomicU32,
+    refreshes: AtomicU32,
+}
+
+#[async_trait]
+impl SessionRenewer for CountingRenewer {
+    async fn renew(&self) -> Result<Session, AppError> {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.renewals.fetch_add(1, Ordering::SeqCst);
+        Ok(make_session("RENEWED", 21600))
+    }
+
+    async fn refresh(&self) -> Result<Session, AppError> {
+        self.refreshes.fetch_add(1, Ordering::SeqCst);
+        Ok(make_oauth_session(60))
+    }
+}
+
+fn guard(session: Option<Session>) -> (Arc<SessionGuard>, Arc<CountingRenewer>) {
+    let renewer = Arc::new(CountingRenewer {
+        renewals: AtomicU32::new(0),
+        refreshes: AtomicU32::new(0),
+    });
+    let guard = SessionGuard::new(Arc::new(RwLock::new(session)), renewer.clone());
+    (Arc::new(guard), renewer)
+}
+
+#[tokio::test]
+async fn test_session_guard_reuses_fresh_session() {
+    let (guard, renewer) = guard(Some(make_session("CURRENT", 21600)));
+    let session = guard.valid_session().await.unwrap();
+    assert_eq!(session.account_id, "CURRENT");
+    assert_eq!(renewer.renewals.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_session_guard_renews_expiring_session_once_for_concurrent_callers() {
+    let (guard, renewer) = guard(Some(make_session("CURRENT", 60)));
+    let calls = (0..8).map(|_| {
+        let guard = guard.clone();
+        tokio::spawn(async move { guard.valid_session().await })
+    });
+    for call in calls {
+        assert_eq!(call.await.unwrap().unwrap().account_id, "RENEWED");
+    }
+    assert_eq!(renewer.renewals.load(Ordering::SeqCst), 1);
+
+    // The renewed session is now served without another login
+    guard.valid_session().await.unwrap();
+    assert_eq!(renewer.renewals.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_session_guard_renews_rejected_session_once_per_burst() {
+    let (guard, renewer) = guard(Some(make_session("CURRENT", 21600)));
+    assert_eq!(guard.renew_rejected().await.unwrap().account_id, "RENEWED");
+    assert_eq!(guard.renew_rejected().await.unwrap().account_id, "RENEWED");
+    assert_eq!(renewer.renewals.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_session_guard_refreshes_oauth_token_only_when_about_to_expire() {
+    let (fresh, renewer) = guard(Some(make_oauth_session(30)));
+    fresh.valid_session().await.unwrap();
+    assert_eq!(renewer.refreshes.load(Ordering::SeqCst), 0);
+
+    let (expiring, renewer) = guard(Some(make_oauth_session(2)));
+    assert!(expiring.valid_session().await.unwrap().is_oauth());
+    assert_eq!(renewer.refreshes.load(Ordering::SeqCst), 1);
+    assert_eq!(renewer.renewals.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,179 @@
+use ig_client::application::client::Client;
+use ig_client::application::spread_fetcher::SpreadFetcher;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_spread_fetcher_paces_chunks_over_window() {
+    let fetcher = SpreadFetcher::new(Arc::new(Client::new()), Duration::from_secs(3600));
+    assert_eq!(fetcher.chunk_count(0), 0);
+    assert_eq!(fetcher.chunk_count(50), 1);
+    assert_eq!(fetcher.chunk_count(3000), 60);
+    assert_eq!(fetcher.interval(50), Duration::ZERO);
+    assert_eq!(fetcher.interval(3000), Duration::from_secs(60));
+
+    // Chunks are capped at what one multiple market details request accepts
+    let fetcher = fetcher.with_chunk_size(500);
+    assert_eq!(fetcher.chunk_count(3000), 60);
+
+    let fetcher = fetcher.with_chunk_size(10);
+    assert_eq!(fetcher.chunk_count(3000), 300);
+    assert_eq!(fetcher.interval(3000), Duration::from_secs(12));
+    assert_eq!(fetcher.with_chunk_size(0).chunk_count(3), 3);
+}
+
+#[cfg(feature = "testing")]
+mod with_mock_service {
+    use super::*;
+    use crate::application::services::mock_market::{MarketRequest, MockMarketService};
+    use futures::StreamExt;
+    use ig_client::application::spread_fetcher::snapshot_all;
+    use ig_client::error::AppError;
+
+    fn epics(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    async fn collect(
+        mut receiver: tokio::sync::mpsc::Receiver<
+            ig_client::application::spread_fetcher::EpicDetails,
+        >,
+    ) -> Vec<(String, Result<String, String>)> {
+        let mut results = Vec::new();
+        while let Some((epic, result)) = receiver.recv().await {
+            let result = result
+                .map(|details| details.instrument.epic)
+                .map_err(|e| e.to_string());
+            results.push((epic, result));
+        }
+        results
+    }
+
+    #[tokio::test]
+    async fn test_spread_fetcher_sends_results_in_input_order() {
+        let service = Arc::new(MockMarketService::with_epics(&["A", "B", "D", "E"]));
+        let fetcher = SpreadFetcher::new(Arc::clone(&service), Duration::ZERO).with_chunk_size(2);
+
+        let results = collect(fetcher.spawn(epics(&["A", "B", "C", "D", "E"]))).await;
+
+        let order: Vec<&str> = results.iter().map(|(epic, _)| epic.as_str()).collect();
+        assert_eq!(order, ["A", "B", "C", "D", "E"]);
+        assert_eq!(results[0].1, Ok("A".to_string()));
+        assert_eq!(results[2].1, Err(AppError::NotFound.to_string()));
+        assert_eq!(
+            service.requests(),
+            [
+                MarketRequest::Batch(epics(&["A", "B"])),
+                MarketRequest::Batch(epics(&["C", "D"])),
+                MarketRequest::Batch(epics(&["E"])),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spread_fetcher_falls_back_to_single_requests() {
+        let service = Arc::new(
+            MockMarketService::with_epics(&["A", "C"])
+                .failing_batches(|| AppError::Unexpected(reqwest::StatusCode::BAD_GATEWAY)),
+        );
+        let fetcher =
+            SpreadFetcher::new(Arc::clone(&service), Duration::from_millis(40)).with_chunk_size(2);
+
+        let started = std::time::Instant::now();
+        let results = collect(fetcher.spawn(epics(&["A", "B", "C"]))).await;
+
+        assert_eq!(results[0], ("A".to_string(), Ok("A".to_string())));
+        assert_eq!(results[1].1, Err(AppError::NotFound.to_string()));
+        assert_eq!(results[2], ("C".to_string(), Ok("C".to_string())));
+        assert_eq!(
+            service.requests(),
+            [
+                MarketRequest::Batch(epics(&["A", "B"])),
+                MarketRequest::Single("A".to_string()),
+                MarketRequest::Single("B".to_string()),
+                MarketRequest::Batch(epics(&["C"])),
+                MarketRequest::Single("C".to_string()),
+            ]
+        );
+        // The two single requests of the first chunk share its 20 ms slot
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_spread_fetcher_does_not_retry_rate_limited_chunks() {
+        let service = Arc::new(
+            MockMarketService::with_epics(&["A", "B"])
+                .failing_batches(|| AppError::RateLimitExceeded),
+        );
+        let fetcher = SpreadFetcher::new(Arc::clone(&service), Duration::ZERO);
+
+        let results = collect(fetcher.spawn(epics(&["A", "B"]))).await;
+
+        assert_eq!(results.len(), 2);
+        assert!(
+            results
+                .iter()
+                .all(|(_, result)| *result == Err(AppError::RateLimitExceeded.to_string()))
+        );
+        assert_eq!(
+            service.requests(),
+            [MarketRequest::Batch(epics(&["A", "B"]))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spread_fetcher_stops_when_receiver_dropped() {
+        let names: Vec<String> = (0..10).map(|i| format!("EPIC{i}")).collect();
+        let known: Vec<&str> = names.iter().map(String::as_str).collect();
+        let service = Arc::new(MockMarketService::with_epics(&known));
+        let fetcher = SpreadFetcher::new(Arc::clone(&service), Duration::ZERO).with_chunk_size(1);
+
+        let mut receiver = fetcher.spawn(names.clone());
+        assert!(receiver.recv().await.is_some());
+        drop(receiver);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(service.requests().len() < names.len());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_all_batches_by_fifty_and_falls_back_per_epic() {
+        let names: Vec<String> = (0..60).map(|i| format!("EPIC{i}")).collect();
+        let known: Vec<&str> = names.iter().map(String::as_str).collect();
+        let service = MockMarketService::with_epics(&known[1..]);
+
+        let results: Vec<_> = snapshot_all(&service, names.clone()).collect().await;
+
+        assert_eq!(results.len(), 60);
+        assert!(matches!(results[0], (ref epic, Err(AppError::NotFound)) if epic == "EPIC0"));
+        assert!(
+            results[1..]
+                .iter()
+                .zip(&names[1..])
+                .all(|((epic, result), name)| epic == name
+                    && result.as_ref().is_ok_and(|d| d.instrument.epic == *name))
+        );
+        assert_eq!(
+            service.requests(),
+            [
+                MarketRequest::Batch(names[..50].to_vec()),
+                MarketRequest::Batch(names[50..].to_vec()),
+            ]
+        );
+
+        let failing = MockMarketService::with_epics(&["A"])
+            .failing_batches(|| AppError::Unexpected(reqwest::StatusCode::BAD_GATEWAY));
+        let results: Vec<_> = snapshot_all(&failing, epics(&["A", "B"])).collect().await;
+
+        assert!(matches!(&results[0], (epic, Ok(_)) if epic == "A"));
+        assert!(matches!(&results[1], (epic, Err(AppError::NotFound)) if epic == "B"));
+        assert_eq!(
+            failing.requests(),
+            [
+                MarketRequest::Batch(epics(&["A", "B"])),
+                MarketRequest::Single("A".to_string()),
+                MarketRequest::Single("B".to_string()),
+            ]
+        );
+    }
+}
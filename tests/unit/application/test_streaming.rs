@@ -0,0 +1,510 @@
+use async_trait::async_trait;
+use ig_client::application::auth::WebsocketInfo;
+use ig_client::application::interfaces::authenticator::IgAuthenticator;
+use ig_client::application::streaming::{
+    MarketStateTransitions, ReconnectPolicy, StreamConnectionState, SubscriptionEvent,
+    SubscriptionManager, UpdateThrottle,
+};
+use ig_client::error::{AppError, StreamError};
+use ig_client::presentation::market::{MarketFields, MarketState, PresentationMarketData};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+fn fast_policy(max_attempts: Option<u32>) -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_delay: Duration::from_millis(1),
+        max_reconnect_delay: Duration::from_millis(4),
+        max_reconnect_attempts: max_attempts,
+    }
+}
+
+#[test]
+fn test_reconnect_policy_backoff_is_capped() {
+    let policy = fast_policy(Some(3));
+    assert_eq!(policy.delay_for(1), Duration::from_millis(1));
+    assert_eq!(policy.delay_for(2), Duration::from_millis(2));
+    assert_eq!(policy.delay_for(3), Duration::from_millis(4));
+    assert_eq!(policy.delay_for(10), Duration::from_millis(4));
+    assert_eq!(policy.delay_for(u32::MAX), Duration::from_millis(4));
+    assert!(policy.allows_retry(2));
+    assert!(!policy.allows_retry(3));
+    assert!(fast_policy(None).allows_retry(u32::MAX));
+}
+
+#[tokio::test]
+async fn test_manager_gives_up_when_connection_always_refused() {
+    let manager = SubscriptionManager::new(fast_policy(Some(3)));
+    let mut events = manager.events();
+    let attempts = AtomicU32::new(0);
+
+    let result = manager
+        .run(|| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>("connection refused") }
+        })
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(AppError::Stream(StreamError::Connection(_)))
+    ));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+    let mut last = None;
+    while let Ok(event) = events.try_recv() {
+        last = Some(event);
+    }
+    assert_eq!(
+        last,
+        Some(SubscriptionEvent::Failed {
+            attempts: 3,
+            reason: "\"connection refused\"".to_string(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_manager_resets_attempts_after_a_successful_connection() {
+    let manager = SubscriptionManager::new(fast_policy(Some(2)));
+    let listener = manager.connection_listener();
+    let mut events = manager.events();
+    let attempts = AtomicU32::new(0);
+
+    // Every connection comes up and later drops, more times than the limit
+    let result = manager
+        .run(|| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            listener.on_status("CONNECTED:WS-STREAMING");
+            async move {
+                if attempt < 5 {
+                    Err("connection dropped")
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(attempts.load(Ordering::SeqCst), 5);
+    while let Ok(event) = events.try_recv() {
+        if let SubscriptionEvent::Reconnecting { attempt, delay, .. } = event {
+            assert_eq!(attempt, 1);
+            assert_eq!(delay, Duration::from_millis(1));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_manager_recovers_after_transient_failures() {
+    let manager = SubscriptionManager::new(fast_policy(Some(5)));
+    let attempts = AtomicU32::new(0);
+
+    let result = manager
+        .run(|| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if attempt < 3 {
+                    Err("connection refused")
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_manager_publishes_released_subscriptions() {
+    let manager = SubscriptionManager::default();
+    let mut events = manager.events();
+
+    let handle = manager.registry().acquire("MARKET:EPIC", ["BID"]);
+    let key = handle.key().clone();
+    drop(handle);
+
+    assert_eq!(
+        events.try_recv().ok(),
+        Some(SubscriptionEvent::Released { key })
+    );
+}
+
+#[test]
+fn test_manager_reports_lost_updates_and_refreshes_item() {
+    let manager = SubscriptionManager::default();
+    let mut events = manager.events();
+    let refreshed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = std::sync::Arc::clone(&refreshed);
+    manager.set_overflow_refresh(move |item| sink.lock().unwrap().push(item.to_string()));
+
+    let handler = manager.lost_updates_handler();
+    handler("MARKET:CS.D.EURUSD.CFD.IP", 7);
+
+    assert_eq!(
+        events.try_recv().ok(),
+        Some(SubscriptionEvent::LostUpdates {
+            item_name: "MARKET:CS.D.EURUSD.CFD.IP".to_string(),
+            count: 7,
+        })
+    );
+    assert_eq!(
+        *refreshed.lock().unwrap(),
+        vec!["MARKET:CS.D.EURUSD.CFD.IP".to_string()]
+    );
+}
+
+#[test]
+fn test_manager_reports_refused_subscription_with_item() {
+    let manager = SubscriptionManager::default();
+    let mut events = manager.events();
+
+    let handler = manager.subscription_error_handler("MARKET:CS.D.BADEPIC.CFD.IP");
+    handler(21, Some("Bad Item"));
+
+    let Ok(SubscriptionEvent::SubscriptionFailed { error }) = events.try_recv() else {
+        panic!("expected a SubscriptionFailed event");
+    };
+    assert_eq!(error.item(), Some("MARKET:CS.D.BADEPIC.CFD.IP"));
+    assert!(!error.is_connection_error());
+    assert_eq!(
+        error,
+        StreamError::Subscription {
+            item: Some("MARKET:CS.D.BADEPIC.CFD.IP".to_string()),
+            code: Some(21),
+            message: "Bad Item".to_string(),
+        }
+    );
+}
+
+fn market_update(bid: Option<f64>, offer: Option<f64>) -> PresentationMarketData {
+    let changed = MarketFields {
+        bid,
+        offer,
+        ..Default::default()
+    };
+    PresentationMarketData {
+        item_name: "MARKET:CS.D.EURUSD.CFD.IP".to_string(),
+        item_pos: 1,
+        fields: MarketFields {
+            bid: bid.or(Some(1.1)),
+            offer: offer.or(Some(1.2)),
+            ..Default::default()
+        },
+        changed_fields: changed,
+        is_snapshot: false,
+    }
+}
+
+#[test]
+fn test_update_throttle_coalesces_updates_within_window() {
+    let mut throttle = UpdateThrottle::new(2.0);
+    let start = Instant::now();
+
+    let first = throttle.offer(&market_update(Some(1.1), None), start);
+    assert!(first.is_some());
+
+    let held = throttle.offer(
+        &market_update(None, Some(1.3)),
+        start + Duration::from_millis(100),
+    );
+    assert!(held.is_none());
+    let held = throttle.offer(
+        &market_update(Some(1.15), None),
+        start + Duration::from_millis(200),
+    );
+    assert!(held.is_none());
+
+    let coalesced = throttle
+        .offer(
+            &market_update(Some(1.16), None),
+            start + Duration::from_millis(500),
+        )
+        .expect("window elapsed");
+    assert_eq!(coalesced.changed_fields.bid, Some(1.16));
+    assert_eq!(coalesced.changed_fields.offer, Some(1.3));
+    assert_eq!(coalesced.fields.bid, Some(1.16));
+}
+
+#[test]
+fn test_update_throttle_flushes_the_last_held_update() {
+    let mut throttle = UpdateThrottle::new(2.0);
+    let start = Instant::now();
+    let item = "MARKET:CS.D.EURUSD.CFD.IP";
+
+    assert!(throttle.schedule_flush(item).is_none());
+    assert!(
+        throttle
+            .offer(&market_update(Some(1.1), None), start)
+            .is_some()
+    );
+    assert!(throttle.schedule_flush(item).is_none());
+
+    let held = throttle.offer(
+        &market_update(Some(1.15), None),
+        start + Duration::from_millis(100),
+    );
+    assert!(held.is_none());
+    assert_eq!(
+        throttle.schedule_flush(item),
+        Some(start + Duration::from_millis(500))
+    );
+    assert!(throttle.schedule_flush(item).is_none());
+
+    assert!(
+        throttle
+            .flush(item, start + Duration::from_millis(400))
+            .is_none()
+    );
+    assert!(throttle.schedule_flush(item).is_some());
+    let flushed = throttle
+        .flush(item, start + Duration::from_millis(500))
+        .expect("window ended");
+    assert_eq!(flushed.changed_fields.bid, Some(1.15));
+    assert!(throttle.schedule_flush(item).is_none());
+}
+
+#[test]
+fn test_connection_state_from_lightstreamer_status() {
+    use StreamConnectionState::*;
+    assert_eq!(
+        StreamConnectionState::from_lightstreamer_status("CONNECTING"),
+        Connecting
+    );
+    assert_eq!(
+        StreamConnectionState::from_lightstreamer_status("CONNECTED:WS-STREAMING"),
+        Connected
+    );
+    assert_eq!(
+        StreamConnectionState::from_lightstreamer_status("STALLED"),
+        Stalled
+    );
+    assert_eq!(
+        StreamConnectionState::from_lightstreamer_status("DISCONNECTED:WILL-RETRY"),
+        Disconnected {
+            reason: Some("WILL-RETRY".to_string())
+        }
+    );
+    assert_eq!(
+        StreamConnectionState::from_lightstreamer_status("DISCONNECTED"),
+        Disconnected { reason: None }
+    );
+    assert!(!StreamConnectionState::from_lightstreamer_status("BOGUS").is_live());
+}
+
+#[tokio::test]
+async fn test_manager_tracks_connection_state() {
+    let manager = SubscriptionManager::new(fast_policy(Some(1)));
+    let state = manager.connection_state();
+    assert_eq!(
+        *state.borrow(),
+        StreamConnectionState::Disconnected { reason: None }
+    );
+
+    let listener = manager.connection_listener();
+    listener.on_status("CONNECTED:WS-STREAMING");
+    assert!(state.borrow().is_live());
+    listener.on_status("STALLED");
+    assert_eq!(*state.borrow(), StreamConnectionState::Stalled);
+
+    let result = manager
+        .run(|| async { Err::<(), _>("connection refused") })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(
+        *state.borrow(),
+        StreamConnectionState::Disconnected {
+            reason: Some("\"connection refused\"".to_string())
+        }
+    );
+}
+
+#[test]
+fn test_default_subscription_mode_per_item_type() {
+    assert!(matches!(
+        default_mode_for_item("TRADE:ABC123"),
+        SubscriptionMode::Distinct
+    ));
+    assert!(matches!(
+        default_mode_for_item("CHART:CS.D.EURUSD.TODAY.IP:TICK"),
+        SubscriptionMode::Distinct
+    ));
+    assert!(matches!(
+        default_mode_for_item("CHART:CS.D.EURUSD.TODAY.IP:1MINUTE"),
+        SubscriptionMode::Merge
+    ));
+    assert!(matches!(
+        default_mode_for_item("MARKET:IX.D.DAX.DAILY.IP"),
+        SubscriptionMode::Merge
+    ));
+
+    let trade = SubscriptionConfig::for_items(
+        vec!["TRADE:ABC123".to_string()],
+        vec!["CONFIRMS".to_string()],
+    )
+    .unwrap();
+    assert!(matches!(trade.mode, SubscriptionMode::Distinct));
+
+    let mixed = SubscriptionConfig::for_items(
+        vec![
+            "TRADE:ABC123".to_string(),
+            "MARKET:IX.D.DAX.DAILY.IP".to_string(),
+        ],
+        vec!["BID".to_string()],
+    );
+    assert!(mixed.is_err());
+
+    let merged_trades = SubscriptionConfig::new(
+        SubscriptionMode::Merge,
+        vec!["TRADE:ABC123".to_string()],
+        vec!["CONFIRMS".to_string()],
+    );
+    assert!(merged_trades.build().is_err());
+}
+
+#[derive(Default)]
+struct CountingAuthenticator {
+    refreshes: AtomicU32,
+}
+
+#[async_trait]
+impl IgAuthenticator for CountingAuthenticator {
+    async fn websocket_info(&self) -> Result<WebsocketInfo, AppError> {
+        Ok(WebsocketInfo::default())
+    }
+
+    async fn refresh(&self) -> Result<WebsocketInfo, AppError> {
+        self.refreshes.fetch_add(1, Ordering::SeqCst);
+        Ok(WebsocketInfo::default())
+    }
+}
+
+#[tokio::test]
+async fn test_manager_reauthenticates_when_password_rejected() {
+    let manager = SubscriptionManager::new(fast_policy(Some(5)));
+    let authenticator = Arc::new(CountingAuthenticator::default());
+    manager.set_authenticator(authenticator.clone());
+    let mut events = manager.events();
+    let attempts = AtomicU32::new(0);
+
+    let result = manager
+        .run(|| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                match attempt {
+                    1 => Err("Server error 1: User/password check failed"),
+                    2 => Err("connection refused"),
+                    _ => Ok(()),
+                }
+            }
+        })
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(authenticator.refreshes.load(Ordering::SeqCst), 1);
+
+    let mut reauthenticated = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let SubscriptionEvent::Reauthenticated { attempt } = event {
+            reauthenticated.push(attempt);
+        }
+    }
+    assert_eq!(reauthenticated, vec![1]);
+}
+
+fn state_update(state: Option<MarketState>, bid: Option<f64>) -> PresentationMarketData {
+    let changed = MarketFields {
+        market_state: state,
+        bid,
+        ..Default::default()
+    };
+    PresentationMarketData {
+        item_name: "MARKET:IX.D.DAX.DAILY.IP".to_string(),
+        item_pos: 1,
+        fields: changed.clone(),
+        changed_fields: changed,
+        is_snapshot: false,
+    }
+}
+
+#[test]
+fn market_state_transitions_report_only_changes() {
+    let mut transitions = MarketStateTransitions::new();
+
+    // The first state is the baseline
+    assert_eq!(
+        transitions.observe(&state_update(Some(MarketState::Closed), None)),
+        None
+    );
+    // Price-only updates and repeated states are not transitions
+    assert_eq!(transitions.observe(&state_update(None, Some(1.0))), None);
+    assert_eq!(
+        transitions.observe(&state_update(Some(MarketState::Closed), None)),
+        None
+    );
+
+    assert_eq!(
+        transitions.observe(&state_update(Some(MarketState::Tradeable), None)),
+        Some((
+            "IX.D.DAX.DAILY.IP".to_string(),
+            MarketState::Tradeable,
+            MarketState::Closed
+        ))
+    );
+    assert_eq!(transitions.observe(&state_update(None, Some(2.0))), None);
+    assert_eq!(
+        transitions.observe(&state_update(Some(MarketState::Edit), None)),
+        Some((
+            "IX.D.DAX.DAILY.IP".to_string(),
+            MarketState::Edit,
+            MarketState::Tradeable
+        ))
+    );
+}
+
+#[test]
+fn retained_market_state_reports_what_moved_across_a_reconnect() {
+    use ig_client::presentation::market::MarketField;
+
+    let manager = SubscriptionManager::default();
+    let mut events = manager.events();
+    manager.set_retain_market_state(true);
+
+    let mut snapshot = state_update(Some(MarketState::Tradeable), Some(18000.0));
+    snapshot.is_snapshot = true;
+    manager.retain_update(&snapshot);
+    manager.retain_update(&state_update(None, Some(18001.0)));
+    assert!(events.try_recv().is_err());
+
+    // The connection drops; the fresh snapshot is compared with what was retained
+    manager
+        .connection_listener()
+        .on_status("DISCONNECTED:WILL-RETRY");
+    let mut fresh = state_update(Some(MarketState::Tradeable), Some(18050.0));
+    fresh.is_snapshot = true;
+    manager.retain_update(&fresh);
+    assert_eq!(
+        events.try_recv().ok(),
+        Some(SubscriptionEvent::Resynced {
+            item_name: "MARKET:IX.D.DAX.DAILY.IP".to_string(),
+            changed: vec![MarketField::Bid],
+        })
+    );
+    assert_eq!(
+        manager
+            .retained_fields("MARKET:IX.D.DAX.DAILY.IP")
+            .and_then(|fields| fields.bid),
+        Some(18050.0)
+    );
+
+    // Only the first update after the reconnect is a resync
+    manager.retain_update(&state_update(None, Some(18060.0)));
+    assert!(events.try_recv().is_err());
+
+    manager.set_retain_market_state(false);
+    assert_eq!(manager.retained_fields("MARKET:IX.D.DAX.DAILY.IP"), None);
+}
@@ -0,0 +1,41 @@
+use ig_client::application::subscription::{SubscriptionKey, SubscriptionRegistry};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_subscription_key_normalises_fields() {
+    let a = SubscriptionKey::new("MARKET:EPIC", ["OFFER", "BID", "BID"]);
+    let b = SubscriptionKey::new("MARKET:EPIC", ["BID", "OFFER"]);
+    assert_eq!(a, b);
+    assert_eq!(a.to_string(), "MARKET:EPIC[BID,OFFER]");
+}
+
+#[test]
+fn test_registry_shares_and_releases_subscription() {
+    let released = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&released);
+    let registry = SubscriptionRegistry::new(move |key| {
+        sink.lock().unwrap().push(key.clone());
+    });
+
+    let first = registry.acquire("MARKET:EPIC", ["BID", "OFFER"]);
+    let second = registry.acquire("MARKET:EPIC", ["OFFER", "BID"]);
+    let other = registry.acquire("MARKET:OTHER", ["BID"]);
+
+    assert!(first.is_first());
+    assert!(!second.is_first());
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.ref_count(first.key()), 2);
+
+    let key = first.key().clone();
+    drop(first);
+    assert_eq!(registry.ref_count(&key), 1);
+    assert!(released.lock().unwrap().is_empty());
+
+    drop(second);
+    assert_eq!(registry.ref_count(&key), 0);
+    assert_eq!(released.lock().unwrap().as_slice(), &[key]);
+
+    drop(other);
+    assert!(registry.is_empty());
+    assert_eq!(released.lock().unwrap().len(), 2);
+}
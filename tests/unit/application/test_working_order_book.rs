@@ -0,0 +1,72 @@
+use ig_client::application::client::Client;
+use ig_client::application::working_order_book::WorkingOrderBook;
+use ig_client::model::responses::WorkingOrdersResponse;
+use ig_client::presentation::order::{Direction, OrderType, Status, TimeInForce};
+use ig_client::presentation::trade::WorkingOrderUpdate;
+use serde_json::json;
+use std::sync::Arc;
+
+fn wou(status: &str, level: &str) -> WorkingOrderUpdate {
+    serde_json::from_value(json!({
+        "dealReference": "REF1",
+        "dealId": "DIAAAAWORKING1",
+        "direction": "BUY",
+        "epic": "IX.D.DAX.IFD.IP",
+        "status": status,
+        "dealStatus": "ACCEPTED",
+        "level": level,
+        "size": "2",
+        "currency": "EUR",
+        "timestamp": "2026-10-16T09:30:00.000",
+        "channel": "PublicRestOTC",
+        "expiry": "-",
+        "stopDistance": "50",
+        "limitDistance": null,
+        "guaranteedStop": false,
+        "orderType": "LIMIT",
+        "timeInForce": "GOOD_TILL_DATE",
+        "goodTillDate": "2026/10/20 20:00"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_working_order_book_tracks_wou_pushes() {
+    let book = WorkingOrderBook::new(Arc::new(Client::new()));
+    assert!(book.is_stale());
+    book.seed(WorkingOrdersResponse {
+        working_orders: Vec::new(),
+    });
+    assert!(!book.is_stale());
+
+    // An order placed elsewhere is added from the push alone
+    book.apply(&wou("OPEN", "17000"));
+    let orders = book.current_orders();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].deal_id, "DIAAAAWORKING1");
+    assert_eq!(orders[0].direction, Direction::Buy);
+    assert_eq!(orders[0].order_level, 17000.0);
+    assert_eq!(orders[0].order_size, 2.0);
+    assert_eq!(orders[0].order_type, OrderType::Limit);
+    assert_eq!(orders[0].time_in_force, TimeInForce::GoodTillDate);
+    assert_eq!(orders[0].stop_distance, Some(50.0));
+    assert_eq!(orders[0].currency_code, "EUR");
+    assert!(!book.is_stale());
+
+    book.apply(&wou("UPDATED", "16950"));
+    assert_eq!(book.current_orders()[0].order_level, 16950.0);
+
+    // Filled or cancelled orders are reported deleted
+    book.apply(&wou("DELETED", "16950"));
+    assert!(book.current_orders().is_empty());
+    assert!(!book.is_stale());
+
+    // Too sparse to build an order from
+    book.apply(&WorkingOrderUpdate {
+        deal_id: Some("DIAAAAWORKING2".to_string()),
+        status: Some(Status::Open),
+        ..Default::default()
+    });
+    assert!(book.is_stale());
+    assert!(book.current_orders().is_empty());
+}
@@ -4,4 +4,6 @@ mod model;
 mod presentation;
 mod storage;
 mod test_error;
+#[cfg(feature = "testing")]
+mod test_fixtures;
 mod utils;
@@ -4,6 +4,8 @@
    Date: 20/10/25
 ******************************************************************************/
 mod test_auth;
+#[cfg(feature = "analytics")]
+mod test_columns;
 mod test_config;
 mod test_requests;
 mod test_responses;
@@ -1,6 +1,6 @@
 use chrono::Utc;
 use ig_client::model::auth::{
-    OAuthToken, SecurityHeaders, SessionResponse, V2Response, V3Response,
+    OAuthToken, SecurityHeaders, SessionDetails, SessionResponse, V2Response, V3Response,
 };
 
 #[test]
@@ -275,3 +275,26 @@ fn test_security_headers_clone() {
     assert_eq!(headers.cst, cloned.cst);
     assert_eq!(headers.x_security_token, cloned.x_security_token);
 }
+
+#[test]
+fn test_session_details_deserialization() {
+    let json = r#"{
+        "clientId": "101290216",
+        "accountId": "ZZZZZ",
+        "timezoneOffset": 1,
+        "locale": "en_GB",
+        "currency": "GBP",
+        "lightstreamerEndpoint": "https://demo-apd.marketdatasystems.com"
+    }"#;
+
+    let details: SessionDetails = serde_json::from_str(json).unwrap();
+    assert_eq!(details.client_id, "101290216");
+    assert_eq!(details.account_id, "ZZZZZ");
+    assert_eq!(details.timezone_offset, 1);
+    assert_eq!(details.locale.as_deref(), Some("en_GB"));
+    assert_eq!(details.currency.as_deref(), Some("GBP"));
+    assert_eq!(
+        details.lightstreamer_endpoint,
+        "https://demo-apd.marketdatasystems.com"
+    );
+}
@@ -0,0 +1,52 @@
+use chrono::{TimeZone, Utc};
+use ig_client::model::responses::HistoricalPricesResponse;
+use serde_json::json;
+
+#[test]
+fn test_to_columns_parses_timestamps_and_aligns_columns() {
+    let response: HistoricalPricesResponse = serde_json::from_value(json!({
+        "prices": [
+            {
+                "snapshotTime": "2024/01/31 10:15:00",
+                "snapshotTimeUTC": "2024-01-31T09:15:00",
+                "openPrice": {"bid": 1.0850, "ask": 1.0852, "lastTraded": null},
+                "highPrice": {"bid": 1.0860, "ask": 1.0862, "lastTraded": null},
+                "lowPrice": {"bid": 1.0840, "ask": 1.0842, "lastTraded": null},
+                "closePrice": {"bid": 1.0855, "ask": 1.0857, "lastTraded": null},
+                "lastTradedVolume": 120
+            },
+            {
+                "snapshotTime": "not a time",
+                "openPrice": {"bid": 1.0, "ask": 1.0, "lastTraded": null},
+                "highPrice": {"bid": 1.0, "ask": 1.0, "lastTraded": null},
+                "lowPrice": {"bid": 1.0, "ask": 1.0, "lastTraded": null},
+                "closePrice": {"bid": 1.0, "ask": 1.0, "lastTraded": null},
+                "lastTradedVolume": 1
+            },
+            {
+                "snapshotTime": "2024/01/31 10:16:00",
+                "openPrice": {"bid": 1.0855, "ask": null, "lastTraded": null},
+                "highPrice": {"bid": 1.0865, "ask": null, "lastTraded": null},
+                "lowPrice": {"bid": 1.0850, "ask": null, "lastTraded": null},
+                "closePrice": {"bid": 1.0860, "ask": null, "lastTraded": null},
+                "lastTradedVolume": null
+            }
+        ],
+        "instrumentType": "CURRENCIES"
+    }))
+    .unwrap();
+
+    let columns = response.to_columns();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(
+        columns.timestamps,
+        vec![
+            Utc.with_ymd_and_hms(2024, 1, 31, 9, 15, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 31, 10, 16, 0).unwrap(),
+        ]
+    );
+    assert_eq!(columns.open_bid, vec![Some(1.0850), Some(1.0855)]);
+    assert_eq!(columns.close_ask, vec![Some(1.0857), None]);
+    assert_eq!(columns.high_ask.len(), 2);
+    assert_eq!(columns.volume, vec![Some(120), None]);
+}
@@ -1,7 +1,10 @@
 use ig_client::application::config::{
-    Config, Credentials, RateLimiterConfig, RestApiConfig, WebSocketConfig,
+    ApiVersions, Config, Credentials, Environment, RateLimitType, RateLimiterConfig, RestApiConfig,
+    WebSocketConfig,
 };
+use ig_client::error::ConfigError;
 use ig_client::storage::config::DatabaseConfig;
+use ig_client::utils::finance::RoundingMode;
 
 #[test]
 fn test_credentials_clone() {
@@ -12,6 +15,7 @@ fn test_credentials_clone() {
         api_key: "key123".to_string(),
         client_token: Some("client_token".to_string()),
         account_token: Some("account_token".to_string()),
+        auto_switch_account: true,
     };
 
     let cloned = creds.clone();
@@ -30,6 +34,7 @@ fn test_credentials_serialization() {
         api_key: "key123".to_string(),
         client_token: None,
         account_token: None,
+        auto_switch_account: true,
     };
 
     let json = serde_json::to_string(&creds).unwrap();
@@ -44,6 +49,13 @@ fn test_rest_api_config_clone() {
     let config = RestApiConfig {
         base_url: "https://api.example.com".to_string(),
         timeout: 30,
+        request_id_header: false,
+        log_bodies: false,
+        market_details_cache_ttl: 300,
+        api_versions: ApiVersions::default(),
+        fill_missing_expiry: false,
+        size_rounding: RoundingMode::Floor,
+        use_server_clock: false,
     };
 
     let cloned = config.clone();
@@ -56,6 +68,8 @@ fn test_websocket_config_clone() {
     let config = WebSocketConfig {
         url: "wss://ws.example.com".to_string(),
         reconnect_interval: 5,
+        max_reconnect_delay: 60,
+        max_reconnect_attempts: None,
     };
 
     let cloned = config.clone();
@@ -69,6 +83,7 @@ fn test_rate_limiter_config_clone() {
         max_requests: 10,
         period_seconds: 60,
         burst_size: 5,
+        disabled: false,
     };
 
     let cloned = config.clone();
@@ -126,23 +141,36 @@ fn test_config_serialization() {
             api_key: "key".to_string(),
             client_token: None,
             account_token: None,
+            auto_switch_account: true,
         },
         rest_api: RestApiConfig {
             base_url: "https://api.test.com".to_string(),
             timeout: 30,
+            request_id_header: false,
+            log_bodies: false,
+            market_details_cache_ttl: 300,
+            api_versions: ApiVersions::default(),
+            fill_missing_expiry: false,
+            size_rounding: RoundingMode::Floor,
+            use_server_clock: false,
         },
         websocket: WebSocketConfig {
             url: "wss://ws.test.com".to_string(),
             reconnect_interval: 5,
+            max_reconnect_delay: 60,
+            max_reconnect_attempts: None,
         },
         database: DatabaseConfig {
             url: "postgres://localhost/test".to_string(),
             max_connections: 5,
+            acquire_timeout: 30,
+            idle_timeout: Some(600),
         },
         rate_limiter: RateLimiterConfig {
             max_requests: 10,
             period_seconds: 60,
             burst_size: 5,
+            disabled: false,
         },
         sleep_hours: 1,
         page_size: 50,
@@ -179,6 +207,7 @@ fn test_credentials_display() {
         api_key: "key123".to_string(),
         client_token: None,
         account_token: None,
+        auto_switch_account: true,
     };
 
     let display = format!("{}", creds);
@@ -190,6 +219,13 @@ fn test_rest_api_config_serialization() {
     let config = RestApiConfig {
         base_url: "https://api.example.com".to_string(),
         timeout: 45,
+        request_id_header: false,
+        log_bodies: false,
+        market_details_cache_ttl: 300,
+        api_versions: ApiVersions::default(),
+        fill_missing_expiry: false,
+        size_rounding: RoundingMode::Floor,
+        use_server_clock: false,
     };
 
     let json = serde_json::to_string(&config).unwrap();
@@ -204,6 +240,8 @@ fn test_websocket_config_serialization() {
     let config = WebSocketConfig {
         url: "wss://ws.example.com".to_string(),
         reconnect_interval: 10,
+        max_reconnect_delay: 60,
+        max_reconnect_attempts: None,
     };
 
     let json = serde_json::to_string(&config).unwrap();
@@ -219,6 +257,7 @@ fn test_rate_limiter_config_serialization() {
         max_requests: 20,
         period_seconds: 120,
         burst_size: 10,
+        disabled: false,
     };
 
     let json = serde_json::to_string(&config).unwrap();
@@ -260,6 +299,7 @@ fn test_credentials_with_tokens() {
         api_key: "key".to_string(),
         client_token: Some("client123".to_string()),
         account_token: Some("account456".to_string()),
+        auto_switch_account: true,
     };
 
     assert_eq!(creds.client_token, Some("client123".to_string()));
@@ -275,8 +315,227 @@ fn test_credentials_without_tokens() {
         api_key: "key".to_string(),
         client_token: None,
         account_token: None,
+        auto_switch_account: true,
     };
 
     assert_eq!(creds.client_token, None);
     assert_eq!(creds.account_token, None);
 }
+
+#[test]
+fn test_config_builder_without_environment() {
+    let config = Config::builder()
+        .username("builder_user")
+        .password("builder_pass")
+        .api_key("builder_key")
+        .account_id("BUILDER1")
+        .environment(Environment::Live)
+        .rate_limit_type(RateLimitType::TradingAccount)
+        .build()
+        .unwrap();
+
+    assert_eq!(config.credentials.username, "builder_user");
+    assert_eq!(config.credentials.password, "builder_pass");
+    assert_eq!(config.credentials.api_key, "builder_key");
+    assert_eq!(config.credentials.account_id, "BUILDER1");
+    assert_eq!(config.rest_api.base_url, Environment::Live.rest_base_url());
+    assert_eq!(config.websocket.url, Environment::Live.ws_url());
+    assert_eq!(config.rate_limiter.max_requests, 100);
+    assert_eq!(config.rate_limiter.period_seconds, 60);
+    assert_eq!(config.api_version, Some(3));
+}
+
+#[test]
+fn test_config_builder_requires_credentials() {
+    let result = Config::builder()
+        .username("builder_user")
+        .password("builder_pass")
+        .build();
+    assert_eq!(result.unwrap_err(), ConfigError::MissingField("api_key"));
+
+    let result = Config::builder()
+        .username("u")
+        .password("p")
+        .api_key("k")
+        .account_id("a")
+        .api_version(4)
+        .build();
+    assert!(matches!(
+        result,
+        Err(ConfigError::InvalidValue {
+            field: "api_version",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_config_validate_rejects_placeholders_and_bad_url() {
+    let mut config = Config::builder()
+        .username("u")
+        .password("p")
+        .api_key("k")
+        .account_id("a")
+        .build()
+        .unwrap();
+    assert!(config.validate().is_ok());
+
+    config.credentials.password = "default_password".to_string();
+    assert_eq!(
+        config.validate().unwrap_err(),
+        ConfigError::MissingField("password")
+    );
+
+    config.credentials.password = "p".to_string();
+    config.rest_api.base_url = "not a url".to_string();
+    let err = config.validate().unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::InvalidUrl {
+            field: "rest_api.base_url",
+            ..
+        }
+    ));
+    assert!(err.to_string().contains("rest_api.base_url"));
+}
+
+#[test]
+fn test_disable_rate_limiting_only_outside_live() {
+    use ig_client::application::rate_limiter::RateLimiter;
+
+    let builder = || {
+        Config::builder()
+            .username("u")
+            .password("p")
+            .api_key("k")
+            .account_id("a")
+    };
+
+    let mut demo = builder().environment(Environment::Demo).build().unwrap();
+    demo.disable_rate_limiting();
+    assert!(demo.rate_limiter.disabled);
+    assert!(demo.validate().is_ok());
+    assert!(RateLimiter::for_config(&demo).is_noop());
+
+    let mut live = builder().environment(Environment::Live).build().unwrap();
+    assert!(live.is_live());
+    live.disable_rate_limiting();
+    assert!(!live.rate_limiter.disabled);
+
+    live.rate_limiter.disabled = true;
+    assert!(matches!(
+        live.validate(),
+        Err(ConfigError::InvalidValue {
+            field: "rate_limiter.disabled",
+            ..
+        })
+    ));
+    assert!(!RateLimiter::for_config(&live).is_noop());
+}
+
+#[test]
+fn test_configured_account_id_and_auto_switch_default() {
+    let config = Config::builder()
+        .username("user")
+        .password("secret")
+        .api_key("key")
+        .account_id("ABC123")
+        .build()
+        .unwrap();
+    assert!(config.credentials.auto_switch_account);
+    assert_eq!(config.credentials.configured_account_id(), Some("ABC123"));
+
+    let mut credentials = config.credentials.clone();
+    credentials.account_id = "default_account_id".to_string();
+    assert_eq!(credentials.configured_account_id(), None);
+    credentials.account_id = "  ".to_string();
+    assert_eq!(credentials.configured_account_id(), None);
+
+    let json = r#"{"username":"u","password":"p","account_id":"A1","api_key":"k","client_token":null,"account_token":null}"#;
+    let credentials: Credentials = serde_json::from_str(json).unwrap();
+    assert!(credentials.auto_switch_account);
+
+    let config = Config::builder()
+        .username("user")
+        .password("secret")
+        .api_key("key")
+        .account_id("ABC123")
+        .auto_switch_account(false)
+        .build()
+        .unwrap();
+    assert!(!config.credentials.auto_switch_account);
+}
+
+#[test]
+fn test_api_versions_defaults_and_partial_override() {
+    let config = Config::builder()
+        .username("user")
+        .password("secret")
+        .api_key("key")
+        .account_id("ABC123")
+        .build()
+        .unwrap();
+    assert_eq!(config.rest_api.api_versions, ApiVersions::default());
+    assert_eq!(config.rest_api.api_versions.positions, 2);
+    assert_eq!(config.rest_api.api_versions.confirms, 1);
+    assert_eq!(config.rest_api.api_versions.markets_bulk, 2);
+
+    // Fields missing from a serialized config keep their defaults
+    let versions: ApiVersions = serde_json::from_str(r#"{"prices":2,"markets_bulk":1}"#).unwrap();
+    assert_eq!(versions.prices, 2);
+    assert_eq!(versions.markets, 3);
+    assert_eq!(versions.markets_bulk, 1);
+
+    let rest_api: RestApiConfig =
+        serde_json::from_str(r#"{"base_url":"https://api.example.com","timeout":30}"#).unwrap();
+    assert_eq!(rest_api.api_versions, ApiVersions::default());
+}
+
+#[test]
+fn test_fill_missing_expiry_is_opt_in() {
+    let builder = || {
+        Config::builder()
+            .username("user")
+            .password("secret")
+            .api_key("key")
+            .account_id("ABC123")
+    };
+    assert!(!builder().build().unwrap().rest_api.fill_missing_expiry);
+    assert!(
+        builder()
+            .fill_missing_expiry(true)
+            .build()
+            .unwrap()
+            .rest_api
+            .fill_missing_expiry
+    );
+
+    let rest_api: RestApiConfig =
+        serde_json::from_str(r#"{"base_url":"https://api.example.com","timeout":30}"#).unwrap();
+    assert!(!rest_api.fill_missing_expiry);
+    assert!(!rest_api.use_server_clock);
+}
+
+#[test]
+fn test_size_rounding_defaults_to_floor() {
+    let builder = || {
+        Config::builder()
+            .username("user")
+            .password("secret")
+            .api_key("key")
+            .account_id("ABC123")
+    };
+    assert_eq!(
+        builder().build().unwrap().rest_api.size_rounding,
+        RoundingMode::Floor
+    );
+    assert_eq!(
+        builder()
+            .size_rounding(RoundingMode::Nearest)
+            .build()
+            .unwrap()
+            .rest_api
+            .size_rounding,
+        RoundingMode::Nearest
+    );
+}
@@ -1,8 +1,17 @@
 use ig_client::constants::{DEFAULT_ORDER_BUY_LEVEL, DEFAULT_ORDER_SELL_LEVEL};
+use ig_client::error::AppError;
 use ig_client::model::requests::{
     ClosePositionRequest, CreateOrderRequest, CreateWorkingOrderRequest, RecentPricesRequest,
+    UpdateApplicationRequest,
+};
+use ig_client::model::responses::ApplicationDetails;
+use ig_client::presentation::account::ApplicationStatus;
+use ig_client::presentation::instrument::InstrumentType;
+use ig_client::presentation::market::{
+    Currency, DealingRules, Instrument, MarketDetails, MarketSnapshot, StepDistance, StepUnit,
 };
 use ig_client::presentation::order::{Direction, OrderType, TimeInForce};
+use ig_client::utils::finance::RoundingMode;
 
 fn json_value<T: serde::Serialize>(v: &T) -> serde_json::Value {
     serde_json::to_value(v).unwrap()
@@ -27,6 +36,22 @@ fn recent_prices_request_builders() {
     assert_eq!(req.page_number, Some(2));
 }
 
+#[test]
+fn create_order_with_size_overrides_rounding_mode() {
+    let order = CreateOrderRequest::market(
+        "CS.D.EURUSD.TODAY.IP".to_string(),
+        Direction::Buy,
+        0.259,
+        None,
+        None,
+    )
+    .with_size(0.259, RoundingMode::Nearest);
+    assert!((order.size - 0.26).abs() < 1e-9);
+
+    let order = order.with_size(0.251, RoundingMode::Ceil);
+    assert!((order.size - 0.26).abs() < 1e-9);
+}
+
 #[test]
 fn create_order_market_defaults_and_rounding() {
     let order = CreateOrderRequest::market(
@@ -179,6 +204,37 @@ fn close_position_requests() {
     assert_eq!(opt_epic.expiry.as_deref(), Some("-"));
 }
 
+#[test]
+fn close_position_with_quote() {
+    let quote = ClosePositionRequest::quote(
+        "DID4".to_string(),
+        Direction::Sell,
+        1.5,
+        "Q123".to_string(),
+        101.25,
+    );
+    assert_eq!(quote.order_type, OrderType::Quote);
+    assert_eq!(quote.quote_id.as_deref(), Some("Q123"));
+    assert_eq!(quote.level, Some(101.25));
+    assert!(quote.validate().is_ok());
+
+    let v = json_value(&quote);
+    assert_eq!(v["orderType"], "QUOTE");
+    assert_eq!(v["quoteId"], "Q123");
+
+    let mut missing_level = quote.clone();
+    missing_level.level = None;
+    assert!(missing_level.validate().is_err());
+
+    let mut missing_quote =
+        ClosePositionRequest::limit("DID5".to_string(), Direction::Buy, 1.0, 99.0);
+    assert!(missing_quote.validate().is_ok());
+    missing_quote.order_type = OrderType::Quote;
+    assert!(missing_quote.validate().is_err());
+    let with_quote = missing_quote.with_quote_id("Q456".to_string());
+    assert!(with_quote.validate().is_ok());
+}
+
 #[test]
 fn create_working_order_builders() {
     let wo = CreateWorkingOrderRequest::limit(
@@ -208,3 +264,414 @@ fn create_working_order_builders() {
     assert_eq!(ws.order_type, OrderType::Stop);
     assert_eq!(ws.time_in_force, TimeInForce::GoodTillCancelled);
 }
+
+#[test]
+fn working_order_good_till_date_requires_date() {
+    let wo = CreateWorkingOrderRequest::limit(
+        "IX.D.DAX.IFD.IP".to_string(),
+        Direction::Buy,
+        1.0,
+        17000.0,
+    );
+    assert!(
+        wo.clone()
+            .with_time_in_force(TimeInForce::GoodTillDate, None)
+            .is_err()
+    );
+    assert!(
+        wo.clone()
+            .with_time_in_force(TimeInForce::GoodTillDate, Some(" ".to_string()))
+            .is_err()
+    );
+
+    let gtd = wo
+        .clone()
+        .with_time_in_force(
+            TimeInForce::GoodTillDate,
+            Some("2025/12/31 23:59:59".to_string()),
+        )
+        .unwrap();
+    assert_eq!(gtd.good_till_date.as_deref(), Some("2025/12/31 23:59:59"));
+
+    let gtc = gtd
+        .with_time_in_force(TimeInForce::GoodTillCancelled, None)
+        .unwrap();
+    assert_eq!(gtc.good_till_date, None);
+    assert!(gtc.validate().is_ok());
+
+    for immediate in [
+        TimeInForce::ImmediateOrCancel,
+        TimeInForce::FillOrKill,
+        TimeInForce::ExecuteAndEliminate,
+    ] {
+        assert!(matches!(
+            wo.clone().with_time_in_force(immediate, None),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    let mut raw = wo;
+    raw.time_in_force = TimeInForce::GoodTillDate;
+    assert!(raw.validate().is_err());
+}
+
+#[test]
+fn time_in_force_execute_and_eliminate_serde() {
+    let json = serde_json::to_string(&TimeInForce::ExecuteAndEliminate).unwrap();
+    assert_eq!(json, "\"EXECUTE_AND_ELIMINATE\"");
+    let parsed: TimeInForce = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, TimeInForce::ExecuteAndEliminate);
+}
+
+fn dealing_rules_with_min_step(min_step: f64) -> DealingRules {
+    let points = |value: f64| StepDistance {
+        unit: Some(StepUnit::Points),
+        value: Some(value),
+    };
+    DealingRules {
+        min_step_distance: points(min_step),
+        min_deal_size: points(1.0),
+        min_controlled_risk_stop_distance: points(5.0),
+        min_normal_stop_or_limit_distance: points(1.0),
+        max_stop_or_limit_distance: points(1000.0),
+        controlled_risk_spacing: points(1.0),
+        market_order_preference: "AVAILABLE_DEFAULT_OFF".into(),
+        trailing_stops_preference: "AVAILABLE".into(),
+        max_deal_size: Some(100.0),
+    }
+}
+
+#[test]
+fn create_order_validate_trailing_stop() {
+    let rules = dealing_rules_with_min_step(2.0);
+    let base = || {
+        CreateOrderRequest::market(
+            "IX.D.DAX.IFD.IP".to_string(),
+            Direction::Buy,
+            1.0,
+            None,
+            None,
+        )
+    };
+
+    // no trailing stop is always valid
+    assert!(base().validate_trailing_stop(&rules).is_ok());
+    assert!(
+        base()
+            .with_trailing_stop_loss(5.0)
+            .validate_trailing_stop(&rules)
+            .is_ok()
+    );
+    // fractional increments are refused by IG
+    assert!(
+        base()
+            .with_trailing_stop_loss(1.5)
+            .validate_trailing_stop(&rules)
+            .is_err()
+    );
+    // below the minimum step distance
+    assert!(
+        base()
+            .with_trailing_stop_loss(1.0)
+            .validate_trailing_stop(&rules)
+            .is_err()
+    );
+    assert!(
+        base()
+            .with_trailing_stop_loss(0.0)
+            .validate_trailing_stop(&rules)
+            .is_err()
+    );
+}
+
+#[test]
+fn dma_flag_serialized_only_when_set() {
+    let order = CreateOrderRequest::market(
+        "IX.D.DAX.IFD.IP".to_string(),
+        Direction::Buy,
+        1.0,
+        None,
+        None,
+    );
+    assert!(json_value(&order).get("dma").is_none());
+    let order = order.with_dma(true);
+    assert_eq!(json_value(&order).get("dma").unwrap(), true);
+
+    let wo = CreateWorkingOrderRequest::limit(
+        "IX.D.DAX.IFD.IP".to_string(),
+        Direction::Sell,
+        1.0,
+        18000.0,
+    );
+    assert!(json_value(&wo).get("dma").is_none());
+    let wo = wo.with_dma(false);
+    assert_eq!(json_value(&wo).get("dma").unwrap(), false);
+}
+
+fn market_details_for(instrument_type: InstrumentType, preference: &str) -> MarketDetails {
+    let mut dealing_rules = dealing_rules_with_min_step(1.0);
+    dealing_rules.market_order_preference = preference.into();
+    MarketDetails {
+        instrument: Instrument {
+            epic: "OP.D.OTCDAX1.18000C.IP".into(),
+            name: "Germany 40 18000 Call".into(),
+            expiry: "DEC-25".into(),
+            contract_size: "1".into(),
+            lot_size: Some(1.0),
+            high_limit_price: None,
+            low_limit_price: None,
+            margin_factor: None,
+            margin_factor_unit: None,
+            currencies: Some(vec![Currency {
+                code: "GBP".into(),
+                symbol: Some("£".into()),
+                base_exchange_rate: None,
+                exchange_rate: None,
+                is_default: Some(true),
+            }]),
+            value_of_one_pip: "1".into(),
+            instrument_type: Some(instrument_type),
+            expiry_details: None,
+            slippage_factor: None,
+            limited_risk_premium: None,
+            news_code: None,
+            chart_code: None,
+        },
+        snapshot: MarketSnapshot {
+            market_status: "TRADEABLE".into(),
+            net_change: None,
+            percentage_change: None,
+            update_time: None,
+            delay_time: None,
+            bid: Some(120.0),
+            offer: Some(122.0),
+            high: None,
+            low: None,
+            binary_odds: None,
+            decimal_places_factor: None,
+            scaling_factor: None,
+            controlled_risk_extra_spread: None,
+        },
+        dealing_rules,
+    }
+}
+
+#[test]
+fn order_type_best_for_and_smart_constructor() {
+    let rules = dealing_rules_with_min_step(1.0);
+    assert_eq!(
+        OrderType::best_for(Some(InstrumentType::OptIndices), &rules),
+        OrderType::Limit
+    );
+    assert_eq!(
+        OrderType::best_for(Some(InstrumentType::Indices), &rules),
+        OrderType::Market
+    );
+    assert_eq!(OrderType::best_for(None, &rules), OrderType::Market);
+
+    let option = market_details_for(InstrumentType::OptIndices, "AVAILABLE_DEFAULT_OFF");
+    let order = CreateOrderRequest::smart(&option, Direction::Sell, 1.239).unwrap();
+    assert_eq!(order.order_type, OrderType::Limit);
+    assert_eq!(order.time_in_force, TimeInForce::FillOrKill);
+    assert_eq!(order.level, Some(DEFAULT_ORDER_SELL_LEVEL));
+    assert!((order.size - 1.23).abs() < 1e-9);
+    assert_eq!(order.expiry.as_deref(), Some("DEC-25"));
+    assert_eq!(order.currency_code, "GBP");
+
+    let limit_only = market_details_for(InstrumentType::Shares, "NOT_AVAILABLE");
+    let order = CreateOrderRequest::smart(&limit_only, Direction::Buy, 2.0).unwrap();
+    assert_eq!(order.order_type, OrderType::Limit);
+    assert_eq!(order.level, Some(122.0));
+
+    let index = market_details_for(InstrumentType::Indices, "AVAILABLE_DEFAULT_ON");
+    let order = CreateOrderRequest::smart(&index, Direction::Buy, 2.0).unwrap();
+    assert_eq!(order.order_type, OrderType::Market);
+    assert_eq!(order.level, None);
+}
+
+#[test]
+fn smart_order_without_a_price_fails_outside_options() {
+    let mut limit_only = market_details_for(InstrumentType::Shares, "NOT_AVAILABLE");
+    limit_only.snapshot.offer = None;
+    assert!(matches!(
+        CreateOrderRequest::smart(&limit_only, Direction::Buy, 2.0),
+        Err(AppError::InvalidInput(_))
+    ));
+    let order = CreateOrderRequest::smart(&limit_only, Direction::Sell, 2.0).unwrap();
+    assert_eq!(order.level, Some(120.0));
+
+    limit_only.snapshot.bid = None;
+    assert!(matches!(
+        CreateOrderRequest::smart(&limit_only, Direction::Sell, 2.0),
+        Err(AppError::InvalidInput(_))
+    ));
+
+    // Options keep their placeholder levels without a price
+    let mut option = market_details_for(InstrumentType::OptIndices, "AVAILABLE_DEFAULT_OFF");
+    option.snapshot.offer = None;
+    option.snapshot.bid = None;
+    let order = CreateOrderRequest::smart(&option, Direction::Buy, 1.0).unwrap();
+    assert_eq!(order.level, Some(DEFAULT_ORDER_BUY_LEVEL));
+}
+
+#[test]
+fn update_application_request_guards_disabling_the_key() {
+    let req = UpdateApplicationRequest::new("KEY123")
+        .with_allowance_account_overall(60)
+        .with_allowance_account_trading(30);
+    assert!(req.validate().is_ok());
+    let json = json_value(&req);
+    assert_eq!(json.get("apiKey").unwrap(), "KEY123");
+    assert_eq!(json.get("allowanceAccountOverall").unwrap(), 60);
+    assert!(json.get("status").is_none());
+    assert!(json.get("disableConfirmed").is_none());
+
+    assert!(UpdateApplicationRequest::new("KEY123").validate().is_err());
+
+    let disable = UpdateApplicationRequest::new("KEY123").with_status(ApplicationStatus::Disabled);
+    assert!(disable.disables_key());
+    assert!(disable.validate().is_err());
+    let disable = disable.confirm_disable();
+    assert!(disable.validate().is_ok());
+    assert_eq!(json_value(&disable).get("status").unwrap(), "DISABLED");
+
+    let details: ApplicationDetails = serde_json::from_str(
+        r#"{"apiKey":"KEY123","clientId":"C1","name":"bot","status":"ENABLED",
+            "allowanceAccountOverall":60,"allowanceAccountTrading":30,
+            "allowanceAccountHistoricalData":10000,"allowanceApplicationOverall":1000,
+            "concurrentSubscriptionsLimit":40,"createdDate":"2025-01-01"}"#,
+    )
+    .unwrap();
+    assert_eq!(details.status, ApplicationStatus::Enabled);
+    assert_eq!(details.allowance_account_trading, Some(30));
+}
+
+#[test]
+fn create_order_with_contract_month() {
+    use ig_client::presentation::instrument::ContractMonth;
+
+    let july = ContractMonth::new(2025, 7).unwrap();
+    assert_eq!(july.to_string(), "JUL-25");
+    assert_eq!(ContractMonth::parse("JUL-25"), Some(july));
+    assert_eq!(
+        ContractMonth::parse("20-AUG-25"),
+        ContractMonth::new(2025, 8)
+    );
+    assert_eq!(ContractMonth::parse("DFB"), None);
+    assert!(ContractMonth::new(2025, 13).is_none());
+
+    let order = CreateOrderRequest::market(
+        "IX.D.FTSE.MONTH1.IP".to_string(),
+        Direction::Buy,
+        1.0,
+        None,
+        None,
+    )
+    .with_contract_month(july);
+    assert_eq!(order.expiry.as_deref(), Some("JUL-25"));
+
+    let markets = vec![market_details_for(
+        InstrumentType::OptIndices,
+        "AVAILABLE_DEFAULT_OFF",
+    )];
+    let december = ContractMonth::new(2025, 12).unwrap();
+    let order = order
+        .with_listed_contract_month(december, &markets)
+        .unwrap();
+    assert_eq!(order.expiry.as_deref(), Some("DEC-25"));
+    assert!(order.with_listed_contract_month(july, &markets).is_err());
+}
+
+#[test]
+fn test_with_reference_prefix_keeps_reference_within_limit() {
+    let order = CreateOrderRequest::market(
+        "CS.D.EURUSD.TODAY.IP".to_string(),
+        Direction::Buy,
+        1.0,
+        None,
+        None,
+    )
+    .with_reference_prefix("mystrat-");
+    let reference = order.deal_reference.unwrap();
+    assert!(reference.starts_with("mystrat-"));
+    assert_eq!(reference.len(), 30);
+
+    let working = CreateWorkingOrderRequest::limit(
+        "CS.D.EURUSD.TODAY.IP".to_string(),
+        Direction::Sell,
+        1.0,
+        1.1,
+    )
+    .with_reference_prefix("a-strategy-name-that-is-far-too-long-");
+    let reference = working.deal_reference.unwrap();
+    assert_eq!(reference.len(), 30);
+    assert!(reference.starts_with("a-strategy-name-that-"));
+}
+
+#[test]
+fn test_request_options_builder() {
+    use ig_client::model::http::RequestOptions;
+    use std::time::Duration;
+
+    let options = RequestOptions::new()
+        .with_header("X-Debug", "1")
+        .with_timeout(Duration::from_secs(5))
+        .for_account("ABC123");
+    assert_eq!(
+        options.headers,
+        vec![("X-Debug".to_string(), "1".to_string())]
+    );
+    assert_eq!(options.timeout, Some(Duration::from_secs(5)));
+    assert_eq!(options.account_id.as_deref(), Some("ABC123"));
+
+    let defaults = RequestOptions::default();
+    assert!(defaults.headers.is_empty());
+    assert!(defaults.timeout.is_none() && defaults.account_id.is_none());
+}
+
+#[test]
+fn test_strategy_builder_builds_correlated_option_legs() {
+    use ig_client::model::requests::StrategyBuilder;
+
+    let orders = StrategyBuilder::new("BULLCS-")
+        .currency_code("GBP")
+        .leg(
+            "OP.D.OTCDAX1.21000C.IP",
+            Direction::Buy,
+            1.0,
+            Some("DEC-25".to_string()),
+        )
+        .leg(
+            "OP.D.OTCDAX1.21200C.IP",
+            Direction::Sell,
+            1.0,
+            Some("DEC-25".to_string()),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(orders.len(), 2);
+    assert_eq!(orders[0].direction, Direction::Buy);
+    assert_eq!(orders[0].level, Some(DEFAULT_ORDER_BUY_LEVEL));
+    assert_eq!(orders[1].direction, Direction::Sell);
+    assert_eq!(orders[1].level, Some(DEFAULT_ORDER_SELL_LEVEL));
+    let references: Vec<&str> = orders
+        .iter()
+        .map(|order| order.deal_reference.as_deref().unwrap())
+        .collect();
+    assert!(references.iter().all(|r| r.starts_with("BULLCS-")));
+    assert_ne!(references[0], references[1]);
+    assert!(
+        orders
+            .iter()
+            .all(|order| order.currency_code == "GBP" && order.expiry.as_deref() == Some("DEC-25"))
+    );
+
+    assert!(StrategyBuilder::new("EMPTY").build().is_err());
+    assert!(
+        StrategyBuilder::new("BAD")
+            .leg("OP.D.OTCDAX1.21000C.IP", Direction::Buy, 0.0, None)
+            .build()
+            .is_err()
+    );
+}
@@ -150,6 +150,7 @@ fn multiple_market_details_response_helpers_and_display() {
 fn historical_prices_response_helpers_and_display() {
     let p1 = HistoricalPrice {
         snapshot_time: "2025-10-19T10:00:00".into(),
+        snapshot_time_utc: None,
         open_price: PricePoint {
             bid: Some(1.1234),
             ask: Some(1.1236),
@@ -174,6 +175,7 @@ fn historical_prices_response_helpers_and_display() {
     };
     let p2 = HistoricalPrice {
         snapshot_time: "2025-10-19T10:01:00".into(),
+        snapshot_time_utc: None,
         open_price: PricePoint {
             bid: Some(1.2234),
             ask: Some(1.2236),
@@ -439,3 +441,570 @@ fn simple_deal_reference_responses_serde_field_names() {
     let j = json_value(&x);
     assert_eq!(j.get("dealReference").unwrap(), "X1");
 }
+
+#[test]
+fn sprint_positions_response_deserialize_and_time_remaining() {
+    let json = r#"{
+        "sprintMarketPositions": [{
+            "createdDate": "2025/10/20 10:00:00:000",
+            "currency": "GBP",
+            "dealId": "DIAAAAA1",
+            "description": "FTSE 100 above 7500.0 in 5 mins",
+            "direction": "BUY",
+            "epic": "FM.D.FTSE.FTSE.IP",
+            "expiryTime": "2025/10/20 10:05:00:000",
+            "instrumentName": "FTSE 100",
+            "marketStatus": "TRADEABLE",
+            "payoutAmount": 18.5,
+            "size": 10.0,
+            "strikeLevel": 7500.0
+        }]
+    }"#;
+
+    let resp: SprintPositionsResponse = serde_json::from_str(json).unwrap();
+    assert_eq!(resp.sprint_market_positions.len(), 1);
+    let position = &resp.sprint_market_positions[0];
+    assert_eq!(position.deal_id, "DIAAAAA1");
+    assert_eq!(position.direction, Direction::Buy);
+    assert_eq!(position.payout_amount, 18.5);
+    assert_eq!(position.strike_level, 7500.0);
+
+    let expiry = position.expiry().unwrap();
+    assert_eq!(
+        position.time_remaining(expiry - Duration::seconds(90)),
+        Some(Duration::seconds(90))
+    );
+    assert_eq!(
+        position.time_remaining(expiry + Duration::seconds(1)),
+        Some(Duration::zero())
+    );
+
+    let empty: SprintPositionsResponse =
+        serde_json::from_str(r#"{"sprintMarketPositions": null}"#).unwrap();
+    assert!(empty.sprint_market_positions.is_empty());
+}
+
+#[test]
+fn transaction_history_with_bom_and_currency_symbols() {
+    use ig_client::model::http::parse_json_body;
+
+    let json = r#"{
+        "transactions": [{
+            "date": "20/10/25",
+            "dateUtc": "2025-10-20T10:00:00",
+            "openDateUtc": "2025-10-19T09:00:00",
+            "instrumentName": "FTSE 100",
+            "period": "-",
+            "profitAndLoss": "£12.50",
+            "transactionType": "DEAL",
+            "reference": "ABC123",
+            "openLevel": "7500",
+            "closeLevel": "7512.5",
+            "size": "+1",
+            "currency": "£",
+            "cashTransaction": false
+        }, {
+            "date": "20/10/25",
+            "dateUtc": "2025-10-20T11:00:00",
+            "openDateUtc": "2025-10-19T09:30:00",
+            "instrumentName": "Germany 40",
+            "period": "-",
+            "profitAndLoss": "€-4.00",
+            "transactionType": "DEAL",
+            "reference": "DEF456",
+            "openLevel": "18000",
+            "closeLevel": "17996",
+            "size": "-1",
+            "currency": "€",
+            "cashTransaction": false
+        }],
+        "metadata": {
+            "pageData": {"pageNumber": 1, "pageSize": 50, "totalPages": 1},
+            "size": 2
+        }
+    }"#;
+    let mut body = b"\xEF\xBB\xBF".to_vec();
+    body.extend_from_slice(json.as_bytes());
+
+    assert!(serde_json::from_slice::<TransactionHistoryResponse>(&body).is_err());
+
+    let resp: TransactionHistoryResponse = parse_json_body(&body).unwrap();
+    assert_eq!(resp.transactions[0].currency, "£");
+    assert_eq!(resp.transactions[0].profit_and_loss, "£12.50");
+    assert_eq!(resp.transactions[1].currency, "€");
+
+    let round_trip: TransactionHistoryResponse =
+        serde_json::from_str(&serde_json::to_string(&resp).unwrap()).unwrap();
+    assert_eq!(round_trip.transactions[0].currency, "£");
+    assert_eq!(round_trip.transactions[1].currency, "€");
+}
+
+#[test]
+fn accounts_response_preferred() {
+    let account = |id: &str, preferred: bool| {
+        serde_json::json!({
+            "accountId": id,
+            "accountName": id,
+            "accountType": "CFD",
+            "balance": {"balance": 1000.0, "deposit": 0.0, "profitLoss": 0.0, "available": 1000.0},
+            "currency": "GBP",
+            "status": "ENABLED",
+            "preferred": preferred
+        })
+    };
+
+    let resp: AccountsResponse = serde_json::from_value(serde_json::json!({
+        "accounts": [account("ABC01", false), account("ABC02", true)]
+    }))
+    .unwrap();
+    assert_eq!(resp.preferred().unwrap().account_id, "ABC02");
+
+    let none: AccountsResponse = serde_json::from_value(serde_json::json!({
+        "accounts": [account("ABC01", false)]
+    }))
+    .unwrap();
+    assert!(none.preferred().is_none());
+}
+
+#[test]
+fn db_entries_report_completeness() {
+    use ig_client::error::AppError;
+
+    let mut report = DBEntriesReport {
+        entries: vec![DBEntryResponse::default()],
+        failures: Vec::new(),
+    };
+    assert!(report.is_complete());
+
+    report.failures.push((
+        "FTSE".to_string(),
+        AppError::InvalidInput("boom".to_string()),
+    ));
+    assert!(!report.is_complete());
+}
+
+#[test]
+fn test_partial_close_result_reports_unprotected_remainder() {
+    use ig_client::error::AppError;
+
+    let confirmation = |deal_status: &str| -> OrderConfirmationResponse {
+        serde_json::from_value(serde_json::json!({
+            "date": "2025-10-14T08:12:44.421",
+            "status": "AMENDED",
+            "reason": "SUCCESS",
+            "dealStatus": deal_status,
+            "dealReference": "REF1",
+            "dealId": "DIAAAAVJVM7Y9AR",
+            "epic": null,
+            "expiry": null,
+            "guaranteedStop": null,
+            "level": null,
+            "limitDistance": null,
+            "limitLevel": null,
+            "size": 0.5,
+            "stopDistance": null,
+            "stopLevel": null,
+            "trailingStop": null,
+            "direction": "SELL"
+        }))
+        .unwrap()
+    };
+
+    let rejected = PartialCloseResult {
+        close: confirmation("REJECTED"),
+        amend: None,
+    };
+    assert!(!rejected.is_closed());
+    assert!(!rejected.is_complete());
+    assert!(!rejected.is_remainder_unprotected());
+
+    let unprotected = PartialCloseResult {
+        close: confirmation("ACCEPTED"),
+        amend: Some(Err(AppError::RateLimitExceeded)),
+    };
+    assert!(unprotected.is_closed());
+    assert!(unprotected.is_remainder_unprotected());
+
+    let complete = PartialCloseResult {
+        close: confirmation("ACCEPTED"),
+        amend: Some(Ok(UpdatePositionResponse {
+            deal_reference: "REF2".to_string(),
+        })),
+    };
+    assert!(complete.is_complete());
+    assert_eq!(Direction::Buy.opposite(), Direction::Sell);
+    assert_eq!(Direction::Sell.opposite(), Direction::Buy);
+}
+
+#[test]
+fn test_order_confirmation_full_payload_with_affected_deals() {
+    let json = r#"{
+        "date": "2025-10-14T15:40:02.113",
+        "status": "CLOSED",
+        "reason": "SUCCESS",
+        "dealStatus": "ACCEPTED",
+        "epic": "CS.D.EURUSD.CFD.IP",
+        "expiry": "-",
+        "dealReference": "CLOSEREF1",
+        "dealId": "DIAAAAVJVM7ZZZZ",
+        "affectedDeals": [
+            { "dealId": "DIAAAAVJVM7Y9AR", "status": "FULLY_CLOSED" },
+            { "dealId": "DIAAAAVJVM7Y9AS", "status": "PARTIALLY_CLOSED" }
+        ],
+        "level": 1.16432,
+        "size": 1.5,
+        "direction": "SELL",
+        "stopLevel": null,
+        "limitLevel": null,
+        "stopDistance": null,
+        "limitDistance": null,
+        "guaranteedStop": false,
+        "trailingStop": false,
+        "profit": 48.0,
+        "profitCurrency": "USD"
+    }"#;
+
+    let confirmation: OrderConfirmationResponse = serde_json::from_str(json).unwrap();
+    assert!(confirmation.is_accepted());
+    assert_eq!(confirmation.level, Some(1.16432));
+    assert_eq!(confirmation.profit, Some(48.0));
+    assert_eq!(confirmation.profit_currency.as_deref(), Some("USD"));
+    assert_eq!(confirmation.affected_deals.len(), 2);
+    assert_eq!(
+        confirmation
+            .affected_deal_ids(Status::FullyClosed)
+            .collect::<Vec<_>>(),
+        vec!["DIAAAAVJVM7Y9AR"]
+    );
+
+    let minimal = r#"{
+        "status": null,
+        "reason": "MARKET_CLOSED_WITH_EDITS",
+        "dealStatus": "REJECTED",
+        "dealReference": "REF2",
+        "affectedDeals": null
+    }"#;
+    let rejected: OrderConfirmationResponse = serde_json::from_str(minimal).unwrap();
+    assert!(!rejected.is_accepted());
+    assert!(rejected.affected_deals.is_empty());
+    assert!(rejected.date.is_empty());
+}
+
+#[test]
+fn test_position_try_add_rejects_currency_mismatch() {
+    use ig_client::presentation::account::MergeError;
+
+    let json = r#"{
+        "position": {
+            "contractSize": 1.0, "createdDate": "2025/10/14 09:12:44:000",
+            "createdDateUTC": "2025-10-14T08:12:44", "dealId": "D1", "dealReference": "R1",
+            "size": 2.0, "direction": "BUY", "level": 100.0, "currency": "GBP",
+            "controlledRisk": false, "stopLevel": null, "limitLevel": null,
+            "trailingStep": null, "trailingStopDistance": null, "limitedRiskPremium": null
+        },
+        "market": {
+            "instrumentName": "FTSE 100", "expiry": "-", "epic": "IX.D.FTSE.DAILY.IP",
+            "instrumentType": "INDICES", "lotSize": 1.0, "high": null, "low": null,
+            "percentageChange": 0.0, "netChange": 0.0, "bid": 99.0, "offer": 100.0,
+            "updateTime": "10:00:00", "updateTimeUTC": "09:00:00", "delayTime": 0,
+            "streamingPricesAvailable": true, "marketStatus": "TRADEABLE", "scalingFactor": 1
+        }
+    }"#;
+    let gbp: Position = serde_json::from_str(json).unwrap();
+    let mut eur = gbp.clone();
+    eur.position.currency = "EUR".to_string();
+    let mut other_market = gbp.clone();
+    other_market.market.epic = "IX.D.DAX.DAILY.IP".to_string();
+
+    let netted = gbp.clone().try_add(gbp.clone()).unwrap();
+    assert_eq!(netted.position.size, 4.0);
+
+    assert_eq!(
+        gbp.clone().try_add(eur.clone()).unwrap_err(),
+        MergeError::CurrencyMismatch {
+            left: "GBP".to_string(),
+            right: "EUR".to_string(),
+        }
+    );
+    assert!(matches!(
+        gbp.clone().try_add(other_market),
+        Err(MergeError::EpicMismatch { .. })
+    ));
+
+    let compacted = PositionsResponse::compact_by_epic(vec![gbp.clone(), eur, gbp]);
+    assert_eq!(compacted.len(), 2);
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn test_transactions_to_csv_parses_amounts_and_currency() {
+    let json = r#"{
+        "transactions": [
+            {
+                "date": "2025-10-14", "dateUtc": "2025-10-14T15:40:02",
+                "openDateUtc": "2025-10-14T08:12:44", "instrumentName": "Germany 40",
+                "period": "-", "profitAndLoss": "E-1,234.50", "transactionType": "TRADE",
+                "reference": "VJVM7Y9AR", "openLevel": "18,012.5", "closeLevel": "17,900",
+                "size": "+2", "currency": "E", "cashTransaction": false
+            }
+        ],
+        "metadata": { "size": 1, "pageData": { "pageNumber": 1, "pageSize": 50, "totalPages": 1 } }
+    }"#;
+    let history: TransactionHistoryResponse = serde_json::from_str(json).unwrap();
+
+    let mut out = Vec::new();
+    history.to_csv(&mut out).unwrap();
+    let csv = String::from_utf8(out).unwrap();
+    let mut lines = csv.lines();
+
+    assert_eq!(
+        lines.next(),
+        Some(
+            "date,date_utc,open_date_utc,instrument_name,period,transaction_type,reference,open_level,close_level,size,currency,profit_and_loss,cash_transaction"
+        )
+    );
+    assert_eq!(
+        lines.next(),
+        Some(
+            "2025-10-14,2025-10-14T15:40:02,2025-10-14T08:12:44,Germany 40,-,TRADE,VJVM7Y9AR,18012.5,17900.0,2.0,EUR,-1234.5,false"
+        )
+    );
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn test_order_confirmation_terminal_states() {
+    let confirmation = |status: &str, deal_status: &str| -> OrderConfirmationResponse {
+        serde_json::from_value(serde_json::json!({
+            "status": if status.is_empty() { serde_json::Value::Null } else { status.into() },
+            "reason": null,
+            "dealStatus": deal_status,
+            "dealReference": "REF1"
+        }))
+        .unwrap()
+    };
+
+    assert!(!confirmation("ACCEPTED", "ACCEPTED").is_terminal());
+    assert!(confirmation("OPEN", "ACCEPTED").is_terminal());
+    assert!(confirmation("FULLY_CLOSED", "ACCEPTED").is_terminal());
+    assert!(confirmation("", "REJECTED").is_terminal());
+    assert!(!Status::Accepted.is_terminal());
+    assert!(Status::Rejected.is_terminal());
+}
+
+#[test]
+fn test_order_confirmation_partial_fill() {
+    let confirmation = |deal_status: &str, size: Option<f64>| -> OrderConfirmationResponse {
+        serde_json::from_value(serde_json::json!({
+            "status": "OPEN",
+            "reason": null,
+            "dealStatus": deal_status,
+            "dealReference": "REF1",
+            "size": size
+        }))
+        .unwrap()
+    };
+
+    let partial = confirmation("ACCEPTED", Some(3.0));
+    assert_eq!(partial.filled_size(), Some(3.0));
+    assert!(partial.is_partial_fill(5.0));
+    assert_eq!(partial.unfilled_size(5.0), Some(2.0));
+
+    let full = confirmation("ACCEPTED", Some(5.0));
+    assert!(!full.is_partial_fill(5.0));
+    assert_eq!(full.unfilled_size(5.0), Some(0.0));
+
+    let rejected = confirmation("REJECTED", None);
+    assert_eq!(rejected.filled_size(), Some(0.0));
+    assert!(!rejected.is_partial_fill(5.0));
+    assert_eq!(rejected.unfilled_size(5.0), Some(5.0));
+
+    let unreported = confirmation("ACCEPTED", None);
+    assert!(!unreported.is_partial_fill(5.0));
+    assert_eq!(unreported.unfilled_size(5.0), None);
+}
+
+#[test]
+fn test_empty_list_responses() {
+    let positions: PositionsResponse = serde_json::from_str(r#"{"positions":[]}"#).unwrap();
+    assert!(positions.is_empty());
+    assert_eq!(positions.len(), 0);
+
+    let orders: WorkingOrdersResponse = serde_json::from_str(r#"{"workingOrders":[]}"#).unwrap();
+    assert!(orders.is_empty());
+    assert_eq!(orders.len(), 0);
+
+    let activity: AccountActivityResponse =
+        serde_json::from_str(r#"{"activities":[],"metadata":null}"#).unwrap();
+    assert!(activity.is_empty());
+    assert_eq!(activity.len(), 0);
+}
+
+#[test]
+fn test_historical_price_prefers_utc_snapshot_time() {
+    let candle = |extra: &str| -> HistoricalPrice {
+        serde_json::from_str(&format!(
+            r#"{{"snapshotTime":"2025/10/19 12:00:00",{extra}"openPrice":{{}},"highPrice":{{}},"lowPrice":{{}},"closePrice":{{}},"lastTradedVolume":null}}"#
+        ))
+        .unwrap()
+    };
+
+    let v3 = candle(r#""snapshotTimeUTC":"2025-10-19T10:00:00","#);
+    assert_eq!(v3.snapshot_time_utc.as_deref(), Some("2025-10-19T10:00:00"));
+    assert_eq!(
+        v3.snapshot_datetime().unwrap().to_rfc3339(),
+        "2025-10-19T10:00:00+00:00"
+    );
+
+    let legacy = candle("");
+    assert_eq!(legacy.snapshot_time_utc, None);
+    assert_eq!(
+        legacy.snapshot_datetime().unwrap().to_rfc3339(),
+        "2025-10-19T12:00:00+00:00"
+    );
+
+    let v2 = candle(r#""snapshotTimeUTC":null,"#);
+    assert!(v2.snapshot_datetime().is_some());
+}
+
+#[test]
+fn test_average_entry_weights_fills_by_size() {
+    let json = r#"{
+        "position": {
+            "contractSize": 1.0, "createdDate": "2025/10/14 09:12:44:000",
+            "createdDateUTC": "2025-10-14T08:12:44", "dealId": "D1", "dealReference": "R1",
+            "size": 1.0, "direction": "BUY", "level": 100.0, "currency": "GBP",
+            "controlledRisk": false, "stopLevel": null, "limitLevel": null,
+            "trailingStep": null, "trailingStopDistance": null, "limitedRiskPremium": null
+        },
+        "market": {
+            "instrumentName": "FTSE 100", "expiry": "-", "epic": "IX.D.FTSE.DAILY.IP",
+            "instrumentType": "INDICES", "lotSize": 1.0, "high": null, "low": null,
+            "percentageChange": 0.0, "netChange": 0.0, "bid": 99.0, "offer": 100.0,
+            "updateTime": "10:00:00", "updateTimeUTC": "09:00:00", "delayTime": 0,
+            "streamingPricesAvailable": true, "marketStatus": "TRADEABLE", "scalingFactor": 1
+        }
+    }"#;
+    let small: Position = serde_json::from_str(json).unwrap();
+    let mut large = small.clone();
+    large.position.deal_id = "D2".to_string();
+    large.position.size = 3.0;
+    large.position.level = 110.0;
+
+    let response = PositionsResponse {
+        positions: vec![small.clone(), large.clone()],
+    };
+    let weighted = response.average_entry("IX.D.FTSE.DAILY.IP").unwrap();
+    let naive = (small.clone() + large.clone()).position.level;
+    assert!((weighted - 107.5).abs() < 1e-9);
+    assert!((naive - 105.0).abs() < 1e-9);
+    assert!(response.average_entry("IX.D.DAX.DAILY.IP").is_none());
+
+    let mut opposite = large;
+    opposite.position.direction = Direction::Sell;
+    let mixed = PositionsResponse {
+        positions: vec![small, opposite],
+    };
+    assert!(mixed.average_entry("IX.D.FTSE.DAILY.IP").is_none());
+}
+
+#[test]
+fn test_strategy_result_unwinds_filled_legs() {
+    use ig_client::error::AppError;
+
+    let confirmation = |deal_status: &str, deal_id: &str, direction: &str| {
+        serde_json::from_value::<OrderConfirmationResponse>(serde_json::json!({
+            "date": "2025-10-14T08:12:44.421",
+            "status": "OPEN",
+            "reason": "SUCCESS",
+            "dealStatus": deal_status,
+            "dealReference": "BULLCS-REF",
+            "dealId": deal_id,
+            "size": 2.0,
+            "direction": direction
+        }))
+        .unwrap()
+    };
+
+    let partial = StrategyResult {
+        requested: 3,
+        legs: vec![
+            Ok(confirmation("ACCEPTED", "DIAAAALEG1", "BUY")),
+            Ok(confirmation("REJECTED", "DIAAAALEG2", "SELL")),
+        ],
+        unconfirmed_fills: Vec::new(),
+    };
+    assert!(!partial.is_complete());
+    assert!(partial.needs_unwind());
+    let unwind = partial.unwind_requests();
+    assert_eq!(unwind.len(), 1);
+    assert_eq!(unwind[0].deal_id.as_deref(), Some("DIAAAALEG1"));
+    assert_eq!(unwind[0].direction, Direction::Sell);
+    assert_eq!(unwind[0].size, 2.0);
+
+    let failed_first = StrategyResult {
+        requested: 2,
+        legs: vec![Err(AppError::RateLimitExceeded)],
+        unconfirmed_fills: Vec::new(),
+    };
+    assert!(!failed_first.needs_unwind());
+    assert!(failed_first.unwind_requests().is_empty());
+
+    let complete = StrategyResult {
+        requested: 2,
+        legs: vec![
+            Ok(confirmation("ACCEPTED", "DIAAAALEG1", "BUY")),
+            Ok(confirmation("ACCEPTED", "DIAAAALEG2", "SELL")),
+        ],
+        unconfirmed_fills: Vec::new(),
+    };
+    assert!(complete.is_complete());
+    assert!(!complete.needs_unwind());
+}
+
+#[test]
+fn test_strategy_result_unwinds_unconfirmed_fills() {
+    use ig_client::error::AppError;
+
+    let json = r#"{
+        "position": {
+            "contractSize": 1.0, "createdDate": "2025/10/14 09:12:44:000",
+            "createdDateUTC": "2025-10-14T08:12:44", "dealId": "DIAAAALEG1",
+            "dealReference": "LEG1-REF", "size": 2.0, "direction": "BUY", "level": 100.0,
+            "currency": "GBP", "controlledRisk": false, "stopLevel": null, "limitLevel": null,
+            "trailingStep": null, "trailingStopDistance": null, "limitedRiskPremium": null
+        },
+        "market": {
+            "instrumentName": "FTSE 100 CALL", "expiry": "DEC-25", "epic": "OP.D.FTSE.7500C.IP",
+            "instrumentType": "OPT_INDICES", "lotSize": 1.0, "high": null, "low": null,
+            "percentageChange": 0.0, "netChange": 0.0, "bid": 99.0, "offer": 100.0,
+            "updateTime": "10:00:00", "updateTimeUTC": "09:00:00", "delayTime": 0,
+            "streamingPricesAvailable": true, "marketStatus": "TRADEABLE", "scalingFactor": 1
+        }
+    }"#;
+    let filled: Position = serde_json::from_str(json).unwrap();
+
+    let timed_out = StrategyResult {
+        requested: 2,
+        legs: vec![Err(AppError::Timeout(
+            "no confirmation for deal LEG1-REF within 10s".to_string(),
+        ))],
+        unconfirmed_fills: vec![filled],
+    };
+    assert!(!timed_out.is_complete());
+    assert!(timed_out.needs_unwind());
+    let unwind = timed_out.unwind_requests();
+    assert_eq!(unwind.len(), 1);
+    assert_eq!(unwind[0].deal_id.as_deref(), Some("DIAAAALEG1"));
+    assert_eq!(unwind[0].direction, Direction::Sell);
+    assert_eq!(unwind[0].size, 2.0);
+
+    let never_filled = StrategyResult {
+        requested: 2,
+        legs: vec![Err(AppError::Timeout(
+            "no confirmation for deal LEG1-REF within 10s".to_string(),
+        ))],
+        unconfirmed_fills: Vec::new(),
+    };
+    assert!(!never_filled.needs_unwind());
+}
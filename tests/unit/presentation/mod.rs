@@ -1,7 +1,10 @@
 mod serialization_tests;
 mod test_account;
 mod test_chart;
+mod test_currency;
 mod test_market;
+#[cfg(feature = "testing")]
+mod test_market_details;
 mod test_price;
 mod test_serialization;
 mod test_trade;
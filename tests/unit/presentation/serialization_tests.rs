@@ -3,7 +3,7 @@
 #[cfg(test)]
 mod tests {
     use ig_client::presentation::serialization::{
-        option_string_empty_as_none, string_as_bool_opt, string_as_float_opt,
+        null_as_zero, option_string_empty_as_none, string_as_bool_opt, string_as_float_opt,
     };
     use serde::{Deserialize, Serialize};
 
@@ -188,4 +188,29 @@ mod tests {
         let deserialized: StringTest = serde_json::from_str(json).unwrap();
         assert_eq!(deserialized, StringTest { value: None });
     }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct ZeroTest {
+        #[serde(with = "null_as_zero", default)]
+        value: f64,
+    }
+
+    #[test]
+    fn test_null_as_zero_deserialize() {
+        for (json, expected) in [
+            (r#"{"value": 1.5}"#, 1.5),
+            (r#"{"value": "-2.25"}"#, -2.25),
+            (r#"{"value": null}"#, 0.0),
+            (r#"{"value": ""}"#, 0.0),
+            (r#"{}"#, 0.0),
+        ] {
+            let deserialized: ZeroTest = serde_json::from_str(json).unwrap();
+            assert_eq!(deserialized.value, expected, "{json}");
+        }
+        assert!(serde_json::from_str::<ZeroTest>(r#"{"value": "abc"}"#).is_err());
+        assert_eq!(
+            serde_json::to_string(&ZeroTest { value: 0.0 }).unwrap(),
+            r#"{"value":0.0}"#
+        );
+    }
 }
@@ -1,4 +1,6 @@
 use ig_client::presentation::account::{AccountData, AccountFields};
+#[cfg(feature = "testing")]
+use ig_client::test_fixtures;
 use lightstreamer_rs::subscription::ItemUpdate;
 use std::collections::HashMap;
 
@@ -60,3 +62,311 @@ fn test_account_data_serialization() {
     let json = serde_json::to_string(&account).unwrap();
     let _deserialized: AccountData = serde_json::from_str(&json).unwrap();
 }
+
+#[test]
+fn test_activity_period_expiry_parsing() {
+    use chrono::NaiveDate;
+    use ig_client::presentation::instrument::Expiry;
+
+    assert_eq!(Expiry::parse("DFB"), Expiry::Dfb);
+    assert_eq!(Expiry::parse("-"), Expiry::NotApplicable);
+    assert_eq!(
+        Expiry::parse("20-AUG-25"),
+        Expiry::Date(NaiveDate::from_ymd_opt(2025, 8, 20).unwrap())
+    );
+    assert_eq!(
+        Expiry::parse("SEP-25"),
+        Expiry::Month {
+            year: 2025,
+            month: 9
+        }
+    );
+    assert_eq!(Expiry::parse("WEEKLY"), Expiry::Other("WEEKLY".to_string()));
+
+    for raw in ["DFB", "-", "20-AUG-25", "SEP-25", "WEEKLY"] {
+        assert_eq!(Expiry::parse(raw).to_string(), raw);
+    }
+
+    let mut periods: Vec<Expiry> = ["DEC-25", "20-AUG-25", "DFB", "SEP-25"]
+        .into_iter()
+        .map(Expiry::parse)
+        .collect();
+    periods.sort_by_key(Expiry::sort_date);
+    let sorted: Vec<String> = periods.iter().map(ToString::to_string).collect();
+    assert_eq!(sorted, ["DFB", "20-AUG-25", "SEP-25", "DEC-25"]);
+}
+
+#[test]
+fn test_channel_parsing_and_activity_filter() {
+    use ig_client::model::responses::AccountActivityResponse;
+    use ig_client::presentation::account::Channel;
+
+    assert_eq!(Channel::parse("WEB"), Channel::Web);
+    assert_eq!(Channel::parse("Mobile"), Channel::Mobile);
+    assert_eq!(Channel::parse("PublicFIXAPI"), Channel::PublicFixApi);
+    assert_eq!(Channel::parse("PUBLIC_WEB_API"), Channel::PublicWebApi);
+    assert_eq!(
+        Channel::parse("Carrier pigeon"),
+        Channel::Unknown("Carrier pigeon".to_string())
+    );
+    assert!(Channel::PublicWebApi.is_api());
+    assert!(!Channel::Dealer.is_api());
+    assert_eq!(Channel::PublicFixApi.to_string(), "PUBLIC_FIX_API");
+
+    let response: AccountActivityResponse = serde_json::from_str(
+        r#"{"activities":[
+            {"date":"2025-10-14T09:12:44","type":"POSITION","channel":"PUBLIC_WEB_API"},
+            {"date":"2025-10-14T09:13:02","type":"POSITION","channel":"WEB"},
+            {"date":"2025-10-14T09:14:10","type":"SYSTEM"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        response.activities[0].channel_typed(),
+        Some(Channel::PublicWebApi)
+    );
+    assert_eq!(response.activities[2].channel_typed(), None);
+    assert_eq!(response.by_channel(&Channel::PublicWebApi).count(), 1);
+    assert_eq!(response.by_channel(&Channel::Web).count(), 1);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_transaction_profit_and_loss_value_and_deal_match() {
+    let transaction = |pnl: &str| test_fixtures::account_transaction("TRADE", pnl);
+
+    assert_eq!(
+        transaction("E-1,234.50").profit_and_loss_value(),
+        Some(-1234.5)
+    );
+    assert_eq!(transaction("$48.00").profit_and_loss_value(), Some(48.0));
+    assert_eq!(transaction("A$+12.10").profit_and_loss_value(), Some(12.1));
+    assert_eq!(transaction("£").profit_and_loss_value(), None);
+    assert_eq!(transaction("-£1.50").profit_and_loss_value(), Some(-1.5));
+    assert_eq!(
+        transaction("-£1.50").profit_and_loss_currency(),
+        Some(ig_client::presentation::currency::CurrencyCode::Gbp)
+    );
+
+    assert!(transaction("E1").is_for_deal("DIAAAAVJVM7Y9AR"));
+    assert!(!transaction("E1").is_for_deal("DIAAAAXXXXXXXXX"));
+}
+
+#[test]
+fn test_portfolio_summary_groups_by_currency() {
+    use ig_client::model::responses::AccountsResponse;
+    use ig_client::presentation::account::PortfolioSummary;
+
+    let json = r#"{
+        "accounts": [
+            {
+                "accountId": "CFD1", "accountName": "CFD", "accountType": "CFD",
+                "balance": { "balance": 1000.0, "deposit": 200.0, "profitLoss": 15.5, "available": 815.5 },
+                "currency": "GBP", "status": "ENABLED", "preferred": true
+            },
+            {
+                "accountId": "SB1", "accountName": "Spread bet", "accountType": "SPREADBET",
+                "balance": { "balance": 500.0, "deposit": 0.0, "profitLoss": -4.5, "available": 495.5 },
+                "currency": "GBP", "status": "ENABLED", "preferred": false
+            },
+            {
+                "accountId": "CFD2", "accountName": "CFD USD", "accountType": "CFD",
+                "balance": { "balance": 300.0, "deposit": 50.0, "profitLoss": 1.0, "available": 251.0 },
+                "currency": "USD", "status": "ENABLED", "preferred": false
+            }
+        ]
+    }"#;
+    let response: AccountsResponse = serde_json::from_str(json).unwrap();
+    let summary = PortfolioSummary::from_accounts(response.accounts);
+
+    assert_eq!(summary.accounts.len(), 3);
+    assert_eq!(
+        summary.by_currency.keys().collect::<Vec<_>>(),
+        vec!["GBP", "USD"]
+    );
+    let gbp = summary.totals_for("GBP").unwrap();
+    assert_eq!(gbp.accounts, 2);
+    assert_eq!(gbp.balance, 1500.0);
+    assert_eq!(gbp.deposit, 200.0);
+    assert_eq!(gbp.profit_loss, 11.0);
+    assert_eq!(gbp.available, 1311.0);
+    assert_eq!(summary.totals_for("USD").unwrap().available, 251.0);
+    assert!(summary.totals_for("EUR").is_none());
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_holding_durations() {
+    use chrono::{Duration, TimeZone, Utc};
+    use ig_client::presentation::account::{AccountTransaction, PositionDetails};
+
+    let details: PositionDetails = serde_json::from_value(serde_json::json!({
+        "contractSize": 1.0,
+        "createdDate": "2025/10/14 09:00:00:000",
+        "createdDateUTC": "2025-10-14T08:00:00",
+        "dealId": "DIAAAAVJVM7Y9AR",
+        "dealReference": "REF",
+        "direction": "BUY",
+        "limitLevel": null,
+        "level": 1.1,
+        "size": 1.0,
+        "stopLevel": null,
+        "trailingStep": null,
+        "trailingStopDistance": null,
+        "currency": "EUR",
+        "controlledRisk": false,
+        "limitedRiskPremium": null
+    }))
+    .unwrap();
+    let now = Utc.with_ymd_and_hms(2025, 10, 14, 10, 30, 0).unwrap();
+    assert_eq!(details.holding_duration(now), Some(Duration::minutes(150)));
+    let before_open = Utc.with_ymd_and_hms(2025, 10, 14, 7, 0, 0).unwrap();
+    assert_eq!(
+        details.holding_duration(before_open),
+        Some(Duration::zero())
+    );
+
+    let transaction = |open_date_utc: &str| AccountTransaction {
+        open_date_utc: open_date_utc.to_string(),
+        ..test_fixtures::account_transaction("TRADE", "E1")
+    };
+    assert_eq!(
+        transaction("2025-10-14T08:12:44").holding_duration(),
+        Some(Duration::seconds(7 * 3600 + 27 * 60 + 18))
+    );
+    assert_eq!(transaction("-").holding_duration(), None);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_transaction_type_parse_and_signed_pnl() {
+    use ig_client::presentation::account::{AccountTransaction, TransactionType};
+
+    assert_eq!(TransactionType::parse("DEPO"), TransactionType::Deposit);
+    assert_eq!(TransactionType::parse("with"), TransactionType::Withdrawal);
+    assert_eq!(TransactionType::parse("DEAL"), TransactionType::Trade);
+    assert_eq!(
+        TransactionType::parse("DIVIDEND"),
+        TransactionType::Unknown("DIVIDEND".to_string())
+    );
+    assert!(TransactionType::Withdrawal.is_cash_movement());
+    assert!(!TransactionType::Trade.is_cash_movement());
+
+    let transaction = |transaction_type: &str, pnl: &str| AccountTransaction {
+        instrument_name: "Cash".to_string(),
+        currency: "£".to_string(),
+        cash_transaction: true,
+        ..test_fixtures::account_transaction(transaction_type, pnl)
+    };
+
+    assert_eq!(transaction("WITH", "£250.00").signed_pnl(), Some(-250.0));
+    assert_eq!(transaction("WITH", "-£250.00").signed_pnl(), Some(-250.0));
+    assert_eq!(transaction("DEPO", "£-250.00").signed_pnl(), Some(250.0));
+    assert_eq!(transaction("TRADE", "-£1.50").signed_pnl(), Some(-1.5));
+    assert_eq!(transaction("TRADE", "£").signed_pnl(), None);
+}
+
+#[test]
+fn test_account_can_trade_from_status_and_type() {
+    use ig_client::presentation::account::Account;
+
+    let account = |status: &str, account_type: &str| -> Account {
+        serde_json::from_value(serde_json::json!({
+            "accountId": "ABC12",
+            "accountName": "Demo",
+            "accountType": account_type,
+            "balance": { "balance": 1000.0, "deposit": 0.0, "profitLoss": 0.0, "available": 1000.0 },
+            "currency": "GBP",
+            "status": status,
+            "preferred": true
+        }))
+        .unwrap()
+    };
+
+    assert!(account("ENABLED", "CFD").can_trade());
+    assert!(account("ENABLED", "SPREADBET").can_trade());
+    assert!(!account("DISABLED", "CFD").can_trade());
+    assert!(!account("SUSPENDED_FROM_DEALING", "CFD").can_trade());
+    assert!(!account("ENABLED", "PHYSICAL").can_trade());
+}
+
+#[test]
+fn test_position_to_close_request_by_instrument_type() {
+    use ig_client::presentation::account::Position;
+    use ig_client::presentation::order::{Direction, OrderType};
+
+    let position = |instrument_type: &str| -> Position {
+        serde_json::from_value(serde_json::json!({
+            "position": {
+                "contractSize": 1.0,
+                "createdDate": "2025/10/19 10:00:00:000",
+                "createdDateUTC": "2025-10-19T08:00:00",
+                "dealId": "DIAAAAOPT",
+                "dealReference": "REF1",
+                "direction": "BUY",
+                "limitLevel": null,
+                "level": 12.5,
+                "size": 2.0,
+                "stopLevel": null,
+                "trailingStep": null,
+                "trailingStopDistance": null,
+                "currency": "EUR",
+                "controlledRisk": false,
+                "limitedRiskPremium": null
+            },
+            "market": {
+                "instrumentName": "Germany 40 24000 CALL",
+                "expiry": "DEC-25",
+                "epic": "OP.D.OTCDAX1.24000C.IP",
+                "instrumentType": instrument_type,
+                "lotSize": 1.0,
+                "high": null,
+                "low": null,
+                "percentageChange": 0.0,
+                "netChange": 0.0,
+                "bid": 12.0,
+                "offer": 13.0,
+                "updateTime": "10:00:00",
+                "updateTimeUTC": "08:00:00",
+                "delayTime": 0,
+                "streamingPricesAvailable": true,
+                "marketStatus": "TRADEABLE",
+                "scalingFactor": 1
+            },
+            "pnl": null
+        }))
+        .unwrap()
+    };
+
+    let option_close = position("OPT_INDICES").to_close_request(None);
+    assert_eq!(option_close.order_type, OrderType::Limit);
+    assert_eq!(option_close.direction, Direction::Sell);
+    assert_eq!(option_close.deal_id.as_deref(), Some("DIAAAAOPT"));
+    assert_eq!(option_close.size, 2.0);
+    assert!(option_close.level.is_some());
+
+    let cfd_close = position("INDICES").to_close_request(None);
+    assert_eq!(cfd_close.order_type, OrderType::Market);
+    assert_eq!(cfd_close.direction, Direction::Sell);
+    assert_eq!(cfd_close.level, None);
+
+    // An unrecognised type falls back to a market close
+    let unknown_close = position("SOMETHING_NEW").to_close_request(None);
+    assert_eq!(unknown_close.order_type, OrderType::Market);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_position_market_reads_null_changes_as_zero() {
+    use ig_client::model::responses::PositionsResponse;
+
+    let mut json: serde_json::Value =
+        serde_json::from_str(test_fixtures::POSITIONS_RESPONSE_JSON).unwrap();
+    let market = &mut json["positions"][0]["market"];
+    market["netChange"] = serde_json::Value::Null;
+    market.as_object_mut().unwrap().remove("percentageChange");
+
+    let response: PositionsResponse = serde_json::from_value(json).unwrap();
+    assert_eq!(response.positions[0].market.net_change, 0.0);
+    assert_eq!(response.positions[0].market.percentage_change, 0.0);
+}
@@ -1,3 +1,4 @@
+use ig_client::error::StreamError;
 use ig_client::presentation::chart::{ChartData, ChartFields};
 use lightstreamer_rs::subscription::ItemUpdate;
 use std::collections::HashMap;
@@ -61,3 +62,27 @@ fn test_chart_data_serialization() {
     let json = serde_json::to_string(&chart).unwrap();
     let _deserialized: ChartData = serde_json::from_str(&json).unwrap();
 }
+
+#[test]
+fn test_chart_data_from_item_update_reports_unparseable_field() {
+    let mut fields = HashMap::new();
+    fields.insert("BID".to_string(), Some("n/a".to_string()));
+
+    let item_update = ItemUpdate {
+        item_name: Some("CHART:CS.D.EURUSD.CFD.IP:TICK".to_string()),
+        item_pos: 1,
+        is_snapshot: false,
+        fields,
+        changed_fields: HashMap::new(),
+    };
+
+    let error = ChartData::from_item_update(&item_update).unwrap_err();
+    assert_eq!(
+        error,
+        StreamError::Parse {
+            item: Some("CHART:CS.D.EURUSD.CFD.IP:TICK".to_string()),
+            field: "BID".to_string(),
+            raw: "n/a".to_string(),
+        }
+    );
+}
@@ -0,0 +1,50 @@
+#[cfg(feature = "testing")]
+use ig_client::presentation::account::AccountTransaction;
+use ig_client::presentation::currency::CurrencyCode;
+#[cfg(feature = "testing")]
+use ig_client::test_fixtures;
+
+#[test]
+fn test_currency_code_parse_and_validate() {
+    assert_eq!(CurrencyCode::parse("gbp"), Some(CurrencyCode::Gbp));
+    assert_eq!(CurrencyCode::parse(" USD "), Some(CurrencyCode::Usd));
+    assert_eq!(
+        CurrencyCode::parse("mxn"),
+        Some(CurrencyCode::Other("MXN".to_string()))
+    );
+    assert_eq!(CurrencyCode::parse("US"), None);
+    assert_eq!(CurrencyCode::parse("U$D"), None);
+    assert!("EURO".parse::<CurrencyCode>().is_err());
+
+    assert_eq!(CurrencyCode::Eur.to_string(), "EUR");
+    assert_eq!(CurrencyCode::Other("MXN".to_string()).code(), "MXN");
+    assert_eq!(CurrencyCode::Gbp.symbol(), Some("£"));
+    assert_eq!(CurrencyCode::Other("MXN".to_string()).symbol(), None);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_currency_code_from_transaction_markers() {
+    let transaction = |pnl: &str, currency: &str| AccountTransaction {
+        currency: currency.to_string(),
+        ..test_fixtures::account_transaction("TRADE", pnl)
+    };
+
+    assert_eq!(
+        transaction("£12.00", "£").profit_and_loss_currency(),
+        Some(CurrencyCode::Gbp)
+    );
+    assert_eq!(
+        transaction("E-1,234.50", "E").profit_and_loss_currency(),
+        Some(CurrencyCode::Eur)
+    );
+    assert_eq!(
+        transaction("A$+12.10", "A$").profit_and_loss_currency(),
+        Some(CurrencyCode::Aud)
+    );
+    assert_eq!(
+        transaction("-5.00", "$").profit_and_loss_currency(),
+        Some(CurrencyCode::Usd)
+    );
+    assert_eq!(transaction("kr5.00", "kr").profit_and_loss_currency(), None);
+}
@@ -72,3 +72,299 @@ fn test_market_data_serialization() {
     assert_eq!(market.epic, deserialized.epic);
     assert_eq!(market.bid, deserialized.bid);
 }
+
+#[test]
+fn test_market_delta_round_trip_with_tracker() {
+    use ig_client::presentation::market::{
+        MarketFields, MarketState, MarketTracker, PresentationMarketData,
+    };
+
+    let snapshot = PresentationMarketData {
+        item_name: "MARKET:CS.D.EURUSD.CFD.IP".to_string(),
+        item_pos: 1,
+        fields: MarketFields {
+            bid: Some(1.1),
+            offer: Some(1.2),
+            market_state: Some(MarketState::Tradeable),
+            ..Default::default()
+        },
+        changed_fields: MarketFields {
+            bid: Some(1.1),
+            offer: Some(1.2),
+            market_state: Some(MarketState::Tradeable),
+            ..Default::default()
+        },
+        is_snapshot: true,
+    };
+
+    let update = PresentationMarketData {
+        item_name: "MARKET:CS.D.EURUSD.CFD.IP".to_string(),
+        item_pos: 1,
+        fields: MarketFields {
+            bid: Some(1.15),
+            offer: Some(1.2),
+            market_state: Some(MarketState::Tradeable),
+            ..Default::default()
+        },
+        changed_fields: MarketFields {
+            bid: Some(1.15),
+            ..Default::default()
+        },
+        is_snapshot: false,
+    };
+
+    let delta = update.to_delta_json().unwrap();
+    assert!(delta.contains("BID"));
+    assert!(!delta.contains("OFFER"));
+
+    let mut tracker = MarketTracker::new();
+    tracker
+        .apply_delta(&snapshot.to_delta_json().unwrap())
+        .unwrap();
+    let rebuilt = tracker.apply_delta(&delta).unwrap();
+
+    assert_eq!(rebuilt.item_name, update.item_name);
+    assert_eq!(rebuilt.fields, update.fields);
+    assert_eq!(rebuilt.changed_fields.bid, Some(1.15));
+    assert_eq!(tracker.len(), 1);
+}
+
+#[test]
+fn test_market_fields_from_rest_snapshot() {
+    use ig_client::presentation::market::{MarketFields, MarketSnapshot, MarketState};
+
+    let snapshot = MarketSnapshot {
+        market_status: "TRADEABLE".into(),
+        net_change: Some(10.0),
+        percentage_change: Some(0.5),
+        update_time: Some("12:34:56".into()),
+        delay_time: Some(0),
+        bid: Some(18000.0),
+        offer: Some(18001.0),
+        high: Some(18100.0),
+        low: Some(17900.0),
+        binary_odds: None,
+        decimal_places_factor: Some(2),
+        scaling_factor: Some(1),
+        controlled_risk_extra_spread: None,
+    };
+
+    let fields = MarketFields::from(&snapshot);
+    assert_eq!(fields.bid, Some(18000.0));
+    assert_eq!(fields.offer, Some(18001.0));
+    assert_eq!(fields.change, Some(10.0));
+    assert_eq!(fields.change_pct, Some(0.5));
+    assert_eq!(fields.market_delay, Some(false));
+    assert_eq!(fields.market_state, Some(MarketState::Tradeable));
+    assert_eq!(fields.update_time.as_deref(), Some("12:34:56"));
+}
+
+#[test]
+fn test_rest_prices_accept_numbers_and_strings() {
+    use ig_client::presentation::market::{MarketSnapshot, PricePoint};
+
+    let numeric = r#"{
+        "marketStatus": "TRADEABLE",
+        "netChange": -0.0012,
+        "percentageChange": -0.1,
+        "bid": 1.2345,
+        "offer": 1.2347,
+        "high": 1.24,
+        "low": 1.23
+    }"#;
+    let stringly = r#"{
+        "marketStatus": "TRADEABLE",
+        "netChange": "-0.0012",
+        "percentageChange": "-0.1",
+        "bid": "1.2345",
+        "offer": "1.2347",
+        "high": "1.24",
+        "low": "",
+        "binaryOdds": null
+    }"#;
+
+    let numeric: MarketSnapshot = serde_json::from_str(numeric).unwrap();
+    let stringly: MarketSnapshot = serde_json::from_str(stringly).unwrap();
+    for snapshot in [&numeric, &stringly] {
+        assert_eq!(snapshot.bid, Some(1.2345));
+        assert_eq!(snapshot.offer, Some(1.2347));
+        assert_eq!(snapshot.net_change, Some(-0.0012));
+        assert_eq!(snapshot.high, Some(1.24));
+        assert_eq!(snapshot.binary_odds, None);
+        assert_eq!(snapshot.controlled_risk_extra_spread, None);
+    }
+    assert_eq!(numeric.low, Some(1.23));
+    assert_eq!(stringly.low, None);
+
+    let point: PricePoint =
+        serde_json::from_str(r#"{"bid": "7500.5", "ask": 7501.5, "lastTraded": null}"#).unwrap();
+    assert_eq!(point.bid, Some(7500.5));
+    assert_eq!(point.ask, Some(7501.5));
+    assert_eq!(point.last_traded, None);
+
+    // Serialized back as numbers
+    let json = serde_json::to_value(&stringly).unwrap();
+    assert_eq!(json["bid"], serde_json::json!(1.2345));
+
+    assert!(serde_json::from_str::<PricePoint>(r#"{"bid": "n/a"}"#).is_err());
+}
+
+/// Snapshot of a tradeable index market with every price set
+fn tradeable_snapshot() -> ig_client::presentation::market::MarketSnapshot {
+    ig_client::presentation::market::MarketSnapshot {
+        market_status: "TRADEABLE".into(),
+        net_change: Some(10.0),
+        percentage_change: Some(0.5),
+        update_time: Some("12:34:56".into()),
+        delay_time: Some(0),
+        bid: Some(18000.0),
+        offer: Some(18001.0),
+        high: Some(18100.0),
+        low: Some(17900.0),
+        binary_odds: None,
+        decimal_places_factor: Some(2),
+        scaling_factor: Some(1),
+        controlled_risk_extra_spread: None,
+    }
+}
+
+#[test]
+fn test_market_snapshot_diff() {
+    use ig_client::presentation::market::{MarketField, MarketState};
+
+    let before = tradeable_snapshot();
+    assert!(before.diff(&before).is_empty());
+
+    let mut after = before.clone();
+    after.bid = Some(18002.5);
+    after.offer = Some(17999.0);
+    let diff = before.diff(&after);
+    assert_eq!(
+        diff.changed_fields,
+        vec![MarketField::Bid, MarketField::Offer]
+    );
+    assert_eq!(diff.bid_delta, Some(2.5));
+    assert_eq!(diff.offer_delta, Some(-2.0));
+    assert_eq!(diff.high_delta, None);
+    assert!(diff.state_transition.is_none());
+
+    after.market_status = "SUSPENDED".into();
+    let transition = before.diff(&after).state_transition.unwrap();
+    assert_eq!(transition.from, MarketState::Tradeable);
+    assert_eq!(transition.to, MarketState::Suspended);
+    assert!(transition.left_tradeable());
+    assert!(transition.is_suspension());
+
+    let reopened = after.diff(&before).state_transition.unwrap();
+    assert!(reopened.became_tradeable());
+    assert!(!reopened.is_suspension());
+
+    let mut unknown = before.clone();
+    unknown.market_status = "SOMETHING_NEW".into();
+    let diff = before.diff(&unknown);
+    assert!(diff.changed(MarketField::MarketState));
+    assert!(diff.state_transition.is_none());
+}
+
+#[test]
+fn test_market_snapshot_change_or_zero() {
+    let before = tradeable_snapshot();
+    assert_eq!(before.net_change_or_zero(), 10.0);
+    assert_eq!(before.percentage_change_or_zero(), 0.5);
+
+    let mut closed = before.clone();
+    closed.net_change = None;
+    closed.percentage_change = None;
+    assert_eq!(closed.net_change_or_zero(), 0.0);
+    assert_eq!(closed.percentage_change_or_zero(), 0.0);
+    assert_eq!(closed.net_change, None);
+}
+
+/// Share market without prices, as returned by market search and navigation
+fn share_market(epic: &str, name: &str, status: &str) -> MarketData {
+    MarketData {
+        bid: None,
+        epic: epic.to_string(),
+        expiry: "-".to_string(),
+        high_limit_price: None,
+        instrument_name: name.to_string(),
+        instrument_type: InstrumentType::Shares,
+        low_limit_price: None,
+        market_status: status.to_string(),
+        net_change: None,
+        offer: None,
+        percentage_change: None,
+        update_time: None,
+        update_time_utc: None,
+    }
+}
+
+#[test]
+fn test_market_ordering_dedup_and_sort() {
+    use ig_client::presentation::market::MarketOrdering;
+
+    let traversal = vec![
+        share_market("UD.D.MSFT.CASH.IP", "Microsoft", "TRADEABLE"),
+        share_market("UA.D.AAPL.CASH.IP", "Apple", "TRADEABLE"),
+        share_market("UD.D.MSFT.CASH.IP", "Microsoft (US Tech)", "TRADEABLE"),
+    ];
+    let epics = |markets: &[MarketData]| markets.iter().map(|m| m.epic.clone()).collect::<Vec<_>>();
+
+    let raw = MarketOrdering::Raw.apply(traversal.clone());
+    assert_eq!(raw.len(), 3);
+
+    let deduped = MarketOrdering::Deduplicated.apply(traversal.clone());
+    assert_eq!(
+        epics(&deduped),
+        vec!["UD.D.MSFT.CASH.IP", "UA.D.AAPL.CASH.IP"]
+    );
+    assert_eq!(deduped[0].instrument_name, "Microsoft");
+
+    let sorted = MarketOrdering::SortedByEpic.apply(traversal);
+    assert_eq!(
+        epics(&sorted),
+        vec!["UA.D.AAPL.CASH.IP", "UD.D.MSFT.CASH.IP"]
+    );
+    assert_eq!(MarketOrdering::default(), MarketOrdering::Deduplicated);
+}
+
+#[test]
+fn test_market_search_ranking() {
+    use ig_client::model::responses::MarketSearchResponse;
+
+    let apple = share_market("UA.D.AAPL.CASH.IP", "Apple Inc", "TRADEABLE");
+    assert_eq!(apple.search_score("ua.d.aapl.cash.ip"), 105);
+    assert_eq!(apple.search_score("Apple"), 65);
+    assert_eq!(apple.search_score("inc"), 55);
+    assert_eq!(apple.search_score("AAPL"), 45);
+    assert_eq!(apple.search_score("msft"), 0);
+    assert_eq!(apple.search_score("  "), 0);
+
+    let response = MarketSearchResponse {
+        markets: vec![
+            share_market(
+                "UC.D.APPLEHOSP.CASH.IP",
+                "Pineapple Hospitality",
+                "TRADEABLE",
+            ),
+            share_market("UA.D.AAPL.CASH.IP", "Apple Inc", "CLOSED"),
+            share_market(
+                "UA.D.AAPLX.CASH.IP",
+                "Apple Inc (All Sessions)",
+                "TRADEABLE",
+            ),
+            share_market("UA.D.APLE.CASH.IP", "Apple", "EDITS_ONLY"),
+        ],
+    }
+    .ranked("apple");
+    let epics: Vec<&str> = response.iter().map(|m| m.epic.as_str()).collect();
+    assert_eq!(
+        epics,
+        vec![
+            "UA.D.APLE.CASH.IP",
+            "UA.D.AAPLX.CASH.IP",
+            "UA.D.AAPL.CASH.IP",
+            "UC.D.APPLEHOSP.CASH.IP",
+        ]
+    );
+}
@@ -0,0 +1,47 @@
+use ig_client::presentation::order::Direction;
+use ig_client::test_fixtures;
+
+#[test]
+fn test_stop_level_and_distance_round_trip() {
+    // EUR/USD quoted at 1.16432 / 1.16441 with a scaling factor of 10000
+    let details = test_fixtures::market_details();
+    assert_eq!(details.point_size(), 0.0001);
+
+    assert_eq!(
+        details.stop_level_for_distance(Direction::Buy, 20.0),
+        Some(1.16232)
+    );
+    assert_eq!(
+        details.stop_level_for_distance(Direction::Sell, 20.0),
+        Some(1.16641)
+    );
+    assert_eq!(
+        details.distance_for_level(Direction::Buy, 1.16232),
+        Some(20.0)
+    );
+    assert_eq!(
+        details.distance_for_level(Direction::Sell, 1.16641),
+        Some(20.0)
+    );
+
+    // Closer than the two point minimum, or on the wrong side of the price
+    assert_eq!(details.stop_level_for_distance(Direction::Buy, 1.0), None);
+    assert_eq!(details.distance_for_level(Direction::Buy, 1.17), None);
+
+    let mut no_prices = details.clone();
+    no_prices.snapshot.bid = None;
+    assert_eq!(
+        no_prices.stop_level_for_distance(Direction::Buy, 20.0),
+        None
+    );
+
+    // Quoted in points: one point is one unit of the price
+    let mut in_points = details;
+    in_points.snapshot.bid = Some(11643.2);
+    in_points.snapshot.decimal_places_factor = Some(1);
+    assert_eq!(in_points.point_size(), 1.0);
+    assert_eq!(
+        in_points.stop_level_for_distance(Direction::Buy, 20.0),
+        Some(11623.2)
+    );
+}
@@ -1,4 +1,6 @@
 mod storage_utils_tests;
+mod test_config;
 mod test_historical_prices;
 mod test_market_database;
+mod test_tick_store;
 mod test_utils;
@@ -0,0 +1,38 @@
+use ig_client::storage::config::{
+    DEFAULT_ACQUIRE_TIMEOUT_SECS, DEFAULT_IDLE_TIMEOUT_SECS, DatabaseConfig,
+};
+use std::time::Duration;
+
+#[test]
+fn test_database_config_new_uses_default_timeouts() {
+    let config = DatabaseConfig::new("postgres://localhost/test".to_string(), 8);
+
+    assert_eq!(config.max_connections, 8);
+    assert_eq!(config.acquire_timeout, DEFAULT_ACQUIRE_TIMEOUT_SECS);
+    assert_eq!(config.idle_timeout, Some(DEFAULT_IDLE_TIMEOUT_SECS));
+}
+
+#[test]
+fn test_database_config_deserialize_without_timeouts() {
+    let json = r#"{"url":"postgres://localhost/test","max_connections":3}"#;
+    let config: DatabaseConfig = serde_json::from_str(json).unwrap();
+
+    assert_eq!(config.max_connections, 3);
+    assert_eq!(config.acquire_timeout, DEFAULT_ACQUIRE_TIMEOUT_SECS);
+    assert_eq!(config.idle_timeout, Some(DEFAULT_IDLE_TIMEOUT_SECS));
+}
+
+#[test]
+fn test_database_config_pool_options() {
+    let config = DatabaseConfig {
+        url: "postgres://localhost/test".to_string(),
+        max_connections: 12,
+        acquire_timeout: 5,
+        idle_timeout: None,
+    };
+
+    let options = config.pool_options();
+    assert_eq!(options.get_max_connections(), 12);
+    assert_eq!(options.get_acquire_timeout(), Duration::from_secs(5));
+    assert_eq!(options.get_idle_timeout(), None);
+}
@@ -0,0 +1,95 @@
+use chrono::Utc;
+use ig_client::presentation::market::{MarketFields, MarketState, PresentationMarketData};
+use ig_client::storage::tick_store::{Tick, TickBackpressure, TickBuffer};
+
+fn tick(bid: f64) -> Tick {
+    Tick {
+        epic: "CS.D.EURUSD.CFD.IP".to_string(),
+        ts: Utc::now(),
+        fields: MarketFields {
+            bid: Some(bid),
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn tick_from_update_keeps_bid_offer_and_state() {
+    let update = PresentationMarketData {
+        item_name: "MARKET:CS.D.EURUSD.CFD.IP".to_string(),
+        item_pos: 1,
+        fields: MarketFields {
+            bid: Some(1.1),
+            offer: Some(1.2),
+            high: Some(1.3),
+            market_state: Some(MarketState::Tradeable),
+            ..Default::default()
+        },
+        changed_fields: MarketFields::default(),
+        is_snapshot: false,
+    };
+    let now = Utc::now();
+    let tick = Tick::from_update(&update, now);
+    assert_eq!(tick.epic, "CS.D.EURUSD.CFD.IP");
+    assert_eq!(tick.ts, now);
+    assert_eq!(tick.fields.bid, Some(1.1));
+    assert_eq!(tick.fields.offer, Some(1.2));
+    assert_eq!(tick.fields.market_state, Some(MarketState::Tradeable));
+    assert_eq!(tick.fields.high, None);
+}
+
+#[test]
+fn tick_buffer_drops_oldest_when_full() {
+    let buffer = TickBuffer::new(2, TickBackpressure::DropOldest);
+    buffer.push(tick(1.0));
+    buffer.push(tick(2.0));
+    assert_eq!(buffer.push(tick(3.0)), 2);
+    assert_eq!(buffer.dropped(), 1);
+
+    let batch = buffer.drain(10);
+    let bids: Vec<_> = batch.iter().map(|tick| tick.fields.bid).collect();
+    assert_eq!(bids, vec![Some(2.0), Some(3.0)]);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn tick_buffer_block_waits_for_room() {
+    let buffer = std::sync::Arc::new(TickBuffer::new(1, TickBackpressure::Block));
+    buffer.push(tick(1.0));
+
+    let producer = {
+        let buffer = std::sync::Arc::clone(&buffer);
+        std::thread::spawn(move || buffer.push(tick(2.0)))
+    };
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert_eq!(buffer.drain(1)[0].fields.bid, Some(1.0));
+    assert_eq!(producer.join().unwrap(), 1);
+    assert_eq!(buffer.dropped(), 0);
+    assert_eq!(buffer.drain(1)[0].fields.bid, Some(2.0));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn tick_buffer_block_on_runtime_worker_lets_writer_run() {
+    let buffer = std::sync::Arc::new(TickBuffer::new(1, TickBackpressure::Block));
+    buffer.push(tick(1.0));
+
+    // Both tasks need the only worker: the blocked producer must hand it over
+    let producer = {
+        let buffer = std::sync::Arc::clone(&buffer);
+        tokio::spawn(async move { buffer.push(tick(2.0)) })
+    };
+    let writer = {
+        let buffer = std::sync::Arc::clone(&buffer);
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            buffer.drain(1)
+        })
+    };
+    let (pushed, drained) = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        (producer.await.unwrap(), writer.await.unwrap())
+    })
+    .await
+    .expect("producer and writer deadlocked");
+    assert_eq!(drained[0].fields.bid, Some(1.0));
+    assert_eq!(pushed, 1);
+}
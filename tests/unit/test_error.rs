@@ -1,4 +1,4 @@
-use ig_client::error::AppError;
+use ig_client::error::{AppError, StreamError, is_auth_rejection};
 use reqwest::StatusCode;
 
 #[test]
@@ -80,3 +80,126 @@ fn test_app_error_oauth_token_expired() {
     let error = AppError::OAuthTokenExpired;
     assert_eq!(error.to_string(), "oauth token expired");
 }
+
+#[test]
+fn test_app_error_timeout() {
+    let error = AppError::Timeout("no confirmation for deal REF1".to_string());
+    assert_eq!(error.to_string(), "timeout: no confirmation for deal REF1");
+}
+
+#[test]
+fn test_app_error_trading_not_allowed() {
+    let error = AppError::TradingNotAllowed("account ABC12 cannot trade".to_string());
+    assert_eq!(
+        error.to_string(),
+        "trading not allowed: account ABC12 cannot trade"
+    );
+}
+
+#[test]
+fn test_is_auth_rejection() {
+    assert!(is_auth_rejection(
+        "Server error 1: user/password check failed"
+    ));
+    assert!(is_auth_rejection("401 Unauthorized"));
+    assert!(!is_auth_rejection("connection refused"));
+}
+
+#[test]
+fn test_stream_error_classification_and_display() {
+    assert_eq!(
+        StreamError::connection("server error 1: user/password check failed"),
+        StreamError::AuthRejected("server error 1: user/password check failed".to_string())
+    );
+    let dropped = StreamError::connection("connection reset");
+    assert!(dropped.is_connection_error());
+    assert_eq!(dropped.item(), None);
+
+    let parse = StreamError::parse("BID", "n/a").for_item("MARKET:CS.D.EURUSD.CFD.IP");
+    assert_eq!(parse.item(), Some("MARKET:CS.D.EURUSD.CFD.IP"));
+    assert_eq!(
+        AppError::from(parse).to_string(),
+        "stream error: cannot parse BID value \"n/a\" for MARKET:CS.D.EURUSD.CFD.IP"
+    );
+
+    let refused = StreamError::Subscription {
+        item: Some("MARKET:BAD".to_string()),
+        code: Some(21),
+        message: "Bad Item".to_string(),
+    };
+    assert_eq!(
+        refused.to_string(),
+        "subscription to MARKET:BAD refused (code 21): Bad Item"
+    );
+}
+
+#[test]
+fn test_maintenance_error_detection_and_display() {
+    use ig_client::model::http::is_maintenance_error;
+    use std::time::Duration;
+
+    assert!(is_maintenance_error(
+        r#"{"errorCode":"error.public-api.failure.maintenance"}"#
+    ));
+    assert!(is_maintenance_error(
+        "<html><body>Scheduled MAINTENANCE in progress</body></html>"
+    ));
+    assert!(!is_maintenance_error(
+        r#"{"errorCode":"error.service.unavailable"}"#
+    ));
+    assert!(!is_maintenance_error(""));
+
+    let error = AppError::Maintenance {
+        retry_after: Some(Duration::from_secs(900)),
+    };
+    assert_eq!(error.to_string(), "IG is under maintenance, retry in 900s");
+    assert_eq!(
+        AppError::Maintenance { retry_after: None }.to_string(),
+        "IG is under maintenance"
+    );
+}
+
+#[test]
+fn test_error_code_reads_json_error_bodies_only() {
+    use ig_client::model::http::error_code;
+
+    assert_eq!(
+        error_code(r#"{"errorCode":"error.public-api.failure.maintenance"}"#).as_deref(),
+        Some("error.public-api.failure.maintenance")
+    );
+    assert_eq!(error_code(r#"{"errorCode":42}"#), None);
+    assert_eq!(error_code(r#"{"message":"maintenance"}"#), None);
+    assert_eq!(error_code("<html>Scheduled maintenance</html>"), None);
+    assert_eq!(error_code(""), None);
+}
+
+#[test]
+fn test_unsupported_version_error_detection_and_display() {
+    use ig_client::model::http::is_unsupported_version_error;
+
+    assert!(is_unsupported_version_error(
+        r#"{"errorCode":"invalid.version"}"#
+    ));
+    assert!(is_unsupported_version_error(
+        r#"{"errorCode":"error.invalid.version"}"#
+    ));
+    assert!(!is_unsupported_version_error(
+        "Endpoint not found for API version 4"
+    ));
+    assert!(!is_unsupported_version_error(
+        r#"{"errorCode":"validation.null-not-allowed.request.version"}"#
+    ));
+    assert!(!is_unsupported_version_error(
+        r#"{"errorCode":"error.markets.epic.not-found"}"#
+    ));
+    assert!(!is_unsupported_version_error(""));
+
+    let error = AppError::UnsupportedVersion {
+        endpoint: "markets/IX.D.DAX.IFD.IP".to_string(),
+        version: 4,
+    };
+    assert_eq!(
+        error.to_string(),
+        "markets/IX.D.DAX.IFD.IP is not available in API version 4"
+    );
+}
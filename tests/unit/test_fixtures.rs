@@ -0,0 +1,103 @@
+use ig_client::model::responses::{
+    AccountActivityResponse, OrderConfirmationResponse, PositionsResponse,
+    TransactionHistoryResponse,
+};
+use ig_client::presentation::instrument::InstrumentType;
+use ig_client::presentation::market::MarketDetails;
+use ig_client::presentation::order::{Direction, Status};
+use ig_client::test_fixtures;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn fixtures_parse_into_populated_models() {
+    let details = test_fixtures::market_details();
+    assert_eq!(details.instrument.epic, "CS.D.EURUSD.CFD.IP");
+    assert_eq!(
+        details.instrument.instrument_type,
+        Some(InstrumentType::Currencies)
+    );
+    assert_eq!(details.snapshot.bid, Some(1.16432));
+
+    let position = test_fixtures::position();
+    assert_eq!(position.position.direction, Direction::Buy);
+    assert_eq!(position.market.epic, details.instrument.epic);
+
+    let confirmation = test_fixtures::order_confirmation();
+    assert_eq!(confirmation.status, Status::Open);
+    assert_eq!(confirmation.deal_id.as_deref(), Some("DIAAAAVJVM7Y9AR"));
+
+    let activity = test_fixtures::account_activity();
+    assert_eq!(activity.activities.len(), 1);
+
+    let transactions = test_fixtures::transaction_history();
+    assert_eq!(transactions.transactions[0].reference, "VJVM7Y9AR");
+
+    let withdrawal = test_fixtures::account_transaction("WITH", "£250.00");
+    assert_eq!(withdrawal.transaction_type, "WITH");
+    assert_eq!(withdrawal.profit_and_loss, "£250.00");
+    assert_eq!(withdrawal.reference, "VJVM7Y9AR");
+}
+
+#[test]
+fn fixtures_survive_serialization_round_trip() {
+    let details: MarketDetails = round_trip(&test_fixtures::market_details());
+    assert_eq!(
+        details.instrument,
+        test_fixtures::market_details().instrument
+    );
+
+    let positions: PositionsResponse = round_trip(&test_fixtures::positions_response());
+    assert_eq!(positions.positions.len(), 1);
+
+    let confirmation: OrderConfirmationResponse = round_trip(&test_fixtures::order_confirmation());
+    assert_eq!(confirmation.deal_reference, "XR4H8ZRF3NQTYQ5");
+
+    let transactions: TransactionHistoryResponse =
+        round_trip(&test_fixtures::transaction_history());
+    assert_eq!(transactions.metadata.page_data.total_pages, 1);
+
+    let activity: AccountActivityResponse =
+        serde_json::from_str(test_fixtures::ACCOUNT_ACTIVITY_JSON).unwrap();
+    assert_eq!(
+        activity.activities[0].deal_id.as_deref(),
+        Some("DIAAAAVJVM7Y9AR")
+    );
+}
+
+#[test]
+fn signed_pnl_sums_cash_and_trades_to_net_change() {
+    use ig_client::presentation::account::TransactionType;
+
+    let history = test_fixtures::cash_transaction_history();
+    let types: Vec<TransactionType> = history
+        .transactions
+        .iter()
+        .map(|t| t.transaction_type_typed())
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            TransactionType::Deposit,
+            TransactionType::Trade,
+            TransactionType::Trade,
+            TransactionType::Withdrawal,
+            TransactionType::Withdrawal,
+        ]
+    );
+
+    let signed: Vec<f64> = history
+        .transactions
+        .iter()
+        .filter_map(|t| t.signed_pnl())
+        .collect();
+    assert_eq!(signed, vec![1000.0, 1.5, -4.1, -0.5, -1000.0]);
+
+    let net: f64 = signed.iter().sum();
+    assert!((net - -3.1).abs() < 1e-9);
+}
@@ -3,5 +3,7 @@ mod test_config;
 mod test_finance;
 mod test_id;
 mod test_model_utils;
+mod test_redact;
 mod test_retry;
+mod test_single_flight;
 mod tools_tests;
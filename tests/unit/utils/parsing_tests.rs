@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use ig_client::utils::parsing::{ParsedOptionInfo, normalize_text, parse_instrument_name};
+    use ig_client::presentation::instrument::InstrumentType;
+    use ig_client::utils::parsing::{
+        OptionEpic, OptionSide, ParsedOptionInfo, instrument_type_from_epic, normalize_text,
+        parse_http_date, parse_ig_datetime, parse_instrument_name,
+    };
 
     #[test]
     fn test_normalize_text() {
@@ -103,4 +107,75 @@ mod tests {
         assert_eq!(info.strike, Some("18500".to_string()));
         assert_eq!(info.option_type, Some("CALL".to_string()));
     }
+
+    #[test]
+    fn test_instrument_type_from_epic() {
+        let cases = [
+            ("IX.D.DAX.DAILY.IP", Some(InstrumentType::Indices)),
+            ("CS.D.EURUSD.TODAY.IP", Some(InstrumentType::Currencies)),
+            ("CS.D.USCGC.TODAY.IP", Some(InstrumentType::Commodities)),
+            ("EN.D.CL.Month1.IP", Some(InstrumentType::Commodities)),
+            ("IR.D.10YEAR100.Month2.IP", Some(InstrumentType::Rates)),
+            ("UA.D.AAPL.CASH.IP", Some(InstrumentType::Shares)),
+            ("OP.D.OTCDAX1.021100P.IP", Some(InstrumentType::Options)),
+            ("do.d.otcdax1.021100c.ip", Some(InstrumentType::Options)),
+            ("ZZ.D.UNKNOWN.IP", None),
+            ("", None),
+        ];
+
+        for (epic, expected) in cases {
+            assert_eq!(instrument_type_from_epic(epic), expected, "epic {epic}");
+        }
+    }
+
+    #[test]
+    fn test_parse_ig_datetime_formats() {
+        let expected = "2024-01-31T10:15:00+00:00";
+        for raw in [
+            "2024-01-31T10:15:00",
+            "2024-01-31T10:15:00.000",
+            "2024/01/31 10:15:00",
+            "2024/01/31 10:15:00:000",
+            "2024-01-31 10:15:00",
+            "2024:01:31-10:15:00",
+            "2024/01/31 10:15",
+            "2024-01-31T10:15",
+            "2024-01-31T10:15:00Z",
+        ] {
+            let parsed = parse_ig_datetime(raw).unwrap_or_else(|| panic!("{raw} not parsed"));
+            assert_eq!(parsed.to_rfc3339(), expected, "{raw}");
+        }
+        assert!(parse_ig_datetime("-").is_none());
+        assert!(parse_ig_datetime("").is_none());
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        let parsed = parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2015-10-21T07:28:00+00:00");
+        assert!(parse_http_date("2015-10-21T07:28:00Z").is_none());
+        assert!(parse_http_date("").is_none());
+    }
+
+    #[test]
+    fn test_option_epic_parse() {
+        let put = OptionEpic::parse("OP.D.OTCDAX1.021100P.IP").unwrap();
+        assert_eq!(put.series, "OTCDAX1");
+        assert_eq!(put.strike, 21100.0);
+        assert_eq!(put.side, OptionSide::Put);
+
+        let call = OptionEpic::parse("op.d.otcasx1.7725c.ip").unwrap();
+        assert_eq!(call.strike, 7725.0);
+        assert_eq!(call.side, OptionSide::Call);
+
+        for epic in [
+            "DO.D.OTCDDAX.71.IP",
+            "OP.D.FUT.TEST.IP",
+            "IX.D.DAX.DAILY.IP",
+            "OP.D.OTCDAX1.P.IP",
+            "",
+        ] {
+            assert!(OptionEpic::parse(epic).is_none(), "epic {epic}");
+        }
+    }
 }
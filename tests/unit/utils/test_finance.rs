@@ -1,6 +1,11 @@
 use ig_client::presentation::account::{Position, PositionDetails, PositionMarket};
+use ig_client::presentation::market::{HistoricalPrice, Instrument, PricePoint};
 use ig_client::presentation::order::Direction;
-use ig_client::utils::finance::{calculate_percentage_return, calculate_pnl};
+use ig_client::utils::finance::{
+    RoundingMode, calculate_instrument_pnl, calculate_margin, calculate_percentage_return,
+    calculate_pnl, round_down_to_step, round_to_step, size_for_risk, size_for_risk_with_step,
+    total_volume, vwap,
+};
 
 fn create_test_position(
     direction: Direction,
@@ -79,6 +84,34 @@ fn test_calculate_pnl_missing_prices() {
     assert_eq!(calculate_pnl(&position_sell), None);
 }
 
+#[test]
+fn test_position_prices_with_suspended_market() {
+    use ig_client::error::AppError;
+
+    let position = create_test_position(Direction::Buy, 100.0, 10.0, Some(110.0), Some(111.0));
+    assert_eq!(position.market.current_price(&Direction::Buy), Some(111.0));
+    assert_eq!(position.market.current_price(&Direction::Sell), Some(110.0));
+    assert_eq!(position.closing_price().unwrap(), 110.0);
+    assert_eq!(position.unrealised_pnl().unwrap(), 100.0);
+    let close = position.to_limit_close_request().unwrap();
+    assert_eq!(close.direction, Direction::Sell);
+    assert_eq!(close.level, Some(110.0));
+
+    let mut suspended = create_test_position(Direction::Sell, 100.0, 10.0, None, None);
+    suspended.market.market_status = "SUSPENDED".into();
+    assert_eq!(suspended.market.current_price(&Direction::Buy), None);
+    assert!(matches!(
+        suspended.unrealised_pnl(),
+        Err(AppError::PriceUnavailable { ref epic, ref market_status })
+            if epic == "TEST.EPIC" && market_status == "SUSPENDED"
+    ));
+    let error = suspended.to_limit_close_request().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "no price available for TEST.EPIC (market SUSPENDED)"
+    );
+}
+
 #[test]
 fn test_calculate_percentage_return() {
     let position = create_test_position(Direction::Buy, 100.0, 10.0, Some(110.0), Some(111.0));
@@ -92,3 +125,243 @@ fn test_calculate_percentage_return_zero_initial_value() {
     let position = create_test_position(Direction::Buy, 100.0, 0.0, Some(110.0), Some(111.0));
     assert_eq!(calculate_percentage_return(&position), None);
 }
+
+fn create_test_candle(close: Option<f64>, volume: Option<i64>) -> HistoricalPrice {
+    let point = |last_traded: Option<f64>| PricePoint {
+        bid: None,
+        ask: None,
+        last_traded,
+    };
+    HistoricalPrice {
+        snapshot_time: "2024/01/01 00:00:00".into(),
+        snapshot_time_utc: None,
+        open_price: point(close),
+        high_price: point(close),
+        low_price: point(close),
+        close_price: point(close),
+        last_traded_volume: volume,
+    }
+}
+
+#[test]
+fn test_vwap_weights_by_volume_and_skips_empty_candles() {
+    let prices = vec![
+        create_test_candle(Some(100.0), Some(10)),
+        create_test_candle(Some(110.0), Some(30)),
+        create_test_candle(Some(500.0), None),
+        create_test_candle(Some(900.0), Some(0)),
+    ];
+    let result = vwap(&prices).unwrap();
+    assert!((result - 107.5).abs() < 1e-9);
+    assert_eq!(total_volume(&prices), 40);
+}
+
+#[test]
+fn test_vwap_without_volume() {
+    let prices = vec![create_test_candle(Some(100.0), None)];
+    assert!(vwap(&prices).is_none());
+    assert!(vwap(&[]).is_none());
+    assert_eq!(total_volume(&prices), 0);
+}
+
+fn create_test_instrument(
+    contract_size: &str,
+    margin_factor: Option<f64>,
+    unit: &str,
+) -> Instrument {
+    Instrument {
+        epic: "IX.D.DAX.IFD.IP".into(),
+        name: "Germany 40 Cash".into(),
+        expiry: "-".into(),
+        contract_size: contract_size.into(),
+        lot_size: Some(1.0),
+        high_limit_price: None,
+        low_limit_price: None,
+        margin_factor,
+        margin_factor_unit: Some(unit.into()),
+        currencies: None,
+        value_of_one_pip: "1".into(),
+        instrument_type: None,
+        expiry_details: None,
+        slippage_factor: None,
+        limited_risk_premium: None,
+        news_code: None,
+        chart_code: None,
+    }
+}
+
+#[test]
+fn test_contract_size_value_parsing() {
+    assert_eq!(
+        create_test_instrument("100", None, "PERCENTAGE").contract_size_value(),
+        Some(100.0)
+    );
+    assert_eq!(
+        create_test_instrument("1,000", None, "PERCENTAGE").contract_size_value(),
+        Some(1000.0)
+    );
+    assert_eq!(
+        create_test_instrument(" 0.5 ", None, "PERCENTAGE").contract_size_value(),
+        Some(0.5)
+    );
+    assert_eq!(
+        create_test_instrument("-", None, "PERCENTAGE").contract_size_value(),
+        None
+    );
+}
+
+#[test]
+fn test_instrument_pnl_and_margin() {
+    let instrument = create_test_instrument("25", Some(5.0), "PERCENTAGE");
+    let pnl = calculate_instrument_pnl(&instrument, &Direction::Sell, 18000.0, 17990.0, 2.0);
+    assert_eq!(pnl, Some(500.0));
+
+    let margin = calculate_margin(&instrument, 2.0, 18000.0).unwrap();
+    assert!((margin - 45000.0).abs() < 1e-9);
+
+    let points = create_test_instrument("1", Some(50.0), "POINTS");
+    assert_eq!(calculate_margin(&points, 3.0, 18000.0), Some(150.0));
+
+    let unparsable = create_test_instrument("n/a", Some(5.0), "PERCENTAGE");
+    assert!(calculate_margin(&unparsable, 1.0, 100.0).is_none());
+}
+
+#[test]
+fn test_position_break_even() {
+    let buy = create_test_position(Direction::Buy, 100.0, 2.0, Some(99.0), Some(101.0));
+    assert_eq!(buy.break_even(), Some(100.0));
+
+    let mut guaranteed = create_test_position(Direction::Sell, 100.0, 2.0, None, None);
+    guaranteed.position.controlled_risk = true;
+    assert_eq!(guaranteed.break_even(), None);
+    guaranteed.position.limited_risk_premium = Some(0.6);
+    assert_eq!(guaranteed.break_even(), Some(99.4));
+
+    let mut empty = create_test_position(Direction::Buy, 100.0, 0.0, None, None);
+    assert_eq!(empty.break_even(), None);
+    empty.position.size = 1.0;
+    empty.position.contract_size = 0.0;
+    assert_eq!(empty.break_even(), None);
+}
+
+#[test]
+fn test_size_for_risk() {
+    // Risk 1% of 10,000 = 100; 25 points stop at 1 per point => 4 contracts
+    assert_eq!(size_for_risk(10_000.0, 1.0, 25.0, 1.0), 4.0);
+    // 100 / (30 * 1) = 3.333... rounded down to 0.01
+    assert_eq!(size_for_risk(10_000.0, 1.0, 30.0, 1.0), 3.33);
+    assert_eq!(size_for_risk_with_step(10_000.0, 1.0, 30.0, 1.0, 0.5), 3.0);
+    assert_eq!(size_for_risk(10_000.0, 1.0, 0.0, 1.0), 0.0);
+    assert_eq!(size_for_risk(-1.0, 1.0, 25.0, 1.0), 0.0);
+    assert_eq!(round_down_to_step(0.3, 0.1), 0.3);
+}
+
+#[test]
+fn test_round_down_to_step_with_non_decimal_steps() {
+    assert_eq!(round_down_to_step(0.8, 0.25), 0.75);
+    assert_eq!(round_down_to_step(0.25, 0.25), 0.25);
+    assert_eq!(round_down_to_step(0.3, 0.125), 0.25);
+    assert_eq!(round_down_to_step(0.375, 0.125), 0.375);
+    assert_eq!(round_down_to_step(7.6, 2.5), 7.5);
+    // 100 / (30 * 1) = 3.333... never rounded above the risked size
+    assert_eq!(
+        size_for_risk_with_step(10_000.0, 1.0, 30.0, 1.0, 0.25),
+        3.25
+    );
+    assert_eq!(size_for_risk_with_step(10_000.0, 1.0, 30.0, 1.0, 2.5), 2.5);
+}
+
+#[test]
+fn test_round_to_step_modes() {
+    assert_eq!(round_to_step(0.259, 0.01, RoundingMode::Floor), 0.25);
+    assert_eq!(round_to_step(0.259, 0.01, RoundingMode::Nearest), 0.26);
+    assert_eq!(round_to_step(0.251, 0.01, RoundingMode::Ceil), 0.26);
+    // Exact multiples are kept in every mode
+    for mode in [
+        RoundingMode::Floor,
+        RoundingMode::Nearest,
+        RoundingMode::Ceil,
+    ] {
+        assert_eq!(round_to_step(0.29, 0.01, mode), 0.29);
+    }
+    assert_eq!(RoundingMode::default(), RoundingMode::Floor);
+}
+
+#[test]
+fn test_round_to_step_modes_with_non_decimal_steps() {
+    let cases = [
+        (0.8, 0.25, [0.75, 0.75, 1.0]),
+        (0.7, 0.125, [0.625, 0.75, 0.75]),
+        (6.2, 2.5, [5.0, 5.0, 7.5]),
+        (7.6, 2.5, [7.5, 7.5, 10.0]),
+    ];
+    for (size, step, [floor, nearest, ceil]) in cases {
+        assert_eq!(round_to_step(size, step, RoundingMode::Floor), floor);
+        assert_eq!(round_to_step(size, step, RoundingMode::Nearest), nearest);
+        assert_eq!(round_to_step(size, step, RoundingMode::Ceil), ceil);
+    }
+    // Exact multiples are kept in every mode
+    for mode in [
+        RoundingMode::Floor,
+        RoundingMode::Nearest,
+        RoundingMode::Ceil,
+    ] {
+        assert_eq!(round_to_step(0.25, 0.25, mode), 0.25);
+        assert_eq!(round_to_step(0.375, 0.125, mode), 0.375);
+        assert_eq!(round_to_step(7.5, 2.5, mode), 7.5);
+    }
+}
+
+#[test]
+fn test_position_live_pnl_from_market_fields() {
+    use ig_client::presentation::market::{MarketField, MarketFields};
+
+    let buy = create_test_position(Direction::Buy, 100.0, 2.0, None, None);
+    let (epic, fields) = buy.pnl_stream_spec();
+    assert_eq!(epic, "TEST.EPIC");
+    assert!(fields.contains(&MarketField::Bid));
+    assert!(fields.contains(&MarketField::Offer));
+    assert_eq!(MarketField::Bid.to_string(), "BID");
+
+    let update = MarketFields {
+        bid: Some(105.0),
+        offer: Some(106.0),
+        ..Default::default()
+    };
+    assert_eq!(buy.live_pnl(&update), Some(10.0));
+
+    let sell = create_test_position(Direction::Sell, 110.0, 1.0, None, None);
+    assert_eq!(sell.live_pnl(&update), Some(4.0));
+
+    let bid_only = MarketFields {
+        bid: Some(105.0),
+        ..Default::default()
+    };
+    assert_eq!(sell.live_pnl(&bid_only), None);
+}
+
+#[cfg(feature = "options")]
+#[test]
+fn test_black_scholes_implied_vol_recovers_pricing_vol() {
+    use ig_client::utils::finance::{BlackScholes, OptionPricing};
+    use ig_client::utils::parsing::{OptionEpic, OptionSide};
+
+    let call = OptionEpic::parse("OP.D.OTCDAX1.021100C.IP").unwrap();
+    let put = OptionEpic::parse("OP.D.OTCDAX1.021100P.IP").unwrap();
+    let (spot, days, rate) = (21000.0, 30.0, 0.03);
+
+    for (option, side) in [(&call, OptionSide::Call), (&put, OptionSide::Put)] {
+        let price = BlackScholes::price(side, spot, option.strike, days / 365.0, rate, 0.2);
+        let vol = BlackScholes
+            .implied_vol(option, spot, price, days, rate)
+            .unwrap();
+        assert!((vol - 0.2).abs() < 1e-6, "{side:?} vol {vol}");
+    }
+
+    // Below intrinsic value and non-positive inputs have no solution
+    assert_eq!(BlackScholes.implied_vol(&put, spot, 40.0, days, rate), None);
+    assert_eq!(
+        BlackScholes.implied_vol(&call, spot, 300.0, 0.0, rate),
+        None
+    );
+}
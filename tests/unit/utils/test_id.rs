@@ -1,4 +1,4 @@
-use ig_client::utils::id::get_id;
+use ig_client::utils::id::{generate_deal_reference, generate_request_id, get_id};
 
 #[test]
 fn test_get_id_returns_some() {
@@ -43,3 +43,31 @@ fn test_get_id_multiple_calls() {
 
     assert_eq!(ids.len(), 100);
 }
+
+#[test]
+fn test_generate_request_id_is_uuid_v4() {
+    let id = generate_request_id();
+    let parts: Vec<&str> = id.split('-').collect();
+    assert_eq!(
+        parts.iter().map(|p| p.len()).collect::<Vec<_>>(),
+        vec![8, 4, 4, 4, 12]
+    );
+    assert!(parts[2].starts_with('4'));
+    assert!(matches!(
+        parts[3].chars().next(),
+        Some('8' | '9' | 'a' | 'b')
+    ));
+    assert!(id.chars().all(|c| c == '-' || c.is_ascii_hexdigit()));
+    assert_ne!(id, generate_request_id());
+}
+
+#[test]
+fn test_generate_deal_reference_prefix_and_length() {
+    let first = generate_deal_reference("bot-");
+    let second = generate_deal_reference("bot-");
+    assert!(first.starts_with("bot-"));
+    assert_eq!(first.len(), 30);
+    assert_ne!(first, second);
+
+    assert_eq!(generate_deal_reference("").len(), 30);
+}
@@ -194,3 +194,84 @@ fn test_extract_markets_preserves_market_data() {
         Some("2024-01-01T12:34:56".to_string())
     );
 }
+
+#[test]
+fn test_split_historical_range() {
+    use ig_client::model::utils::split_historical_range;
+
+    let ranges =
+        split_historical_range("MINUTE", "2025-01-01T00:00:00", "2025-01-01T02:30:00", 60).unwrap();
+    assert_eq!(
+        ranges,
+        vec![
+            (
+                "2025-01-01T00:00:00".to_string(),
+                "2025-01-01T01:00:00".to_string()
+            ),
+            (
+                "2025-01-01T01:00:00".to_string(),
+                "2025-01-01T02:00:00".to_string()
+            ),
+            (
+                "2025-01-01T02:00:00".to_string(),
+                "2025-01-01T02:30:00".to_string()
+            ),
+        ]
+    );
+
+    let single =
+        split_historical_range("DAY", "2025-01-01 00:00:00", "2025-02-01 00:00:00", 1000).unwrap();
+    assert_eq!(single.len(), 1);
+
+    assert!(
+        split_historical_range("MINUTE", "2025-01-02T00:00:00", "2025-01-01T00:00:00", 10).is_err()
+    );
+    assert!(
+        split_historical_range(
+            "FORTNIGHT",
+            "2025-01-01T00:00:00",
+            "2025-01-02T00:00:00",
+            10
+        )
+        .is_err()
+    );
+    assert!(split_historical_range("MINUTE", "yesterday", "2025-01-02T00:00:00", 10).is_err());
+}
+
+#[test]
+fn test_historical_prices_extend_dedup() {
+    use ig_client::model::responses::HistoricalPricesResponse;
+
+    let response = |times: &[&str], remaining: i64| -> HistoricalPricesResponse {
+        let prices: Vec<serde_json::Value> = times
+            .iter()
+            .map(|time| {
+                let point = serde_json::json!({"bid": 1.0, "ask": 1.1, "lastTraded": null});
+                serde_json::json!({
+                    "snapshotTime": time,
+                    "openPrice": point,
+                    "highPrice": point,
+                    "lowPrice": point,
+                    "closePrice": point,
+                    "lastTradedVolume": 1
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "prices": prices,
+            "instrumentType": "CURRENCIES",
+            "allowance": {"remainingAllowance": remaining, "totalAllowance": 10000, "allowanceExpiry": 1000}
+        }))
+        .unwrap()
+    };
+
+    let mut acc = response(&["2025/01/01 00:00:00", "2025/01/01 00:01:00"], 9998);
+    let added = acc.extend_dedup(response(
+        &["2025/01/01 00:01:00", "2025/01/01 00:02:00"],
+        9996,
+    ));
+    assert_eq!(added, 1);
+    assert_eq!(acc.len(), 3);
+    assert_eq!(acc.prices[2].snapshot_time, "2025/01/01 00:02:00");
+    assert_eq!(acc.allowance.unwrap().remaining_allowance, 9996);
+}
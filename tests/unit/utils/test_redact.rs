@@ -0,0 +1,95 @@
+use ig_client::utils::redact::{REDACTED, redact_headers, trace_request, trace_response};
+use serde_json::json;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct CapturedLog(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CapturedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_redact_headers_hides_credentials() {
+    let formatted = redact_headers(&[
+        ("X-IG-API-KEY", "api-key-123"),
+        ("CST", "cst-456"),
+        ("Authorization", "Bearer access-789"),
+        ("Version", "2"),
+    ]);
+    assert!(formatted.contains("Version: 2"));
+    assert!(formatted.contains(&format!("CST: {REDACTED}")));
+    assert!(!formatted.contains("api-key-123"));
+    assert!(!formatted.contains("cst-456"));
+    assert!(!formatted.contains("access-789"));
+}
+
+#[test]
+fn test_trace_logs_contain_no_secrets() {
+    let secrets = [
+        "api-key-123",
+        "cst-456",
+        "xst-789",
+        "Bearer access-abc",
+        "hunter2",
+        "my-login",
+        "refresh-def",
+        "access-ghi",
+    ];
+
+    let log = CapturedLog::default();
+    let writer = log.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    tracing::subscriber::with_default(subscriber, || {
+        trace_request(
+            "POST",
+            "https://demo-api.ig.com/gateway/deal/session",
+            &[
+                ("X-IG-API-KEY", "api-key-123"),
+                ("CST", "cst-456"),
+                ("X-SECURITY-TOKEN", "xst-789"),
+                ("Authorization", "Bearer access-abc"),
+            ],
+            &Some(json!({
+                "identifier": "my-login",
+                "password": "hunter2",
+                "encryptedPassword": false,
+                "epic": "CS.D.EURUSD.CFD.IP"
+            })),
+        );
+        trace_response(
+            "https://demo-api.ig.com/gateway/deal/session",
+            json!({
+                "accountId": "ABC123",
+                "oauthToken": {
+                    "access_token": "access-ghi",
+                    "refresh_token": "refresh-def",
+                    "expires_in": "60"
+                }
+            })
+            .to_string()
+            .as_bytes(),
+        );
+    });
+
+    let output = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("CS.D.EURUSD.CFD.IP"));
+    assert!(output.contains("ABC123"));
+    assert!(output.contains(REDACTED));
+    for secret in secrets {
+        assert!(!output.contains(secret), "log leaked {secret}: {output}");
+    }
+}
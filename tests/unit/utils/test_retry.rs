@@ -71,3 +71,43 @@ fn test_retry_config_delay_secs_getter() {
     };
     assert_eq!(config2.delay_secs(), 10);
 }
+
+#[test]
+fn test_parse_retry_after_seconds_and_http_date() {
+    use chrono::{TimeZone, Utc};
+    use ig_client::model::retry::parse_retry_after;
+    use std::time::Duration;
+
+    let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 27, 0).unwrap();
+    assert_eq!(
+        parse_retry_after(" 120 ", now),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+        Some(Duration::from_secs(60))
+    );
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+}
+
+#[test]
+fn test_retry_delay_prefers_longer_retry_after() {
+    use std::time::Duration;
+
+    let config = RetryConfig::with_delay(2);
+    let first = config.backoff(1);
+    assert!(first >= Duration::from_secs(2) && first <= Duration::from_millis(2400));
+    let third = config.backoff(3);
+    assert!(third >= Duration::from_secs(8) && third <= Duration::from_millis(9600));
+    assert!(config.backoff(30) <= Duration::from_secs(360));
+
+    assert_eq!(
+        config.retry_delay(1, Some(Duration::from_secs(90))),
+        Duration::from_secs(90)
+    );
+    assert!(config.retry_delay(1, Some(Duration::from_secs(1))) >= Duration::from_secs(2));
+}
@@ -0,0 +1,47 @@
+use ig_client::utils::single_flight::SingleFlight;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Barrier;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_relogins_run_once() {
+    const CALLERS: usize = 64;
+    let gate = Arc::new(SingleFlight::<String>::new());
+    let logins = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(CALLERS));
+
+    let mut tasks = Vec::with_capacity(CALLERS);
+    for _ in 0..CALLERS {
+        let gate = Arc::clone(&gate);
+        let logins = Arc::clone(&logins);
+        let barrier = Arc::clone(&barrier);
+        tasks.push(tokio::spawn(async move {
+            barrier.wait().await;
+            gate.run(|| async {
+                let n = logins.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok::<_, String>(format!("session-{n}"))
+            })
+            .await
+        }));
+    }
+
+    for task in tasks {
+        assert_eq!(task.await.unwrap().unwrap(), "session-1");
+    }
+    assert_eq!(logins.load(Ordering::SeqCst), 1);
+    assert_eq!(gate.completed_runs(), 1);
+
+    // A later caller starts a fresh run
+    let next = gate.run(|| async { Ok::<_, String>("session-2".to_string()) });
+    assert_eq!(next.await.unwrap(), "session-2");
+}
+
+#[tokio::test]
+async fn failed_run_is_not_shared() {
+    let gate = SingleFlight::<u32>::new();
+    assert!(gate.run(|| async { Err::<u32, _>("down") }).await.is_err());
+    assert_eq!(gate.completed_runs(), 0);
+    assert_eq!(gate.run(|| async { Ok::<_, &str>(7) }).await, Ok(7));
+}